@@ -181,6 +181,18 @@ pub fn string_from_args(args: &Arguments) -> String {
             s.push(' ');
             Some(input_file.clone())
         }
+        FuzzerCommand::Enumerate => {
+            s.push_str("--command ");
+            s.push_str(COMMAND_ENUMERATE);
+            s.push(' ');
+            None
+        }
+        FuzzerCommand::CheckMutator => {
+            s.push_str("--command ");
+            s.push_str(COMMAND_CHECK_MUTATOR);
+            s.push(' ');
+            None
+        }
     };
     if let Some(input_file) = input_file {
         s.push_str(&format!("--{} {} ", INPUT_FILE_FLAG, input_file.display()));
@@ -229,5 +241,23 @@ pub fn string_from_args(args: &Arguments) -> String {
     if args.detect_infinite_loop {
         s.push_str(&format!("--{} ", DETECT_INFINITE_LOOP_FLAG));
     }
+    if !args.harvest_constants {
+        s.push_str(&format!("--{} ", NO_DICTIONARY_FLAG));
+    }
+    if args.corpus_wide_crossover {
+        s.push_str(&format!("--{} ", CORPUS_WIDE_CROSSOVER_FLAG));
+    }
+    if args.check_repetitions {
+        s.push_str(&format!("--{} ", CHECK_REPETITIONS_FLAG));
+    }
+    match &args.target {
+        Some(CoverageTarget::Function(name)) => {
+            s.push_str(&format!("--{} {:?} ", TARGET_FN_FLAG, name));
+        }
+        Some(CoverageTarget::Line { file, line }) => {
+            s.push_str(&format!("--{} {}:{} ", TARGET_LINE_FLAG, file.display(), line));
+        }
+        None => {}
+    }
     s
 }
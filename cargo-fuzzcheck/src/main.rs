@@ -99,7 +99,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     };
 
     match args.command {
-        FuzzerCommand::Fuzz => {
+        FuzzerCommand::Fuzz | FuzzerCommand::Enumerate => {
             if args.corpus_in.is_none() && matches.opt_present(NO_IN_CORPUS_FLAG) == false {
                 args.corpus_in = Some(PathBuf::new().join(format!("fuzz/{}/corpus", target_name)));
             }
@@ -136,7 +136,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                 &process::Stdio::inherit,
             )?;
         }
-        FuzzerCommand::Read { .. } => {
+        FuzzerCommand::Read { .. } | FuzzerCommand::CheckMutator => {
             let exec = launch_executable(
                 target_name,
                 &args,
@@ -0,0 +1,17 @@
+//! Runs the built-in suite of mutator benchmarks.
+//!
+//! ```sh
+//! cargo run --release --example mutator_benchmarks
+//! ```
+use std::alloc::System;
+
+use fuzzcheck::mutators::benchmark::MutatorBenchmark;
+use fuzzcheck::sensors_and_pools::CountingAllocator;
+
+#[global_allocator]
+static ALLOC: CountingAllocator<System> = CountingAllocator(System);
+
+fn main() {
+    let suite = MutatorBenchmark::default().builtin_suite();
+    println!("{}", suite);
+}
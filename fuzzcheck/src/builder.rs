@@ -65,22 +65,25 @@ use std::result::Result;
 use std::time::Duration;
 
 use fuzzcheck_common::arg::{
-    options_parser, Arguments, ArgumentsError, FuzzerCommand, COMMAND_FUZZ, COMMAND_MINIFY_INPUT, INPUT_FILE_FLAG,
+    options_parser, Arguments, ArgumentsError, FuzzerCommand, COMMAND_CHECK_MUTATOR, COMMAND_ENUMERATE, COMMAND_FUZZ,
+    COMMAND_MINIFY_INPUT, INPUT_FILE_FLAG, MAX_INPUT_CPLX_FLAG, MAX_ITERATIONS_FLAG,
 };
 
+use crate::code_coverage_sensor::target::{distances_to_target, CounterLocation};
 use crate::code_coverage_sensor::CodeCoverageSensor;
 use crate::fuzzer::{Fuzzer, FuzzingResult};
 use crate::sensors_and_pools::{
-    AndPool, DifferentObservations, MaximiseEachCounterPool, MaximiseObservationPool, MostNDiversePool,
-    SameObservations, SimplestToActivateCounterPool, WrapperSensor,
+    AndPool, DifferentObservations, DistanceToTargetPool, HitCountBucketsPool, MaximiseEachCounterPool,
+    MaximiseObservationPool, MostNDiversePool, PowerSchedule, SameObservations, SimplestToActivateCounterPool,
+    WrapperSensor,
 };
 #[cfg(feature = "serde_ron_serializer")]
 use crate::SerdeRonSerializer;
 #[cfg(feature = "serde_json_serializer")]
 use crate::SerdeSerializer;
 use crate::{
-    split_string_by_whitespace, CompatibleWithObservations, DefaultMutator, Mutator, PoolExt, Sensor, SensorExt,
-    Serializer,
+    split_string_by_whitespace, CompatibleWithObservations, CoverageTarget, DefaultMutator, Mutator, PoolExt, Sensor,
+    SensorAndPool, SensorExt, Serializer,
 };
 
 /** A function that can be fuzz-tested.
@@ -213,6 +216,7 @@ where
     serializer: Box<dyn Serializer<Value = V>>,
     sensor: Sens,
     pool: P,
+    directed_sensor_and_pool: Option<DirectedSensorAndPool<Sens, P>>,
     _phantom: PhantomData<*const V>,
 }
 
@@ -243,6 +247,7 @@ where
     sensor: Sens,
     pool: P,
     arguments: Arguments,
+    directed_sensor_and_pool: Option<DirectedSensorAndPool<Sens, P>>,
     _phantom: PhantomData<*const V>,
 }
 
@@ -461,13 +466,16 @@ where
         self,
         keep: impl Fn(&Path, &str) -> bool,
     ) -> FuzzerBuilder4<F, M, V, DiverseAndMaxHitsSensor, BasicAndDiverseAndMaxHitsPool> {
-        let (sensor, pool) = default_sensor_and_pool_with_custom_filter(keep).finish();
+        let basic = basic_sensor_and_pool_with_custom_filter(keep);
+        let directed_sensor_and_pool = DirectedSensorAndPool::new(&basic.sensor);
+        let (sensor, pool) = augment_basic_sensor_and_pool(basic).finish();
         FuzzerBuilder4 {
             test_function: self.test_function,
             mutator: self.mutator,
             serializer: self.serializer,
             sensor,
             pool,
+            directed_sensor_and_pool: Some(directed_sensor_and_pool),
             _phantom: PhantomData,
         }
     }
@@ -481,13 +489,16 @@ where
     pub fn default_sensor_and_pool(
         self,
     ) -> FuzzerBuilder4<F, M, V, DiverseAndMaxHitsSensor, BasicAndDiverseAndMaxHitsPool> {
-        let (sensor, pool) = default_sensor_and_pool().finish();
+        let basic = basic_sensor_and_pool();
+        let directed_sensor_and_pool = DirectedSensorAndPool::new(&basic.sensor);
+        let (sensor, pool) = augment_basic_sensor_and_pool(basic).finish();
         FuzzerBuilder4 {
             test_function: self.test_function,
             mutator: self.mutator,
             serializer: self.serializer,
             sensor,
             pool,
+            directed_sensor_and_pool: Some(directed_sensor_and_pool),
             _phantom: PhantomData,
         }
     }
@@ -503,6 +514,7 @@ where
             serializer: self.serializer,
             sensor,
             pool,
+            directed_sensor_and_pool: None,
             _phantom: PhantomData,
         }
     }
@@ -525,6 +537,7 @@ where
             sensor: self.sensor,
             pool: self.pool,
             arguments,
+            directed_sensor_and_pool: self.directed_sensor_and_pool,
             _phantom: self._phantom,
         }
     }
@@ -538,9 +551,13 @@ fuzzcheck <SUBCOMMAND> [OPTIONS]
 SUBCOMMANDS:
     {fuzz}    Run the fuzz test
     {minify}    Minify a crashing test input, requires --{input_file}
+    {enumerate}    Test the inputs generated by the mutator in order, up to the maximum complexity
+    {check_mutator}    Check that the mutator respects the requirements of the Mutator trait
 "#,
             fuzz = COMMAND_FUZZ,
             minify = COMMAND_MINIFY_INPUT,
+            enumerate = COMMAND_ENUMERATE,
+            check_mutator = COMMAND_CHECK_MUTATOR,
             input_file = INPUT_FILE_FLAG,
         );
         help += parser.usage("").as_str();
@@ -558,9 +575,25 @@ fuzzcheck {minify} --{input_file} "artifacts/crash.json"
     and name them {{complexity}}-{{hash}}.json. 
     For example, artifacts/crash.minified/4213--8cd7777109b57b8c.json
     is a minified input of complexity 42.13.
+
+fuzzcheck {enumerate} --{max_cplx} 20
+
+    Test the inputs of complexity no greater than 20 that the mutator
+    generates in order, until it cannot generate more of them. Mutators
+    that never run out of inputs, such as those of vectors, require
+    --{max_iter} to stop.
+
+fuzzcheck {check_mutator}
+
+    Check that the mutator respects the requirements of the Mutator trait
+    and report the first violation found.
 "#,
             fuzz = COMMAND_FUZZ,
             minify = COMMAND_MINIFY_INPUT,
+            enumerate = COMMAND_ENUMERATE,
+            check_mutator = COMMAND_CHECK_MUTATOR,
+            max_cplx = MAX_INPUT_CPLX_FLAG,
+            max_iter = MAX_ITERATIONS_FLAG,
             input_file = INPUT_FILE_FLAG,
         )
         .as_str();
@@ -585,6 +618,7 @@ fuzzcheck {minify} --{input_file} "artifacts/crash.json"
             sensor: self.sensor,
             pool: self.pool,
             arguments,
+            directed_sensor_and_pool: self.directed_sensor_and_pool,
             _phantom: PhantomData,
         }
     }
@@ -599,6 +633,40 @@ where
     P: CompatibleWithObservations<Sens::Observations> + 'static,
     Fuzzer<V, M>: 'static,
 {
+    /// Sets the task that the fuzzer performs.
+    ///
+    /// With [`FuzzerCommand::Enumerate`], the fuzzer tests the values of the input corpus, followed by
+    /// the values generated by [`Mutator::ordered_arbitrary`] up to the maximum complexity, and stops
+    /// when the mutator cannot generate more values. This is useful to verify small input spaces,
+    /// such as those of integers, booleans, and enums, before fuzzing them randomly.
+    ///
+    /// The enumeration is only exhaustive if the mutator’s `ordered_arbitrary` method is. The mutators
+    /// of vectors, for example, generate random values after the first few ones and never run out of
+    /// values. In that case, the fuzzer only stops after the limits given to
+    /// [`self.stop_after_iterations(..)`](Self::stop_after_iterations) or
+    /// [`self.stop_after_duration(..)`](Self::stop_after_duration).
+    /// ```
+    /// # #![feature(no_coverage)]
+    /// use fuzzcheck::{Arguments, FuzzerCommand, ReasonForStopping};
+    /// use fuzzcheck::sensors_and_pools::{MaximiseObservationPool, StaticValueSensor};
+    ///
+    /// static mut COUNT: usize = 0;
+    /// fn test_function(x: &(u8, bool)) -> bool {
+    ///     unsafe { COUNT += 1 };
+    ///     *x != (217, true)
+    /// }
+    /// let sensor = unsafe { StaticValueSensor::new(&mut COUNT, 0) };
+    /// let pool = MaximiseObservationPool::<usize>::new("count");
+    /// let result = fuzzcheck::fuzz_test(test_function)
+    ///     .default_mutator()
+    ///     .serde_serializer()
+    ///     .sensor_and_pool(sensor, pool)
+    ///     .arguments(Arguments::for_internal_documentation_test())
+    ///     .command(FuzzerCommand::Enumerate)
+    ///     .launch();
+    ///
+    /// assert!(matches!(result.reason_for_stopping, ReasonForStopping::TestFailure((217, true))));
+    /// ```
     #[must_use]
     #[no_coverage]
     pub fn command(self, command: FuzzerCommand) -> Self {
@@ -655,6 +723,53 @@ where
         x.arguments.stop_after_first_failure = stop_after_first_test_failure;
         x
     }
+    /// Whether the integer and string constants of the fuzzed binary should be added to the
+    /// [dictionary](crate::mutators::dictionary) used by the mutators. It is `true` by default.
+    #[must_use]
+    #[no_coverage]
+    pub fn harvest_constants(self, harvest_constants: bool) -> Self {
+        let mut x = self;
+        x.arguments.harvest_constants = harvest_constants;
+        x
+    }
+    /// Whether the mutators can splice in the subvalues of any test case of the pool, instead
+    /// of only those of a single other test case. It is `false` by default.
+    ///
+    /// The subvalues of the corpus are indexed by type and complexity, and the index is updated
+    /// whenever a test case enters or leaves the pool.
+    #[must_use]
+    #[no_coverage]
+    pub fn corpus_wide_crossover(self, corpus_wide_crossover: bool) -> Self {
+        let mut x = self;
+        x.arguments.corpus_wide_crossover = corpus_wide_crossover;
+        x
+    }
+    /// Whether the [`CheckMutator`](FuzzerCommand::CheckMutator) command also fails when
+    /// `ordered_arbitrary` generates the same value twice. It is `false` by default.
+    #[must_use]
+    #[no_coverage]
+    pub fn check_repetitions(self, check_repetitions: bool) -> Self {
+        let mut x = self;
+        x.arguments.check_repetitions = check_repetitions;
+        x
+    }
+    /// The function or line of code toward which the fuzzer is directed. It is `None` by default.
+    ///
+    /// When a target is given, the test cases coming closest to it are prioritised, and the statistics
+    /// of the fuzzer show the closest distance to the target reached so far. It is only supported by the
+    /// default sensor and pool, whose code coverage sensor must observe the target. Otherwise, a
+    /// [`DistanceToTargetPool`] can be used directly.
+    ///
+    /// The distance to the target is measured in lines of source code, within the files containing the
+    /// target only. Reaching code in other files, such as the callers of the target, does not bring the
+    /// fuzzer any closer to it.
+    #[must_use]
+    #[no_coverage]
+    pub fn target(self, target: Option<CoverageTarget>) -> Self {
+        let mut x = self;
+        x.arguments.target = target;
+        x
+    }
     /// Launch the fuzz test!
     #[no_coverage]
     pub fn launch(self) -> FuzzingResult<V> {
@@ -665,18 +780,84 @@ where
             pool,
             sensor,
             arguments,
+            directed_sensor_and_pool,
             _phantom,
         } = self;
 
         mutator.initialize();
 
-        crate::fuzzer::launch(
-            Box::new(test_function),
-            mutator,
-            serializer,
-            Box::new((sensor, pool)),
-            arguments,
-        )
+        let sensor_and_pool: Box<dyn SensorAndPool> = if let Some(target) = &arguments.target
+            && matches!(arguments.command, FuzzerCommand::Fuzz | FuzzerCommand::Enumerate)
+        {
+            let directed_sensor_and_pool = directed_sensor_and_pool.unwrap_or_else(
+                #[no_coverage]
+                || panic!("A target can only be given to fuzz tests using the default sensor and pool. Otherwise, use a DistanceToTargetPool."),
+            );
+            directed_sensor_and_pool.finish(target, sensor, pool)
+        } else {
+            Box::new((sensor, pool))
+        };
+
+        crate::fuzzer::launch(Box::new(test_function), mutator, serializer, sensor_and_pool, arguments)
+    }
+}
+
+/// What is needed to direct the default sensor and pool toward a [`CoverageTarget`]
+///
+/// It is only created by the builder methods that already use a [`CodeCoverageSensor`], such that the fuzz
+/// tests compiled without code coverage instrumentation can still be linked.
+struct DirectedSensorAndPool<Sens, P> {
+    /// The location of each counter of the code coverage sensor wrapped by `Sens`
+    counter_locations: Vec<CounterLocation>,
+    combine: fn(Sens, P, DistanceToTargetPool) -> Box<dyn SensorAndPool>,
+}
+
+impl<Sens, P, O> DirectedSensorAndPool<Sens, P>
+where
+    Sens: Sensor<Observations = (<CodeCoverageSensor as Sensor>::Observations, O)>,
+    P: CompatibleWithObservations<Sens::Observations> + 'static,
+    O: 'static,
+{
+    #[no_coverage]
+    fn new(coverage_sensor: &CodeCoverageSensor) -> Self {
+        Self {
+            counter_locations: coverage_sensor.counter_locations(),
+            combine: Self::combine,
+        }
+    }
+
+    /// Adds a [`DistanceToTargetPool`] to the given pool, sharing the code coverage observations of the sensor
+    #[no_coverage]
+    fn combine(sensor: Sens, pool: P, target_pool: DistanceToTargetPool) -> Box<dyn SensorAndPool> {
+        let sensor = sensor.map(
+            #[no_coverage]
+            |(coverage, observations)| {
+                let target_observations = coverage.clone();
+                ((coverage, observations), target_observations)
+            },
+        );
+        // the directed pool is chosen twice as often as the original one
+        let pool = AndPool::<_, _, DifferentObservations>::new(pool, target_pool, 1.0, 2.0);
+        Box::new((sensor, pool))
+    }
+}
+
+impl<Sens, P> DirectedSensorAndPool<Sens, P> {
+    /// Combines the given sensor and pool with a [`DistanceToTargetPool`] prioritising the test cases that come
+    /// closest to the target
+    #[no_coverage]
+    fn finish(self, target: &CoverageTarget, sensor: Sens, pool: P) -> Box<dyn SensorAndPool> {
+        let distances = distances_to_target(&self.counter_locations, target);
+        if !distances.values().any(
+            #[no_coverage]
+            |&distance| distance == 0,
+        ) {
+            panic!(
+                "No region of code observed by the code coverage sensor matches the target {}",
+                target
+            );
+        }
+        (self.combine)(sensor, pool, DistanceToTargetPool::new("target", distances))
     }
 }
 
@@ -705,6 +886,7 @@ pub type BasicAndDiversePool = AndPool<
     MaximiseObservationPool<usize>,
     DifferentObservations,
 >;
+pub type BasicAndHitCountBucketsPool = AndPool<SimplestToActivateCounterPool, HitCountBucketsPool, SameObservations>;
 pub type BasicAndMaxHitsPool = AndPool<
     AndPool<SimplestToActivateCounterPool, MaximiseEachCounterPool, SameObservations>,
     MaximiseObservationPool<u64>,
@@ -748,8 +930,11 @@ pub fn max_cov_hits_sensor_and_pool() -> SensorAndPoolBuilder<MaxHitsSensor, Max
 
 /// Create the initial [sensor and pool builder](SensorAndPoolBuilder)
 ///
+/// Use [`.with_power_schedule(..)`](SensorAndPoolBuilder::<BasicSensor, BasicPool>::with_power_schedule) to change how
+/// the test cases of the pool are prioritised.
 /// Use [`.find_most_diverse_set_of_test_cases()`](SensorAndPoolBuilder::<BasicPool>::find_most_diverse_set_of_test_cases)
 /// or [`.find_test_cases_repeatedly_hitting_coverage_counters()`](SensorAndPoolBuilder::<BasicPool>::find_test_cases_repeatedly_hitting_coverage_counters)
+/// or [`.find_test_cases_reaching_new_hit_count_buckets()`](SensorAndPoolBuilder::<BasicPool>::find_test_cases_reaching_new_hit_count_buckets)
 /// on the result to augment the pool. Or use [`.finish()`](SensorAndPoolBuilder::finish) to obtain the concrete sensor and pool.
 #[no_coverage]
 pub fn basic_sensor_and_pool() -> SensorAndPoolBuilder<BasicSensor, BasicPool> {
@@ -781,9 +966,7 @@ pub fn basic_sensor_and_pool_with_custom_filter(
 /// use [`.finish()`](SensorAndPoolBuilder::finish) to obtain the concrete sensor and pool.
 #[no_coverage]
 pub fn default_sensor_and_pool() -> SensorAndPoolBuilder<DiverseAndMaxHitsSensor, BasicAndDiverseAndMaxHitsPool> {
-    basic_sensor_and_pool()
-        .find_most_diverse_set_of_test_cases(20)
-        .find_test_cases_repeatedly_hitting_coverage_counters()
+    augment_basic_sensor_and_pool(basic_sensor_and_pool())
 }
 
 /// Like [`default_sensor_and_pool`], but uses a closure to determine which function should
@@ -792,7 +975,15 @@ pub fn default_sensor_and_pool() -> SensorAndPoolBuilder<DiverseAndMaxHitsSensor
 pub fn default_sensor_and_pool_with_custom_filter(
     keep: impl Fn(&Path, &str) -> bool,
 ) -> SensorAndPoolBuilder<DiverseAndMaxHitsSensor, BasicAndDiverseAndMaxHitsPool> {
-    basic_sensor_and_pool_with_custom_filter(keep)
+    augment_basic_sensor_and_pool(basic_sensor_and_pool_with_custom_filter(keep))
+}
+
+/// Augment the basic sensor and pool into the default ones
+#[no_coverage]
+fn augment_basic_sensor_and_pool(
+    basic: SensorAndPoolBuilder<BasicSensor, BasicPool>,
+) -> SensorAndPoolBuilder<DiverseAndMaxHitsSensor, BasicAndDiverseAndMaxHitsPool> {
+    basic
         .find_most_diverse_set_of_test_cases(20)
         .find_test_cases_repeatedly_hitting_coverage_counters()
 }
@@ -831,6 +1022,18 @@ where
 }
 
 impl SensorAndPoolBuilder<BasicSensor, BasicPool> {
+    /// Use the given [power schedule](PowerSchedule) to choose which test case of the basic pool is mutated next.
+    ///
+    /// By default, the [shared](PowerSchedule::Shared) schedule is used. The [entropic](PowerSchedule::Entropic)
+    /// schedule favours the test cases whose mutations recently found new test cases and keep activating
+    /// rarely seen counters.
+    #[no_coverage]
+    pub fn with_power_schedule(self, schedule: PowerSchedule) -> Self {
+        SensorAndPoolBuilder {
+            sensor: self.sensor,
+            pool: self.pool.with_power_schedule(schedule),
+        }
+    }
     /// Augment the current pool such that it also tries to find a fixed-length set of test cases which, together,
     /// trigger the most code coverage.
     ///
@@ -896,6 +1099,22 @@ impl SensorAndPoolBuilder<BasicSensor, BasicPool> {
             );
         SensorAndPoolBuilder { sensor, pool }
     }
+    /// Augment the current pool such that it also keeps the simplest test case reaching each bucket of hit
+    /// counts of each coverage counter, as AFL does. See [`HitCountBucketsPool`] for more details.
+    #[no_coverage]
+    pub fn find_test_cases_reaching_new_hit_count_buckets(
+        self,
+    ) -> SensorAndPoolBuilder<BasicSensor, BasicAndHitCountBucketsPool> {
+        let pool = self.pool.and(
+            HitCountBucketsPool::new("hit_count_buckets"),
+            Some(0.1),
+            SameObservations,
+        );
+        SensorAndPoolBuilder {
+            sensor: self.sensor,
+            pool,
+        }
+    }
 }
 impl SensorAndPoolBuilder<DiverseSensor, BasicAndDiversePool> {
     /// Augment the current pool such that it also tries to find test cases repeatedly hitting the same regions of code.
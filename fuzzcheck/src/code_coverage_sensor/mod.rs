@@ -1,9 +1,11 @@
 //! Code coverage analysis
 
 mod leb128;
-mod llvm_coverage;
+pub(crate) mod llvm_coverage;
+pub(crate) mod read_only_data;
 #[cfg(feature = "serde_json_serializer")]
 mod serialized;
+pub(crate) mod target;
 
 use std::collections::{BTreeSet, HashMap};
 use std::convert::TryFrom;
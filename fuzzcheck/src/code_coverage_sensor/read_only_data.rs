@@ -0,0 +1,247 @@
+//! Finding the constants that the machine code of the crate being tested refers to
+//!
+//! The literal constants of a binary are stored in its read-only data sections, which contain the
+//! constants of every crate linked into the binary. To keep only the ones of the crate being tested,
+//! the code of its functions is searched for instructions computing the address of a piece of
+//! read-only data.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::path::Path;
+
+use object::{Architecture, Object, ObjectSection, ObjectSymbol, SectionKind, SymbolKind};
+
+use super::llvm_coverage::{read_covfun, read_covmap, read_prf_names, CovMapSection, ReadCovMapError};
+
+/// The names of the sections that may contain the literal constants of the binary, depending on the platform.
+const READ_ONLY_DATA_SECTIONS: [&str; 4] = [".rodata", ".rdata", "__const", "__cstring"];
+
+/// Returns the pieces of read-only data that the functions of the crate being tested refer to,
+/// in the binary at the given path, along with their addresses.
+///
+/// The functions of the crate being tested are the instrumented functions whose files are given by
+/// relative paths, as for [`CodeCoverageSensor::observing_only_files_from_current_dir`](super::CodeCoverageSensor::observing_only_files_from_current_dir).
+/// Each piece starts at an address that they refer to, and ends at the next address referred to by
+/// any function of the binary.
+///
+/// The references are only recognised on x86-64 and AArch64. On other architectures, nothing is returned.
+#[no_coverage]
+pub(crate) fn get_target_read_only_data(path: &Path) -> Result<Vec<(u64, Vec<u8>)>, ReadCovMapError> {
+    let bin_data = std::fs::read(path).map_err(
+        #[no_coverage]
+        |_| ReadCovMapError::CannotReadObjectFile {
+            path: path.to_path_buf(),
+        },
+    )?;
+    let obj_file = object::File::parse(&*bin_data).map_err(
+        #[no_coverage]
+        |_| ReadCovMapError::CannotReadObjectFile {
+            path: path.to_path_buf(),
+        },
+    )?;
+    let section_data = #[no_coverage]
+    |name: &str, section: CovMapSection| {
+        obj_file
+            .section_by_name(name)
+            .and_then(
+                #[no_coverage]
+                |section| section.data().ok(),
+            )
+            .ok_or(ReadCovMapError::CannotFindSection { section })
+    };
+    let covmap = read_covmap(section_data("__llvm_covmap", CovMapSection::CovMap)?, &mut 0)?;
+    let covfun = read_covfun(section_data("__llvm_covfun", CovMapSection::CovFun)?)?;
+    let prf_names = read_prf_names(section_data("__llvm_prf_names", CovMapSection::PrfNames)?, &mut 0)?;
+    let prf_names = prf_names
+        .into_iter()
+        .map(
+            #[no_coverage]
+            |name| {
+                let name_md5 = md5::compute(name.as_bytes());
+                let name_md5 = i64::from_le_bytes(<[u8; 8]>::try_from(&name_md5[0..8]).unwrap());
+                (name_md5, name)
+            },
+        )
+        .collect::<HashMap<_, _>>();
+
+    let mut target_functions = HashSet::new();
+    for function in covfun {
+        if let Some(name) = prf_names.get(&function.header.id.name_md5)
+            && let Some(filenames) = covmap.get(&function.header.hash_translation_unit)
+            && function.file_id_mapping.filename_indices.iter().all(
+                #[no_coverage]
+                |&idx| filenames.get(idx).map_or(false, #[no_coverage] |file| Path::new(file).is_relative()),
+            )
+        {
+            // the names of local functions are prefixed by the name of their file
+            let name = name.rsplit(';').next().unwrap();
+            target_functions.insert(name.to_owned());
+        }
+    }
+
+    let architecture = obj_file.architecture();
+    let mut references = BTreeSet::new();
+    for section in obj_file.sections() {
+        if section.kind() == SectionKind::Text && let Ok(code) = section.data() {
+            references.extend(referenced_addresses(architecture, section.address(), code));
+        }
+    }
+    let mut target_references = BTreeSet::new();
+    for symbol in obj_file.symbols() {
+        if symbol.kind() != SymbolKind::Text {
+            continue;
+        }
+        let is_target = symbol.name().map_or(
+            false,
+            #[no_coverage]
+            |name| {
+                // Mach-O symbols start with an additional underscore
+                target_functions.contains(name)
+                    || name.strip_prefix('_').map_or(
+                        false,
+                        #[no_coverage]
+                        |name| target_functions.contains(name),
+                    )
+            },
+        );
+        if is_target
+            && let Some(section_idx) = symbol.section_index()
+            && let Ok(section) = obj_file.section_by_index(section_idx)
+            && let Ok(Some(code)) = section.data_range(symbol.address(), symbol.size())
+        {
+            target_references.extend(referenced_addresses(architecture, symbol.address(), code));
+        }
+    }
+
+    let mut sections = vec![];
+    for section_name in READ_ONLY_DATA_SECTIONS {
+        if let Some(section) = obj_file.section_by_name(section_name) && let Ok(data) = section.data() {
+            sections.push((section.address(), data));
+        }
+    }
+    Ok(referenced_data(&sections, &references, &target_references))
+}
+
+/// Returns the pieces of the given sections that start at one of `target_references` and end
+/// at the next address of `references`, along with their addresses
+#[no_coverage]
+fn referenced_data(
+    sections: &[(u64, &[u8])],
+    references: &BTreeSet<u64>,
+    target_references: &BTreeSet<u64>,
+) -> Vec<(u64, Vec<u8>)> {
+    let mut data = vec![];
+    for &(address, content) in sections {
+        let end_address = address + content.len() as u64;
+        for &start in target_references.range(address..end_address) {
+            let end = references
+                .range(start + 1..end_address)
+                .next()
+                .copied()
+                .unwrap_or(end_address);
+            data.push((
+                start,
+                content[(start - address) as usize..(end - address) as usize].to_vec(),
+            ));
+        }
+    }
+    data
+}
+
+/// Returns the addresses computed by the instructions of the given machine code, which starts at
+/// `code_address`
+#[no_coverage]
+fn referenced_addresses(architecture: Architecture, code_address: u64, code: &[u8]) -> Vec<u64> {
+    let mut addresses = vec![];
+    match architecture {
+        Architecture::X86_64 => {
+            // lea r64, [rip + disp32], encoded as REX.W 8D modrm disp32
+            for idx in 3..code.len().saturating_sub(3) {
+                if code[idx - 3] & 0xFB == 0x48 && code[idx - 2] == 0x8D && code[idx - 1] & 0xC7 == 0x05 {
+                    let disp = i32::from_le_bytes(<[u8; 4]>::try_from(&code[idx..idx + 4]).unwrap());
+                    let next_instruction = code_address + idx as u64 + 4;
+                    addresses.push(next_instruction.wrapping_add(disp as i64 as u64));
+                }
+            }
+        }
+        Architecture::Aarch64 => {
+            // adrp xd, page followed by add xd, xd, #offset
+            let instructions = code
+                .chunks_exact(4)
+                .map(
+                    #[no_coverage]
+                    |bytes| u32::from_le_bytes(<[u8; 4]>::try_from(bytes).unwrap()),
+                )
+                .collect::<Vec<_>>();
+            for (idx, window) in instructions.windows(2).enumerate() {
+                let (adrp, add) = (window[0], window[1]);
+                let rd = adrp & 0x1F;
+                if adrp & 0x9F00_0000 != 0x9000_0000
+                    || add & 0xFFC0_0000 != 0x9100_0000
+                    || (add >> 5) & 0x1F != rd
+                    || add & 0x1F != rd
+                {
+                    continue;
+                }
+                let imm = (((adrp >> 5) & 0x7FFFF) << 2) | ((adrp >> 29) & 0b11);
+                // sign-extend the 21-bit immediate, which is a number of 4KiB pages
+                let pages = ((imm << 11) as i32 >> 11) as i64;
+                let pc = code_address + 4 * idx as u64;
+                let page = (pc & !0xFFF).wrapping_add((pages << 12) as u64);
+                addresses.push(page + ((add >> 10) & 0xFFF) as u64);
+            }
+        }
+        _ => {}
+    }
+    addresses
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use object::Architecture;
+
+    use super::{referenced_addresses, referenced_data};
+
+    #[test]
+    #[no_coverage]
+    fn test_referenced_addresses() {
+        // lea rax, [rip + 0x10]; lea r9, [rip - 0x20]; mov rax, [rip + 0x10]
+        let code = [
+            0x48, 0x8D, 0x05, 0x10, 0x00, 0x00, 0x00, 0x4C, 0x8D, 0x0D, 0xE0, 0xFF, 0xFF, 0xFF, 0x48, 0x8B, 0x05, 0x10,
+            0x00, 0x00, 0x00,
+        ];
+        assert_eq!(
+            referenced_addresses(Architecture::X86_64, 0x1000, &code),
+            vec![0x1007 + 0x10, 0x100E - 0x20]
+        );
+
+        // adrp x1, #0x2000; add x1, x1, #0x20; adrp x2, #-0x1000; add x3, x2, #0x8
+        let code = [0xD0000001u32, 0x91008021, 0xF0FFFFE2, 0x91002043]
+            .iter()
+            .flat_map(
+                #[no_coverage]
+                |x| x.to_le_bytes(),
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(
+            referenced_addresses(Architecture::Aarch64, 0x1004, &code),
+            vec![0x1000 + 0x2000 + 0x20]
+        );
+
+        assert!(referenced_addresses(Architecture::Riscv64, 0x1000, &[0x48, 0x8D, 0x05, 0, 0, 0, 0]).is_empty());
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_referenced_data() {
+        let section = b"BEGINcalled unwrap\x00END";
+        let references = BTreeSet::from_iter([0x100, 0x105, 0x113]);
+        let target_references = BTreeSet::from_iter([0x100, 0x113, 0x200]);
+        assert_eq!(
+            referenced_data(&[(0x100, section)], &references, &target_references),
+            [(0x100, b"BEGIN".to_vec()), (0x113, b"END".to_vec())]
+        );
+    }
+}
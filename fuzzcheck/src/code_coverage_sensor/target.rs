@@ -0,0 +1,227 @@
+//! Locating the counters of a [`CodeCoverageSensor`] and measuring their distance to a [`CoverageTarget`]
+
+use std::path::PathBuf;
+
+use ahash::AHashMap;
+use fuzzcheck_common::arg::CoverageTarget;
+
+use super::llvm_coverage::{FunctionRecord, MappingRegion};
+use super::CodeCoverageSensor;
+
+/// The location of a counter of the code coverage sensor in the source code
+#[derive(Debug, Clone)]
+pub(crate) struct CounterLocation {
+    /// The demangled name of the function containing the counter
+    pub function: String,
+    /// The file, first line, and last line of each code region of the counter
+    pub regions: Vec<(PathBuf, usize, usize)>,
+}
+
+impl CodeCoverageSensor {
+    /// Returns the distance between the counters of the sensor and the given target, indexed by counter.
+    ///
+    /// The distance of a counter covering the target is 0. Otherwise, it is one more than the number
+    /// of lines separating the regions of the counter from the regions of the target. Only the counters
+    /// located in the same files as the target are given a distance.
+    #[no_coverage]
+    pub fn distances_to_target(&self, target: &CoverageTarget) -> AHashMap<usize, usize> {
+        distances_to_target(&self.counter_locations(), target)
+    }
+
+    /// Returns the location of each counter, in the same order as the indices of the observations
+    #[no_coverage]
+    pub(crate) fn counter_locations(&self) -> Vec<CounterLocation> {
+        let mut locations = vec![];
+        for coverage in self.coverage.iter() {
+            let f_record = &coverage.function_record;
+            let location = #[no_coverage]
+            |regions: &[MappingRegion]| CounterLocation {
+                function: f_record.name_function.clone(),
+                regions: regions
+                    .iter()
+                    .filter_map(
+                        #[no_coverage]
+                        |region| Some((region_file(f_record, region)?, region.line_start, region.line_end)),
+                    )
+                    .collect(),
+            };
+            // same order as in the observations: the single counters first, then the expressions
+            for (e, regions) in f_record.expressions.iter() {
+                if e.add_terms.len() == 1 && e.sub_terms.is_empty() {
+                    locations.push(location(regions));
+                }
+            }
+            for (e, regions) in f_record.expressions.iter() {
+                if !(e.add_terms.len() == 1 && e.sub_terms.is_empty()) && !e.add_terms.is_empty() {
+                    locations.push(location(regions));
+                }
+            }
+        }
+        locations
+    }
+}
+
+#[no_coverage]
+fn region_file(f_record: &FunctionRecord, region: &MappingRegion) -> Option<PathBuf> {
+    let file_idx = f_record.file_id_mapping.filename_indices.iter().position(
+        #[no_coverage]
+        |idx| *idx == region.filename_index,
+    )?;
+    f_record.filenames.get(file_idx).cloned()
+}
+
+/// Returns the distance to the target of each counter located in the same file as the target
+///
+/// For a line target, only the innermost regions spanning the line are considered to be the target.
+/// Otherwise, reaching the function containing the line would be enough to reach the target.
+#[no_coverage]
+pub(crate) fn distances_to_target(locations: &[CounterLocation], target: &CoverageTarget) -> AHashMap<usize, usize> {
+    let target_counters: Vec<usize> = match target {
+        CoverageTarget::Function(name) => (0..locations.len())
+            .filter(
+                #[no_coverage]
+                |&i| locations[i].function.contains(name.as_str()),
+            )
+            .collect(),
+        CoverageTarget::Line { file, line } => {
+            let span_covering_line = #[no_coverage]
+            |location: &CounterLocation| {
+                location
+                    .regions
+                    .iter()
+                    .filter(
+                        #[no_coverage]
+                        |(f, start, end)| f.ends_with(file) && (*start..=*end).contains(line),
+                    )
+                    .map(
+                        #[no_coverage]
+                        |(_, start, end)| end - start,
+                    )
+                    .min()
+            };
+            let smallest_span = locations.iter().filter_map(span_covering_line).min();
+            (0..locations.len())
+                .filter(
+                    #[no_coverage]
+                    |&i| smallest_span.is_some() && span_covering_line(&locations[i]) == smallest_span,
+                )
+                .collect()
+        }
+    };
+    let target_regions: Vec<&(PathBuf, usize, usize)> = target_counters
+        .iter()
+        .flat_map(
+            #[no_coverage]
+            |&i| locations[i].regions.iter(),
+        )
+        .collect();
+
+    let mut distances = AHashMap::new();
+    for &i in &target_counters {
+        distances.insert(i, 0);
+    }
+    for (i, location) in locations.iter().enumerate() {
+        if distances.contains_key(&i) {
+            continue;
+        }
+        let distance = location
+            .regions
+            .iter()
+            .flat_map(
+                #[no_coverage]
+                |(file, start, end)| {
+                    target_regions
+                        .iter()
+                        .filter(
+                            #[no_coverage]
+                            move |(target_file, _, _)| target_file == file,
+                        )
+                        .map(
+                            #[no_coverage]
+                            move |(_, target_start, target_end)| {
+                                1 + target_start.saturating_sub(*end).max(start.saturating_sub(*target_end))
+                            },
+                        )
+                },
+            )
+            .min();
+        if let Some(distance) = distance {
+            distances.insert(i, distance);
+        }
+    }
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use fuzzcheck_common::arg::CoverageTarget;
+
+    use super::{distances_to_target, CounterLocation};
+
+    #[no_coverage]
+    fn location(function: &str, regions: &[(&str, usize, usize)]) -> CounterLocation {
+        CounterLocation {
+            function: function.to_string(),
+            regions: regions
+                .iter()
+                .map(
+                    #[no_coverage]
+                    |&(file, start, end)| (PathBuf::from(file), start, end),
+                )
+                .collect(),
+        }
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_distances_to_target() {
+        let locations = vec![
+            location("parser::parse::h0123", &[("src/parser.rs", 10, 30)]),
+            location("parser::parse::h0123", &[("src/parser.rs", 14, 16)]),
+            location("parser::parse::h0123", &[("src/parser.rs", 20, 22)]),
+            location("parser::lex::h4567", &[("src/parser.rs", 40, 50)]),
+            location("main::h89ab", &[("src/main.rs", 1, 5)]),
+        ];
+
+        let target = CoverageTarget::Line {
+            file: PathBuf::from("parser.rs"),
+            line: 15,
+        };
+        let distances = distances_to_target(&locations, &target);
+        assert_eq!(distances.get(&1), Some(&0));
+        // the function body spans the target line but does not cover it
+        assert_eq!(distances.get(&0), Some(&1));
+        assert_eq!(distances.get(&2), Some(&5));
+        assert_eq!(distances.get(&3), Some(&25));
+        assert_eq!(distances.get(&4), None);
+
+        let target = CoverageTarget::Function("parser::lex".to_string());
+        let distances = distances_to_target(&locations, &target);
+        assert_eq!(distances.get(&3), Some(&0));
+        assert_eq!(distances.get(&0), Some(&11));
+        assert_eq!(distances.get(&2), Some(&19));
+        assert_eq!(distances.get(&4), None);
+
+        let target = CoverageTarget::Function("does_not_exist".to_string());
+        assert!(distances_to_target(&locations, &target).is_empty());
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_distances_to_target_same_file_only() {
+        // the caller of the target is in another file, at the same lines as the target
+        let locations = vec![
+            location("parser::parse::h0123", &[("src/parser.rs", 10, 12)]),
+            location("main::h89ab", &[("src/main.rs", 10, 12)]),
+            location("main::h89ab", &[("src/main.rs", 11, 11), ("src/parser.rs", 20, 20)]),
+        ];
+        let target = CoverageTarget::Function("parser::parse".to_string());
+        let distances = distances_to_target(&locations, &target);
+        assert_eq!(distances.get(&0), Some(&0));
+        assert_eq!(distances.get(&1), None);
+        // only the regions of a counter located in the file of the target are measured
+        assert_eq!(distances.get(&2), Some(&9));
+    }
+}
@@ -123,6 +123,15 @@ impl<T> Slab<T> {
         }
         SlabKey::new(idx)
     }
+    /// The inverse of [`get_nth_key`](Slab::get_nth_key)
+    #[no_coverage]
+    pub fn position_of_key(&self, key: SlabKey<T>) -> usize {
+        key.key
+            - self.available_slots.partition_point(
+                #[no_coverage]
+                |&i| i < key.key,
+            )
+    }
 
     #[no_coverage]
     pub fn insert(&mut self, x: T) -> SlabKey<T> {
@@ -12,11 +12,13 @@ use fuzzcheck_common::{FuzzerEvent, FuzzerStats};
 use libc::{SIGABRT, SIGALRM, SIGBUS, SIGFPE, SIGINT, SIGSEGV, SIGTERM, SIGTRAP};
 
 use crate::data_structures::RcSlab;
+use crate::lineage::InputOrigin;
+use crate::mutators::conformance::ConformanceChecker;
 use crate::sensors_and_pools::{
     AndSensorAndPool, NoopSensor, TestFailure, TestFailurePool, TestFailureSensor, UnitPool, TEST_FAILURE,
 };
 use crate::signals_handler::set_signal_handlers;
-use crate::subvalue_provider::{CrossoverSubValueProvider, Generation, SubValueProviderId};
+use crate::subvalue_provider::{CorpusSubValueProvider, CrossoverSubValueProvider, Generation, SubValueProviderId};
 use crate::traits::{CorpusDelta, Mutator, SaveToStatsFolder, SensorAndPool, Serializer};
 use crate::world::World;
 use crate::{CSVField, SubValueProvider, ToCSV};
@@ -30,6 +32,10 @@ static UPDATE_CORPUS_ERROR: &str = "the corpus could not be updated on the file
 
 static mut DID_FIND_ANY_TEST_FAILURE: bool = false;
 
+/// The number of values in a row that are more complex than the maximum complexity after which
+/// the enumeration of the values generated by the mutator stops
+const MAX_CONSECUTIVE_TOO_COMPLEX_VALUES: usize = 10_000;
+
 /// The result of a fuzz test, if it ends.
 ///
 /// It contains two fields:
@@ -56,7 +62,7 @@ pub enum ReasonForStopping<T> {
 /// The index to a test case in the fuzzer’s storage.
 #[cfg_attr(feature = "serde_json_serializer", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct PoolStorageIndex(usize);
+pub struct PoolStorageIndex(pub(crate) usize);
 
 // #[cfg(test)]
 impl PoolStorageIndex {
@@ -79,6 +85,8 @@ where
 {
     input: FuzzedInput<T, M>,
     subvalues: CrossoverSubValueProvider<T, M>,
+    /// The generation of the test case in the provenance tree, see [`InputOrigin::generation`]
+    lineage_generation: usize,
 }
 
 /**
@@ -151,13 +159,16 @@ struct FuzzerState<T: Clone + 'static, M: Mutator<T>> {
     mutator: M,
     sensor_and_pool: Box<dyn SensorAndPool>,
     pool_storage: RcSlab<FuzzedInputAndSubValueProvider<T, M>>,
+    /// The subvalues of every test case in `pool_storage`, if corpus-wide crossover is enabled
+    corpus_subvalues: Option<CorpusSubValueProvider>,
     /// The step given to the mutator when the fuzzer wants to create a new arbitrary test case
     arbitrary_step: M::ArbitraryStep,
     /// The index of the test case that is being tested
     input_idx: FuzzerInputIndex<FuzzedInput<T, M>>,
+    /// How the test case that is being tested was obtained
+    input_origin: InputOrigin,
     /// Various statistics about the fuzzer run
     fuzzer_stats: FuzzerStats,
-
     settings: Arguments,
     serializer: Box<dyn Serializer<Value = T>>,
     /// The world handles effects
@@ -215,6 +226,7 @@ where
     fn save_to_stats_folder(&self) -> Vec<(std::path::PathBuf, Vec<u8>)> {
         let mut contents = self.sensor_and_pool.save_to_stats_folder();
         contents.extend(self.world.save_to_stats_folder());
+        contents.extend(self.fuzzer_stats.mutations.save_to_stats_folder());
         contents
     }
 }
@@ -241,7 +253,9 @@ where
                     let input = input.new_source(&self.mutator, Generation(0));
                     let cplx = input.complexity(&self.mutator);
                     let content = self.serializer.to_data(&input.value);
-                    let _ = self.world.save_artifact(content, cplx, self.serializer.extension());
+                    let _ =
+                        self.world
+                            .save_artifact(content, cplx, self.serializer.extension(), Some(self.input_origin));
                     self.write_stats().expect(WRITE_STATS_ERROR);
                     exit(TerminationStatus::Crash as i32);
                 } else {
@@ -309,13 +323,19 @@ where
         world: World,
     ) -> Self {
         let arbitrary_step = mutator.default_arbitrary_step();
+        let corpus_subvalues = settings.corpus_wide_crossover.then(
+            #[no_coverage]
+            || CorpusSubValueProvider::new(usize::MAX),
+        );
         Fuzzer {
             state: FuzzerState {
                 sensor_and_pool,
                 pool_storage: RcSlab::new(),
+                corpus_subvalues,
                 mutator,
                 arbitrary_step,
                 input_idx: FuzzerInputIndex::None,
+                input_origin: InputOrigin::new("arbitrary"),
                 fuzzer_stats: FuzzerStats::default(),
                 settings,
                 serializer,
@@ -335,7 +355,9 @@ where
                     mutator,
                     sensor_and_pool,
                     pool_storage,
+                    corpus_subvalues,
                     input_idx,
+                    input_origin,
                     fuzzer_stats,
                     serializer,
                     world,
@@ -405,11 +427,13 @@ where
             }
         }
         sensor_and_pool.stop_recording();
+        // the generation of a test case is the number of runs of the fuzzer, including its own
+        let generation = Generation(fuzzer_stats.total_number_of_runs + 1);
         if test_failure && self.state.settings.stop_after_first_failure {
             let serialized_input = serializer.to_data(&input.value);
             self.state
                 .world
-                .save_artifact(serialized_input, cplx, serializer.extension())
+                .save_artifact(serialized_input, cplx, serializer.extension(), Some(*input_origin))
                 .expect(SAVE_ARTIFACTS_ERROR);
             return Err(ReasonForStopping::TestFailure(input.value.clone()));
         }
@@ -418,14 +442,16 @@ where
 
         let input_id = PoolStorageIndex(pool_storage.next_slot());
 
+        sensor_and_pool.set_parent(input_origin.parent.map(PoolStorageIndex));
         let deltas = sensor_and_pool.process(input_id, cplx);
+        let add_ref_count = deltas.iter().fold(
+            0,
+            #[no_coverage]
+            |acc, delta| if delta.add { acc + 1 } else { acc },
+        );
+        fuzzer_stats.mutations.record(input_origin.mutation, add_ref_count > 0);
 
         if !deltas.is_empty() {
-            let add_ref_count = deltas.iter().fold(
-                0,
-                #[no_coverage]
-                |acc, delta| if delta.add { acc + 1 } else { acc },
-            );
             update_fuzzer_stats(fuzzer_stats, world);
             let event = CorpusDelta::fuzzer_event(&deltas);
             let content = if add_ref_count > 0 {
@@ -434,11 +460,10 @@ where
                 vec![]
             };
             world
-                .update_corpus(input_id, content, &deltas, serializer.extension())
+                .update_corpus(input_id, content, &deltas, serializer.extension(), *input_origin)
                 .expect(UPDATE_CORPUS_ERROR);
             world.report_event(event, Some((fuzzer_stats, sensor_and_pool.stats().as_ref())));
             if add_ref_count > 0 {
-                let generation = Generation(fuzzer_stats.total_number_of_runs);
                 let input = input.new_source(mutator, generation);
                 // check that the mutator's handling of the complexity is correct
                 let serialised = String::from_utf8(serializer.to_data(&input.value)).unwrap();
@@ -476,13 +501,27 @@ where
                     &input.cache,
                     mutator,
                 );
-                let stored_input = FuzzedInputAndSubValueProvider { input, subvalues };
+                let stored_input = FuzzedInputAndSubValueProvider {
+                    input,
+                    subvalues,
+                    lineage_generation: input_origin.generation,
+                };
                 let storage_idx_2 = pool_storage.insert(stored_input, add_ref_count);
                 assert_eq!(storage_idx_1, storage_idx_2);
+                if let Some(corpus_subvalues) = corpus_subvalues {
+                    corpus_subvalues.add(storage_idx_2, &pool_storage[storage_idx_2].subvalues);
+                }
             }
             for delta in deltas {
                 for r in delta.remove {
                     pool_storage.remove(r.0);
+                    // the test case is dropped when its slot is reused, so its subvalues must be removed now
+                    if pool_storage.get_mut(r.0).is_none() {
+                        world.evict_from_lineage(r);
+                        if let Some(corpus_subvalues) = corpus_subvalues {
+                            corpus_subvalues.remove(r.0);
+                        }
+                    }
                 }
             }
         }
@@ -493,14 +532,19 @@ where
     #[no_coverage]
     fn get_input_and_subvalue_provider<'a>(
         pool_storage: &'a mut RcSlab<FuzzedInputAndSubValueProvider<T, M>>,
+        corpus_subvalues: Option<&'a CorpusSubValueProvider>,
         sensor_and_pool: &mut dyn SensorAndPool,
         rng: &fastrand::Rng,
         idx: PoolStorageIndex,
-    ) -> (&'a mut FuzzedInput<T, M>, &'a (impl SubValueProvider + 'a)) {
+    ) -> (&'a mut FuzzedInput<T, M>, &'a dyn SubValueProvider) {
+        if let Some(corpus_subvalues) = corpus_subvalues {
+            // crossover with any test case of the pool, including the mutated one
+            return (&mut pool_storage[idx.0].input, corpus_subvalues);
+        }
         let idx_cross = sensor_and_pool.get_random_index().unwrap();
 
         if idx == idx_cross || rng.u8(..5) == 0 {
-            let FuzzedInputAndSubValueProvider { input, subvalues } = &mut pool_storage[idx.0];
+            let FuzzedInputAndSubValueProvider { input, subvalues, .. } = &mut pool_storage[idx.0];
             (input, subvalues)
         } else {
             // crossover of two different test cases
@@ -514,8 +558,10 @@ where
     fn process_next_input(&mut self) -> Result<(), ReasonForStopping<T>> {
         let FuzzerState {
             pool_storage,
+            corpus_subvalues,
             sensor_and_pool,
             input_idx,
+            input_origin,
             mutator,
             settings,
             rng,
@@ -526,12 +572,19 @@ where
 
         if let Some(idx) = sensor_and_pool.get_random_index() {
             *input_idx = FuzzerInputIndex::Pool(idx);
-            let (input, subvalue_provider) =
-                Self::get_input_and_subvalue_provider(pool_storage, sensor_and_pool.as_mut(), rng, idx);
+            let parent_generation = pool_storage[idx.0].lineage_generation;
+            let (input, subvalue_provider) = Self::get_input_and_subvalue_provider(
+                pool_storage,
+                corpus_subvalues.as_ref(),
+                sensor_and_pool.as_mut(),
+                rng,
+                idx,
+            );
             let generation = input.generation;
             if let Some((unmutate_token, complexity)) =
                 input.mutate(mutator, subvalue_provider, settings.max_input_cplx)
             {
+                *input_origin = InputOrigin::mutated(idx.0, parent_generation, mutator.mutation_kind(&unmutate_token));
                 //drop(subvalue_provider);
                 if complexity < self.state.settings.max_input_cplx {
                     self.test_and_process_input(complexity)?;
@@ -552,6 +605,7 @@ where
             }
         } else if let Some((input, cplx)) = self.state.arbitrary_input() {
             self.state.input_idx = FuzzerInputIndex::Temporary(input);
+            self.state.input_origin = InputOrigin::new("arbitrary");
 
             if cplx < self.state.settings.max_input_cplx {
                 self.test_and_process_input(cplx)?;
@@ -567,9 +621,11 @@ where
         }
     }
 
+    /// Tests the values of the input corpus followed by the first values generated by the mutator,
+    /// whose origin is recorded as `arbitrary_origin`
     #[no_coverage]
-    fn process_initial_inputs(&mut self) -> Result<(), ReasonForStopping<T>> {
-        let mut inputs: Vec<FuzzedInput<T, M>> = self
+    fn process_initial_inputs(&mut self, arbitrary_origin: &'static str) -> Result<(), ReasonForStopping<T>> {
+        let mut inputs: Vec<(FuzzedInput<T, M>, &'static str)> = self
             .state
             .world
             .read_input_corpus()
@@ -581,28 +637,32 @@ where
                     let value = self.state.serializer.from_data(&value)?;
                     let cache = self.state.mutator.validate_value(&value)?;
                     let mutation_step = self.state.mutator.default_mutation_step(&value, &cache);
-                    Some(FuzzedInput::new(value, cache, mutation_step, Generation(0)))
+                    Some((
+                        FuzzedInput::new(value, cache, mutation_step, Generation(0)),
+                        "input_corpus",
+                    ))
                 },
             )
             .collect();
 
         for _ in 0..100 {
             if let Some((input, _)) = self.state.arbitrary_input() {
-                inputs.push(input);
+                inputs.push((input, arbitrary_origin));
             } else {
                 break;
             }
         }
         inputs.retain(
             #[no_coverage]
-            |i| i.complexity(&self.state.mutator) <= self.state.settings.max_input_cplx,
+            |(i, _)| i.complexity(&self.state.mutator) <= self.state.settings.max_input_cplx,
         );
         // assert!(!inputs.is_empty());
 
         self.state.world.set_checkpoint_instant();
-        for input in inputs {
+        for (input, origin) in inputs {
             let cplx = input.complexity(&self.state.mutator);
             self.state.input_idx = FuzzerInputIndex::Temporary(input);
+            self.state.input_origin = InputOrigin::new(origin);
             self.test_and_process_input(cplx)?;
         }
 
@@ -616,7 +676,7 @@ where
             Some((&self.state.fuzzer_stats, self.state.sensor_and_pool.stats().as_ref())),
         );
         if !minify {
-            self.process_initial_inputs()?;
+            self.process_initial_inputs("arbitrary")?;
             self.state.world.report_event(
                 FuzzerEvent::DidReadCorpus,
                 Some((&self.state.fuzzer_stats, self.state.sensor_and_pool.stats().as_ref())),
@@ -644,6 +704,74 @@ where
             }
         }
     }
+
+    /// Tests the values of the input corpus, followed by every value generated by the mutator’s
+    /// `ordered_arbitrary` method up to the maximum complexity.
+    ///
+    /// The enumeration ends when the mutator cannot generate more values, or when it generated
+    /// `MAX_CONSECUTIVE_TOO_COMPLEX_VALUES` values in a row that are more complex than the
+    /// maximum complexity. Some mutators, such as those of vectors, generate random values after
+    /// the first few ones and never run out of values, in which case the enumeration only stops
+    /// after the maximum number of iterations or the maximum duration.
+    #[no_coverage]
+    fn enumeration_loop(&mut self) -> Result<!, ReasonForStopping<T>> {
+        self.state.world.report_event(
+            FuzzerEvent::Start,
+            Some((&self.state.fuzzer_stats, self.state.sensor_and_pool.stats().as_ref())),
+        );
+        self.process_initial_inputs("enumerate")?;
+        self.state.world.report_event(
+            FuzzerEvent::DidReadCorpus,
+            Some((&self.state.fuzzer_stats, self.state.sensor_and_pool.stats().as_ref())),
+        );
+        self.state.world.set_checkpoint_instant();
+        let mut next_milestone = (self.state.fuzzer_stats.total_number_of_runs + 10) * 2;
+        let mut nbr_consecutive_too_complex_values = 0;
+        loop {
+            let duration_since_beginning = self.state.world.elapsed_time_since_start();
+            if duration_since_beginning > self.state.settings.maximum_duration {
+                return Err(ReasonForStopping::MaxDurationReached);
+            }
+            if self.state.fuzzer_stats.total_number_of_runs >= self.state.settings.maximum_iterations {
+                return Err(ReasonForStopping::MaxIterationsReached);
+            }
+            let next_input = if nbr_consecutive_too_complex_values < MAX_CONSECUTIVE_TOO_COMPLEX_VALUES {
+                self.state.arbitrary_input()
+            } else {
+                None
+            };
+            if let Some((input, cplx)) = next_input {
+                if cplx <= self.state.settings.max_input_cplx {
+                    nbr_consecutive_too_complex_values = 0;
+                    self.state.input_idx = FuzzerInputIndex::Temporary(input);
+                    self.state.input_origin = InputOrigin::new("enumerate");
+                    self.test_and_process_input(cplx)?;
+                } else {
+                    nbr_consecutive_too_complex_values += 1;
+                }
+            } else {
+                // report the final coverage before stopping
+                update_fuzzer_stats(&mut self.state.fuzzer_stats, &mut self.state.world);
+                self.state.world.report_event(
+                    FuzzerEvent::Pulse,
+                    Some((&self.state.fuzzer_stats, self.state.sensor_and_pool.stats().as_ref())),
+                );
+                self.state.world.report_event(
+                    FuzzerEvent::End,
+                    Some((&self.state.fuzzer_stats, self.state.sensor_and_pool.stats().as_ref())),
+                );
+                return Err(ReasonForStopping::ExhaustedAllPossibleMutations);
+            }
+            if self.state.fuzzer_stats.total_number_of_runs >= next_milestone {
+                update_fuzzer_stats(&mut self.state.fuzzer_stats, &mut self.state.world);
+                self.state.world.report_event(
+                    FuzzerEvent::Pulse,
+                    Some((&self.state.fuzzer_stats, self.state.sensor_and_pool.stats().as_ref())),
+                );
+                next_milestone = self.state.fuzzer_stats.total_number_of_runs * 2;
+            }
+        }
+    }
 }
 
 pub enum TerminationStatus {
@@ -667,8 +795,12 @@ where
     Fuzzer<T, M>: 'static,
 {
     let command = &args.command;
+    if args.harvest_constants && !matches!(command, FuzzerCommand::Read { .. }) {
+        crate::mutators::dictionary::harvest_constants_from_current_exe();
+    }
     let reason_for_stopping = match command {
-        FuzzerCommand::Fuzz => {
+        FuzzerCommand::Fuzz | FuzzerCommand::Enumerate => {
+            let enumerate = matches!(command, FuzzerCommand::Enumerate);
             if !args.stop_after_first_failure {
                 let test_failure_sensor = TestFailureSensor::default();
                 let test_failure_pool = TestFailurePool::new("test_failures");
@@ -697,7 +829,11 @@ where
                     .expect(WRITE_STATS_ERROR);
                 unsafe { fuzzer.state.set_up_signal_handler() };
 
-                let reason_for_stopping = fuzzer.main_loop(false).unwrap_err();
+                let reason_for_stopping = if enumerate {
+                    fuzzer.enumeration_loop().unwrap_err()
+                } else {
+                    fuzzer.main_loop(false).unwrap_err()
+                };
                 fuzzer.state.write_stats().expect(WRITE_STATS_ERROR);
 
                 reason_for_stopping
@@ -720,7 +856,11 @@ where
                     .world
                     .append_stats_file(&stats_headers)
                     .expect(WRITE_STATS_ERROR);
-                let reason_for_stopping = fuzzer.main_loop(false).unwrap_err();
+                let reason_for_stopping = if enumerate {
+                    fuzzer.enumeration_loop().unwrap_err()
+                } else {
+                    fuzzer.main_loop(false).unwrap_err()
+                };
                 fuzzer.state.write_stats().expect(WRITE_STATS_ERROR);
 
                 reason_for_stopping
@@ -779,10 +919,14 @@ where
                 let stored_input = FuzzedInputAndSubValueProvider {
                     input: FuzzedInput::new(value, cache, mutation_step, generation),
                     subvalues,
+                    lineage_generation: 0,
                 };
                 let storage_idx_2 = fuzzer.state.pool_storage.insert(stored_input, 1);
 
                 assert_eq!(storage_idx_1, storage_idx_2);
+                if let Some(corpus_subvalues) = &mut fuzzer.state.corpus_subvalues {
+                    corpus_subvalues.add(storage_idx_2, &fuzzer.state.pool_storage[storage_idx_2].subvalues);
+                }
 
                 unsafe { fuzzer.state.set_up_signal_handler() };
 
@@ -828,7 +972,7 @@ where
                     world.report_event(FuzzerEvent::TestFailure, None);
                     let content = serializer.to_data(&input.value);
                     world
-                        .save_artifact(content, cplx, serializer.extension())
+                        .save_artifact(content, cplx, serializer.extension(), None)
                         .expect(SAVE_ARTIFACTS_ERROR);
                     // in this case we really want to exit with a non-zero termination status here
                     // because the Read command is only used by the input minify command from cargo-fuzzcheck
@@ -843,6 +987,22 @@ where
                 panic!("A value in the input corpus is invalid.");
             }
         }
+        FuzzerCommand::CheckMutator => {
+            let checker = check_mutator_conformance_checker(&args);
+            match checker.check_with_serializer(&mutator, serializer.as_ref()) {
+                Ok(report) => {
+                    println!(
+                        "The mutator respects the requirements of the Mutator trait ({} values generated, {} mutations performed).",
+                        report.nbr_arbitraries, report.nbr_mutations
+                    );
+                    exit(TerminationStatus::Success as i32);
+                }
+                Err(violation) => {
+                    println!("{}", violation);
+                    exit(TerminationStatus::TestFailure as i32);
+                }
+            }
+        }
     };
     let _ = std::panic::take_hook();
 
@@ -854,3 +1014,40 @@ where
         reason_for_stopping,
     }
 }
+
+/// The conformance checker used by the `check-mutator` command.
+///
+/// Repetitions are only reported with `--check-repetitions`, since many mutators,
+/// including the default ones of vectors and tuples, only avoid them on a best-effort basis.
+#[no_coverage]
+fn check_mutator_conformance_checker(args: &Arguments) -> ConformanceChecker {
+    ConformanceChecker::default()
+        .max_complexity(args.max_input_cplx)
+        .check_repetitions(args.check_repetitions)
+}
+
+#[cfg(test)]
+mod tests {
+    use fuzzcheck_common::arg::{Arguments, FuzzerCommand};
+
+    use super::check_mutator_conformance_checker;
+    use crate::mutators::conformance::Requirement;
+    use crate::DefaultMutator;
+
+    #[test]
+    #[no_coverage]
+    fn test_check_mutator_command_accepts_default_mutators() {
+        let mut args = Arguments::for_internal_documentation_test();
+        args.command = FuzzerCommand::CheckMutator;
+        let checker = check_mutator_conformance_checker(&args);
+        checker.check(&<Vec<u8>>::default_mutator()).unwrap();
+        checker
+            .check::<(u8, bool), _>(&<(u8, bool)>::default_mutator())
+            .unwrap();
+
+        args.check_repetitions = true;
+        let checker = check_mutator_conformance_checker(&args);
+        let violation = checker.check(&<Vec<u8>>::default_mutator()).unwrap_err();
+        assert_eq!(violation.requirement, Requirement::NoRepetition);
+    }
+}
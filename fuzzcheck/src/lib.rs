@@ -31,6 +31,12 @@
 // essential for tuple mutators, but there may be a (more complicated) way
 // to do without them
 #![feature(generic_associated_types)]
+// used by the mutator benchmarks to prevent the compiler from optimising
+// away the measured operations, can be removed with the benchmarks
+#![feature(bench_black_box)]
+// used for the global dictionary of the mutators, can be replaced by the
+// once_cell crate
+#![feature(once_cell)]
 //
 // end nightly features
 //
@@ -58,6 +64,8 @@ mod code_coverage_sensor;
 mod data_structures;
 mod fenwick_tree;
 mod fuzzer;
+mod lineage;
+mod mutation_stats;
 pub mod mutators;
 pub mod sensors_and_pools;
 pub mod serializers;
@@ -69,7 +77,7 @@ mod world;
 
 #[doc(inline)]
 pub use builder::fuzz_test;
-pub use fuzzcheck_common::arg::Arguments;
+pub use fuzzcheck_common::arg::{Arguments, CoverageTarget, FuzzerCommand};
 /**
     Make a mutator for a custom type, optionally making it the type’s default mutator.
 
@@ -183,6 +191,34 @@ pub use fuzzcheck_common::arg::Arguments;
     ```
 */
 pub use fuzzcheck_mutators_derive::make_mutator;
+/// Create a mutator for each variant of an enum, which only generates values of that variant.
+///
+/// The mutator is called `<Name>SingleVariant`. It is an enum with one case per variant of the
+/// original enum, each holding a [tuple mutator](crate::mutators::tuples) for the fields of the variant.
+/// Since all the single-variant mutators have the same type, they can be used as the
+/// [terminals of a token grammar](crate::mutators::grammar::tokens).
+/// ```
+/// # #![feature(no_coverage)]
+/// use fuzzcheck::make_single_variant_mutator;
+/// use fuzzcheck::mutators::tuples::{Tuple0Mutator, Tuple1Mutator};
+/// use fuzzcheck::{DefaultMutator, Mutator};
+///
+/// #[derive(Clone)]
+/// pub enum Token {
+///     Number(u8),
+///     Plus,
+/// }
+/// make_single_variant_mutator! {
+///     pub enum Token {
+///         Number(u8),
+///         Plus,
+///     }
+/// }
+/// let numbers = TokenSingleVariant::<_, Tuple0Mutator>::Number(Tuple1Mutator::new(u8::default_mutator()));
+/// assert!(numbers.validate_value(&Token::Number(8)).is_some());
+/// assert!(numbers.validate_value(&Token::Plus).is_none());
+/// ```
+pub use fuzzcheck_mutators_derive::make_single_variant_mutator;
 /// Implement a mutator for the type and make it the type’s `DefaultMutator`.
 ///
 /// The mutator will be called `<Name>Mutator`. It can be constructed in two ways:
@@ -214,6 +250,95 @@ pub use fuzzcheck_mutators_derive::make_mutator;
 /// ```
 /// Similarly to [`make_mutator!`](crate::make_mutator), you can use the attributes `#[field_mutator]` and `#[ignore_variant]`
 /// to customise the generated mutator.
+///
+/// The `#[fuzzcheck(..)]` attribute offers shorthands for common customisations:
+/// * `range = <range>` on integer fields, to use an integer mutator constrained to the range
+/// * `len = <inclusive range>` on `Vec<T>` fields, to constrain the length of the vector.
+/// It can be combined with `range` for vectors of integers.
+/// * `fixed = <expr>` on any field, to always give it the same value
+/// * `regex = "<regex>"` on `String` fields, to only generate strings matching the regular
+/// expression __(supported on crate feature `regex_grammar` only)__
+/// * `weight = <number>` on enum variants, to change how often the variant is chosen, both by
+/// the random values and mutations and by the ordered ones, which try heavier variants first.
+/// The default weight is `1.0`.
+/// ```
+/// # #![feature(no_coverage)]
+/// use fuzzcheck::DefaultMutator;
+///
+/// #[derive(Clone, DefaultMutator)]
+/// struct Packet {
+///     #[fuzzcheck(range = 1 ..= 100)]
+///     priority: u8,
+///     #[fuzzcheck(len = 0 ..= 8, range = 10 ..= 20)]
+///     ports: Vec<u16>,
+///     #[fuzzcheck(fixed = 2)]
+///     version: u8,
+///     #[fuzzcheck(regex = "[a-z]{1,8}@[a-z]{1,8}")]
+///     sender: String,
+///     kind: Kind,
+/// }
+/// #[derive(Clone, DefaultMutator)]
+/// enum Kind {
+///     #[fuzzcheck(weight = 0.1)]
+///     Ping,
+///     Data(bool),
+/// }
+/// let mutator = Packet::default_mutator();
+/// ```
+///
+/// Types whose invariants are upheld by a constructor function can use
+/// `#[fuzzcheck(constructor = <path>)]`. The constructor must take the same arguments as the
+/// fields of the struct, in the same order. The generated mutator fuzzes the tuple of arguments and
+/// builds each value by calling the constructor. The field attributes apply to the corresponding
+/// arguments. The arguments are kept in the mutator’s cache,
+/// so that the values can still be mutated, reverted, and used as subvalues.
+/// ```
+/// # #![feature(no_coverage)]
+/// use fuzzcheck::DefaultMutator;
+///
+/// #[derive(Clone, DefaultMutator)]
+/// #[fuzzcheck(constructor = SortedPair::new)]
+/// struct SortedPair {
+///     #[fuzzcheck(range = 0 ..= 100)]
+///     min: u8,
+///     max: u8,
+/// }
+/// impl SortedPair {
+///     fn new(a: u8, b: u8) -> Self {
+///         Self { min: a.min(b), max: a.max(b) }
+///     }
+/// }
+/// ```
+///
+/// A constructor that returns an `Option` or a `Result` is given by `#[fuzzcheck(try_constructor = <path>)]`
+/// instead, and the arguments for which it fails are never produced by the mutator. When the
+/// arguments of the constructor are not the fields of the struct, their types are given by
+/// `args = (<types>)` and each of them is fuzzed by its default mutator. The `parse = <path>`
+/// setting must then give a function returning the arguments from which a value can be
+/// constructed, or `None` if there are none, so that existing values can be validated.
+/// ```
+/// # #![feature(no_coverage)]
+/// use fuzzcheck::DefaultMutator;
+///
+/// #[derive(Clone, DefaultMutator)]
+/// #[fuzzcheck(try_constructor = Grid::new, args = (u8, u8), parse = Grid::size)]
+/// struct Grid {
+///     width: usize,
+///     cells: Vec<bool>,
+/// }
+/// impl Grid {
+///     fn new(width: u8, height: u8) -> Result<Self, String> {
+///         if width == 0 {
+///             return Err("the grid must have at least one column".to_string());
+///         }
+///         Ok(Self { width: width as usize, cells: vec![false; width as usize * height as usize] })
+///     }
+///     fn size(&self) -> Option<(u8, u8)> {
+///         let height = self.cells.len().checked_div(self.width)?;
+///         Some((u8::try_from(self.width).ok()?, u8::try_from(height).ok()?))
+///     }
+/// }
+/// ```
 pub use fuzzcheck_mutators_derive::DefaultMutator;
 #[doc(inline)]
 pub use fuzzer::FuzzingResult;
@@ -232,6 +357,9 @@ pub use sensors_and_pools::PoolExt;
 pub use sensors_and_pools::SensorExt;
 #[doc(inline)]
 pub use serializers::ByteSerializer;
+#[cfg(feature = "grammar_mutator")]
+#[doc(inline)]
+pub use serializers::GrammarSerializer;
 #[cfg(feature = "serde_ron_serializer")]
 #[doc(inline)]
 pub use serializers::SerdeRonSerializer;
@@ -0,0 +1,290 @@
+//! Keeps track of how each test case of the pool was obtained.
+//!
+//! Every test case added to the pool, as well as every failing test case saved as an
+//! artifact, is recorded along with its parent, the kind of mutation that produced it
+//! (see [`Mutator::mutation_kind`](crate::Mutator::mutation_kind)), and its generation, which is
+//! the number of mutations separating it from the root of its provenance tree.
+//! This information is written next to each file of the output corpus, in a sidecar
+//! file with the `lineage` extension, and the whole provenance tree is saved in the stats
+//! folder as `lineage.json` and `lineage.dot`.
+//!
+//! When a test case is evicted from the pool, it is removed from the provenance tree
+//! unless it is an ancestor of a test case that is still in the pool or of an artifact.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+/// The extension of the sidecar files describing the lineage of a test case
+pub(crate) const LINEAGE_EXTENSION: &str = "lineage";
+
+/// How the test case currently being tested was obtained
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct InputOrigin {
+    /// The key of the parent test case in the fuzzer's storage, if any
+    pub parent: Option<usize>,
+    /// A short description of the mutation applied to the parent, or of how the test case was generated
+    pub mutation: &'static str,
+    /// The number of mutations separating the test case from the root of its provenance tree
+    pub generation: usize,
+}
+impl InputOrigin {
+    /// The origin of a test case that was not obtained by mutating another one
+    #[no_coverage]
+    pub fn new(mutation: &'static str) -> Self {
+        Self {
+            parent: None,
+            mutation,
+            generation: 0,
+        }
+    }
+    /// The origin of a test case obtained by mutating the test case stored at the given key,
+    /// whose own generation is `parent_generation`
+    #[no_coverage]
+    pub fn mutated(parent: usize, parent_generation: usize, mutation: &'static str) -> Self {
+        Self {
+            parent: Some(parent),
+            mutation,
+            generation: parent_generation + 1,
+        }
+    }
+}
+
+/// A node of the provenance tree
+#[derive(Clone, Debug)]
+pub(crate) struct LineageEntry {
+    /// The hash of the serialized test case, which is also the name of its file
+    hash: String,
+    /// The key of the test case in the fuzzer's storage, or `None` if it is an artifact
+    index: Option<usize>,
+    /// The position of the parent in the provenance tree
+    parent: Option<usize>,
+    mutation: &'static str,
+    generation: usize,
+    /// The number of entries of the provenance tree whose parent is this entry
+    nbr_children: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct Lineage {
+    /// The nodes of the provenance tree, by identifier
+    entries: BTreeMap<usize, LineageEntry>,
+    next_id: usize,
+    /// The identifier of the test case stored at a given key of the fuzzer's storage
+    entry_for_index: HashMap<usize, usize>,
+}
+
+impl Lineage {
+    /// Records a new test case and returns the content of its sidecar file
+    #[no_coverage]
+    pub fn record(&mut self, hash: String, index: Option<usize>, origin: InputOrigin) -> String {
+        let parent = origin.parent.and_then(
+            #[no_coverage]
+            |parent| self.entry_for_index.get(&parent).copied(),
+        );
+        let id = self.next_id;
+        self.next_id += 1;
+        if let Some(parent) = parent {
+            self.entries.get_mut(&parent).unwrap().nbr_children += 1;
+        }
+        self.entries.insert(
+            id,
+            LineageEntry {
+                hash,
+                index,
+                parent,
+                mutation: origin.mutation,
+                generation: origin.generation,
+                nbr_children: 0,
+            },
+        );
+        if let Some(index) = index && let Some(previous) = self.entry_for_index.insert(index, id) {
+            self.prune(previous);
+        }
+        self.entry_to_json(id)
+    }
+
+    /// Records that the test case stored at the given key of the fuzzer's storage was evicted from the pool
+    #[no_coverage]
+    pub fn remove(&mut self, index: usize) {
+        if let Some(id) = self.entry_for_index.remove(&index) {
+            self.prune(id);
+        }
+    }
+
+    /// Removes the given entry and its ancestors from the provenance tree, for as long as they
+    /// are neither in the pool, artifacts, nor the ancestors of other entries
+    #[no_coverage]
+    fn prune(&mut self, mut id: usize) {
+        loop {
+            let entry = &self.entries[&id];
+            let is_alive = entry.index.map_or(
+                true,
+                #[no_coverage]
+                |index| self.entry_for_index.get(&index) == Some(&id),
+            );
+            if is_alive || entry.nbr_children > 0 {
+                return;
+            }
+            let entry = self.entries.remove(&id).unwrap();
+            if let Some(parent) = entry.parent {
+                self.entries.get_mut(&parent).unwrap().nbr_children -= 1;
+                id = parent;
+            } else {
+                return;
+            }
+        }
+    }
+
+    #[cfg(test)]
+    #[no_coverage]
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[no_coverage]
+    fn entry_to_json(&self, id: usize) -> String {
+        let entry = &self.entries[&id];
+        let parent = entry.parent.map(
+            #[no_coverage]
+            |parent| &self.entries[&parent],
+        );
+        format!(
+            "{{\"id\":{},\"hash\":{:?},\"index\":{},\"parent\":{},\"parent_index\":{},\"parent_hash\":{},\"mutation\":{:?},\"generation\":{}}}",
+            id,
+            entry.hash,
+            json_option(entry.index),
+            json_option(entry.parent),
+            json_option(parent.and_then(
+                #[no_coverage]
+                |parent| parent.index
+            )),
+            parent.map_or(
+                "null".to_string(),
+                #[no_coverage]
+                |parent| format!("{:?}", parent.hash)
+            ),
+            entry.mutation,
+            entry.generation,
+        )
+    }
+
+    /// The whole provenance tree, as a JSON array
+    #[no_coverage]
+    pub fn to_json(&self) -> String {
+        let entries = self
+            .entries
+            .keys()
+            .map(
+                #[no_coverage]
+                |&id| self.entry_to_json(id),
+            )
+            .collect::<Vec<_>>();
+        format!("[\n{}\n]\n", entries.join(",\n"))
+    }
+
+    /// The whole provenance tree, in the DOT format of graphviz
+    #[no_coverage]
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph lineage {\n".to_string();
+        for (id, entry) in self.entries.iter() {
+            let shape = if entry.index.is_some() { "box" } else { "doubleoctagon" };
+            writeln!(
+                dot,
+                "    {} [label=\"{}\\n{}\\ngeneration {}\", shape={}];",
+                id, entry.hash, entry.mutation, entry.generation, shape
+            )
+            .unwrap();
+            if let Some(parent) = entry.parent {
+                writeln!(dot, "    {} -> {} [label=\"{}\"];", parent, id, entry.mutation).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[no_coverage]
+fn json_option(x: Option<usize>) -> String {
+    x.map_or(
+        "null".to_string(),
+        #[no_coverage]
+        |x| x.to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InputOrigin, Lineage};
+
+    #[test]
+    #[no_coverage]
+    fn test_lineage() {
+        let mut lineage = Lineage::default();
+        lineage.record("a1".to_string(), Some(0), InputOrigin::new("arbitrary"));
+        lineage.record("b2".to_string(), Some(1), InputOrigin::mutated(0, 0, "insert_element"));
+        // the slot 0 is reused by a new test case
+        lineage.record("c3".to_string(), Some(0), InputOrigin::mutated(1, 1, "remove"));
+        let sidecar = lineage.record("d4".to_string(), None, InputOrigin::mutated(0, 2, "mutate_element"));
+        assert_eq!(
+            sidecar,
+            r#"{"id":3,"hash":"d4","index":null,"parent":2,"parent_index":0,"parent_hash":"c3","mutation":"mutate_element","generation":3}"#
+        );
+        let json = lineage.to_json();
+        assert_eq!(json.lines().count(), 6);
+        assert!(json.contains(r#""id":1,"hash":"b2","index":1,"parent":0,"parent_index":0,"parent_hash":"a1""#));
+
+        let dot = lineage.to_dot();
+        assert!(dot.contains("0 -> 1 [label=\"insert_element\"]"));
+        assert!(dot.contains("1 -> 2 [label=\"remove\"]"));
+        assert!(dot.contains("2 -> 3 [label=\"mutate_element\"]"));
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_lineage_eviction() {
+        let mut lineage = Lineage::default();
+        lineage.record("a1".to_string(), Some(0), InputOrigin::new("arbitrary"));
+        lineage.record("b2".to_string(), Some(1), InputOrigin::mutated(0, 0, "insert_element"));
+        lineage.record("c3".to_string(), Some(2), InputOrigin::mutated(1, 1, "remove"));
+        lineage.record("d4".to_string(), None, InputOrigin::mutated(1, 1, "mutate_element"));
+
+        // the ancestors of test cases that are still in the pool are kept
+        lineage.remove(0);
+        assert_eq!(lineage.len(), 4);
+        // the ancestors of artifacts are kept
+        lineage.remove(2);
+        lineage.remove(1);
+        assert_eq!(lineage.len(), 3);
+        assert!(!lineage.to_json().contains("c3"));
+
+        // evicting a leaf also removes its evicted ancestors
+        let mut lineage = Lineage::default();
+        lineage.record("a1".to_string(), Some(0), InputOrigin::new("arbitrary"));
+        lineage.record("b2".to_string(), Some(1), InputOrigin::mutated(0, 0, "insert_element"));
+        lineage.remove(0);
+        lineage.remove(1);
+        assert_eq!(lineage.len(), 0);
+        // the identifiers are not reused
+        let sidecar = lineage.record("c3".to_string(), Some(0), InputOrigin::new("arbitrary"));
+        assert!(sidecar.starts_with(r#"{"id":2,"#));
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_lineage_generation() {
+        let mut lineage = Lineage::default();
+        let root = InputOrigin::new("arbitrary");
+        lineage.record("a1".to_string(), Some(0), root);
+        let child = InputOrigin::mutated(0, root.generation, "insert_element");
+        lineage.record("b2".to_string(), Some(1), child);
+        let grandchild = InputOrigin::mutated(1, child.generation, "remove");
+        let sidecar = lineage.record("c3".to_string(), Some(2), grandchild);
+
+        // the generation counts the mutations from the root, not the runs of the fuzzer
+        assert_eq!(grandchild.generation, 2);
+        assert!(sidecar.ends_with(r#""generation":2}"#));
+        let json = lineage.to_json();
+        assert!(json.contains(r#""hash":"a1","index":0,"parent":null,"parent_index":null,"parent_hash":null,"mutation":"arbitrary","generation":0}"#));
+        assert!(json.contains(r#""mutation":"insert_element","generation":1}"#));
+    }
+}
@@ -0,0 +1,99 @@
+//! Keeps track of how effective each kind of mutation is.
+//!
+//! For each [kind of mutation](crate::Mutator::mutation_kind), as well as for the test cases
+//! that were generated from scratch or read from the input corpus, the fuzzer counts how many
+//! test cases were tested and how many of them were added to the pool. These counters are
+//! written in the stats folder as `mutations.csv`, and the kind of mutation that added the
+//! most test cases to the pool is shown in the statistics of the fuzzer.
+
+use std::path::PathBuf;
+
+use fuzzcheck_common::{MutationKindStats, MutationStats};
+
+use crate::traits::SaveToStatsFolder;
+use crate::{CSVField, ToCSV};
+
+impl ToCSV for MutationKindStats {
+    #[no_coverage]
+    fn csv_headers(&self) -> Vec<CSVField> {
+        vec![
+            CSVField::String("kind".to_string()),
+            CSVField::String("attempts".to_string()),
+            CSVField::String("corpus_additions".to_string()),
+            CSVField::String("addition_rate".to_string()),
+        ]
+    }
+    #[no_coverage]
+    fn to_csv_record(&self) -> Vec<CSVField> {
+        vec![
+            CSVField::String(self.kind.to_string()),
+            CSVField::Integer(self.attempts as isize),
+            CSVField::Integer(self.corpus_additions as isize),
+            CSVField::Float(self.addition_rate()),
+        ]
+    }
+}
+
+/// The content of the `mutations.csv` file, with one line per kind of mutation
+#[no_coverage]
+fn mutations_csv(stats: &MutationStats) -> Vec<u8> {
+    let kinds = stats.sorted_kinds();
+    let mut bytes = vec![];
+    if let Some(first) = kinds.first() {
+        bytes.extend(CSVField::to_bytes(&first.csv_headers()));
+    }
+    for kind in kinds {
+        bytes.extend(CSVField::to_bytes(&kind.to_csv_record()));
+    }
+    bytes
+}
+
+impl SaveToStatsFolder for MutationStats {
+    #[no_coverage]
+    fn save_to_stats_folder(&self) -> Vec<(PathBuf, Vec<u8>)> {
+        vec![(PathBuf::new().join("mutations.csv"), mutations_csv(self))]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fuzzcheck_common::MutationStats;
+
+    use super::mutations_csv;
+
+    #[test]
+    #[no_coverage]
+    fn test_mutation_stats() {
+        let mut stats = MutationStats::default();
+        stats.record("arbitrary", true);
+        stats.record("insert_element", false);
+        stats.record("insert_element", true);
+        stats.record("insert_element", true);
+        stats.record("remove", false);
+        stats.record("arbitrary", false);
+
+        let kinds = stats.sorted_kinds();
+        let summary = kinds
+            .iter()
+            .map(
+                #[no_coverage]
+                |k| (k.kind, k.attempts, k.corpus_additions),
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![("insert_element", 3, 2), ("arbitrary", 2, 1), ("remove", 1, 0)]
+        );
+
+        let csv = String::from_utf8(mutations_csv(&stats)).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some(r#""kind","attempts","corpus_additions","addition_rate","#)
+        );
+        assert_eq!(lines.next(), Some(r#""insert_element",3,2,0.6667,"#));
+        assert_eq!(lines.count(), 2);
+
+        assert_eq!(stats.most_productive_kind().unwrap().kind, "insert_element");
+    }
+}
@@ -3,6 +3,7 @@ use std::cell::Cell;
 use std::cmp::Ordering;
 use std::marker::PhantomData;
 
+use crate::mutators::vose_alias::VoseAlias;
 use crate::Mutator;
 
 /**
@@ -19,6 +20,13 @@ let m = AlternationMutator::new(vec![m1, m2], 0.0);
 
 // m will produce values either in 3..=10 or in 78..=200
 ```
+
+The values can be biased towards some of the mutators by giving them a weight
+with [`with_weights`](AlternationMutator::with_weights).
+
+When the randomly chosen mutator cannot generate a value within the maximum complexity
+given to `random_arbitrary`, the value is generated by the mutator with the smallest
+minimum complexity instead. This keeps recursive values from growing without bounds.
 */
 pub struct AlternationMutator<T, M>
 where
@@ -26,6 +34,7 @@ where
     M: Mutator<T>,
 {
     mutators: Vec<M>,
+    weights: Option<(Vec<f64>, VoseAlias)>,
     rng: fastrand::Rng,
     added_complexity: f64,
     initialized: Cell<bool>,
@@ -46,6 +55,7 @@ where
 
         Self {
             mutators,
+            weights: None,
             rng: fastrand::Rng::default(),
             added_complexity,
             initialized: Cell::new(false),
@@ -55,6 +65,59 @@ where
             _phantom: PhantomData,
         }
     }
+
+    /// Create an [`AlternationMutator`] whose random values come from each mutator with
+    /// a probability proportional to its weight.
+    ///
+    /// The ordered arbitrary values also come from each mutator in proportion to its weight,
+    /// until the mutator runs out of values.
+    ///
+    /// The weights must be positive and there must be one weight for each mutator.
+    #[no_coverage]
+    pub fn with_weights(mutators: Vec<M>, weights: Vec<f64>, added_complexity: f64) -> Self {
+        assert_eq!(mutators.len(), weights.len());
+        assert!(weights.iter().all(
+            #[no_coverage]
+            |w| *w > 0.0
+        ));
+        Self {
+            weights: Some((weights.clone(), VoseAlias::new(weights))),
+            ..Self::new(mutators, added_complexity)
+        }
+    }
+
+    #[no_coverage]
+    fn random_mutator_idx(&self) -> usize {
+        if let Some((_, weights)) = &self.weights {
+            weights.sample()
+        } else {
+            self.rng.usize(..self.mutators.len())
+        }
+    }
+
+    /// Returns the position in `step.indices` of the mutator producing the next ordered arbitrary value
+    ///
+    /// The weighted mutators are chosen by smooth weighted round-robin: each remaining mutator
+    /// accumulates its weight at every step, and the one with the most credit is chosen and pays
+    /// back the total weight of the remaining mutators.
+    #[no_coverage]
+    fn next_arbitrary_position<AS>(&self, step: &mut ArbitraryStep<AS>) -> usize {
+        if let Some((weights, _)) = &self.weights {
+            let mut total_weight = 0.0;
+            let mut position = 0;
+            for (i, &idx) in step.indices.iter().enumerate() {
+                total_weight += weights[idx];
+                step.credits[idx] += weights[idx];
+                if step.credits[idx] > step.credits[step.indices[position]] {
+                    position = i;
+                }
+            }
+            step.credits[step.indices[position]] -= total_weight;
+            position
+        } else {
+            step.idx % step.indices.len()
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -63,6 +126,7 @@ pub struct ArbitraryStep<AS> {
     inner: Vec<AS>,
     indices: Vec<usize>,
     idx: usize,
+    credits: Vec<f64>,
 }
 
 #[doc(hidden)]
@@ -190,6 +254,7 @@ where
                 .collect(),
             indices: (0..self.mutators.len()).collect(),
             idx: 0,
+            credits: vec![0.0; self.mutators.len()],
         }
     }
 
@@ -282,14 +347,15 @@ where
             return None;
         }
 
-        let idx = step.indices[step.idx % step.indices.len()];
+        let position = self.next_arbitrary_position(step);
+        let idx = step.indices[position];
         let mutator = &self.mutators[idx];
         let inner_step = &mut step.inner[idx];
         if let Some((v, c)) = mutator.ordered_arbitrary(inner_step, max_cplx) {
             step.idx += 1;
             Some((v, self.complexity_from_inner(c)))
         } else {
-            step.indices.remove(step.idx % step.indices.len());
+            step.indices.remove(position);
             self.ordered_arbitrary(step, max_cplx)
         }
     }
@@ -297,7 +363,22 @@ where
     #[doc(hidden)]
     #[no_coverage]
     fn random_arbitrary(&self, max_cplx: f64) -> (T, f64) {
-        let idx = self.rng.usize(..self.mutators.len());
+        let mut idx = self.random_mutator_idx();
+        if self.complexity_from_inner(self.mutators[idx].min_complexity()) > max_cplx {
+            // the chosen mutator cannot generate values that are simple enough, so we use the simplest one instead
+            // this prevents recursive values from growing without bounds
+            idx = (0..self.mutators.len())
+                .min_by(
+                    #[no_coverage]
+                    |&a, &b| {
+                        self.mutators[a]
+                            .min_complexity()
+                            .partial_cmp(&self.mutators[b].min_complexity())
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    },
+                )
+                .unwrap();
+        }
         let mutator = &self.mutators[idx];
 
         let (v, c) = mutator.random_arbitrary(max_cplx);
@@ -429,4 +510,13 @@ where
             mutator.visit_subvalues(value, &cache.inner, visit);
         }
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        match t {
+            UnmutateToken::Replace(_) => "replace",
+            UnmutateToken::Inner(idx, t) => self.mutators[*idx].mutation_kind(t),
+        }
+    }
 }
@@ -0,0 +1,531 @@
+/*!
+A micro-benchmark harness measuring the performance of mutators.
+
+A [`MutatorBenchmark`] exercises a mutator in the same way as the fuzzer does: it generates
+values with `ordered_arbitrary` and `random_arbitrary`, validates them, and then repeatedly
+mutates and unmutates them. For each of these operations, it measures the time spent and the
+number of allocations performed.
+
+```
+use fuzzcheck::mutators::benchmark::MutatorBenchmark;
+use fuzzcheck::DefaultMutator;
+
+let mutator = <Vec<Option<u8>>>::default_mutator();
+let report = MutatorBenchmark::default()
+    .max_complexity(256.0)
+    .arbitraries(10)
+    .mutations(10)
+    .run(&mutator);
+assert!(report.mutate.nbr_calls > 0);
+println!("{}", report);
+```
+
+The allocations are only counted if the global allocator is a
+[`CountingAllocator`](crate::sensors_and_pools::CountingAllocator), for example:
+```
+use std::alloc::System;
+use fuzzcheck::sensors_and_pools::CountingAllocator;
+
+#[global_allocator]
+static ALLOC: CountingAllocator<System> = CountingAllocator(System);
+```
+Otherwise, the allocation counts are all zero and
+[`counts_allocations`](BenchmarkReport::counts_allocations) is `false`.
+
+A suite of benchmarks covering the mutators provided by fuzzcheck is available with
+[`MutatorBenchmark::builtin_suite`]. It can be run with
+`cargo run --release --example mutator_benchmarks`.
+*/
+
+extern crate self as fuzzcheck;
+
+use std::fmt::{self, Display};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use fuzzcheck_mutators_derive::make_mutator;
+
+#[cfg(feature = "grammar_mutator")]
+use crate::mutators::grammar::AST;
+use crate::mutators::recursive::RecurToMutator;
+use crate::mutators::vector::VecMutator;
+use crate::sensors_and_pools::allocations_sensor::get_allocation_stats;
+use crate::subvalue_provider::{
+    CrossoverSubValueProvider, EmptySubValueProvider, Generation, SubValueProvider, SubValueProviderId,
+};
+use crate::{DefaultMutator, Mutator};
+
+/// The time spent and the allocations performed by all the calls to one operation of a mutator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OperationStats {
+    pub nbr_calls: usize,
+    pub duration: Duration,
+    /// The number of allocated blocks, including reallocations
+    pub nbr_allocations: u64,
+    pub allocated_bytes: u64,
+}
+
+impl OperationStats {
+    /// Calls `f`, and adds the time it took and the allocations it made to the stats
+    #[no_coverage]
+    fn record<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        let allocs_before = get_allocation_stats();
+        let start = Instant::now();
+        let result = black_box(f());
+        let duration = start.elapsed();
+        let allocs_after = get_allocation_stats();
+        self.nbr_calls += 1;
+        self.duration += duration;
+        self.nbr_allocations += allocs_after.total_blocks - allocs_before.total_blocks;
+        self.allocated_bytes += allocs_after.total_bytes - allocs_before.total_bytes;
+        result
+    }
+
+    /// The average number of nanoseconds spent in each call
+    #[no_coverage]
+    pub fn nanos_per_call(&self) -> f64 {
+        if self.nbr_calls == 0 {
+            0.0
+        } else {
+            self.duration.as_nanos() as f64 / self.nbr_calls as f64
+        }
+    }
+    /// The number of calls that can be performed in one second
+    #[no_coverage]
+    pub fn calls_per_second(&self) -> f64 {
+        let secs = self.duration.as_secs_f64();
+        if secs == 0.0 {
+            0.0
+        } else {
+            self.nbr_calls as f64 / secs
+        }
+    }
+    /// The average number of allocations performed by each call
+    #[no_coverage]
+    pub fn allocations_per_call(&self) -> f64 {
+        if self.nbr_calls == 0 {
+            0.0
+        } else {
+            self.nbr_allocations as f64 / self.nbr_calls as f64
+        }
+    }
+    /// The average number of bytes allocated by each call
+    #[no_coverage]
+    pub fn bytes_per_call(&self) -> f64 {
+        if self.nbr_calls == 0 {
+            0.0
+        } else {
+            self.allocated_bytes as f64 / self.nbr_calls as f64
+        }
+    }
+}
+
+/// The result of a [`MutatorBenchmark`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BenchmarkReport {
+    /// The calls to `ordered_arbitrary` and `random_arbitrary`
+    pub arbitrary: OperationStats,
+    /// The calls to `ordered_mutate` and `random_mutate`
+    pub mutate: OperationStats,
+    /// The calls to `unmutate`
+    pub unmutate: OperationStats,
+    /// The calls to `validate_value` on the generated values
+    pub validate: OperationStats,
+    /// Whether the global allocator is a [`CountingAllocator`](crate::sensors_and_pools::CountingAllocator)
+    pub counts_allocations: bool,
+}
+
+impl BenchmarkReport {
+    #[no_coverage]
+    fn operations(&self) -> [(&'static str, &OperationStats); 4] {
+        [
+            ("arbitrary", &self.arbitrary),
+            ("mutate", &self.mutate),
+            ("unmutate", &self.unmutate),
+            ("validate", &self.validate),
+        ]
+    }
+}
+
+impl Display for BenchmarkReport {
+    #[no_coverage]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<10} {:>10} {:>12} {:>14} {:>12} {:>12}",
+            "operation", "calls", "ns/call", "calls/s", "allocs/call", "bytes/call"
+        )?;
+        for (name, stats) in self.operations() {
+            writeln!(f)?;
+            write!(
+                f,
+                "{:<10} {:>10} {:>12.1} {:>14.0} ",
+                name,
+                stats.nbr_calls,
+                stats.nanos_per_call(),
+                stats.calls_per_second()
+            )?;
+            if self.counts_allocations {
+                write!(
+                    f,
+                    "{:>12.2} {:>12.1}",
+                    stats.allocations_per_call(),
+                    stats.bytes_per_call()
+                )?;
+            } else {
+                write!(f, "{:>12} {:>12}", "-", "-")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The reports of a list of named benchmarks, see [`MutatorBenchmark::builtin_suite`]
+#[derive(Debug, Clone, Default)]
+pub struct BenchmarkSuite {
+    pub reports: Vec<(String, BenchmarkReport)>,
+}
+
+impl BenchmarkSuite {
+    /// Runs the benchmark on the given mutator and adds its report to the suite
+    #[no_coverage]
+    pub fn add<T, M>(&mut self, name: &str, benchmark: &MutatorBenchmark, mutator: &M)
+    where
+        T: Clone + 'static,
+        M: Mutator<T>,
+    {
+        self.reports.push((name.to_string(), benchmark.run(mutator)));
+    }
+}
+
+impl Display for BenchmarkSuite {
+    #[no_coverage]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, report)) in self.reports.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+                writeln!(f)?;
+            }
+            writeln!(f, "== {} ==", name)?;
+            write!(f, "{}", report)?;
+        }
+        Ok(())
+    }
+}
+
+/// Measures the throughput and the allocations of the methods of a [`Mutator`].
+///
+/// See the [module documentation](crate::mutators::benchmark) for an example.
+#[derive(Debug, Clone)]
+pub struct MutatorBenchmark {
+    max_arbitrary_cplx: f64,
+    max_mutate_cplx: f64,
+    nbr_arbitraries: usize,
+    nbr_mutations: usize,
+}
+impl Default for MutatorBenchmark {
+    #[no_coverage]
+    fn default() -> Self {
+        Self {
+            max_arbitrary_cplx: 1000.0,
+            max_mutate_cplx: 1000.0,
+            nbr_arbitraries: 1000,
+            nbr_mutations: 100,
+        }
+    }
+}
+
+impl MutatorBenchmark {
+    /// The maximum complexity given to both the arbitrary and the mutate methods
+    #[must_use]
+    #[no_coverage]
+    pub fn max_complexity(self, max_cplx: f64) -> Self {
+        Self {
+            max_arbitrary_cplx: max_cplx,
+            max_mutate_cplx: max_cplx,
+            ..self
+        }
+    }
+    /// The maximum complexity given to `ordered_arbitrary` and `random_arbitrary`
+    #[must_use]
+    #[no_coverage]
+    pub fn max_arbitrary_complexity(self, max_arbitrary_cplx: f64) -> Self {
+        Self {
+            max_arbitrary_cplx,
+            ..self
+        }
+    }
+    /// The maximum complexity given to `ordered_mutate` and `random_mutate`
+    #[must_use]
+    #[no_coverage]
+    pub fn max_mutate_complexity(self, max_mutate_cplx: f64) -> Self {
+        Self {
+            max_mutate_cplx,
+            ..self
+        }
+    }
+    /// The number of values generated by each of `ordered_arbitrary` and `random_arbitrary`
+    #[must_use]
+    #[no_coverage]
+    pub fn arbitraries(self, nbr_arbitraries: usize) -> Self {
+        Self {
+            nbr_arbitraries,
+            ..self
+        }
+    }
+    /// The number of mutations performed by each of `ordered_mutate` and `random_mutate` on each
+    /// generated value
+    #[must_use]
+    #[no_coverage]
+    pub fn mutations(self, nbr_mutations: usize) -> Self {
+        Self { nbr_mutations, ..self }
+    }
+
+    /// Benchmarks the mutator.
+    ///
+    /// # Panics
+    /// If the mutator generates a value that is rejected by its `validate_value` method.
+    /// The [conformance checker](crate::mutators::conformance) can be used to find out why.
+    #[no_coverage]
+    pub fn run<T, M>(&self, mutator: &M) -> BenchmarkReport
+    where
+        T: Clone + 'static,
+        M: Mutator<T>,
+    {
+        let m = mutator;
+        m.initialize();
+        let mut report = BenchmarkReport {
+            counts_allocations: counting_allocator_is_active(),
+            ..BenchmarkReport::default()
+        };
+
+        let mut step = m.default_arbitrary_step();
+        // the previous arbitrary value is used for crossover mutations
+        let mut donor: Option<CrossoverSubValueProvider<T, M>> = None;
+        for i in 0..self.nbr_arbitraries {
+            let (value, _) = if let Some(x) = report.arbitrary.record(
+                #[no_coverage]
+                || m.ordered_arbitrary(&mut step, self.max_arbitrary_cplx),
+            ) {
+                x
+            } else {
+                break;
+            };
+            let cache = Self::validate(m, &value, &mut report);
+            let subvalue_provider: &dyn SubValueProvider = if let Some(donor) = &donor {
+                donor
+            } else {
+                &EmptySubValueProvider
+            };
+            self.ordered_mutations(m, &value, &cache, subvalue_provider, &mut report);
+            self.random_mutations(m, &value, &cache, &mut report);
+            donor = Some(CrossoverSubValueProvider::new(
+                SubValueProviderId {
+                    idx: 0,
+                    generation: Generation(i),
+                },
+                &value,
+                &cache,
+                m,
+            ));
+        }
+        for _ in 0..self.nbr_arbitraries {
+            let (value, _) = report.arbitrary.record(
+                #[no_coverage]
+                || m.random_arbitrary(self.max_arbitrary_cplx),
+            );
+            let cache = Self::validate(m, &value, &mut report);
+            self.random_mutations(m, &value, &cache, &mut report);
+        }
+        report
+    }
+
+    #[no_coverage]
+    fn validate<T, M>(m: &M, value: &T, report: &mut BenchmarkReport) -> M::Cache
+    where
+        T: Clone + 'static,
+        M: Mutator<T>,
+    {
+        report
+            .validate
+            .record(
+                #[no_coverage]
+                || m.validate_value(value),
+            )
+            .expect("the mutator generated a value that it considers invalid")
+    }
+
+    #[no_coverage]
+    fn ordered_mutations<T, M>(
+        &self,
+        m: &M,
+        original: &T,
+        original_cache: &M::Cache,
+        subvalue_provider: &dyn SubValueProvider,
+        report: &mut BenchmarkReport,
+    ) where
+        T: Clone + 'static,
+        M: Mutator<T>,
+    {
+        let mut value = original.clone();
+        let mut cache = original_cache.clone();
+        let mut step = m.default_mutation_step(&value, &cache);
+        for _ in 0..self.nbr_mutations {
+            let (token, _) = if let Some(x) = report.mutate.record(
+                #[no_coverage]
+                || {
+                    m.ordered_mutate(
+                        &mut value,
+                        &mut cache,
+                        &mut step,
+                        subvalue_provider,
+                        self.max_mutate_cplx,
+                    )
+                },
+            ) {
+                x
+            } else {
+                break;
+            };
+            report.unmutate.record(
+                #[no_coverage]
+                || m.unmutate(&mut value, &mut cache, token),
+            );
+        }
+    }
+
+    #[no_coverage]
+    fn random_mutations<T, M>(&self, m: &M, original: &T, original_cache: &M::Cache, report: &mut BenchmarkReport)
+    where
+        T: Clone + 'static,
+        M: Mutator<T>,
+    {
+        let mut value = original.clone();
+        let mut cache = original_cache.clone();
+        for _ in 0..self.nbr_mutations {
+            let (token, _) = report.mutate.record(
+                #[no_coverage]
+                || m.random_mutate(&mut value, &mut cache, self.max_mutate_cplx),
+            );
+            report.unmutate.record(
+                #[no_coverage]
+                || m.unmutate(&mut value, &mut cache, token),
+            );
+        }
+    }
+
+    /// Benchmarks a selection of the mutators provided by fuzzcheck: vectors, strings,
+    /// tuples, recursive types, and syntax trees generated from a grammar.
+    #[no_coverage]
+    pub fn builtin_suite(&self) -> BenchmarkSuite {
+        let mut suite = BenchmarkSuite::default();
+        suite.add("Vec<u8>", self, &<Vec<u8>>::default_mutator());
+        suite.add("Vec<Vec<u16>>", self, &<Vec<Vec<u16>>>::default_mutator());
+        suite.add("String", self, &String::default_mutator());
+        suite.add::<(u8, Vec<bool>, Option<u32>), _>(
+            "(u8, Vec<bool>, Option<u32>)",
+            self,
+            &<(u8, Vec<bool>, Option<u32>)>::default_mutator(),
+        );
+        suite.add("recursive tree", self, &Tree::default_mutator());
+        #[cfg(feature = "grammar_mutator")]
+        suite.add::<AST, _>("grammar AST", self, &grammar::ast_mutator());
+        suite
+    }
+}
+
+/// Whether the allocations are counted by a [`CountingAllocator`](crate::sensors_and_pools::CountingAllocator)
+#[no_coverage]
+fn counting_allocator_is_active() -> bool {
+    let before = get_allocation_stats().total_blocks;
+    drop(black_box(Box::new(0_u64)));
+    get_allocation_stats().total_blocks != before
+}
+
+/// A recursive type used by the built-in benchmark suite
+#[derive(Clone)]
+struct Tree {
+    value: u8,
+    children: Vec<Tree>,
+}
+
+make_mutator! {
+    name: TreeMutator,
+    recursive: true,
+    default: true,
+    type:
+    struct Tree {
+        value: u8,
+        #[field_mutator(
+            VecMutator<Tree, RecurToMutator<TreeMutator<M0>>> = {
+                VecMutator::new(self_.into(), 0..=4)
+            }
+        )]
+        children: Vec<Tree>,
+    }
+}
+
+#[cfg(feature = "grammar_mutator")]
+mod grammar {
+    use crate::mutators::grammar::{
+        alternation, concatenation, grammar_based_ast_mutator, literal, literal_range, recurse, recursive, repetition,
+        ASTMutator,
+    };
+
+    /// A mutator for the syntax trees of simple arithmetic expressions
+    #[no_coverage]
+    pub fn ast_mutator() -> ASTMutator {
+        let number = repetition(literal_range('0'..='9'), 1..4);
+        let expr = recursive(
+            #[no_coverage]
+            |expr| {
+                let term = alternation([
+                    number.clone(),
+                    concatenation([literal('('), recurse(expr), literal(')')]),
+                ]);
+                let operator = alternation([literal('+'), literal('-'), literal('*')]);
+                concatenation([term.clone(), repetition(concatenation([operator, term]), 0..4)])
+            },
+        );
+        grammar_based_ast_mutator(expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MutatorBenchmark;
+    use crate::DefaultMutator;
+
+    #[test]
+    #[no_coverage]
+    fn test_benchmark_counts_calls() {
+        let mutator = <Vec<u8>>::default_mutator();
+        let report = MutatorBenchmark::default()
+            .max_complexity(100.0)
+            .arbitraries(5)
+            .mutations(10)
+            .run(&mutator);
+        // 5 ordered and 5 random arbitraries
+        assert_eq!(report.arbitrary.nbr_calls, 10);
+        assert_eq!(report.validate.nbr_calls, 10);
+        assert_eq!(report.mutate.nbr_calls, report.unmutate.nbr_calls);
+        assert!(report.mutate.nbr_calls >= 5 * 10);
+        // the unit tests do not use the counting allocator
+        assert!(!report.counts_allocations);
+        assert_eq!(report.mutate.nbr_allocations, 0);
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_builtin_suite() {
+        let suite = MutatorBenchmark::default()
+            .max_complexity(200.0)
+            .arbitraries(3)
+            .mutations(3)
+            .builtin_suite();
+        assert!(suite.reports.len() >= 5);
+        let display = suite.to_string();
+        assert!(display.contains("== recursive tree =="));
+        assert!(display.contains("unmutate"));
+    }
+}
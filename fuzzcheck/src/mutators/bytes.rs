@@ -0,0 +1,659 @@
+/*!
+A mutator for unstructured binary inputs of type `Vec<u8>`, inspired by AFL.
+
+Unlike [`VecMutator<u8, _>`](crate::mutators::vector::VecMutator), which delegates
+each byte to an integer mutator, [`BytesMutator`] treats the vector as a flat
+sequence of bits and bytes. It first goes through a series of *deterministic stages*
+for each input:
+
+1. flipping 1, 2, and 4 consecutive bits at every bit position
+2. flipping 1, 2, and 4 consecutive bytes at every byte position
+3. adding and subtracting small values to 8, 16, and 32-bit integers at every byte
+   position, in both little and big endian
+4. overwriting 8, 16, 32, and 64-bit integers at every byte position with
+   “interesting” values such as `-1`, `0`, `i16::MAX`, or `65536`, in both little and big endian
+
+Once these stages are exhausted, it switches to *havoc* mode, where it applies a random
+stack of the mutations above, as well as block deletions, block duplications, block
+overwrites, and insertions of strings from the [dictionary](crate::mutators::dictionary).
+
+The complexity of a value is the same as the one computed by `VecMutator<u8, U8Mutator>`,
+that is, `1.0 + 8.0 * value.len()`.
+*/
+
+use std::any::{Any, TypeId};
+use std::cmp;
+use std::ops::RangeInclusive;
+
+use crate::mutators::dictionary;
+use crate::subvalue_provider::EmptySubValueProvider;
+use crate::{Mutator, SubValueProvider};
+
+/// The largest value added to or subtracted from an integer during the arithmetic stages
+const ARITH_MAX: u64 = 35;
+
+const INTERESTING_8: &[i64] = &[-128, -1, 0, 1, 16, 32, 64, 100, 127];
+const INTERESTING_16: &[i64] = &[-32768, -129, 128, 255, 256, 512, 1000, 1024, 4096, 32767];
+const INTERESTING_32: &[i64] = &[
+    -2147483648,
+    -100663046,
+    -32769,
+    32768,
+    65535,
+    65536,
+    100663045,
+    2147483647,
+];
+const INTERESTING_64: &[i64] = &[i64::MIN, -2147483649, 2147483648, 4294967295, 4294967296, i64::MAX];
+
+/// The havoc stage applies between 1 and `2^HAVOC_MAX_STACK_POW` mutations at once
+const HAVOC_MAX_STACK_POW: u32 = 4;
+const HAVOC_BLOCK_SMALL: usize = 4;
+const HAVOC_BLOCK_MEDIUM: usize = 32;
+const HAVOC_BLOCK_LARGE: usize = 128;
+
+#[derive(Clone, Copy)]
+enum Stage {
+    FlipBits(usize),
+    FlipBytes(usize),
+    Arith(usize),
+    Interesting(usize),
+}
+
+const DETERMINISTIC_STAGES: [Stage; 13] = [
+    Stage::FlipBits(1),
+    Stage::FlipBits(2),
+    Stage::FlipBits(4),
+    Stage::FlipBytes(1),
+    Stage::FlipBytes(2),
+    Stage::FlipBytes(4),
+    Stage::Arith(1),
+    Stage::Arith(2),
+    Stage::Arith(4),
+    Stage::Interesting(1),
+    Stage::Interesting(2),
+    Stage::Interesting(4),
+    Stage::Interesting(8),
+];
+
+impl Stage {
+    /// The number of positions at which the stage can be applied to a value of the given length
+    #[no_coverage]
+    fn nbr_positions(self, len: usize) -> usize {
+        match self {
+            Stage::FlipBits(width) => (len * 8 + 1).saturating_sub(width),
+            Stage::FlipBytes(width) | Stage::Arith(width) | Stage::Interesting(width) => {
+                (len + 1).saturating_sub(width)
+            }
+        }
+    }
+    /// The number of different mutations that the stage applies at each position
+    #[no_coverage]
+    fn nbr_mutations_per_position(self) -> usize {
+        let nbr_endians = |width: usize| if width == 1 { 1 } else { 2 };
+        match self {
+            Stage::FlipBits(_) | Stage::FlipBytes(_) => 1,
+            Stage::Arith(width) => (ARITH_MAX as usize) * 2 * nbr_endians(width),
+            Stage::Interesting(width) => nbr_interesting_values(width) * nbr_endians(width),
+        }
+    }
+}
+
+#[no_coverage]
+fn nbr_interesting_values(width: usize) -> usize {
+    interesting_values(width).map(<[i64]>::len).sum()
+}
+
+/// The interesting values that fit in an integer of `width` bytes
+#[no_coverage]
+fn interesting_values(width: usize) -> impl Iterator<Item = &'static [i64]> {
+    [INTERESTING_8, INTERESTING_16, INTERESTING_32, INTERESTING_64]
+        .into_iter()
+        .take(width.trailing_zeros() as usize + 1)
+}
+
+#[no_coverage]
+fn nth_interesting_value(width: usize, mut n: usize) -> i64 {
+    for table in interesting_values(width) {
+        if n < table.len() {
+            return table[n];
+        }
+        n -= table.len();
+    }
+    unreachable!()
+}
+
+#[no_coverage]
+fn read_int(bytes: &[u8], big_endian: bool) -> u64 {
+    let mut buffer = [0; 8];
+    if big_endian {
+        buffer[8 - bytes.len()..].copy_from_slice(bytes);
+        u64::from_be_bytes(buffer)
+    } else {
+        buffer[..bytes.len()].copy_from_slice(bytes);
+        u64::from_le_bytes(buffer)
+    }
+}
+
+#[no_coverage]
+fn write_int(bytes: &mut [u8], x: u64, big_endian: bool) {
+    let width = bytes.len();
+    if big_endian {
+        bytes.copy_from_slice(&x.to_be_bytes()[8 - width..]);
+    } else {
+        bytes.copy_from_slice(&x.to_le_bytes()[..width]);
+    }
+}
+
+/// Keeps track of the next deterministic mutation to apply to a value
+#[derive(Clone)]
+pub struct BytesMutationStep {
+    stage: usize,
+    position: usize,
+    sub_step: usize,
+}
+
+/// Describes how to reverse a mutation performed by [`BytesMutator`]
+pub enum UnmutateBytesToken {
+    /// Copy the saved bytes back at the given index
+    RestoreBytes { idx: usize, old: [u8; 8], width: usize },
+    /// Replace the whole value
+    Replace(Vec<u8>),
+}
+
+/// An AFL-style mutator for `Vec<u8>`.
+///
+/// See the [module documentation](crate::mutators::bytes) for more information.
+pub struct BytesMutator {
+    len_range: RangeInclusive<usize>,
+    deterministic_stages: bool,
+    rng: fastrand::Rng,
+}
+
+impl BytesMutator {
+    #[no_coverage]
+    pub fn new(len_range: RangeInclusive<usize>) -> Self {
+        Self {
+            len_range,
+            deterministic_stages: true,
+            rng: fastrand::Rng::new(),
+        }
+    }
+
+    /// Create a mutator that skips the deterministic stages and only performs
+    /// havoc mutations, which is faster on large inputs.
+    #[no_coverage]
+    pub fn new_without_deterministic_stages(len_range: RangeInclusive<usize>) -> Self {
+        Self {
+            len_range,
+            deterministic_stages: false,
+            rng: fastrand::Rng::new(),
+        }
+    }
+
+    #[no_coverage]
+    fn complexity_of_len(len: usize) -> f64 {
+        1.0 + (len as f64) * 8.0
+    }
+
+    /// The maximum length that a value can have while staying under `max_cplx`
+    #[no_coverage]
+    fn max_len_for_cplx(&self, max_cplx: f64) -> usize {
+        let max_len = if max_cplx <= 1.0 {
+            0
+        } else {
+            ((max_cplx - 1.0) / 8.0) as usize
+        };
+        cmp::min(max_len, *self.len_range.end())
+    }
+
+    /// Apply the deterministic mutation described by `step` and advance the step.
+    ///
+    /// Returns `None` once all deterministic stages have been tried.
+    #[no_coverage]
+    fn deterministic_mutate(&self, value: &mut Vec<u8>, step: &mut BytesMutationStep) -> Option<UnmutateBytesToken> {
+        loop {
+            let stage = *DETERMINISTIC_STAGES.get(step.stage)?;
+            if step.position >= stage.nbr_positions(value.len()) {
+                step.stage += 1;
+                step.position = 0;
+                step.sub_step = 0;
+                continue;
+            }
+            let position = step.position;
+            let sub_step = step.sub_step;
+            step.sub_step += 1;
+            if step.sub_step == stage.nbr_mutations_per_position() {
+                step.sub_step = 0;
+                step.position += 1;
+            }
+
+            let (idx, width) = match stage {
+                Stage::FlipBits(width) => (position / 8, ((position % 8) + width + 7) / 8),
+                Stage::FlipBytes(width) | Stage::Arith(width) | Stage::Interesting(width) => (position, width),
+            };
+            let mut old = [0; 8];
+            old[..width].copy_from_slice(&value[idx..idx + width]);
+            match stage {
+                Stage::FlipBits(nbr_bits) => {
+                    for bit in position..position + nbr_bits {
+                        value[bit / 8] ^= 0b1000_0000 >> (bit % 8);
+                    }
+                }
+                Stage::FlipBytes(_) => {
+                    for b in value[idx..idx + width].iter_mut() {
+                        *b ^= 0xFF;
+                    }
+                }
+                Stage::Arith(width) => {
+                    let bytes = &mut value[idx..idx + width];
+                    let big_endian = width > 1 && sub_step % 2 == 1;
+                    let sub_step = if width > 1 { sub_step / 2 } else { sub_step };
+                    let delta = (sub_step as u64 / 2) + 1;
+                    let x = read_int(bytes, big_endian);
+                    let x = if sub_step % 2 == 0 {
+                        x.wrapping_add(delta)
+                    } else {
+                        x.wrapping_sub(delta)
+                    };
+                    write_int(bytes, x, big_endian);
+                }
+                Stage::Interesting(width) => {
+                    let big_endian = width > 1 && sub_step % 2 == 1;
+                    let sub_step = if width > 1 { sub_step / 2 } else { sub_step };
+                    write_int(
+                        &mut value[idx..idx + width],
+                        nth_interesting_value(width, sub_step) as u64,
+                        big_endian,
+                    );
+                }
+            }
+            if value[idx..idx + width] == old[..width] {
+                // the mutation did not change anything, e.g. writing 0 to a byte that was already 0
+                continue;
+            }
+            return Some(UnmutateBytesToken::RestoreBytes { idx, old, width });
+        }
+    }
+
+    #[no_coverage]
+    fn choose_block_len(&self, limit: usize) -> usize {
+        let max = match self.rng.u8(..3) {
+            0 => HAVOC_BLOCK_SMALL,
+            1 => HAVOC_BLOCK_MEDIUM,
+            _ => HAVOC_BLOCK_LARGE,
+        };
+        self.rng.usize(1..=cmp::min(limit, max))
+    }
+
+    #[no_coverage]
+    fn random_byte_block(&self, len: usize) -> Vec<u8> {
+        if self.rng.bool() {
+            vec![self.rng.u8(..); len]
+        } else {
+            (0..len).map(|_| self.rng.u8(..)).collect()
+        }
+    }
+
+    /// Apply a single random havoc mutation to the value, such that its length stays within
+    /// `min_len..=max_len`.
+    #[no_coverage]
+    fn havoc_once(
+        &self,
+        value: &mut Vec<u8>,
+        min_len: usize,
+        max_len: usize,
+        subvalue_provider: &dyn SubValueProvider,
+    ) {
+        let len = value.len();
+        if len == 0 {
+            // the only thing we can do is insert bytes
+            if max_len > 0 {
+                let block_len = self.choose_block_len(max_len);
+                *value = self.random_byte_block(block_len);
+            }
+            return;
+        }
+        match self.rng.u8(..15) {
+            0 => {
+                let bit = self.rng.usize(..len * 8);
+                value[bit / 8] ^= 0b1000_0000 >> (bit % 8);
+            }
+            1 => {
+                value[self.rng.usize(..len)] ^= self.rng.u8(1..);
+            }
+            2 => {
+                let idx = self.rng.usize(..len);
+                value[idx] = INTERESTING_8[self.rng.usize(..INTERESTING_8.len())] as u8;
+            }
+            x @ 3..=5 => {
+                let width = 1 << (x - 2);
+                if len >= width {
+                    let idx = self.rng.usize(..=len - width);
+                    let n = self.rng.usize(..nbr_interesting_values(width));
+                    write_int(
+                        &mut value[idx..idx + width],
+                        nth_interesting_value(width, n) as u64,
+                        self.rng.bool(),
+                    );
+                }
+            }
+            x @ 6..=8 => {
+                let width = 1 << (x - 6);
+                if len >= width {
+                    let idx = self.rng.usize(..=len - width);
+                    let big_endian = self.rng.bool();
+                    let bytes = &mut value[idx..idx + width];
+                    let delta = self.rng.u64(1..=ARITH_MAX);
+                    let x = read_int(bytes, big_endian);
+                    let x = if self.rng.bool() {
+                        x.wrapping_add(delta)
+                    } else {
+                        x.wrapping_sub(delta)
+                    };
+                    write_int(bytes, x, big_endian);
+                }
+            }
+            9 | 10 => {
+                // delete a block
+                if len > min_len {
+                    let block_len = self.choose_block_len(len - min_len);
+                    let idx = self.rng.usize(..=len - block_len);
+                    let _ = value.drain(idx..idx + block_len);
+                }
+            }
+            11 => {
+                // insert a block, either cloned from the value or made of random bytes
+                if len < max_len {
+                    let block_len = self.choose_block_len(max_len - len);
+                    let block = if self.rng.u8(..4) != 0 && block_len <= len {
+                        let from = self.rng.usize(..=len - block_len);
+                        value[from..from + block_len].to_vec()
+                    } else {
+                        self.random_byte_block(block_len)
+                    };
+                    let idx = self.rng.usize(..=len);
+                    let _ = value.splice(idx..idx, block);
+                }
+            }
+            12 => {
+                // overwrite a block, either with another part of the value or with random bytes
+                let block_len = self.choose_block_len(len);
+                let to = self.rng.usize(..=len - block_len);
+                if self.rng.u8(..4) != 0 {
+                    let from = self.rng.usize(..=len - block_len);
+                    value.copy_within(from..from + block_len, to);
+                } else {
+                    let block = self.random_byte_block(block_len);
+                    value[to..to + block_len].copy_from_slice(&block);
+                }
+            }
+            13 => {
+                // insert or overwrite a string of the dictionary
+                let entries = dictionary::sequences::<u8>();
+                if !entries.is_empty() {
+                    let entry = &entries[self.rng.usize(..entries.len())];
+                    if self.rng.bool() && len + entry.len() <= max_len {
+                        let idx = self.rng.usize(..=len);
+                        let _ = value.splice(idx..idx, entry.iter().copied());
+                    } else if entry.len() <= len {
+                        let to = self.rng.usize(..=len - entry.len());
+                        value[to..to + entry.len()].copy_from_slice(entry);
+                    }
+                }
+            }
+            _ => {
+                // splice in a block taken from another Vec<u8> in the corpus
+                if let Some((other, _)) = subvalue_provider.get_random_subvalue(TypeId::of::<Vec<u8>>(), f64::INFINITY)
+                {
+                    let other = other.downcast_ref::<Vec<u8>>().unwrap();
+                    if !other.is_empty() {
+                        let block_len = self.choose_block_len(other.len());
+                        let from = self.rng.usize(..=other.len() - block_len);
+                        let block = &other[from..from + block_len];
+                        if self.rng.bool() && len + block_len <= max_len {
+                            let idx = self.rng.usize(..=len);
+                            let _ = value.splice(idx..idx, block.iter().copied());
+                        } else if block_len <= len {
+                            let to = self.rng.usize(..=len - block_len);
+                            value[to..to + block_len].copy_from_slice(block);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[no_coverage]
+    fn havoc(
+        &self,
+        value: &mut Vec<u8>,
+        max_cplx: f64,
+        subvalue_provider: &dyn SubValueProvider,
+    ) -> UnmutateBytesToken {
+        let old = value.clone();
+        let min_len = *self.len_range.start();
+        let max_len = cmp::max(self.max_len_for_cplx(max_cplx), value.len());
+        let nbr_stacked = 1 << self.rng.u32(..=HAVOC_MAX_STACK_POW);
+        for _ in 0..nbr_stacked {
+            self.havoc_once(value, min_len, max_len, subvalue_provider);
+        }
+        UnmutateBytesToken::Replace(old)
+    }
+}
+
+impl Mutator<Vec<u8>> for BytesMutator {
+    #[doc(hidden)]
+    type Cache = ();
+    #[doc(hidden)]
+    type MutationStep = BytesMutationStep;
+    #[doc(hidden)]
+    type ArbitraryStep = bool;
+    #[doc(hidden)]
+    type UnmutateToken = UnmutateBytesToken;
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn initialize(&self) {}
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_arbitrary_step(&self) -> Self::ArbitraryStep {
+        false
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn is_valid(&self, value: &Vec<u8>) -> bool {
+        self.len_range.contains(&value.len())
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn validate_value(&self, value: &Vec<u8>) -> Option<Self::Cache> {
+        if self.is_valid(value) {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_mutation_step(&self, _value: &Vec<u8>, _cache: &Self::Cache) -> Self::MutationStep {
+        BytesMutationStep {
+            stage: if self.deterministic_stages {
+                0
+            } else {
+                DETERMINISTIC_STAGES.len()
+            },
+            position: 0,
+            sub_step: 0,
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn global_search_space_complexity(&self) -> f64 {
+        8.0 * ((self.len_range.end() - self.len_range.start()) as f64)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn max_complexity(&self) -> f64 {
+        Self::complexity_of_len(*self.len_range.end())
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn min_complexity(&self) -> f64 {
+        Self::complexity_of_len(*self.len_range.start())
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn complexity(&self, value: &Vec<u8>, _cache: &Self::Cache) -> f64 {
+        Self::complexity_of_len(value.len())
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(Vec<u8>, f64)> {
+        if max_cplx < self.min_complexity() {
+            return None;
+        }
+        if !*step {
+            *step = true;
+            let value = self.random_byte_block(*self.len_range.start());
+            let cplx = Self::complexity_of_len(value.len());
+            Some((value, cplx))
+        } else {
+            Some(self.random_arbitrary(max_cplx))
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_arbitrary(&self, max_cplx: f64) -> (Vec<u8>, f64) {
+        let min_len = *self.len_range.start();
+        let max_len = cmp::max(self.max_len_for_cplx(max_cplx), min_len);
+        let len = self.rng.usize(min_len..=max_len);
+        let value = self.random_byte_block(len);
+        (value, Self::complexity_of_len(len))
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_mutate(
+        &self,
+        value: &mut Vec<u8>,
+        _cache: &mut Self::Cache,
+        step: &mut Self::MutationStep,
+        subvalue_provider: &dyn SubValueProvider,
+        max_cplx: f64,
+    ) -> Option<(Self::UnmutateToken, f64)> {
+        if max_cplx < self.min_complexity() {
+            return None;
+        }
+        if value.is_empty() && self.max_len_for_cplx(max_cplx) == 0 {
+            return None;
+        }
+        let token = if let Some(token) = self.deterministic_mutate(value, step) {
+            token
+        } else {
+            self.havoc(value, max_cplx, subvalue_provider)
+        };
+        Some((token, Self::complexity_of_len(value.len())))
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_mutate(
+        &self,
+        value: &mut Vec<u8>,
+        _cache: &mut Self::Cache,
+        max_cplx: f64,
+    ) -> (Self::UnmutateToken, f64) {
+        let token = self.havoc(value, max_cplx, &EmptySubValueProvider);
+        (token, Self::complexity_of_len(value.len()))
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn unmutate(&self, value: &mut Vec<u8>, _cache: &mut Self::Cache, t: Self::UnmutateToken) {
+        match t {
+            UnmutateBytesToken::RestoreBytes { idx, old, width } => {
+                value[idx..idx + width].copy_from_slice(&old[..width]);
+            }
+            UnmutateBytesToken::Replace(old) => {
+                *value = old;
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn visit_subvalues<'a>(
+        &self,
+        _value: &'a Vec<u8>,
+        _cache: &'a Self::Cache,
+        _visit: &mut dyn FnMut(&'a dyn Any, f64),
+    ) {
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        match t {
+            UnmutateBytesToken::RestoreBytes { .. } => "modify_bytes",
+            UnmutateBytesToken::Replace(_) => "replace",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{BytesMutator, DETERMINISTIC_STAGES};
+    use crate::subvalue_provider::EmptySubValueProvider;
+    use crate::Mutator;
+
+    #[allow(clippy::let_unit_value)]
+    #[test]
+    #[no_coverage]
+    fn test_deterministic_stages_are_exhausted() {
+        let m = BytesMutator::new(0..=100);
+        let mut value = vec![0x12, 0x34];
+        let mut cache = m.validate_value(&value).unwrap();
+        let mut step = m.default_mutation_step(&value, &cache);
+        let mut seen = HashSet::new();
+        let mut nbr_mutations = 0;
+        loop {
+            let (token, cplx) = m
+                .ordered_mutate(&mut value, &mut cache, &mut step, &EmptySubValueProvider, 100.0)
+                .unwrap();
+            if step.stage == DETERMINISTIC_STAGES.len() {
+                // the deterministic stages are exhausted, so that mutation was a havoc one
+                m.unmutate(&mut value, &mut cache, token);
+                break;
+            }
+            assert_eq!(cplx, 17.0);
+            assert_ne!(value, vec![0x12, 0x34]);
+            seen.insert(value.clone());
+            nbr_mutations += 1;
+            m.unmutate(&mut value, &mut cache, token);
+            assert_eq!(value, vec![0x12, 0x34]);
+        }
+        // every single bit flip was tried
+        for bit in 0..16 {
+            let mut expected = vec![0x12, 0x34];
+            expected[bit / 8] ^= 0b1000_0000 >> (bit % 8);
+            assert!(seen.contains(&expected));
+        }
+        // interesting values were inserted in both endians
+        assert!(seen.contains(&vec![0xFF, 0x7F]));
+        assert!(seen.contains(&vec![0x7F, 0xFF]));
+        assert!(nbr_mutations > 100);
+    }
+}
@@ -1,6 +1,7 @@
 use std::any::Any;
 use std::ops::{Bound, RangeBounds};
 
+use crate::mutators::dictionary::{self, InterleavedStep};
 use crate::mutators::integer::binary_search_arbitrary_u32;
 use crate::{DefaultMutator, Mutator, MutatorExt};
 
@@ -71,6 +72,64 @@ impl CharWithinRangeMutator {
             search_space_complexity,
         }
     }
+
+    #[no_coverage]
+    fn contains(&self, c: char) -> bool {
+        (self.start_range..=self.start_range + self.len_range).contains(&(c as u32))
+    }
+
+    /// Returns the character corresponding to the given step, or `None` if all characters within
+    /// the range were already produced.
+    ///
+    /// The characters of the dictionary alternate with the rest of the range, which is produced
+    /// in binary-search order.
+    #[no_coverage]
+    fn char_for_step(&self, step: &mut u64) -> Option<char> {
+        let dictionary = dictionary::chars();
+        loop {
+            let current_step = *step;
+            let nbr_search_steps = self.len_range as u128 + 1;
+            let interleaved_step = dictionary::interleaved_step(current_step, dictionary.len(), nbr_search_steps)?;
+            *step += 1;
+            match interleaved_step {
+                InterleavedStep::Entry(idx) => {
+                    let c = dictionary[idx];
+                    if self.contains(c) {
+                        return Some(c);
+                    }
+                }
+                InterleavedStep::Other(search_step) => {
+                    let result = binary_search_arbitrary_u32(0, self.len_range, search_step);
+                    if let Some(c) = char::from_u32(self.start_range.wrapping_add(result)) {
+                        // the characters of the dictionary are produced separately
+                        if dictionary.binary_search(&c).is_err() {
+                            return Some(c);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// A random character within the range, taken from the dictionary once in a while
+    #[no_coverage]
+    fn random_char(&self) -> char {
+        let dictionary = dictionary::chars();
+        if !dictionary.is_empty() && self.rng.usize(..dictionary::DICTIONARY_RATE) == 0 {
+            let c = dictionary[self.rng.usize(..dictionary.len())];
+            if self.contains(c) {
+                return c;
+            }
+        }
+        loop {
+            let value = self
+                .rng
+                .u32(self.start_range..=self.start_range.wrapping_add(self.len_range));
+            if let Some(value) = char::from_u32(value) {
+                return value;
+            }
+        }
+    }
 }
 
 impl Mutator<char> for CharWithinRangeMutator {
@@ -96,13 +155,13 @@ impl Mutator<char> for CharWithinRangeMutator {
     #[doc(hidden)]
     #[no_coverage]
     fn is_valid(&self, value: &char) -> bool {
-        (self.start_range..=self.start_range + self.len_range).contains(&(*value as u32))
+        self.contains(*value)
     }
 
     #[doc(hidden)]
     #[no_coverage]
     fn validate_value(&self, value: &char) -> Option<Self::Cache> {
-        if (self.start_range..=self.start_range + self.len_range).contains(&(*value as u32)) {
+        if self.contains(*value) {
             Some((value.len_utf8() * 8) as f64)
         } else {
             None
@@ -141,78 +200,41 @@ impl Mutator<char> for CharWithinRangeMutator {
         if max_cplx < self.min_complexity() {
             return None;
         }
-        if *step > self.len_range as u64 {
-            None
-        } else {
-            let result = binary_search_arbitrary_u32(0, self.len_range, *step);
-            *step += 1;
-            if let Some(c) = char::from_u32(self.start_range.wrapping_add(result)) {
-                Some((c, (c.len_utf8() * 8) as f64))
-            } else {
-                *step += 1;
-                self.ordered_arbitrary(step, max_cplx)
-            }
-        }
+        let c = self.char_for_step(step)?;
+        Some((c, (c.len_utf8() * 8) as f64))
     }
 
     #[doc(hidden)]
     #[no_coverage]
-    fn random_arbitrary(&self, max_cplx: f64) -> (char, f64) {
-        let value = self
-            .rng
-            .u32(self.start_range..=self.start_range.wrapping_add(self.len_range));
-        if let Some(value) = char::from_u32(value) {
-            (value, (value.len_utf8() * 8) as f64)
-        } else {
-            // try again
-            self.random_arbitrary(max_cplx)
-        }
+    fn random_arbitrary(&self, _max_cplx: f64) -> (char, f64) {
+        let value = self.random_char();
+        (value, (value.len_utf8() * 8) as f64)
     }
     #[doc(hidden)]
     #[no_coverage]
     fn ordered_mutate(
         &self,
         value: &mut char,
-        cache: &mut Self::Cache,
+        _cache: &mut Self::Cache,
         step: &mut Self::MutationStep,
-        subvalue_provider: &dyn crate::SubValueProvider,
+        _subvalue_provider: &dyn crate::SubValueProvider,
         max_cplx: f64,
     ) -> Option<(Self::UnmutateToken, f64)> {
         if max_cplx < self.min_complexity() {
             return None;
         }
-        if *step > self.len_range as u64 {
-            return None;
-        }
-        let token = *value;
-
-        let result = binary_search_arbitrary_u32(0, self.len_range, *step);
-        // TODO: loop instead of recurse
-        if let Some(result) = char::from_u32(self.start_range.wrapping_add(result)) {
-            *step += 1;
-            if result == *value {
-                return self.ordered_mutate(value, cache, step, subvalue_provider, max_cplx);
+        loop {
+            let c = self.char_for_step(step)?;
+            if c != *value {
+                let token = std::mem::replace(value, c);
+                return Some((token, (value.len_utf8() * 8) as f64));
             }
-
-            *value = result;
-
-            Some((token, (value.len_utf8() * 8) as f64))
-        } else {
-            *step += 1;
-            self.ordered_mutate(value, cache, step, subvalue_provider, max_cplx)
         }
     }
     #[doc(hidden)]
     #[no_coverage]
     fn random_mutate(&self, value: &mut char, _cache: &mut Self::Cache, _max_cplx: f64) -> (Self::UnmutateToken, f64) {
-        let old_value = std::mem::replace(
-            value,
-            char::from_u32(
-                self.rng
-                    .u32(self.start_range..=self.start_range.wrapping_add(self.len_range)),
-            )
-            .unwrap_or(*value),
-        );
+        let old_value = std::mem::replace(value, self.random_char());
         (old_value, (value.len_utf8() * 8) as f64)
     }
     #[doc(hidden)]
@@ -225,4 +247,10 @@ impl Mutator<char> for CharWithinRangeMutator {
     #[no_coverage]
     fn visit_subvalues<'a>(&self, _value: &'a char, _cache: &'a Self::Cache, _visit: &mut dyn FnMut(&'a dyn Any, f64)) {
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, _t: &Self::UnmutateToken) -> &'static str {
+        "mutate_char"
+    }
 }
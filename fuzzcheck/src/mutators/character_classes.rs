@@ -216,6 +216,12 @@ impl Mutator<char> for CharacterMutator {
     #[no_coverage]
     fn visit_subvalues<'a>(&self, _value: &'a char, _cache: &'a Self::Cache, _visit: &mut dyn FnMut(&'a dyn Any, f64)) {
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, _t: &Self::UnmutateToken) -> &'static str {
+        "mutate_char"
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,661 @@
+/*!
+A conformance checker verifying that a mutator respects the requirements of the [`Mutator`] trait.
+
+A mutator that does not uphold these requirements can make the fuzzer panic in the middle
+of a fuzz test, or silently make it less efficient. The [`ConformanceChecker`] stress-tests
+any mutator and reports the first violation it finds, with a description of the
+offending value. It checks that:
+
+* the values generated or mutated are accepted by [`validate_value`](Mutator::validate_value)
+* the complexity returned by each method is equal to [`complexity`](Mutator::complexity)
+* the values given by [`ordered_arbitrary`](Mutator::ordered_arbitrary) and
+  [`ordered_mutate`](Mutator::ordered_mutate) are within the maximum complexity
+* [`ordered_arbitrary`](Mutator::ordered_arbitrary) never produces the same value twice,
+  unless [disabled](ConformanceChecker::check_repetitions)
+* [`unmutate`](Mutator::unmutate) restores the original value and cache
+* [`visit_subvalues`](Mutator::visit_subvalues) gives subvalues of valid complexities, and the
+  subvalues of the same type as the value are themselves checked recursively
+
+```
+use fuzzcheck::mutators::conformance::ConformanceChecker;
+use fuzzcheck::DefaultMutator;
+
+let mutator = <Vec<u8>>::default_mutator();
+let report = ConformanceChecker::default()
+    .max_complexity(256.0)
+    // the vector mutator only avoids repetitions on a best-effort basis
+    .check_repetitions(false)
+    .check(&mutator)
+    .unwrap_or_else(|violation| panic!("{}", violation));
+assert!(report.nbr_arbitraries > 0);
+```
+
+The same checks can be performed on the mutator of a fuzz test with the `check-mutator`
+command of `cargo fuzzcheck`. In that case, the values are compared and displayed through
+their serialized form.
+*/
+
+use std::any::{Any, TypeId};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display};
+use std::hash::{Hash, Hasher};
+
+use crate::subvalue_provider::{
+    CrossoverSubValueProvider, EmptySubValueProvider, Generation, SubValueProvider, SubValueProviderId,
+};
+use crate::{Mutator, Serializer};
+
+const CPLX_TOLERANCE: f64 = 0.01;
+
+/// The requirement of the [`Mutator`] trait that was violated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Requirement {
+    /// A generated or mutated value was rejected by [`validate_value`](Mutator::validate_value)
+    Validity,
+    /// A complexity was negative, not a number, or different from [`complexity`](Mutator::complexity)
+    ConsistentComplexity,
+    /// A value given by an ordered method exceeded the maximum complexity
+    MaxComplexity,
+    /// [`ordered_arbitrary`](Mutator::ordered_arbitrary) produced the same value twice
+    NoRepetition,
+    /// [`unmutate`](Mutator::unmutate) did not restore the original value or cache
+    Unmutate,
+    /// [`visit_subvalues`](Mutator::visit_subvalues) gave a subvalue with an invalid complexity
+    Subvalues,
+}
+
+/// A description of the first violation found by a [`ConformanceChecker`]
+#[derive(Debug, Clone)]
+pub struct ConformanceViolation {
+    pub requirement: Requirement,
+    /// The method of the mutator that led to the violation, e.g. `"ordered_mutate"`
+    pub method: &'static str,
+    /// A description of the offending value
+    pub value: String,
+    /// A description of the value before it was mutated, if any
+    pub original_value: Option<String>,
+    pub message: String,
+}
+impl Display for ConformanceViolation {
+    #[no_coverage]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "mutator requirement violated: {:?}", self.requirement)?;
+        writeln!(f, "method: {}", self.method)?;
+        writeln!(f, "{}", self.message)?;
+        if let Some(original_value) = &self.original_value {
+            writeln!(f, "original value: {}", original_value)?;
+        }
+        write!(f, "value: {}", self.value)
+    }
+}
+impl std::error::Error for ConformanceViolation {}
+
+/// A summary of the checks performed by a [`ConformanceChecker`] that found no violation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConformanceReport {
+    /// The number of values generated by `ordered_arbitrary` and `random_arbitrary`
+    pub nbr_arbitraries: usize,
+    /// The number of mutations performed by `ordered_mutate` and `random_mutate`
+    pub nbr_mutations: usize,
+}
+
+/// Stress-tests a [`Mutator`] to verify that it respects the requirements of the trait.
+///
+/// See the [module documentation](crate::mutators::conformance) for the list of checks.
+#[derive(Debug, Clone)]
+pub struct ConformanceChecker {
+    max_arbitrary_cplx: f64,
+    max_mutate_cplx: f64,
+    nbr_arbitraries: usize,
+    nbr_mutations: usize,
+    check_repetitions: bool,
+    check_max_cplx: bool,
+}
+impl Default for ConformanceChecker {
+    #[no_coverage]
+    fn default() -> Self {
+        Self {
+            max_arbitrary_cplx: 1000.0,
+            max_mutate_cplx: 1000.0,
+            nbr_arbitraries: 100,
+            nbr_mutations: 100,
+            check_repetitions: true,
+            check_max_cplx: true,
+        }
+    }
+}
+
+impl ConformanceChecker {
+    /// The maximum complexity given to both the arbitrary and the mutate methods
+    #[must_use]
+    #[no_coverage]
+    pub fn max_complexity(self, max_cplx: f64) -> Self {
+        Self {
+            max_arbitrary_cplx: max_cplx,
+            max_mutate_cplx: max_cplx,
+            ..self
+        }
+    }
+    /// The maximum complexity given to `ordered_arbitrary` and `random_arbitrary`
+    #[must_use]
+    #[no_coverage]
+    pub fn max_arbitrary_complexity(self, max_arbitrary_cplx: f64) -> Self {
+        Self {
+            max_arbitrary_cplx,
+            ..self
+        }
+    }
+    /// The maximum complexity given to `ordered_mutate` and `random_mutate`
+    #[must_use]
+    #[no_coverage]
+    pub fn max_mutate_complexity(self, max_mutate_cplx: f64) -> Self {
+        Self {
+            max_mutate_cplx,
+            ..self
+        }
+    }
+    /// The number of values generated by each of `ordered_arbitrary` and `random_arbitrary`
+    #[must_use]
+    #[no_coverage]
+    pub fn arbitraries(self, nbr_arbitraries: usize) -> Self {
+        Self {
+            nbr_arbitraries,
+            ..self
+        }
+    }
+    /// The number of mutations performed by each of `ordered_mutate` and `random_mutate` on each
+    /// generated value
+    #[must_use]
+    #[no_coverage]
+    pub fn mutations(self, nbr_mutations: usize) -> Self {
+        Self { nbr_mutations, ..self }
+    }
+    /// Whether to check that `ordered_arbitrary` never produces the same value twice.
+    /// It is `true` by default. It can be disabled for mutators that only try to avoid
+    /// repetitions on a best-effort basis.
+    #[must_use]
+    #[no_coverage]
+    pub fn check_repetitions(self, check_repetitions: bool) -> Self {
+        Self {
+            check_repetitions,
+            ..self
+        }
+    }
+    /// Whether to check that the ordered methods respect the maximum complexity.
+    /// It is `true` by default.
+    #[must_use]
+    #[no_coverage]
+    pub fn check_max_complexity(self, check_max_cplx: bool) -> Self {
+        Self { check_max_cplx, ..self }
+    }
+
+    /// Checks the mutator, using the `Debug`, `Hash`, and `Eq` implementations of its values.
+    #[no_coverage]
+    pub fn check<T, M>(&self, mutator: &M) -> Result<ConformanceReport, ConformanceViolation>
+    where
+        T: Clone + Debug + Hash + Eq + 'static,
+        M: Mutator<T>,
+    {
+        Checker {
+            settings: self,
+            mutator,
+            inspector: &DebugInspector,
+            report: ConformanceReport::default(),
+        }
+        .run()
+    }
+
+    /// Checks the mutator, comparing and displaying its values through the given serializer.
+    ///
+    /// This is useful for types that do not implement `Debug`, `Hash`, or `Eq`, such as the
+    /// test cases of a fuzz test.
+    #[no_coverage]
+    pub fn check_with_serializer<T, M>(
+        &self,
+        mutator: &M,
+        serializer: &dyn Serializer<Value = T>,
+    ) -> Result<ConformanceReport, ConformanceViolation>
+    where
+        T: Clone + 'static,
+        M: Mutator<T>,
+    {
+        Checker {
+            settings: self,
+            mutator,
+            inspector: &SerializerInspector(serializer),
+            report: ConformanceReport::default(),
+        }
+        .run()
+    }
+}
+
+/// Compares, hashes, and describes the values of a mutator
+trait Inspector<T> {
+    fn equal(&self, a: &T, b: &T) -> bool;
+    fn fingerprint(&self, value: &T) -> u64;
+    fn describe(&self, value: &T) -> String;
+}
+
+struct DebugInspector;
+impl<T> Inspector<T> for DebugInspector
+where
+    T: Debug + Hash + Eq,
+{
+    #[no_coverage]
+    fn equal(&self, a: &T, b: &T) -> bool {
+        a == b
+    }
+    #[no_coverage]
+    fn fingerprint(&self, value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+    #[no_coverage]
+    fn describe(&self, value: &T) -> String {
+        format!("{:?}", value)
+    }
+}
+
+struct SerializerInspector<'a, T>(&'a dyn Serializer<Value = T>);
+impl<'a, T> Inspector<T> for SerializerInspector<'a, T> {
+    #[no_coverage]
+    fn equal(&self, a: &T, b: &T) -> bool {
+        self.0.to_data(a) == self.0.to_data(b)
+    }
+    #[no_coverage]
+    fn fingerprint(&self, value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.0.to_data(value).hash(&mut hasher);
+        hasher.finish()
+    }
+    #[no_coverage]
+    fn describe(&self, value: &T) -> String {
+        String::from_utf8_lossy(&self.0.to_data(value)).into_owned()
+    }
+}
+
+/// The values already produced by an ordered method
+struct SeenValues<T> {
+    values: HashMap<u64, Vec<T>>,
+}
+impl<T: Clone> SeenValues<T> {
+    #[no_coverage]
+    fn new() -> Self {
+        Self { values: HashMap::new() }
+    }
+    /// Returns `false` if the value was already seen
+    #[no_coverage]
+    fn insert(&mut self, value: &T, inspector: &dyn Inspector<T>) -> bool {
+        let values = self.values.entry(inspector.fingerprint(value)).or_default();
+        if values.iter().any(
+            #[no_coverage]
+            |x| inspector.equal(x, value),
+        ) {
+            false
+        } else {
+            values.push(value.clone());
+            true
+        }
+    }
+}
+
+struct Checker<'a, T, M> {
+    settings: &'a ConformanceChecker,
+    mutator: &'a M,
+    inspector: &'a dyn Inspector<T>,
+    report: ConformanceReport,
+}
+
+impl<'a, T, M> Checker<'a, T, M>
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    #[no_coverage]
+    fn violation(
+        &self,
+        requirement: Requirement,
+        method: &'static str,
+        value: &T,
+        original_value: Option<&T>,
+        message: String,
+    ) -> ConformanceViolation {
+        ConformanceViolation {
+            requirement,
+            method,
+            value: self.inspector.describe(value),
+            original_value: original_value.map(
+                #[no_coverage]
+                |x| self.inspector.describe(x),
+            ),
+            message,
+        }
+    }
+
+    #[no_coverage]
+    fn run(mut self) -> Result<ConformanceReport, ConformanceViolation> {
+        let m = self.mutator;
+        let max_cplx = self.settings.max_arbitrary_cplx;
+        m.initialize();
+
+        let mut step = m.default_arbitrary_step();
+        let mut seen = SeenValues::new();
+        // the previous arbitrary value is used for crossover mutations
+        let mut donor: Option<CrossoverSubValueProvider<T, M>> = None;
+        for i in 0..self.settings.nbr_arbitraries {
+            let (value, cplx) = if let Some(x) = m.ordered_arbitrary(&mut step, max_cplx) {
+                x
+            } else {
+                break;
+            };
+            self.report.nbr_arbitraries += 1;
+            let cache = self.check_value("ordered_arbitrary", &value, None, cplx, Some(max_cplx))?;
+            if self.settings.check_repetitions && !seen.insert(&value, self.inspector) {
+                return Err(self.violation(
+                    Requirement::NoRepetition,
+                    "ordered_arbitrary",
+                    &value,
+                    None,
+                    format!("the value was already generated by a previous call (call #{})", i),
+                ));
+            }
+            let subvalue_provider: &dyn SubValueProvider = if let Some(donor) = &donor {
+                donor
+            } else {
+                &EmptySubValueProvider
+            };
+            self.check_ordered_mutations(&value, &cache, cplx, subvalue_provider)?;
+            self.check_random_mutations(&value, &cache, cplx)?;
+            donor = Some(CrossoverSubValueProvider::new(
+                SubValueProviderId {
+                    idx: 0,
+                    generation: Generation(i),
+                },
+                &value,
+                &cache,
+                m,
+            ));
+        }
+        for _ in 0..self.settings.nbr_arbitraries {
+            let (value, cplx) = m.random_arbitrary(max_cplx);
+            self.report.nbr_arbitraries += 1;
+            let cache = self.check_value("random_arbitrary", &value, None, cplx, None)?;
+            self.check_random_mutations(&value, &cache, cplx)?;
+        }
+        Ok(self.report)
+    }
+
+    /// Checks the validity and complexity of a value, then returns its cache
+    #[no_coverage]
+    fn check_value(
+        &self,
+        method: &'static str,
+        value: &T,
+        original_value: Option<&T>,
+        cplx: f64,
+        max_cplx: Option<f64>,
+    ) -> Result<M::Cache, ConformanceViolation> {
+        let m = self.mutator;
+        let cache = m.validate_value(value).ok_or_else(
+            #[no_coverage]
+            || {
+                self.violation(
+                    Requirement::Validity,
+                    method,
+                    value,
+                    original_value,
+                    "validate_value returned None".to_string(),
+                )
+            },
+        )?;
+        let actual_cplx = m.complexity(value, &cache);
+        if actual_cplx.is_nan() || actual_cplx < 0.0 || (cplx - actual_cplx).abs() >= CPLX_TOLERANCE {
+            return Err(self.violation(
+                Requirement::ConsistentComplexity,
+                method,
+                value,
+                original_value,
+                format!(
+                    "the method returned a complexity of {:.3}, but `complexity` returns {:.3}",
+                    cplx, actual_cplx
+                ),
+            ));
+        }
+        if let Some(max_cplx) = max_cplx && self.settings.check_max_cplx && cplx > max_cplx + CPLX_TOLERANCE {
+            return Err(self.violation(
+                Requirement::MaxComplexity,
+                method,
+                value,
+                original_value,
+                format!("the complexity {:.3} is greater than the maximum {:.3}", cplx, max_cplx),
+            ));
+        }
+        if let Err(message) = self.check_subvalues(value, &cache) {
+            return Err(self.violation(Requirement::Subvalues, method, value, original_value, message));
+        }
+        Ok(cache)
+    }
+
+    /// Checks the complexity of each subvalue of the value.
+    ///
+    /// The subvalues of the same type as the value are checked recursively: they must be
+    /// accepted by the mutator, with the complexity given by `visit_subvalues`.
+    #[no_coverage]
+    fn check_subvalues(&self, value: &T, cache: &M::Cache) -> Result<(), String> {
+        let m = self.mutator;
+        let mut subvalues: Vec<(&dyn Any, f64)> = vec![];
+        m.visit_subvalues(
+            value,
+            cache,
+            &mut #[no_coverage]
+            |subvalue, cplx| subvalues.push((subvalue, cplx)),
+        );
+        for (subvalue, cplx) in subvalues {
+            if cplx.is_nan() || cplx < 0.0 {
+                return Err(format!("a subvalue has an invalid complexity of {:.3}", cplx));
+            }
+            if let Some(subvalue) = subvalue.downcast_ref::<T>() {
+                let subvalue_cache = m.validate_value(subvalue).ok_or_else(
+                    #[no_coverage]
+                    || {
+                        format!(
+                            "validate_value returned None for the subvalue {}",
+                            self.inspector.describe(subvalue)
+                        )
+                    },
+                )?;
+                let actual_cplx = m.complexity(subvalue, &subvalue_cache);
+                if (cplx - actual_cplx).abs() >= CPLX_TOLERANCE {
+                    return Err(format!(
+                        "the subvalue {} was given a complexity of {:.3}, but `complexity` returns {:.3}",
+                        self.inspector.describe(subvalue),
+                        cplx,
+                        actual_cplx
+                    ));
+                }
+                self.check_subvalues(subvalue, &subvalue_cache)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The type and complexity of each subvalue, sorted
+    #[no_coverage]
+    fn subvalues(&self, value: &T, cache: &M::Cache) -> Vec<(TypeId, f64)> {
+        let mut subvalues = vec![];
+        self.mutator.visit_subvalues(
+            value,
+            cache,
+            &mut #[no_coverage]
+            |subvalue: &dyn Any, cplx| subvalues.push((subvalue.type_id(), cplx)),
+        );
+        subvalues.sort_by(
+            #[no_coverage]
+            |x, y| (x.0, x.1).partial_cmp(&(y.0, y.1)).unwrap_or(std::cmp::Ordering::Equal),
+        );
+        subvalues
+    }
+
+    #[no_coverage]
+    fn same_subvalues(a: &[(TypeId, f64)], b: &[(TypeId, f64)]) -> bool {
+        a.len() == b.len()
+            && a.iter().zip(b.iter()).all(
+                #[no_coverage]
+                |(x, y)| x.0 == y.0 && (x.1 - y.1).abs() < CPLX_TOLERANCE,
+            )
+    }
+
+    /// Checks that the value and cache were restored by `unmutate`
+    #[no_coverage]
+    fn check_unmutated_value(
+        &self,
+        method: &'static str,
+        original: &T,
+        original_cplx: f64,
+        original_subvalues: &[(TypeId, f64)],
+        value: &T,
+        cache: &M::Cache,
+    ) -> Result<(), ConformanceViolation> {
+        if !self.inspector.equal(original, value) {
+            return Err(self.violation(
+                Requirement::Unmutate,
+                method,
+                value,
+                Some(original),
+                "unmutate did not restore the original value".to_string(),
+            ));
+        }
+        let cplx = self.mutator.complexity(value, cache);
+        if (cplx - original_cplx).abs() >= CPLX_TOLERANCE
+            || !Self::same_subvalues(&self.subvalues(value, cache), original_subvalues)
+        {
+            return Err(self.violation(
+                Requirement::Unmutate,
+                method,
+                value,
+                Some(original),
+                format!(
+                    "unmutate did not restore the original cache: the complexity is {:.3} instead of {:.3}, or the subvalues differ",
+                    cplx, original_cplx
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    #[no_coverage]
+    fn check_ordered_mutations(
+        &mut self,
+        original: &T,
+        original_cache: &M::Cache,
+        original_cplx: f64,
+        subvalue_provider: &dyn SubValueProvider,
+    ) -> Result<(), ConformanceViolation> {
+        let m = self.mutator;
+        let max_cplx = self.settings.max_mutate_cplx;
+        let original_subvalues = self.subvalues(original, original_cache);
+        let mut value = original.clone();
+        let mut cache = original_cache.clone();
+        let mut step = m.default_mutation_step(&value, &cache);
+        for _ in 0..self.settings.nbr_mutations {
+            let (token, cplx) =
+                if let Some(x) = m.ordered_mutate(&mut value, &mut cache, &mut step, subvalue_provider, max_cplx) {
+                    x
+                } else {
+                    break;
+                };
+            self.report.nbr_mutations += 1;
+            self.check_value("ordered_mutate", &value, Some(original), cplx, Some(max_cplx))?;
+            m.unmutate(&mut value, &mut cache, token);
+            self.check_unmutated_value(
+                "ordered_mutate",
+                original,
+                original_cplx,
+                &original_subvalues,
+                &value,
+                &cache,
+            )?;
+        }
+        Ok(())
+    }
+
+    #[no_coverage]
+    fn check_random_mutations(
+        &mut self,
+        original: &T,
+        original_cache: &M::Cache,
+        original_cplx: f64,
+    ) -> Result<(), ConformanceViolation> {
+        let m = self.mutator;
+        let max_cplx = self.settings.max_mutate_cplx;
+        let original_subvalues = self.subvalues(original, original_cache);
+        let mut value = original.clone();
+        let mut cache = original_cache.clone();
+        for _ in 0..self.settings.nbr_mutations {
+            let (token, cplx) = m.random_mutate(&mut value, &mut cache, max_cplx);
+            self.report.nbr_mutations += 1;
+            self.check_value("random_mutate", &value, Some(original), cplx, None)?;
+            m.unmutate(&mut value, &mut cache, token);
+            self.check_unmutated_value(
+                "random_mutate",
+                original,
+                original_cplx,
+                &original_subvalues,
+                &value,
+                &cache,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::{ConformanceChecker, Requirement};
+    use crate::mutators::char::CharWithinRangeMutator;
+    use crate::mutators::integer::U8Mutator;
+    use crate::mutators::map::MapMutator;
+    use crate::DefaultMutator;
+
+    #[test]
+    #[no_coverage]
+    fn test_conformance_of_default_mutators() {
+        let checker = ConformanceChecker::default()
+            .max_complexity(256.0)
+            .arbitraries(30)
+            .mutations(30);
+        checker.check(&<Result<char, Box<u8>>>::default_mutator()).unwrap();
+        // the vector and tuple mutators only avoid repetitions on a best-effort basis
+        let best_effort_checker = checker.clone().check_repetitions(false);
+        best_effort_checker
+            .check(&<Vec<Option<u8>>>::default_mutator())
+            .unwrap();
+        best_effort_checker
+            .check::<(bool, Vec<u16>), _>(&<(bool, Vec<u16>)>::default_mutator())
+            .unwrap();
+        checker.check(&CharWithinRangeMutator::new('a'..='z')).unwrap();
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_conformance_violation() {
+        // the complexity of a value changes each time it is evaluated
+        let counter = Cell::new(0.0);
+        let mutator = MapMutator::new(
+            U8Mutator::default(),
+            |x: &u8| Some(*x),
+            |x: &u8| *x,
+            move |_x: &u8, cplx: f64| {
+                counter.set(counter.get() + 1.0);
+                cplx + counter.get()
+            },
+        );
+        let violation = ConformanceChecker::default().check(&mutator).unwrap_err();
+        assert_eq!(violation.requirement, Requirement::ConsistentComplexity);
+        assert_eq!(violation.method, "ordered_arbitrary");
+    }
+}
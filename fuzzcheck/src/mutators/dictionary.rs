@@ -0,0 +1,500 @@
+/*!
+A dictionary of constants shared by the integer, `char`, string and `Vec<u8>` mutators.
+
+When the fuzzer starts, it harvests the string literals that the code of the crate being tested
+refers to, and stores them in a global dictionary. The mutators then try these values early on,
+which helps them get past checks such as `if input.starts_with("BEGIN")`.
+
+Only the functions defined in files given by relative paths, which are the ones of the crate being
+tested, are searched for references to constants. The references are recognised on x86-64 and
+AArch64 only, and the dictionary stays empty on other architectures. Strings are extracted as runs
+of printable UTF-8 characters, and integers are parsed from the decimal and hexadecimal numbers that
+appear inside these strings. Integers are also read from the 2, 4, and 8-byte values stored at aligned
+addresses, such as the elements of a constant table. Integer immediates, such as the `0x4d5a` in
+`if x == 0x4d5a`, are encoded in the machine code itself and are not harvested.
+
+The harvesting can be disabled with the `--no-dictionary` command line flag, or with
+[`FuzzerBuilder5::harvest_constants`](crate::builder::FuzzerBuilder5::harvest_constants).
+Constants can also be added manually with [`set_dictionary`], before the dictionary is first used.
+The global dictionary is initialized once, and it cannot change afterwards.
+
+```
+use fuzzcheck::mutators::dictionary::{self, Dictionary};
+
+dictionary::set_dictionary(Dictionary::new([0x4d5a, -12], ["BEGIN", "END"])).unwrap();
+assert_eq!(dictionary::integers(), &[-12, 0x4d5a]);
+// the dictionary was used, so it cannot be changed anymore
+assert!(dictionary::set_dictionary(Dictionary::new([1], ["x"])).is_err());
+```
+*/
+
+use std::any::Any;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use crate::code_coverage_sensor::read_only_data::get_target_read_only_data;
+
+const MIN_STRING_LEN: usize = 3;
+const MAX_STRING_LEN: usize = 64;
+const MAX_NBR_STRINGS: usize = 10_000;
+const MAX_NBR_INTEGERS: usize = 10_000;
+
+/// The inverse of the probability that a random mutation uses the dictionary when it is not empty
+pub(crate) const DICTIONARY_RATE: usize = 16;
+
+/// The global dictionary, initialized the first time that it is used
+static DICTIONARY: OnceLock<Dictionary> = OnceLock::new();
+/// The constants given to [`set_dictionary`] before the global dictionary was initialized
+static PENDING_DICTIONARY: Mutex<Option<Dictionary>> = Mutex::new(None);
+
+/// A set of integer and string constants used by the mutators.
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    integers: Vec<i128>,
+    strings: Vec<String>,
+    byte_strings: Vec<Vec<u8>>,
+    char_strings: Vec<Vec<char>>,
+    chars: Vec<char>,
+}
+
+impl Dictionary {
+    #[no_coverage]
+    pub fn new<S: Into<String>>(
+        integers: impl IntoIterator<Item = i128>,
+        strings: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let integers = integers.into_iter().collect::<BTreeSet<_>>();
+        let mut unique_strings = BTreeSet::new();
+        let strings = strings
+            .into_iter()
+            .map(
+                #[no_coverage]
+                |s| s.into(),
+            )
+            .filter(
+                #[no_coverage]
+                |s: &String| !s.is_empty() && unique_strings.insert(s.clone()),
+            )
+            .collect::<Vec<_>>();
+        let chars = strings
+            .iter()
+            .flat_map(
+                #[no_coverage]
+                |s| s.chars(),
+            )
+            .collect::<BTreeSet<_>>();
+        Self {
+            integers: integers.into_iter().collect(),
+            byte_strings: strings
+                .iter()
+                .map(
+                    #[no_coverage]
+                    |s| s.as_bytes().to_vec(),
+                )
+                .collect(),
+            char_strings: strings
+                .iter()
+                .map(
+                    #[no_coverage]
+                    |s| s.chars().collect(),
+                )
+                .collect(),
+            strings,
+            chars: chars.into_iter().collect(),
+        }
+    }
+
+    /// Extracts the string and integer constants from the given pieces of read-only data, each
+    /// given along with its address.
+    #[no_coverage]
+    pub fn harvest<'a>(read_only_data: impl IntoIterator<Item = (u64, &'a [u8])>) -> Self {
+        let mut strings = BTreeSet::new();
+        let mut integers = BTreeSet::new();
+        for (address, data) in read_only_data {
+            harvest_strings(data, &mut strings, &mut integers);
+            harvest_aligned_integers(address, data, &mut integers);
+        }
+        Self::new(integers, strings)
+    }
+
+    /// Harvests the constants referred to by the code of the crate being tested, in the
+    /// instrumented binary at the given path.
+    ///
+    /// Returns `None` if its sections could not be read.
+    #[no_coverage]
+    pub fn harvest_from_binary(path: &Path) -> Option<Self> {
+        let read_only_data = get_target_read_only_data(path).ok()?;
+        Some(Self::harvest(read_only_data.iter().map(
+            #[no_coverage]
+            |(address, data)| (*address, data.as_slice()),
+        )))
+    }
+
+    /// Returns a dictionary containing the constants of both `self` and `other`.
+    #[must_use]
+    #[no_coverage]
+    pub fn merge(self, other: Self) -> Self {
+        Self::new(
+            self.integers.into_iter().chain(other.integers),
+            self.strings.into_iter().chain(other.strings),
+        )
+    }
+
+    #[no_coverage]
+    pub fn is_empty(&self) -> bool {
+        self.integers.is_empty() && self.strings.is_empty()
+    }
+    /// The integers of the dictionary, sorted and without duplicates
+    #[no_coverage]
+    pub fn integers(&self) -> &[i128] {
+        &self.integers
+    }
+    #[no_coverage]
+    pub fn strings(&self) -> &[String] {
+        &self.strings
+    }
+    /// The characters contained in the strings of the dictionary, sorted and without duplicates
+    #[no_coverage]
+    pub fn chars(&self) -> &[char] {
+        &self.chars
+    }
+}
+
+/// Adds the given constants to the global dictionary used by the mutators.
+///
+/// The global dictionary is initialized the first time that it is used, which happens at the latest
+/// when the fuzzer starts. Afterwards, it cannot change, because the ordered mutations and arbitrary
+/// values of the mutators assume that it is fixed. In that case, the given dictionary is returned
+/// as an error.
+#[no_coverage]
+pub fn set_dictionary(dictionary: Dictionary) -> Result<(), Dictionary> {
+    let mut pending = PENDING_DICTIONARY.lock().unwrap();
+    if DICTIONARY.get().is_some() {
+        return Err(dictionary);
+    }
+    let previous = pending.take();
+    *pending = Some(match previous {
+        Some(previous) => previous.merge(dictionary),
+        None => dictionary,
+    });
+    Ok(())
+}
+
+/// Returns the global dictionary used by the mutators, initializing it if needed.
+#[no_coverage]
+pub fn dictionary() -> &'static Dictionary {
+    initialize_dictionary(
+        #[no_coverage]
+        || None,
+    )
+}
+
+/// Returns the global dictionary, initializing it with the constants given to [`set_dictionary`] and
+/// the ones returned by `harvest` if it was not initialized yet.
+#[no_coverage]
+fn initialize_dictionary(harvest: impl FnOnce() -> Option<Dictionary>) -> &'static Dictionary {
+    DICTIONARY.get_or_init(
+        #[no_coverage]
+        || {
+            // the lock is held until the dictionary is initialized, so that no constants can be lost
+            let mut pending = PENDING_DICTIONARY.lock().unwrap();
+            let dictionary = pending.take().unwrap_or_default();
+            match harvest() {
+                Some(harvested) => dictionary.merge(harvested),
+                None => dictionary,
+            }
+        },
+    )
+}
+
+/// Initializes the global dictionary with the constants of the current executable, unless it
+/// was already initialized.
+#[no_coverage]
+pub(crate) fn harvest_constants_from_current_exe() {
+    initialize_dictionary(
+        #[no_coverage]
+        || {
+            std::env::current_exe().ok().and_then(
+                #[no_coverage]
+                |exe| Dictionary::harvest_from_binary(&exe),
+            )
+        },
+    );
+}
+
+/// An ordered step of a mutator that alternates between the entries of the dictionary and the
+/// mutator's own values, given by [`interleaved_step`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum InterleavedStep {
+    Entry(usize),
+    Other(u64),
+}
+
+/// Returns the meaning of the given step among `nbr_entries` dictionary entries and `nbr_others`
+/// other values, or `None` if all of them were already produced.
+///
+/// The entries and the other values alternate until one of them is exhausted, so that a large
+/// dictionary does not delay the other values of a mutator.
+#[no_coverage]
+pub(crate) fn interleaved_step(step: u64, nbr_entries: usize, nbr_others: u128) -> Option<InterleavedStep> {
+    let step = step as u128;
+    let nbr_entries = nbr_entries as u128;
+    let nbr_alternating = nbr_entries.min(nbr_others);
+    if step < 2 * nbr_alternating {
+        return Some(if step % 2 == 0 {
+            InterleavedStep::Entry((step / 2) as usize)
+        } else {
+            InterleavedStep::Other((step / 2) as u64)
+        });
+    }
+    let idx = nbr_alternating + (step - 2 * nbr_alternating);
+    if idx < nbr_entries {
+        Some(InterleavedStep::Entry(idx as usize))
+    } else if idx < nbr_others {
+        Some(InterleavedStep::Other(idx as u64))
+    } else {
+        None
+    }
+}
+
+/// The integers of the global dictionary, sorted and without duplicates
+#[no_coverage]
+pub fn integers() -> &'static [i128] {
+    dictionary().integers()
+}
+
+/// The characters of the global dictionary, sorted and without duplicates
+#[no_coverage]
+pub fn chars() -> &'static [char] {
+    dictionary().chars()
+}
+
+/// The strings of the global dictionary, as sequences of `T`.
+///
+/// They are available as `Vec<u8>` (the UTF-8 encoding of each string) and as `Vec<char>`.
+/// For any other type, the returned slice is empty.
+#[no_coverage]
+pub fn sequences<T: 'static>() -> &'static [Vec<T>] {
+    let dictionary = dictionary();
+    if let Some(sequences) = (&dictionary.byte_strings as &dyn Any).downcast_ref::<Vec<Vec<T>>>() {
+        return sequences;
+    }
+    if let Some(sequences) = (&dictionary.char_strings as &dyn Any).downcast_ref::<Vec<Vec<T>>>() {
+        return sequences;
+    }
+    &[]
+}
+
+/// Adds the printable strings of the given data to `strings`, and the numbers written inside
+/// them to `integers`
+#[no_coverage]
+fn harvest_strings(data: &[u8], strings: &mut BTreeSet<String>, integers: &mut BTreeSet<i128>) {
+    for run in printable_runs(data) {
+        if strings.len() >= MAX_NBR_STRINGS && integers.len() >= MAX_NBR_INTEGERS {
+            break;
+        }
+        if strings.len() < MAX_NBR_STRINGS && (MIN_STRING_LEN..=MAX_STRING_LEN).contains(&run.chars().count()) {
+            strings.insert(run.to_owned());
+        }
+        for word in run.split(
+            #[no_coverage]
+            |c: char| !(c.is_alphanumeric() || c == '_'),
+        ) {
+            if let Some(integer) = parse_integer(word) {
+                if integers.len() < MAX_NBR_INTEGERS {
+                    integers.insert(integer);
+                }
+            } else if strings.len() < MAX_NBR_STRINGS
+                && word.len() != run.len()
+                && (MIN_STRING_LEN..=MAX_STRING_LEN).contains(&word.chars().count())
+            {
+                strings.insert(word.to_owned());
+            }
+        }
+    }
+}
+
+/// Adds the 2, 4, and 8-byte integers stored at the aligned addresses of the given data, which
+/// starts at `address`, to `integers`.
+///
+/// Both the unsigned and signed interpretations of each value are kept. The values whose bytes are
+/// all printable, which are more likely to be part of a string, and the single digits are skipped.
+#[no_coverage]
+fn harvest_aligned_integers(address: u64, data: &[u8], integers: &mut BTreeSet<i128>) {
+    for width in [2, 4, 8] {
+        let start = ((width - address % width) % width) as usize;
+        for bytes in data.get(start..).unwrap_or_default().chunks_exact(width as usize) {
+            if integers.len() >= MAX_NBR_INTEGERS {
+                return;
+            }
+            if bytes.iter().all(
+                #[no_coverage]
+                |b| (0x20..=0x7E).contains(b),
+            ) {
+                continue;
+            }
+            let (unsigned, signed) = match *bytes {
+                [a, b] => {
+                    let x = u16::from_ne_bytes([a, b]);
+                    (x as i128, x as i16 as i128)
+                }
+                [a, b, c, d] => {
+                    let x = u32::from_ne_bytes([a, b, c, d]);
+                    (x as i128, x as i32 as i128)
+                }
+                _ => {
+                    let x = u64::from_ne_bytes(bytes.try_into().unwrap());
+                    (x as i128, x as i64 as i128)
+                }
+            };
+            for integer in [unsigned, signed] {
+                // single digits are too common to be interesting
+                if !(-9..=9).contains(&integer) {
+                    integers.insert(integer);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the maximal runs of printable characters of the given data
+#[no_coverage]
+fn printable_runs(data: &[u8]) -> impl Iterator<Item = &str> {
+    let mut idx = 0;
+    std::iter::from_fn(
+        #[no_coverage]
+        move || {
+            while idx < data.len() {
+                let start = idx;
+                while let Some(width) = printable_char_width(&data[idx..]) {
+                    idx += width;
+                }
+                if idx == start {
+                    idx += 1;
+                } else {
+                    return Some(std::str::from_utf8(&data[start..idx]).unwrap());
+                }
+            }
+            None
+        },
+    )
+}
+
+/// Returns the width of the printable UTF-8 character at the start of `data`, if there is one
+#[no_coverage]
+fn printable_char_width(data: &[u8]) -> Option<usize> {
+    let width = match *data.first()? {
+        0x20..=0x7E => return Some(1),
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return None,
+    };
+    let c = std::str::from_utf8(data.get(..width)?).ok()?.chars().next()?;
+    if c.is_control() {
+        None
+    } else {
+        Some(width)
+    }
+}
+
+#[no_coverage]
+fn parse_integer(word: &str) -> Option<i128> {
+    if let Some(hex) = word.strip_prefix("0x").or_else(
+        #[no_coverage]
+        || word.strip_prefix("0X"),
+    ) {
+        i128::from_str_radix(&hex.replace('_', ""), 16).ok()
+    } else if word.starts_with(
+        #[no_coverage]
+        |c: char| c.is_ascii_digit(),
+    ) {
+        // single digits are too common to be interesting
+        if word.len() < 2 {
+            return None;
+        }
+        word.replace('_', "").parse::<i128>().ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interleaved_step, Dictionary, InterleavedStep};
+
+    #[test]
+    #[no_coverage]
+    fn test_harvest() {
+        let data = b"\x00\x01BEGIN\xFF\xFEmagic=0x4d5a len 1024\x00ab\x00caf\xC3\xA9\x00";
+        let dictionary = Dictionary::harvest([(0, &data[..])]);
+        assert!(dictionary.integers().contains(&1024));
+        assert!(dictionary.integers().contains(&0x4d5a));
+        for s in ["BEGIN", "magic=0x4d5a len 1024", "magic", "len", "café"] {
+            assert!(dictionary.strings().iter().any(|x| x == s), "{}", s);
+        }
+        assert!(!dictionary.strings().iter().any(|x| x == "ab"));
+        assert!(dictionary.chars().contains(&'é'));
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_harvest_aligned_integers() {
+        let mut data = vec![];
+        data.extend_from_slice(&0x4d5a_u16.to_ne_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(&0xdead_beef_u32.to_ne_bytes());
+        data.extend_from_slice(b"ABCDEFGH");
+        data.extend_from_slice(&(-1000_i64).to_ne_bytes());
+        let dictionary = Dictionary::harvest([(0x1000, data.as_slice())]);
+        let integers = dictionary.integers();
+        for integer in [0x4d5a, 0xdead_beef, -1000, u64::MAX as i128 - 999] {
+            assert!(integers.contains(&integer), "{}", integer);
+        }
+        // the bytes of strings are not read as integers
+        assert!(!integers.contains(&(u16::from_ne_bytes(*b"AB") as i128)));
+        assert!(!integers.contains(&0));
+
+        // the integers are read at aligned addresses only
+        let dictionary = Dictionary::harvest([(0x1001, data.as_slice())]);
+        assert!(!dictionary.integers().contains(&0x4d5a));
+        assert!(!dictionary.integers().contains(&0xdead_beef));
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_interleaved_step() {
+        use InterleavedStep::{Entry, Other};
+        let steps = (0..8)
+            .map(
+                #[no_coverage]
+                |step| interleaved_step(step, 2, 5),
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(
+            steps,
+            [
+                Some(Entry(0)),
+                Some(Other(0)),
+                Some(Entry(1)),
+                Some(Other(1)),
+                Some(Other(2)),
+                Some(Other(3)),
+                Some(Other(4)),
+                None
+            ]
+        );
+        let steps = (0..5)
+            .map(
+                #[no_coverage]
+                |step| interleaved_step(step, 3, 1),
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(
+            steps,
+            [Some(Entry(0)), Some(Other(0)), Some(Entry(1)), Some(Entry(2)), None]
+        );
+        assert_eq!(interleaved_step(u64::MAX, 10, 1 << 64), Some(Other(u64::MAX - 10)));
+    }
+}
@@ -210,6 +210,16 @@ where
             _ => unreachable!(),
         }
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        match (self, t) {
+            (Either::Left(m), Either::Left(t)) => m.mutation_kind(t),
+            (Either::Right(m), Either::Right(t)) => m.mutation_kind(t),
+            _ => unreachable!(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -450,4 +460,15 @@ where
             _ => unreachable!(),
         }
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        match (self, t) {
+            (Either3::A(m), Either3::A(t)) => m.mutation_kind(t),
+            (Either3::B(m), Either3::B(t)) => m.mutation_kind(t),
+            (Either3::C(m), Either3::C(t)) => m.mutation_kind(t),
+            _ => unreachable!(),
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use std::any::Any;
 
+use crate::mutators::vose_alias::VoseAlias;
 use crate::Mutator;
 
 /// Trait used by the [DefaultMutator derive macro](fuzzcheck_mutators_derive::DefaultMutator)
@@ -12,6 +13,10 @@ pub trait BasicEnumStructure {
 /// A mutator used for enums implementing [BasicEnumStructure]
 pub struct BasicEnumMutator {
     non_ignored_variant_count: usize,
+    weights: Option<VoseAlias>,
+    /// The variant indices sorted by decreasing weight, in which order they are tried by the
+    /// ordered mutations of a weighted mutator
+    variants_by_weight: Vec<usize>,
     rng: fastrand::Rng,
     cplx: f64,
 }
@@ -23,10 +28,47 @@ impl BasicEnumMutator {
     {
         Self {
             non_ignored_variant_count,
+            weights: None,
+            variants_by_weight: (0..non_ignored_variant_count).collect(),
             rng: <_>::default(),
             cplx: crate::mutators::size_to_cplxity(non_ignored_variant_count),
         }
     }
+
+    /// Create a [`BasicEnumMutator`] whose random values are the non-ignored variants, each
+    /// chosen with a probability proportional to its weight.
+    ///
+    /// The ordered arbitrary values and mutations go through the variants by decreasing weight.
+    #[no_coverage]
+    pub fn with_weights<T>(weights: Vec<f64>) -> Self
+    where
+        T: BasicEnumStructure,
+    {
+        assert!(weights.iter().all(
+            #[no_coverage]
+            |w| *w > 0.0
+        ));
+        let non_ignored_variant_count = weights.len();
+        let mut variants_by_weight = (0..non_ignored_variant_count).collect::<Vec<_>>();
+        variants_by_weight.sort_by(
+            #[no_coverage]
+            |&a, &b| weights[b].partial_cmp(&weights[a]).unwrap_or(std::cmp::Ordering::Equal),
+        );
+        Self {
+            weights: Some(VoseAlias::new(weights)),
+            variants_by_weight,
+            ..Self::new::<T>(non_ignored_variant_count)
+        }
+    }
+
+    #[no_coverage]
+    fn random_variant_index(&self) -> usize {
+        if let Some(weights) = &self.weights {
+            weights.sample()
+        } else {
+            self.rng.usize(..self.non_ignored_variant_count)
+        }
+    }
 }
 
 const INITIAL_MUTATION_STEP: usize = 1;
@@ -105,7 +147,7 @@ where
         if *step < self.non_ignored_variant_count {
             let old_step = *step;
             *step += 1;
-            Some((T::from_variant_index(old_step), self.cplx))
+            Some((T::from_variant_index(self.variants_by_weight[old_step]), self.cplx))
         } else {
             None
         }
@@ -114,7 +156,7 @@ where
     #[doc(hidden)]
     #[no_coverage]
     fn random_arbitrary(&self, _max_cplx: f64) -> (T, f64) {
-        let item_idx = self.rng.usize(..self.non_ignored_variant_count);
+        let item_idx = self.random_variant_index();
         (T::from_variant_index(item_idx), self.cplx)
     }
 
@@ -137,7 +179,25 @@ where
             let old_index = value.get_variant_index();
             let old_step = *step;
             *step += 1;
-            *value = T::from_variant_index((old_index + old_step) % self.non_ignored_variant_count);
+            let new_index = if self.weights.is_some() {
+                // go through the other variants by decreasing weight
+                let old_position = self.variants_by_weight.iter().position(
+                    #[no_coverage]
+                    |&idx| idx == old_index,
+                );
+                let mut position = old_step - 1;
+                if old_position.map_or(
+                    false,
+                    #[no_coverage]
+                    |old_position| position >= old_position,
+                ) {
+                    position += 1;
+                }
+                self.variants_by_weight[position]
+            } else {
+                (old_index + old_step) % self.non_ignored_variant_count
+            };
+            *value = T::from_variant_index(new_index);
             Some((old_index, self.cplx))
         } else {
             None
@@ -148,7 +208,7 @@ where
     #[no_coverage]
     fn random_mutate(&self, value: &mut T, _cache: &mut Self::Cache, _max_cplx: f64) -> (Self::UnmutateToken, f64) {
         let old_index = value.get_variant_index();
-        let item_idx = self.rng.usize(..self.non_ignored_variant_count);
+        let item_idx = self.random_variant_index();
         *value = T::from_variant_index(item_idx);
         (old_index, self.cplx)
     }
@@ -162,4 +222,10 @@ where
     #[doc(hidden)]
     #[no_coverage]
     fn visit_subvalues<'a>(&self, _value: &'a T, _cache: &'a Self::Cache, _visit: &mut dyn FnMut(&'a dyn Any, f64)) {}
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, _t: &Self::UnmutateToken) -> &'static str {
+        "change_variant"
+    }
 }
@@ -0,0 +1,291 @@
+use std::any::Any;
+
+use crate::Mutator;
+
+/// The number of times the random methods of a [`FixupMutator`] try to find a repaired value
+/// within the maximum complexity
+const MAX_RANDOM_TRIES: usize = 10;
+
+/// A [`FixupMutator`] repairs the values outputted by a mutator.
+///
+/// Given any [`Mutator<Value=T>`] and a function [`Fn(&mut T)`], it creates a new
+/// mutator which calls the function on every value generated or mutated by the
+/// underlying mutator. It is useful for inputs containing fields that depend on the
+/// rest of the value, such as checksums or length fields. Unlike a
+/// [`FilterMutator`](crate::mutators::filter::FilterMutator), which would reject
+/// nearly all of these inputs, the `FixupMutator` makes them consistent.
+///
+/// The complexity of a value is computed on the repaired value, and the subvalues handed
+/// to crossover mutations are those of the repaired value. The values whose repaired
+/// version is not a valid value of the underlying mutator are skipped, as with a
+/// [`FilterMutator`](crate::mutators::filter::FilterMutator).
+///
+/// Since the repair can make a value more complex, the ordered methods also skip the
+/// repaired values whose complexity exceeds the maximum complexity. The random methods
+/// retry a few times before accepting such a value.
+///
+/// ```
+/// use fuzzcheck::{DefaultMutator, MutatorExt};
+///
+/// // the last byte of the message is the sum of the other ones
+/// let m = <Vec<u8>>::default_mutator().fixup(|msg: &mut Vec<u8>| {
+///     if let Some((checksum, payload)) = msg.split_last_mut() {
+///         *checksum = payload.iter().fold(0u8, |sum, x| sum.wrapping_add(*x));
+///     }
+/// });
+/// ```
+pub struct FixupMutator<M, F> {
+    mutator: M,
+    fixup: F,
+}
+
+impl<M, F> FixupMutator<M, F> {
+    /// Creates a new [`FixupMutator`].
+    #[no_coverage]
+    pub fn new<T>(mutator: M, fixup: F) -> FixupMutator<M, F>
+    where
+        M: Mutator<T>,
+        T: Clone + 'static,
+        F: Fn(&mut T),
+        Self: 'static,
+    {
+        FixupMutator { mutator, fixup }
+    }
+}
+
+pub struct FixupMutatorCache<T, C> {
+    /// The value generated by the underlying mutator, before it was repaired
+    raw_value: T,
+    raw_cache: C,
+    /// The cache of the underlying mutator for the repaired value
+    cache: C,
+    cplx: f64,
+}
+impl<T, C> Clone for FixupMutatorCache<T, C>
+where
+    T: Clone,
+    C: Clone,
+{
+    #[no_coverage]
+    fn clone(&self) -> Self {
+        Self {
+            raw_value: self.raw_value.clone(),
+            raw_cache: self.raw_cache.clone(),
+            cache: self.cache.clone(),
+            cplx: self.cplx,
+        }
+    }
+}
+
+pub struct FixupMutatorUnmutateToken<T, C, U> {
+    inner: U,
+    old_value: T,
+    old_cache: C,
+    old_cplx: f64,
+}
+
+impl<M, F> FixupMutator<M, F> {
+    /// Repairs the given raw value and returns it along with its cache and complexity, or
+    /// `None` if the repaired value is not a valid value of the underlying mutator
+    #[no_coverage]
+    fn repair<T>(&self, mut value: T) -> Option<(T, M::Cache, f64)>
+    where
+        M: Mutator<T>,
+        T: Clone + 'static,
+        F: Fn(&mut T),
+    {
+        (self.fixup)(&mut value);
+        let cache = self.mutator.validate_value(&value)?;
+        let cplx = self.mutator.complexity(&value, &cache);
+        Some((value, cache, cplx))
+    }
+
+    /// Replaces the value and its cache by their repaired versions, and returns the token
+    /// restoring them
+    #[no_coverage]
+    fn replace<T, C, U>(
+        &self,
+        value: &mut T,
+        cache: &mut FixupMutatorCache<T, C>,
+        inner: U,
+        new_value: T,
+        new_cache: C,
+        cplx: f64,
+    ) -> FixupMutatorUnmutateToken<T, C, U> {
+        FixupMutatorUnmutateToken {
+            inner,
+            old_value: std::mem::replace(value, new_value),
+            old_cache: std::mem::replace(&mut cache.cache, new_cache),
+            old_cplx: std::mem::replace(&mut cache.cplx, cplx),
+        }
+    }
+}
+
+impl<T, M, F> Mutator<T> for FixupMutator<M, F>
+where
+    M: Mutator<T>,
+    T: Clone + 'static,
+    F: Fn(&mut T),
+    Self: 'static,
+{
+    #[doc(hidden)]
+    type Cache = FixupMutatorCache<T, M::Cache>;
+    #[doc(hidden)]
+    type MutationStep = M::MutationStep;
+    #[doc(hidden)]
+    type ArbitraryStep = M::ArbitraryStep;
+    #[doc(hidden)]
+    type UnmutateToken = FixupMutatorUnmutateToken<T, M::Cache, M::UnmutateToken>;
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn initialize(&self) {
+        self.mutator.initialize();
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_arbitrary_step(&self) -> Self::ArbitraryStep {
+        self.mutator.default_arbitrary_step()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn is_valid(&self, value: &T) -> bool {
+        self.mutator.is_valid(value)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn validate_value(&self, value: &T) -> Option<Self::Cache> {
+        let raw_cache = self.mutator.validate_value(value)?;
+        let cplx = self.mutator.complexity(value, &raw_cache);
+        Some(FixupMutatorCache {
+            raw_value: value.clone(),
+            cache: raw_cache.clone(),
+            raw_cache,
+            cplx,
+        })
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_mutation_step(&self, _value: &T, cache: &Self::Cache) -> Self::MutationStep {
+        self.mutator.default_mutation_step(&cache.raw_value, &cache.raw_cache)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn global_search_space_complexity(&self) -> f64 {
+        self.mutator.global_search_space_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn max_complexity(&self) -> f64 {
+        self.mutator.max_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn min_complexity(&self) -> f64 {
+        self.mutator.min_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn complexity(&self, _value: &T, cache: &Self::Cache) -> f64 {
+        cache.cplx
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(T, f64)> {
+        loop {
+            let (raw_value, _) = self.mutator.ordered_arbitrary(step, max_cplx)?;
+            if let Some((value, _, cplx)) = self.repair(raw_value) && cplx <= max_cplx {
+                return Some((value, cplx));
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_arbitrary(&self, max_cplx: f64) -> (T, f64) {
+        let mut tries = 0;
+        loop {
+            let (raw_value, _) = self.mutator.random_arbitrary(max_cplx);
+            if let Some((value, _, cplx)) = self.repair(raw_value) {
+                tries += 1;
+                if cplx <= max_cplx || tries >= MAX_RANDOM_TRIES {
+                    return (value, cplx);
+                }
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_mutate(
+        &self,
+        value: &mut T,
+        cache: &mut Self::Cache,
+        step: &mut Self::MutationStep,
+        subvalue_provider: &dyn crate::SubValueProvider,
+        max_cplx: f64,
+    ) -> Option<(Self::UnmutateToken, f64)> {
+        loop {
+            let (inner, _) = self.mutator.ordered_mutate(
+                &mut cache.raw_value,
+                &mut cache.raw_cache,
+                step,
+                subvalue_provider,
+                max_cplx,
+            )?;
+            if let Some((new_value, new_cache, cplx)) = self.repair(cache.raw_value.clone()) && cplx <= max_cplx {
+                return Some((self.replace(value, cache, inner, new_value, new_cache, cplx), cplx));
+            }
+            self.mutator.unmutate(&mut cache.raw_value, &mut cache.raw_cache, inner);
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_mutate(&self, value: &mut T, cache: &mut Self::Cache, max_cplx: f64) -> (Self::UnmutateToken, f64) {
+        let mut tries = 0;
+        loop {
+            let (inner, _) = self
+                .mutator
+                .random_mutate(&mut cache.raw_value, &mut cache.raw_cache, max_cplx);
+            if let Some((new_value, new_cache, cplx)) = self.repair(cache.raw_value.clone()) {
+                tries += 1;
+                if cplx <= max_cplx || tries >= MAX_RANDOM_TRIES {
+                    return (self.replace(value, cache, inner, new_value, new_cache, cplx), cplx);
+                }
+            }
+            self.mutator.unmutate(&mut cache.raw_value, &mut cache.raw_cache, inner);
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn unmutate(&self, value: &mut T, cache: &mut Self::Cache, t: Self::UnmutateToken) {
+        self.mutator
+            .unmutate(&mut cache.raw_value, &mut cache.raw_cache, t.inner);
+        *value = t.old_value;
+        cache.cache = t.old_cache;
+        cache.cplx = t.old_cplx;
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn visit_subvalues<'a>(&self, value: &'a T, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
+        self.mutator.visit_subvalues(value, &cache.cache, visit)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        self.mutator.mutation_kind(&t.inner)
+    }
+}
@@ -5,6 +5,10 @@ use serde::{Deserialize, Serialize};
 
 /// An abstract syntax tree.
 ///
+/// Its leaves are the characters, or [bytes](crate::mutators::grammar::byte), that it represents. When the grammar contains
+/// [named rules](crate::mutators::grammar::rule), each subtree generated by a rule is
+/// wrapped in an `AST::Rule` node, which makes it possible to walk the syntax tree by rule name.
+///
 #[cfg_attr(
     feature = "serde_json_serializer",
     doc = "It can be serialized with [`SerdeSerializer`](crate::SerdeSerializer) on crate feature `serde_json_serializer`"
@@ -16,10 +20,21 @@ use serde::{Deserialize, Serialize};
 #[cfg_attr(feature = "serde_json_serializer", derive(Serialize, Deserialize))]
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum AST {
-    #[doc(hidden)]
+    /// A single character
     Token(char),
-    #[doc(hidden)]
+    /// A single byte, generated by a [byte literal](crate::mutators::grammar::byte)
+    Byte(u8),
+    /// The syntax trees of a concatenation, a repetition, or a recursion
     Sequence(Vec<AST>),
+    /// The syntax tree generated by a named grammar rule
+    Rule {
+        /// The name of the rule
+        name: String,
+        /// The index of the chosen alternative if the rule is an alternation, and 0 otherwise
+        alternative: usize,
+        /// The syntax tree of the chosen alternative
+        ast: Box<AST>,
+    },
 }
 
 impl AST {
@@ -29,11 +44,46 @@ impl AST {
             AST::Token(c) => {
                 string.push(*c);
             }
+            AST::Byte(b) => {
+                // bytes are not always valid UTF-8, see `generate_bytes_in`
+                string.push(if b.is_ascii() {
+                    *b as char
+                } else {
+                    char::REPLACEMENT_CHARACTER
+                });
+            }
             AST::Sequence(asts) => {
                 for ast in asts {
                     ast.generate_string_in(string);
                 }
             }
+            AST::Rule { ast, .. } => {
+                ast.generate_string_in(string);
+            }
+        }
+    }
+
+    /// Appends the bytes represented by the AST to the given vector.
+    ///
+    /// The characters are encoded in UTF-8.
+    #[no_coverage]
+    pub fn generate_bytes_in(&self, bytes: &mut Vec<u8>) {
+        match self {
+            AST::Token(c) => {
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            }
+            AST::Byte(b) => {
+                bytes.push(*b);
+            }
+            AST::Sequence(asts) => {
+                for ast in asts {
+                    ast.generate_bytes_in(bytes);
+                }
+            }
+            AST::Rule { ast, .. } => {
+                ast.generate_bytes_in(bytes);
+            }
         }
     }
 
@@ -45,4 +95,66 @@ impl AST {
         self.generate_string_in(&mut s);
         s
     }
+
+    /// Converts the AST to its byte representation, in which the characters are encoded in UTF-8
+    ///
+    /// Unlike [`to_string`](AST::to_string), it preserves the bytes generated by
+    /// [byte literals](crate::mutators::grammar::byte) that are not ASCII characters.
+    #[no_coverage]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(64);
+        self.generate_bytes_in(&mut bytes);
+        bytes
+    }
+
+    /// The name of the rule that generated this node and the index of its chosen alternative,
+    /// or `None` if the node was not generated by a named rule
+    #[no_coverage]
+    pub fn rule(&self) -> Option<(&str, usize)> {
+        match self {
+            AST::Rule { name, alternative, .. } => Some((name, *alternative)),
+            _ => None,
+        }
+    }
+
+    /// The direct children of the node
+    #[no_coverage]
+    pub fn children(&self) -> &[AST] {
+        match self {
+            AST::Token(_) | AST::Byte(_) => &[],
+            AST::Sequence(asts) => asts,
+            AST::Rule { ast, .. } => std::slice::from_ref(ast),
+        }
+    }
+
+    /// Returns all the nodes generated by the rule with the given name, in depth-first order
+    ///
+    /// ```
+    /// use fuzzcheck::mutators::grammar::{concatenation, literal, literal_range, repetition, rule, GrammarParser};
+    ///
+    /// let digit = rule("digit", literal_range('0'..='9'));
+    /// let number = rule("number", repetition(digit, 1..));
+    /// let grammar = concatenation([number.clone(), literal('+'), number]);
+    ///
+    /// let ast = GrammarParser::new(&grammar).parse("12+345").unwrap();
+    /// let numbers = ast.find_rules("number").into_iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    /// assert_eq!(numbers, ["12", "345"]);
+    /// assert_eq!(ast.find_rules("digit").len(), 5);
+    /// ```
+    #[no_coverage]
+    pub fn find_rules<'a>(&'a self, name: &str) -> Vec<&'a AST> {
+        let mut nodes = vec![];
+        self.find_rules_in(name, &mut nodes);
+        nodes
+    }
+
+    #[no_coverage]
+    fn find_rules_in<'a>(&'a self, name: &str, nodes: &mut Vec<&'a AST>) {
+        if matches!(self, AST::Rule { name: n, .. } if n == name) {
+            nodes.push(self);
+        }
+        for child in self.children() {
+            child.find_rules_in(name, nodes);
+        }
+    }
 }
@@ -0,0 +1,357 @@
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use super::ast::AST;
+use super::grammar::Grammar;
+use super::parser::GrammarParser;
+
+/// The maximum number of parsed values cached for each referencing rule
+const MAX_PARSED_VALUES: usize = 4096;
+
+/// The role of a named rule with respect to a symbol table
+#[derive(Clone)]
+enum Binding {
+    Declaration(String),
+    Reference(String),
+    LatestReference(String),
+    Scope(String),
+}
+
+/**
+Context-sensitive constraints on the syntax trees generated by a grammar.
+
+Grammars cannot express that a variable must be declared before it is used, or that a closing tag
+must match its opening tag. The constraints bind [named rules](crate::mutators::grammar::rule) to
+symbol tables instead:
+
+* the subtrees generated by a [declaration](GrammarConstraints::declaration) rule are added to a table
+* the subtrees generated by a [reference](GrammarConstraints::reference) rule are replaced by one
+of the values previously added to the table, if it also matches the referencing rule
+* the values added to a table within a [scope](GrammarConstraints::scope) rule are removed from it
+at the end of the scope
+
+The constraints are applied to the syntax trees by the mutator returned by
+[`.with_constraints(..)`](crate::mutators::grammar::ASTMutator::with_constraints), such that most
+generated syntax trees are semantically valid.
+
+```
+use fuzzcheck::mutators::grammar::{
+    alternation, concatenation, grammar_based_ast_mutator, literal, literal_range, repetition, rule, GrammarConstraints,
+};
+
+let name = repetition(literal_range('a'..='z'), 1..5);
+let statement = alternation([
+    concatenation([literal('+'), rule("declared", name.clone()), literal(';')]),
+    concatenation([literal('?'), rule("used", name), literal(';')]),
+]);
+let grammar = repetition(statement, 0..10);
+
+// the used variables are always among the ones declared earlier
+let constraints = GrammarConstraints::new(&grammar)
+    .declaration("declared", "variables")
+    .reference("used", "variables");
+let mutator = grammar_based_ast_mutator(grammar).with_constraints(constraints);
+```
+*/
+pub struct GrammarConstraints {
+    rules: HashMap<String, Rc<Grammar>>,
+    bindings: HashMap<String, Vec<Binding>>,
+    parsers: HashMap<String, GrammarParser>,
+    /// The syntax trees of the values of the tables, parsed by each referencing rule
+    parsed_values: HashMap<String, RefCell<HashMap<Vec<u8>, Option<Rc<AST>>>>>,
+}
+
+impl GrammarConstraints {
+    /// Create an empty set of constraints for the named rules of the given grammar.
+    #[no_coverage]
+    pub fn new(grammar: &Rc<Grammar>) -> Self {
+        let mut rules = HashMap::new();
+        find_rules(grammar, &mut rules, &mut vec![]);
+        Self {
+            rules,
+            bindings: HashMap::new(),
+            parsers: HashMap::new(),
+            parsed_values: HashMap::new(),
+        }
+    }
+
+    #[no_coverage]
+    fn bind(mut self, rule: &str, binding: Binding) -> Self {
+        let grammar = self.rules.get(rule).unwrap_or_else(
+            #[no_coverage]
+            || panic!("the grammar does not contain a rule named \"{}\"", rule),
+        );
+        if matches!(binding, Binding::Reference(_) | Binding::LatestReference(_)) {
+            self.parsers.entry(rule.to_string()).or_insert_with(
+                #[no_coverage]
+                || GrammarParser::new(grammar),
+            );
+            self.parsed_values.entry(rule.to_string()).or_default();
+        }
+        self.bindings.entry(rule.to_string()).or_default().push(binding);
+        self
+    }
+
+    /// The subtrees generated by the rule are added to the table, once they are complete.
+    ///
+    /// # Panics
+    /// Panics if the grammar does not contain a rule with that name.
+    #[no_coverage]
+    pub fn declaration(self, rule: &str, table: &str) -> Self {
+        self.bind(rule, Binding::Declaration(table.to_string()))
+    }
+
+    /// The subtrees generated by the rule are replaced by one of the values previously added to the table.
+    ///
+    /// The chosen value depends on the original subtree, such that the mutations of the rest of the
+    /// syntax tree tend to preserve it. Values that do not match the rule are ignored, and the subtree
+    /// is left unchanged if the table is empty.
+    ///
+    /// # Panics
+    /// Panics if the grammar does not contain a rule with that name.
+    #[no_coverage]
+    pub fn reference(self, rule: &str, table: &str) -> Self {
+        self.bind(rule, Binding::Reference(table.to_string()))
+    }
+
+    /// Like [`reference`](GrammarConstraints::reference), but the subtrees are always replaced by the most
+    /// recent value of the table, which is useful for closing tags matching their opening tag.
+    ///
+    /// # Panics
+    /// Panics if the grammar does not contain a rule with that name.
+    #[no_coverage]
+    pub fn latest_reference(self, rule: &str, table: &str) -> Self {
+        self.bind(rule, Binding::LatestReference(table.to_string()))
+    }
+
+    /// The values added to the table within the subtrees generated by the rule are removed from the
+    /// table at the end of the subtree.
+    ///
+    /// # Panics
+    /// Panics if the grammar does not contain a rule with that name.
+    #[no_coverage]
+    pub fn scope(self, rule: &str, table: &str) -> Self {
+        self.bind(rule, Binding::Scope(table.to_string()))
+    }
+
+    /// Modifies the syntax tree such that it satisfies the constraints.
+    #[no_coverage]
+    pub fn apply(&self, ast: &mut AST) {
+        let mut tables = HashMap::new();
+        self.apply_rec(ast, &mut tables);
+    }
+
+    #[no_coverage]
+    fn apply_rec(&self, ast: &mut AST, tables: &mut HashMap<String, Vec<Vec<u8>>>) {
+        let bindings = match ast {
+            AST::Token(_) | AST::Byte(_) => return,
+            AST::Sequence(asts) => {
+                for ast in asts {
+                    self.apply_rec(ast, tables);
+                }
+                return;
+            }
+            AST::Rule { name, .. } => self.bindings.get(name.as_str()).cloned().unwrap_or_default(),
+        };
+        let scopes = bindings
+            .iter()
+            .filter_map(
+                #[no_coverage]
+                |binding| match binding {
+                    Binding::Scope(table) => Some((table.clone(), tables.get(table).map_or(0, Vec::len))),
+                    _ => None,
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let mut replaced = false;
+        for binding in &bindings {
+            if let Binding::Reference(table) | Binding::LatestReference(table) = binding {
+                if let Some(new_ast) = self.pick_reference(ast, binding, tables.get(table)) {
+                    *ast = new_ast;
+                    replaced = true;
+                    break;
+                }
+            }
+        }
+        if !replaced && let AST::Rule { ast: inner, .. } = ast {
+            self.apply_rec(inner, tables);
+        }
+
+        for binding in &bindings {
+            if let Binding::Declaration(table) = binding {
+                tables.entry(table.clone()).or_default().push(ast.to_bytes());
+            }
+        }
+        for (table, len) in scopes {
+            if let Some(values) = tables.get_mut(&table) {
+                values.truncate(len);
+            }
+        }
+    }
+
+    /// Returns the syntax tree of the value of the table that should replace the referencing rule
+    #[no_coverage]
+    fn pick_reference(&self, ast: &AST, binding: &Binding, values: Option<&Vec<Vec<u8>>>) -> Option<AST> {
+        let values = values?;
+        let name = ast.rule()?.0;
+        let bytes = ast.to_bytes();
+        if matches!(binding, Binding::Reference(_)) && values.contains(&bytes) {
+            return None;
+        }
+        let candidates = values.iter().filter_map(
+            #[no_coverage]
+            |value| self.parse_value(name, value),
+        );
+        let value = if matches!(binding, Binding::LatestReference(_)) {
+            candidates.last()?
+        } else {
+            let candidates = candidates.collect::<Vec<_>>();
+            if candidates.is_empty() {
+                return None;
+            }
+            // the choice is deterministic, so that unrelated mutations do not change it
+            let mut hasher = DefaultHasher::new();
+            bytes.hash(&mut hasher);
+            candidates[hasher.finish() as usize % candidates.len()].clone()
+        };
+        Some(value.as_ref().clone())
+    }
+
+    /// Parses a value of a table with the referencing rule, reusing the syntax tree of a previous
+    /// parse of the same value if possible
+    #[no_coverage]
+    fn parse_value(&self, rule: &str, value: &[u8]) -> Option<Rc<AST>> {
+        let mut parsed_values = self.parsed_values[rule].borrow_mut();
+        if let Some(ast) = parsed_values.get(value) {
+            return ast.clone();
+        }
+        if parsed_values.len() >= MAX_PARSED_VALUES {
+            parsed_values.clear();
+        }
+        let ast = self.parsers[rule].parse_bytes(value).map(Rc::new);
+        parsed_values.insert(value.to_vec(), ast.clone());
+        ast
+    }
+}
+
+#[no_coverage]
+fn find_rules(grammar: &Rc<Grammar>, rules: &mut HashMap<String, Rc<Grammar>>, visited: &mut Vec<*const Grammar>) {
+    if visited.contains(&Rc::as_ptr(grammar)) {
+        return;
+    }
+    visited.push(Rc::as_ptr(grammar));
+    match grammar.as_ref() {
+        Grammar::Literal(_) | Grammar::ByteLiteral(_) | Grammar::Recurse(_) => {}
+        Grammar::Alternation(gs) | Grammar::Concatenation(gs) => {
+            for g in gs {
+                find_rules(g, rules, visited);
+            }
+        }
+        Grammar::Repetition(g, _) | Grammar::Recursive(g) => find_rules(g, rules, visited),
+        Grammar::Rule(name, g) => {
+            rules.entry(name.clone()).or_insert_with(
+                #[no_coverage]
+                || grammar.clone(),
+            );
+            find_rules(g, rules, visited);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::GrammarConstraints;
+    use crate::mutators::grammar::{
+        concatenation, grammar_based_ast_mutator, literal, literal_range, recurse, recursive, repetition, rule, AST,
+    };
+    use crate::mutators::testing_utilities::test_mutator;
+    use crate::Mutator;
+
+    #[test]
+    #[no_coverage]
+    fn test_matching_tags() {
+        let name = repetition(literal_range('a'..='c'), 1..4);
+        let grammar = recursive(
+            #[no_coverage]
+            |element| {
+                rule(
+                    "element",
+                    concatenation([
+                        literal('<'),
+                        rule("open", name.clone()),
+                        literal('>'),
+                        repetition(recurse(element), 0..2),
+                        literal('<'),
+                        literal('/'),
+                        rule("close", name.clone()),
+                        literal('>'),
+                    ]),
+                )
+            },
+        );
+        let constraints = GrammarConstraints::new(&grammar)
+            .scope("element", "tags")
+            .declaration("open", "tags")
+            .latest_reference("close", "tags");
+        let mutator = grammar_based_ast_mutator(grammar).with_constraints(constraints);
+        mutator.initialize();
+        for _ in 0..100 {
+            let (ast, _) = mutator.random_arbitrary(100.);
+            for element in ast.find_rules("element") {
+                let open = element.find_rules("open")[0].to_string();
+                let close = element.find_rules("close").last().unwrap().to_string();
+                assert_eq!(open, close, "{}", ast.to_string());
+            }
+        }
+        test_mutator(mutator, 100., 100., false, true, 50, 50);
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_parsed_values_are_cached() {
+        let name = repetition(literal_range('a'..='c'), 1..3);
+        let grammar = repetition(
+            concatenation([rule("declared", name.clone()), literal(';'), rule("used", name)]),
+            0..10,
+        );
+        let constraints = GrammarConstraints::new(&grammar)
+            .declaration("declared", "variables")
+            .reference("used", "variables");
+        let mutator = grammar_based_ast_mutator(grammar);
+        mutator.initialize();
+        let mut declared = HashSet::new();
+        for _ in 0..100 {
+            let (mut ast, _) = mutator.random_arbitrary(100.);
+            constraints.apply(&mut ast);
+            let len = constraints.parsed_values["used"].borrow().len();
+            // the values of the table are only parsed once
+            constraints.apply(&mut ast);
+            assert_eq!(constraints.parsed_values["used"].borrow().len(), len);
+            declared.extend(ast.find_rules("declared").into_iter().map(AST::to_bytes));
+        }
+        let parsed_values = constraints.parsed_values["used"].borrow();
+        assert!(!parsed_values.is_empty());
+        for (value, ast) in parsed_values.iter() {
+            assert!(declared.contains(value));
+            let bytes = ast.as_ref().map(
+                #[no_coverage]
+                |ast| ast.to_bytes(),
+            );
+            assert_eq!(bytes.as_ref(), Some(value));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the grammar does not contain a rule named \"b\"")]
+    #[no_coverage]
+    fn test_unknown_rule() {
+        let grammar = rule("a", literal('a'));
+        let _ = GrammarConstraints::new(&grammar).declaration("b", "table");
+    }
+}
@@ -0,0 +1,930 @@
+//! Loads grammars written in EBNF, see [`ebnf`].
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::path::Path;
+use std::rc::{Rc, Weak};
+
+use super::grammar::Grammar;
+use crate::mutators::grammar::{alternation, concatenation, literal, literal_range, recurse, repetition, rule};
+
+/// An error encountered while loading an EBNF grammar
+#[derive(Debug)]
+pub enum EbnfError {
+    /// The grammar file could not be read
+    Io(std::io::Error),
+    /// The grammar is not written in the supported notation
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// A rule is defined twice
+    DuplicateRule { rule: String, line: usize },
+    /// A rule refers to a rule that is not defined
+    UndefinedRule {
+        rule: String,
+        referenced_by: String,
+        line: usize,
+    },
+    /// The given start rule is not defined
+    UndefinedStartRule(String),
+    /// A rule can derive itself without consuming any character. The cycle starts and ends with the same rule.
+    LeftRecursion { cycle: Vec<String> },
+    /// A rule can never generate a finite string, because it always refers to itself
+    NonTerminating { rule: String },
+}
+
+impl Display for EbnfError {
+    #[no_coverage]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EbnfError::Io(e) => write!(f, "cannot read the grammar file: {}", e),
+            EbnfError::Syntax { line, column, message } => {
+                write!(f, "syntax error at line {}, column {}: {}", line, column, message)
+            }
+            EbnfError::DuplicateRule { rule, line } => {
+                write!(f, "the rule `{}` is defined a second time at line {}", rule, line)
+            }
+            EbnfError::UndefinedRule {
+                rule,
+                referenced_by,
+                line,
+            } => write!(
+                f,
+                "the rule `{}`, referenced by `{}` at line {}, is not defined",
+                rule, referenced_by, line
+            ),
+            EbnfError::UndefinedStartRule(rule) => write!(f, "the start rule `{}` is not defined", rule),
+            EbnfError::LeftRecursion { cycle } => write!(
+                f,
+                "the grammar is left-recursive, which is not supported: {}",
+                cycle.join(" -> ")
+            ),
+            EbnfError::NonTerminating { rule } => {
+                write!(f, "the rule `{}` can never generate a finite string", rule)
+            }
+        }
+    }
+}
+impl std::error::Error for EbnfError {}
+
+/**
+Creates a grammar from its description in EBNF, starting from the rule named `start_rule`.
+
+The supported notation is the following:
+```text
+(* a comment *)
+rule_name = expression ;
+```
+where an expression is made of:
+* `other_rule`, a reference to another rule, which may be defined before or after
+* `"abc"` or `'abc'`, a string literal, which supports the escape sequences
+  `\n`, `\r`, `\t`, `\\`, `\"`, `\'`, and `\u{..}`
+* `'a'..'z'`, any character within the given range
+* `a b` or `a , b`, a concatenation
+* `a | b`, an alternation
+* `( a )`, a group
+* `[ a ]` or `a?`, an optional expression
+* `{ a }` or `a*`, an expression repeated any number of times
+* `a+`, an expression repeated at least once
+
+`::=` can be used instead of `=`. The rules can refer to each other recursively, but
+they must not be left-recursive, and each rule must be able to generate a finite string.
+
+Each rule is given its name with [`rule`](crate::mutators::grammar::rule), so that the
+syntax trees record which rule generated each of their nodes.
+
+```
+use fuzzcheck::mutators::grammar::{ebnf, GrammarParser};
+
+let grammar = ebnf(r#"
+    (* arithmetic expressions *)
+    expr   = term , { ( "+" | "-" ) , term } ;
+    term   = factor , { "*" , factor } ;
+    factor = number | "(" , expr , ")" ;
+    number = '1'..'9' , { '0'..'9' } ;
+"#, "expr").unwrap();
+
+let parser = GrammarParser::new(&grammar);
+assert!(parser.parse("(1+20)*3").is_some());
+assert!(parser.parse("(1+20)*").is_none());
+
+let error = ebnf("expr = expr , '+' , number | number ; number = '0'..'9' ;", "expr").unwrap_err();
+assert_eq!(error.to_string(), "the grammar is left-recursive, which is not supported: expr -> expr");
+```
+*/
+#[no_coverage]
+pub fn ebnf(source: &str, start_rule: &str) -> Result<Rc<Grammar>, EbnfError> {
+    let rules = Parser::new(source)?.parse_rules()?;
+    Loader::new(rules)?.load(start_rule)
+}
+
+/// Reads the file at the given path and creates a grammar from its content with [`ebnf`]
+#[no_coverage]
+pub fn ebnf_from_file(path: impl AsRef<Path>, start_rule: &str) -> Result<Rc<Grammar>, EbnfError> {
+    let source = std::fs::read_to_string(path).map_err(EbnfError::Io)?;
+    ebnf(&source, start_rule)
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Rule {
+        name: String,
+        line: usize,
+    },
+    String(String),
+    Range(char, char),
+    Concatenation(Vec<Expr>),
+    Alternation(Vec<Expr>),
+    Repetition {
+        expr: Box<Expr>,
+        min: usize,
+        max: Option<usize>,
+    },
+}
+
+struct Rule {
+    name: String,
+    line: usize,
+    expr: Expr,
+}
+
+// ===== PARSER =====
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    String(String),
+    Define,
+    Terminator,
+    Pipe,
+    Comma,
+    DotDot,
+    Open(char),
+    Close(char),
+    Postfix(char),
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize, usize)>,
+    idx: usize,
+    /// The position of the end of the source, used to report unexpected ends
+    end: (usize, usize),
+}
+
+impl Parser {
+    #[no_coverage]
+    fn new(source: &str) -> Result<Self, EbnfError> {
+        let chars = source.chars().collect::<Vec<_>>();
+        let mut tokens = vec![];
+        let (mut line, mut column) = (1, 1);
+        let mut i = 0;
+        // advances the position by `n` characters
+        let advance = #[no_coverage]
+        |i: &mut usize, n: usize, line: &mut usize, column: &mut usize| {
+            for c in &chars[*i..*i + n] {
+                if *c == '\n' {
+                    *line += 1;
+                    *column = 1;
+                } else {
+                    *column += 1;
+                }
+            }
+            *i += n;
+        };
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            let (token_line, token_column) = (line, column);
+            let syntax_error = #[no_coverage]
+            |message: String| EbnfError::Syntax {
+                line: token_line,
+                column: token_column,
+                message,
+            };
+            let token = match c {
+                _ if c.is_whitespace() => {
+                    advance(&mut i, 1, &mut line, &mut column);
+                    continue;
+                }
+                '(' if next == Some('*') => {
+                    let mut len = 2;
+                    while i + len + 1 < chars.len() && !(chars[i + len] == '*' && chars[i + len + 1] == ')') {
+                        len += 1;
+                    }
+                    if i + len + 1 >= chars.len() {
+                        return Err(syntax_error("unterminated comment".to_string()));
+                    }
+                    advance(&mut i, len + 2, &mut line, &mut column);
+                    continue;
+                }
+                'a'..='z' | 'A'..='Z' | '_' => {
+                    let mut len = 1;
+                    while let Some(c) = chars.get(i + len) && (c.is_ascii_alphanumeric() || *c == '_' || *c == '-') {
+                        len += 1;
+                    }
+                    let ident = chars[i..i + len].iter().collect();
+                    advance(&mut i, len, &mut line, &mut column);
+                    tokens.push((Token::Ident(ident), token_line, token_column));
+                    continue;
+                }
+                '"' | '\'' => {
+                    let (string, len) = Self::parse_string(&chars[i..]).map_err(syntax_error)?;
+                    advance(&mut i, len, &mut line, &mut column);
+                    tokens.push((Token::String(string), token_line, token_column));
+                    continue;
+                }
+                ':' if chars.get(i + 1..i + 3) == Some(&[':', '=']) => {
+                    advance(&mut i, 2, &mut line, &mut column);
+                    Token::Define
+                }
+                '.' if next == Some('.') => {
+                    advance(&mut i, 1, &mut line, &mut column);
+                    Token::DotDot
+                }
+                '=' => Token::Define,
+                ';' => Token::Terminator,
+                '|' => Token::Pipe,
+                ',' => Token::Comma,
+                '(' | '[' | '{' => Token::Open(c),
+                ')' | ']' | '}' => Token::Close(c),
+                '?' | '*' | '+' => Token::Postfix(c),
+                _ => return Err(syntax_error(format!("unexpected character `{}`", c))),
+            };
+            advance(&mut i, 1, &mut line, &mut column);
+            tokens.push((token, token_line, token_column));
+        }
+        Ok(Self {
+            tokens,
+            idx: 0,
+            end: (line, column),
+        })
+    }
+
+    /// Parses the string literal at the start of `chars`, and returns its content and its length in the source
+    #[no_coverage]
+    fn parse_string(chars: &[char]) -> Result<(String, usize), String> {
+        let quote = chars[0];
+        let mut string = String::new();
+        let mut i = 1;
+        loop {
+            match chars.get(i) {
+                None | Some('\n') => return Err("unterminated string literal".to_string()),
+                Some(c) if *c == quote => return Ok((string, i + 1)),
+                Some('\\') => {
+                    let escaped = match chars.get(i + 1) {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('\\') => '\\',
+                        Some('"') => '"',
+                        Some('\'') => '\'',
+                        Some('u') if chars.get(i + 2) == Some(&'{') => {
+                            let len = chars[i + 3..].iter().position(
+                                #[no_coverage]
+                                |c| *c == '}',
+                            );
+                            let len = len.ok_or_else(
+                                #[no_coverage]
+                                || "unterminated unicode escape sequence".to_string(),
+                            )?;
+                            let hex = chars[i + 3..i + 3 + len].iter().collect::<String>();
+                            let c = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32).ok_or_else(
+                                #[no_coverage]
+                                || format!("invalid unicode escape sequence `\\u{{{}}}`", hex),
+                            )?;
+                            string.push(c);
+                            i += len + 4;
+                            continue;
+                        }
+                        _ => return Err("invalid escape sequence".to_string()),
+                    };
+                    string.push(escaped);
+                    i += 2;
+                }
+                Some(c) => {
+                    string.push(*c);
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    #[no_coverage]
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.idx).map(
+            #[no_coverage]
+            |(token, _, _)| token,
+        )
+    }
+
+    #[no_coverage]
+    fn position(&self) -> (usize, usize) {
+        self.tokens.get(self.idx).map_or(
+            self.end,
+            #[no_coverage]
+            |(_, line, column)| (*line, *column),
+        )
+    }
+
+    #[no_coverage]
+    fn error(&self, expected: &str) -> EbnfError {
+        let (line, column) = self.position();
+        let found = match self.peek() {
+            Some(token) => format!("{:?}", token),
+            None => "the end of the grammar".to_string(),
+        };
+        EbnfError::Syntax {
+            line,
+            column,
+            message: format!("expected {}, found {}", expected, found),
+        }
+    }
+
+    #[no_coverage]
+    fn parse_rules(mut self) -> Result<Vec<Rule>, EbnfError> {
+        let mut rules = vec![];
+        while let Some(token) = self.peek() {
+            let name = if let Token::Ident(name) = token {
+                name.clone()
+            } else {
+                return Err(self.error("the name of a rule"));
+            };
+            let (line, _) = self.position();
+            self.idx += 1;
+            if self.peek() != Some(&Token::Define) {
+                return Err(self.error("`=`"));
+            }
+            self.idx += 1;
+            let expr = self.parse_alternation()?;
+            if self.peek() != Some(&Token::Terminator) {
+                return Err(self.error("`;`"));
+            }
+            self.idx += 1;
+            rules.push(Rule { name, line, expr });
+        }
+        Ok(rules)
+    }
+
+    #[no_coverage]
+    fn parse_alternation(&mut self) -> Result<Expr, EbnfError> {
+        let mut alternatives = vec![self.parse_concatenation()?];
+        while self.peek() == Some(&Token::Pipe) {
+            self.idx += 1;
+            alternatives.push(self.parse_concatenation()?);
+        }
+        if alternatives.len() == 1 {
+            Ok(alternatives.pop().unwrap())
+        } else {
+            Ok(Expr::Alternation(alternatives))
+        }
+    }
+
+    #[no_coverage]
+    fn parse_concatenation(&mut self) -> Result<Expr, EbnfError> {
+        let mut elements = vec![];
+        loop {
+            match self.peek() {
+                Some(Token::Comma) if !elements.is_empty() => {
+                    self.idx += 1;
+                    elements.push(self.parse_postfix()?);
+                }
+                Some(Token::Ident(_) | Token::String(_) | Token::Open(_)) => {
+                    elements.push(self.parse_postfix()?);
+                }
+                _ => break,
+            }
+        }
+        if elements.len() == 1 {
+            Ok(elements.pop().unwrap())
+        } else {
+            // an empty concatenation matches the empty string
+            Ok(Expr::Concatenation(elements))
+        }
+    }
+
+    #[no_coverage]
+    fn parse_postfix(&mut self) -> Result<Expr, EbnfError> {
+        let mut expr = self.parse_primary()?;
+        while let Some(Token::Postfix(c)) = self.peek() {
+            let (min, max) = match c {
+                '?' => (0, Some(1)),
+                '*' => (0, None),
+                _ => (1, None),
+            };
+            self.idx += 1;
+            expr = Expr::Repetition {
+                expr: Box::new(expr),
+                min,
+                max,
+            };
+        }
+        Ok(expr)
+    }
+
+    #[no_coverage]
+    fn parse_primary(&mut self) -> Result<Expr, EbnfError> {
+        let (line, column) = self.position();
+        match self.peek().cloned() {
+            Some(Token::Ident(name)) => {
+                self.idx += 1;
+                Ok(Expr::Rule { name, line })
+            }
+            Some(Token::String(start)) => {
+                self.idx += 1;
+                if self.peek() != Some(&Token::DotDot) {
+                    return Ok(Expr::String(start));
+                }
+                self.idx += 1;
+                let end = if let Some(Token::String(end)) = self.peek() {
+                    end.clone()
+                } else {
+                    return Err(self.error("a character"));
+                };
+                self.idx += 1;
+                let mut start = start.chars();
+                let mut end = end.chars();
+                match (start.next(), start.next(), end.next(), end.next()) {
+                    (Some(start), None, Some(end), None) if start <= end => Ok(Expr::Range(start, end)),
+                    _ => Err(EbnfError::Syntax {
+                        line,
+                        column,
+                        message: "the bounds of a character range must be single characters in increasing order"
+                            .to_string(),
+                    }),
+                }
+            }
+            Some(Token::Open(open)) => {
+                self.idx += 1;
+                let expr = self.parse_alternation()?;
+                let (close, min, max) = match open {
+                    '(' => (')', 1, Some(1)),
+                    '[' => (']', 0, Some(1)),
+                    _ => ('}', 0, None),
+                };
+                if self.peek() != Some(&Token::Close(close)) {
+                    return Err(self.error(&format!("`{}`", close)));
+                }
+                self.idx += 1;
+                if open == '(' {
+                    Ok(expr)
+                } else {
+                    Ok(Expr::Repetition {
+                        expr: Box::new(expr),
+                        min,
+                        max,
+                    })
+                }
+            }
+            _ => Err(self.error("an expression")),
+        }
+    }
+}
+
+// ===== LOADER =====
+
+impl Expr {
+    #[no_coverage]
+    fn visit_rules<'a>(&'a self, visit: &mut impl FnMut(&'a str, usize)) {
+        match self {
+            Expr::Rule { name, line } => visit(name, *line),
+            Expr::String(_) | Expr::Range(_, _) => {}
+            Expr::Concatenation(es) | Expr::Alternation(es) => {
+                for e in es {
+                    e.visit_rules(visit);
+                }
+            }
+            Expr::Repetition { expr, .. } => expr.visit_rules(visit),
+        }
+    }
+
+    /// Whether the expression can match the empty string, given the rules known to be nullable
+    #[no_coverage]
+    fn is_nullable(&self, nullable: &HashSet<&str>) -> bool {
+        match self {
+            Expr::Rule { name, .. } => nullable.contains(name.as_str()),
+            Expr::String(s) => s.is_empty(),
+            Expr::Range(_, _) => false,
+            Expr::Concatenation(es) => es.iter().all(
+                #[no_coverage]
+                |e| e.is_nullable(nullable),
+            ),
+            Expr::Alternation(es) => es.iter().any(
+                #[no_coverage]
+                |e| e.is_nullable(nullable),
+            ),
+            Expr::Repetition { expr, min, .. } => *min == 0 || expr.is_nullable(nullable),
+        }
+    }
+
+    /// Whether the expression can generate a finite string, given the rules known to be productive
+    #[no_coverage]
+    fn is_productive(&self, productive: &HashSet<&str>) -> bool {
+        match self {
+            Expr::Rule { name, .. } => productive.contains(name.as_str()),
+            Expr::String(_) | Expr::Range(_, _) => true,
+            Expr::Concatenation(es) => es.iter().all(
+                #[no_coverage]
+                |e| e.is_productive(productive),
+            ),
+            Expr::Alternation(es) => es.iter().any(
+                #[no_coverage]
+                |e| e.is_productive(productive),
+            ),
+            Expr::Repetition { expr, min, .. } => *min == 0 || expr.is_productive(productive),
+        }
+    }
+
+    /// Visits the rules that can be referenced before any character is consumed
+    #[no_coverage]
+    fn visit_leftmost_rules<'a>(&'a self, nullable: &HashSet<&str>, visit: &mut impl FnMut(&'a str)) {
+        match self {
+            Expr::Rule { name, .. } => visit(name),
+            Expr::String(_) | Expr::Range(_, _) => {}
+            Expr::Concatenation(es) => {
+                for e in es {
+                    e.visit_leftmost_rules(nullable, visit);
+                    if !e.is_nullable(nullable) {
+                        break;
+                    }
+                }
+            }
+            Expr::Alternation(es) => {
+                for e in es {
+                    e.visit_leftmost_rules(nullable, visit);
+                }
+            }
+            Expr::Repetition { expr, .. } => expr.visit_leftmost_rules(nullable, visit),
+        }
+    }
+}
+
+/// Computes the fixpoint of a property of the rules, such as nullability
+#[no_coverage]
+fn fixpoint<'a>(
+    rules: &'a HashMap<String, Rule>,
+    property: impl Fn(&Expr, &HashSet<&'a str>) -> bool,
+) -> HashSet<&'a str> {
+    let mut set = HashSet::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (name, rule) in rules {
+            if !set.contains(name.as_str()) && property(&rule.expr, &set) {
+                set.insert(name.as_str());
+                changed = true;
+            }
+        }
+    }
+    set
+}
+
+struct Loader {
+    rules: HashMap<String, Rule>,
+    /// The rules that can reference themselves
+    recursive: HashSet<String>,
+    /// The grammars of the non-recursive rules that were built
+    built: HashMap<String, Rc<Grammar>>,
+    /// The points of recursion of the recursive rules that were built or are being built
+    recursion_points: HashMap<String, Weak<Grammar>>,
+}
+
+impl Loader {
+    #[no_coverage]
+    fn new(rule_list: Vec<Rule>) -> Result<Self, EbnfError> {
+        let mut rules = HashMap::new();
+        let mut order = vec![];
+        for rule in rule_list {
+            if rules.contains_key(&rule.name) {
+                return Err(EbnfError::DuplicateRule {
+                    rule: rule.name,
+                    line: rule.line,
+                });
+            }
+            order.push(rule.name.clone());
+            rules.insert(rule.name.clone(), rule);
+        }
+        // the rules are checked in the order in which they are defined, for deterministic errors
+        for name in &order {
+            let mut undefined = None;
+            rules[name].expr.visit_rules(
+                &mut #[no_coverage]
+                |referenced, line| {
+                    if undefined.is_none() && !rules.contains_key(referenced) {
+                        undefined = Some((referenced.to_string(), line));
+                    }
+                },
+            );
+            if let Some((rule, line)) = undefined {
+                return Err(EbnfError::UndefinedRule {
+                    rule,
+                    referenced_by: name.clone(),
+                    line,
+                });
+            }
+        }
+        let productive = fixpoint(&rules, Expr::is_productive);
+        if let Some(name) = order.iter().find(
+            #[no_coverage]
+            |name| !productive.contains(name.as_str()),
+        ) {
+            return Err(EbnfError::NonTerminating { rule: name.clone() });
+        }
+        let nullable = fixpoint(&rules, Expr::is_nullable);
+        let mut leftmost = HashMap::new();
+        for name in &order {
+            let mut refs = vec![];
+            rules[name].expr.visit_leftmost_rules(
+                &nullable,
+                &mut #[no_coverage]
+                |r| refs.push(r.to_string()),
+            );
+            leftmost.insert(name.clone(), refs);
+        }
+        for name in &order {
+            if let Some(cycle) = find_cycle(name, &leftmost) {
+                return Err(EbnfError::LeftRecursion { cycle });
+            }
+        }
+        let mut references = HashMap::new();
+        for name in &order {
+            let mut refs = vec![];
+            rules[name].expr.visit_rules(
+                &mut #[no_coverage]
+                |r, _| refs.push(r.to_string()),
+            );
+            references.insert(name.clone(), refs);
+        }
+        let recursive = order
+            .iter()
+            .filter(
+                #[no_coverage]
+                |name| find_cycle(name, &references).is_some(),
+            )
+            .cloned()
+            .collect();
+        Ok(Self {
+            rules,
+            recursive,
+            built: HashMap::new(),
+            recursion_points: HashMap::new(),
+        })
+    }
+
+    #[no_coverage]
+    fn load(mut self, start_rule: &str) -> Result<Rc<Grammar>, EbnfError> {
+        if !self.rules.contains_key(start_rule) {
+            return Err(EbnfError::UndefinedStartRule(start_rule.to_string()));
+        }
+        Ok(self.build_rule(start_rule))
+    }
+
+    /// Builds the grammar of a rule, or refers to the grammar that was already built for it
+    ///
+    /// Each rule is only built once. A recursive rule is built as a [`Grammar::Recursive`] the first time it is
+    /// referenced, and is then referred to by its point of recursion. Its other references may be outside of its
+    /// recursive grammar, which is supported by the grammar-based mutators and parsers as long as they come after it.
+    #[no_coverage]
+    fn build_rule(&mut self, name: &str) -> Rc<Grammar> {
+        if let Some(weak) = self.recursion_points.get(name) {
+            return recurse(weak);
+        }
+        if let Some(grammar) = self.built.get(name) {
+            return grammar.clone();
+        }
+        let expr = self.rules[name].expr.clone();
+        if self.recursive.contains(name) {
+            let inner = Rc::new_cyclic(
+                #[no_coverage]
+                |weak| {
+                    self.recursion_points.insert(name.to_string(), weak.clone());
+                    Grammar::Rule(name.to_string(), self.build_expr(&expr))
+                },
+            );
+            Rc::new(Grammar::Recursive(inner))
+        } else {
+            let grammar = rule(name, self.build_expr(&expr));
+            self.built.insert(name.to_string(), grammar.clone());
+            grammar
+        }
+    }
+
+    #[no_coverage]
+    fn build_exprs(&mut self, exprs: &[Expr]) -> Vec<Rc<Grammar>> {
+        exprs
+            .iter()
+            .map(
+                #[no_coverage]
+                |expr| self.build_expr(expr),
+            )
+            .collect()
+    }
+
+    #[no_coverage]
+    fn build_expr(&mut self, expr: &Expr) -> Rc<Grammar> {
+        match expr {
+            Expr::Rule { name, .. } => self.build_rule(name),
+            Expr::String(s) => {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => literal(c),
+                    _ => concatenation(s.chars().map(literal)),
+                }
+            }
+            Expr::Range(start, end) => literal_range(*start..=*end),
+            Expr::Concatenation(es) => concatenation(self.build_exprs(es)),
+            Expr::Alternation(es) => alternation(self.build_exprs(es)),
+            Expr::Repetition { expr, min, max } => {
+                let grammar = self.build_expr(expr);
+                match max {
+                    Some(max) => repetition(grammar, *min..=*max),
+                    None => repetition(grammar, *min..),
+                }
+            }
+        }
+    }
+}
+
+/// Returns a path from `start` back to itself in the given graph, if there is one
+#[no_coverage]
+fn find_cycle(start: &str, graph: &HashMap<String, Vec<String>>) -> Option<Vec<String>> {
+    let mut visited = HashSet::new();
+    let mut path = vec![start.to_string()];
+    if find_path_to(start, start, graph, &mut visited, &mut path) {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+#[no_coverage]
+fn find_path_to(
+    from: &str,
+    to: &str,
+    graph: &HashMap<String, Vec<String>>,
+    visited: &mut HashSet<String>,
+    path: &mut Vec<String>,
+) -> bool {
+    for next in &graph[from] {
+        path.push(next.clone());
+        if next == to {
+            return true;
+        }
+        if visited.insert(next.clone()) && find_path_to(next, to, graph, visited, path) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ebnf, EbnfError};
+    use crate::mutators::grammar::{grammar_based_ast_mutator, Grammar, GrammarParser};
+    use crate::mutators::testing_utilities::test_mutator;
+    use crate::Mutator;
+
+    const JSON: &str = r#"
+        (* a subset of JSON *)
+        value   = object | array | string | number | "true" | "false" | "null" ;
+        object  = "{" , ws , [ member , { "," , ws , member } ] , "}" ;
+        member  = string , ws , ":" , ws , value , ws ;
+        array   = "[" , ws , [ value , ws , { "," , ws , value , ws } ] , "]" ;
+        string  = '"' , { 'a'..'z' | '0'..'9' | "\\\"" | "\u{e9}" } , '"' ;
+        number  = [ "-" ] , ( "0" | '1'..'9' , { '0'..'9' } ) ;
+        ws      ::= { " " | "\n" } ;
+    "#;
+
+    #[test]
+    #[no_coverage]
+    fn test_mutually_recursive_rules() {
+        let grammar = ebnf(JSON, "value").unwrap();
+        let parser = GrammarParser::new(&grammar);
+        for string in [
+            "null",
+            "-120",
+            r#"{"a": [1, {"b\"": "été"}], "c": {}}"#,
+            "[[], [[]], {\"x\": [true, false]}]",
+        ] {
+            assert!(parser.parse(string).is_some(), "{}", string);
+        }
+        for string in ["01", "[1,]", "{\"a\" 1}", "\"A\""] {
+            assert!(parser.parse(string).is_none(), "{}", string);
+        }
+        let mutator = grammar_based_ast_mutator(grammar);
+        test_mutator(mutator, 200., 200., false, true, 50, 50);
+
+        // `b` is first built inside `a`, but is also referenced outside of it
+        let grammar = ebnf("s = a | b ; a = 'x' , b | 'y' ; b = '(' , a , ')' | 'z' ;", "s").unwrap();
+        let parser = GrammarParser::new(&grammar);
+        assert!(parser.parse("(x(y))").is_some());
+        assert!(parser.parse("x(xz)").is_some());
+        let mutator = grammar_based_ast_mutator(grammar);
+        test_mutator(mutator, 200., 200., false, true, 50, 50);
+    }
+
+    /// The number of nodes of the grammar, where each point of recursion counts as one node
+    #[no_coverage]
+    fn size(grammar: &Grammar) -> usize {
+        1 + match grammar {
+            Grammar::Literal(_) | Grammar::ByteLiteral(_) | Grammar::Recurse(_) => 0,
+            Grammar::Alternation(gs) | Grammar::Concatenation(gs) => gs
+                .iter()
+                .map(
+                    #[no_coverage]
+                    |g| size(g),
+                )
+                .sum(),
+            Grammar::Repetition(g, _) | Grammar::Recursive(g) | Grammar::Rule(_, g) => size(g),
+        }
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_each_rule_is_built_once() {
+        // every rule references all the other ones
+        let source = |nbr_rules: usize| {
+            let mut source = String::new();
+            for i in 0..nbr_rules {
+                let references = (0..nbr_rules)
+                    .map(
+                        #[no_coverage]
+                        |j| format!("r{}", j),
+                    )
+                    .collect::<Vec<_>>()
+                    .join(" , ");
+                source.push_str(&format!("r{} = 'x' | '(' , {} , ')' ;\n", i, references));
+            }
+            source
+        };
+        // each rule has 7 nodes and a point of recursion for each of its references, except for
+        // the first reference to each of the other rules, where the rule is built
+        for nbr_rules in [3, 11] {
+            let grammar = ebnf(&source(nbr_rules), "r0").unwrap();
+            assert_eq!(size(&grammar), 7 * nbr_rules + nbr_rules * nbr_rules - (nbr_rules - 1));
+        }
+        let grammar = ebnf(&source(11), "r0").unwrap();
+        let parser = GrammarParser::new(&grammar);
+        assert!(parser.parse("(x(xxxxxxxxxxx)xxxxxxxxx)").is_some());
+        let mutator = grammar_based_ast_mutator(grammar);
+        test_mutator(mutator, 200., 200., false, true, 50, 50);
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_empty_string() {
+        let grammar = ebnf(r#"a ::= "" ; b ::= "x" , a , [ a ] ;"#, "b").unwrap();
+        let parser = GrammarParser::new(&grammar);
+        assert!(parser.parse("x").is_some());
+        let mutator = grammar_based_ast_mutator(grammar);
+        mutator.initialize();
+        let (mut ast, _) = mutator.random_arbitrary(100.);
+        assert_eq!(ast.to_string(), "x");
+        let mut cache = mutator.validate_value(&ast).unwrap();
+        let (token, _) = mutator.random_mutate(&mut ast, &mut cache, 100.);
+        assert_eq!(ast.to_string(), "x");
+        mutator.unmutate(&mut ast, &mut cache, token);
+        test_mutator(mutator, 100., 100., false, true, 10, 10);
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_errors() {
+        let error = |source: &str| ebnf(source, "a").unwrap_err().to_string();
+        assert_eq!(
+            error("a = b ;"),
+            "the rule `b`, referenced by `a` at line 1, is not defined"
+        );
+        assert_eq!(error("b = 'x' ;"), "the start rule `a` is not defined");
+        assert_eq!(
+            error("a = 'x' ;\na = 'y' ;"),
+            "the rule `a` is defined a second time at line 2"
+        );
+        assert_eq!(
+            error("a = [ 'x' ] , b | 'y' ;\nb = { 'z' } , a ;"),
+            "the grammar is left-recursive, which is not supported: a -> b -> a"
+        );
+        assert_eq!(
+            error("a = 'x' | b ;\nb = '(' , b , ')' ;"),
+            "the rule `b` can never generate a finite string"
+        );
+        assert_eq!(
+            error("a = 'x' \n  | 'y' ) ;"),
+            "syntax error at line 2, column 9: expected `;`, found Close(')')"
+        );
+        assert_eq!(
+            error("a = 'z'..'a' ;"),
+            "syntax error at line 1, column 5: the bounds of a character range must be single characters in increasing order"
+        );
+        assert_eq!(
+            error("a = 'x ;"),
+            "syntax error at line 1, column 5: unterminated string literal"
+        );
+        assert!(matches!(
+            super::ebnf_from_file("does/not/exist.ebnf", "a"),
+            Err(EbnfError::Io(_))
+        ));
+    }
+}
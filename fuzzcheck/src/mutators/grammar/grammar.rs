@@ -10,16 +10,26 @@ use crate::mutators::grammar::regex::grammar_from_regex;
 /// See [the module documentation](crate::mutators::grammar) for advice on how to create a grammar.
 pub enum Grammar {
     Literal(Vec<RangeInclusive<char>>),
+    ByteLiteral(Vec<RangeInclusive<u8>>),
     Alternation(Vec<Rc<Grammar>>),
     Concatenation(Vec<Rc<Grammar>>),
     Repetition(Rc<Grammar>, Range<usize>),
     Recurse(Weak<Grammar>),
     Recursive(Rc<Grammar>),
+    Rule(String, Rc<Grammar>),
 }
 
 #[cfg(feature = "regex_grammar")]
 #[doc(cfg(feature = "regex_grammar"))]
 #[no_coverage]
+/// Creates an [`Rc<Grammar>`] from a regular expression.
+///
+/// Regular expressions matching arbitrary bytes, such as `(?-u)\xFF[\x00-\x7F]+`, produce
+/// [byte literals](byte).
+///
+/// # Panics
+/// Panics if the regular expression is malformed or cannot be converted to a grammar.
+/// Use [`try_regex`](crate::mutators::grammar::try_regex) to handle these errors instead.
 pub fn regex(s: &str) -> Rc<Grammar> {
     grammar_from_regex(s)
 }
@@ -64,6 +74,71 @@ where
     Rc::new(Grammar::Literal(vec![start..=end]))
 }
 
+#[no_coverage]
+/// Creates an [`Rc<Grammar>`] which matches a single byte.
+///
+/// Byte literals can be combined with character literals, which are then encoded in UTF-8.
+/// The resulting syntax trees should be converted to bytes with [`AST::to_bytes`](crate::mutators::grammar::AST::to_bytes).
+///
+/// ```
+/// use fuzzcheck::mutators::grammar::{byte, concatenation, literal};
+///
+/// let magic = concatenation([byte(0x7f), literal('E'), literal('L'), literal('F')]);
+/// ```
+pub fn byte(b: u8) -> Rc<Grammar> {
+    Rc::new(Grammar::ByteLiteral(vec![b..=b]))
+}
+
+#[no_coverage]
+/// Creates an [`Rc<Grammar>`] which matches a single byte within the given range.
+///
+/// If the range is empty, such as `..0`, the grammar does not match any byte.
+pub fn byte_range<R>(range: R) -> Rc<Grammar>
+where
+    R: RangeBounds<u8>,
+{
+    let start = match range.start_bound() {
+        std::ops::Bound::Included(x) => Some(*x),
+        std::ops::Bound::Excluded(x) => x.checked_add(1),
+        std::ops::Bound::Unbounded => Some(u8::MIN),
+    };
+    let end = match range.end_bound() {
+        std::ops::Bound::Included(x) => Some(*x),
+        std::ops::Bound::Excluded(x) => x.checked_sub(1),
+        std::ops::Bound::Unbounded => Some(u8::MAX),
+    };
+    let ranges = match (start, end) {
+        (Some(start), Some(end)) if start <= end => vec![start..=end],
+        _ => vec![],
+    };
+    Rc::new(Grammar::ByteLiteral(ranges))
+}
+
+#[no_coverage]
+/// Creates an [`Rc<Grammar>`] which matches a single byte within any of the given ranges.
+pub fn byte_ranges(ranges: Vec<RangeInclusive<u8>>) -> Rc<Grammar> {
+    Rc::new(Grammar::ByteLiteral(ranges))
+}
+
+impl Grammar {
+    /// Whether the grammar can match a byte that is not valid UTF-8 on its own
+    #[no_coverage]
+    pub(crate) fn matches_non_ascii_bytes(&self) -> bool {
+        match self {
+            Grammar::Literal(_) | Grammar::Recurse(_) => false,
+            Grammar::ByteLiteral(ranges) => ranges.iter().any(
+                #[no_coverage]
+                |r| !r.is_empty() && !r.end().is_ascii(),
+            ),
+            Grammar::Alternation(gs) | Grammar::Concatenation(gs) => gs.iter().any(
+                #[no_coverage]
+                |g| g.matches_non_ascii_bytes(),
+            ),
+            Grammar::Repetition(g, _) | Grammar::Recursive(g) | Grammar::Rule(_, g) => g.matches_non_ascii_bytes(),
+        }
+    }
+}
+
 /// Produces a grammar which will choose between the provided grammars.
 #[no_coverage]
 pub fn alternation(gs: impl IntoIterator<Item = Rc<Grammar>>) -> Rc<Grammar> {
@@ -92,6 +167,12 @@ pub fn repetition<R>(gs: Rc<Grammar>, range: R) -> Rc<Grammar>
 where
     R: RangeBounds<usize>,
 {
+    Rc::new(Grammar::Repetition(gs, repetition_range(range)))
+}
+
+/// Converts the range of a repetition to an exclusive range, where `usize::MAX` means unbounded
+#[no_coverage]
+pub(crate) fn repetition_range(range: impl RangeBounds<usize>) -> Range<usize> {
     let start = match range.start_bound() {
         std::ops::Bound::Included(x) => *x,
         std::ops::Bound::Excluded(x) => *x + 1,
@@ -102,7 +183,7 @@ where
         std::ops::Bound::Excluded(x) => *x,
         std::ops::Bound::Unbounded => usize::MAX,
     };
-    Rc::new(Grammar::Repetition(gs, start..end))
+    start..end
 }
 
 #[no_coverage]
@@ -124,3 +205,20 @@ pub fn recursive(data_fn: impl Fn(&Weak<Grammar>) -> Rc<Grammar>) -> Rc<Grammar>
         Rc::try_unwrap(data_fn(g)).unwrap()
     })))
 }
+
+#[no_coverage]
+/// Gives a name to a grammar rule.
+///
+/// The syntax trees generated by the rule are wrapped in an [`AST::Rule`](crate::mutators::grammar::AST::Rule)
+/// node holding its name and, if the rule is an alternation, the index of the chosen alternative.
+/// Test functions can then walk the syntax tree by rule name, and the grammar-based mutators
+/// can replace a subtree by another one generated by the same rule in a different test case.
+///
+/// ```
+/// use fuzzcheck::mutators::grammar::{alternation, literal, rule};
+///
+/// let boolean = rule("boolean", alternation([literal('0'), literal('1')]));
+/// ```
+pub fn rule(name: impl Into<String>, grammar: Rc<Grammar>) -> Rc<Grammar> {
+    Rc::new(Grammar::Rule(name.into(), grammar))
+}
@@ -4,19 +4,34 @@
 //! tree satisfying a grammar, created through [`grammar_based_ast_mutator`]. The resulting mutator can be
 //! transformed into a `Mutator<(AST, String)>`, where the second element of the tuple is the string corresponding
 //! to the abstract syntax tree, by calling [`.with_string()`](ASTMutator::with_string).
+//! A `Mutator<String>` can also be created directly with [`string_from_grammar`], and a `Mutator<Vec<u8>>`
+//! with [`bytes_from_grammar`], which is useful for grammars containing [bytes](byte).
+//! Existing strings can be turned into syntax trees matching a grammar with a [`GrammarParser`],
+//! and the [`GrammarSerializer`](crate::GrammarSerializer) uses it to save the syntax trees of the
+//! corpus as plain text files.
 //!
 //! To specify a grammar, you should use the following functions:
 #![cfg_attr(
     feature = "regex_grammar",
-    doc = "* [`regex`](crate::mutators::grammar::regex) to create a grammar from a regular expression **(only supported on crate feature `regex_grammar`)**"
+    doc = "* [`regex`](crate::mutators::grammar::regex) to create a grammar from a regular expression, or [`try_regex`](crate::mutators::grammar::try_regex) to handle malformed regular expressions **(only supported on crate feature `regex_grammar`)**"
 )]
 //! * [`literal`] for a grammar that matches a single character
 //! * [`literal_ranges`] for a grammar matching a single character within a specified ranges
 //! * [`literal_ranges`] for a grammar matching a single character within any of multiple ranges
+//! * [`byte`], [`byte_range`], and [`byte_ranges`] for a grammar that matches a single byte
 //! * [`alternation`] for a grammar matching any of a list of grammar rules
 //! * [`concatenation`] matching multiple grammar rules one after the other
 //! * [`repetition`] matching a grammar rule multiple times
 //! * [`recursive`] and [`recurse`] to create recursive grammar rules
+//! * [`rule`] to give a name to a grammar rule, which is recorded in the [`AST`]
+//!
+//! Alternatively, a grammar written in EBNF can be loaded with [`ebnf`] or [`ebnf_from_file`].
+//!
+//! Constraints that a grammar cannot express, such as variables being declared before they are used,
+//! can be enforced by binding named rules to symbol tables with [`GrammarConstraints`].
+//!
+//! The [`tokens`] module provides grammars whose terminals are values of a custom token type, generated
+//! by arbitrary mutators, in order to fuzz above the character level.
 #![cfg_attr(
     feature = "regex_grammar",
     doc = r###"
@@ -63,14 +78,23 @@ let rule = recursive(|rule| {
 #![allow(clippy::nonstandard_macro_braces)]
 
 mod ast;
+mod constraints;
+mod ebnf;
 mod grammar;
 mod mutators;
+mod parser;
 
 #[cfg(feature = "regex_grammar")]
 mod regex;
 
+pub mod tokens;
+
 #[doc(inline)]
 pub use ast::AST;
+#[doc(inline)]
+pub use constraints::GrammarConstraints;
+#[doc(inline)]
+pub use ebnf::{ebnf, ebnf_from_file, EbnfError};
 #[cfg(feature = "regex_grammar")]
 #[doc(inline)]
 #[doc(cfg(feature = "regex_grammar"))]
@@ -78,8 +102,20 @@ pub use grammar::regex;
 #[doc(inline)]
 pub use grammar::Grammar;
 #[doc(inline)]
-pub use grammar::{alternation, concatenation, literal, literal_range, literal_ranges, recurse, recursive, repetition};
+pub use grammar::{
+    alternation, byte, byte_range, byte_ranges, concatenation, literal, literal_range, literal_ranges, recurse,
+    recursive, repetition, rule,
+};
 #[doc(inline)]
-pub use mutators::grammar_based_ast_mutator;
+pub use mutators::{bytes_from_grammar, string_from_grammar, BytesFromGrammarMutator, StringFromGrammarMutator};
+#[doc(inline)]
+pub use mutators::{grammar_based_ast_mutator, grammar_based_ast_mutator_with_size_control};
+#[doc(inline)]
+pub use mutators::{ASTMutator, ConstrainedASTMutator};
+#[doc(inline)]
+pub use mutators::{GrammarNode, GrammarTreeMutator, SyntaxTree, TreeGrammar};
+#[doc(inline)]
+pub use parser::GrammarParser;
+#[cfg(feature = "regex_grammar")]
 #[doc(inline)]
-pub use mutators::ASTMutator;
+pub use regex::{try_regex, try_regex_with_options, RegexError, RegexOptions};
@@ -2,21 +2,29 @@ extern crate self as fuzzcheck;
 
 use std::any::Any;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::rc::{Rc, Weak};
 
 use fuzzcheck_mutators_derive::make_single_variant_mutator;
 
+use super::constraints::GrammarConstraints;
 use super::grammar::Grammar;
+use super::parser::GrammarParser;
 use crate::mutators::alternation::AlternationMutator;
 use crate::mutators::character_classes::CharacterMutator;
-use crate::mutators::either::Either3;
+use crate::mutators::either::{Either, Either3};
+use crate::mutators::filter::FilterMutator;
 use crate::mutators::fixed_len_vector::FixedLenVecMutator;
+use crate::mutators::fixup::FixupMutator;
 use crate::mutators::grammar::ast::AST;
-use crate::mutators::map::AndMapMutator;
-use crate::mutators::recursive::{RecurToMutator, RecursiveMutator};
+use crate::mutators::integer_within_range::U8WithinRangeMutator;
+use crate::mutators::map::{AndMapMutator, MapMutator};
+use crate::mutators::recursive::{RecurToMutator, RecursiveMutator, SizeControl};
 use crate::mutators::tuples::Tuple1Mutator;
+use crate::mutators::unit::UnitMutator;
 use crate::mutators::vector::VecMutator;
-use crate::Mutator;
+use crate::mutators::{CrossoverStep, MutatorWrapper};
+use crate::{Mutator, MutatorExt, CROSSOVER_RATE};
 
 // NOTE: the complexity of the vectors in the AST is the complexity of their
 // elements and nothing else. That is, we don't take their inherent complexity
@@ -42,91 +50,252 @@ use crate::Mutator;
 make_single_variant_mutator! {
     pub enum AST {
         Token(char),
+        Byte(u8),
         Sequence(Vec<AST>),
     }
 }
 
-type InnerASTMutator = Either3<
-    AlternationMutator<AST, ASTMutator>,
-    ASTSingleVariant<
-        Tuple1Mutator<CharacterMutator>,
-        Tuple1Mutator<
-            Either3<
-                FixedLenVecMutator<AST, RecurToMutator<ASTMutator>>,
-                FixedLenVecMutator<AST, ASTMutator>,
-                VecMutator<AST, ASTMutator>,
-            >,
-        >,
-    >,
-    RecursiveMutator<ASTMutator>,
+/// A syntax tree generated by a [`GrammarTreeMutator`]
+///
+/// The subtrees generated by the [named rules](crate::mutators::grammar::rule) of a grammar
+/// are wrapped in a node recording the name of the rule and the index of the chosen alternative.
+pub trait SyntaxTree: Clone + 'static {
+    /// Wraps the tree in the node of the given rule and alternative
+    fn wrap_in_rule(name: &str, alternative: usize, tree: Self) -> Self;
+    /// The subtree of the node, if it was generated by the given rule and alternative
+    fn rule_subtree(&self, name: &str, alternative: usize) -> Option<&Self>;
+    /// The subtree of the node, if it was generated by a rule
+    fn rule_subtree_mut(&mut self) -> Option<&mut Self>;
+}
+
+/// The structure of a grammar, as seen by a [`GrammarTreeMutator`]
+pub enum GrammarNode<'a, G> {
+    Terminal,
+    Alternation(&'a [Rc<G>]),
+    Concatenation(&'a [Rc<G>]),
+    Repetition(&'a Rc<G>, &'a Range<usize>),
+    Recurse(&'a Weak<G>),
+    Recursive(&'a Rc<G>),
+    Rule(&'a str, &'a Rc<G>),
+}
+
+/// A grammar from which a [`GrammarTreeMutator`] can be created
+///
+/// It is implemented by [`Grammar`], whose terminals are characters and bytes, and by
+/// [`TokenGrammar`](crate::mutators::grammar::tokens::TokenGrammar), whose terminals are
+/// custom tokens.
+pub trait TreeGrammar<Tree: SyntaxTree>: Sized + 'static {
+    /// The mutator of the leaves and sequences of the syntax trees, where `S` is the mutator of
+    /// the sequences
+    type NodeMutator<S>: Mutator<Tree>
+    where
+        S: Mutator<Vec<Tree>>;
+
+    /// The structure of the grammar
+    fn node(&self) -> GrammarNode<Self>;
+    /// The mutator of the leaves generated by a [terminal](GrammarNode::Terminal) grammar
+    fn terminal_mutator<S>(&self) -> Self::NodeMutator<S>
+    where
+        S: Mutator<Vec<Tree>>;
+    /// The mutator of a sequence of syntax trees
+    fn sequence_mutator<S>(sequence: S) -> Self::NodeMutator<S>
+    where
+        S: Mutator<Vec<Tree>>;
+}
+
+/// The mutator of the empty concatenation, which only generates the empty sequence
+type EmptySequenceMutator<Tree> = FilterMutator<UnitMutator<Vec<Tree>>, fn(&Vec<Tree>) -> bool>;
+
+type SequenceMutator<Tree, G> = Either3<
+    FixedLenVecMutator<Tree, RecurToMutator<GrammarTreeMutator<Tree, G>>>,
+    Either<FixedLenVecMutator<Tree, GrammarTreeMutator<Tree, G>>, EmptySequenceMutator<Tree>>,
+    VecMutator<Tree, GrammarTreeMutator<Tree, G>>,
+>;
+
+type InnerGrammarTreeMutator<Tree, G> = Either3<
+    AlternationMutator<Tree, GrammarTreeMutator<Tree, G>>,
+    <G as TreeGrammar<Tree>>::NodeMutator<SequenceMutator<Tree, G>>,
+    Either<RecursiveMutator<GrammarTreeMutator<Tree, G>>, RuleMutator<Tree, G>>,
 >;
 
-/// A mutator created by [`grammar_based_ast_mutator`](crate::mutators::grammar::grammar_based_ast_mutator)
+/// A mutator that only generates syntax trees matching a grammar, whose terminals are
+/// generated by the grammar itself
+///
+/// It is created by [`grammar_based_ast_mutator`] for a [`Grammar`], and by
+/// [`token_tree_mutator`](crate::mutators::grammar::tokens::token_tree_mutator) for a
+/// [`TokenGrammar`](crate::mutators::grammar::tokens::TokenGrammar).
+pub struct GrammarTreeMutator<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
+    inner: Box<InnerGrammarTreeMutator<Tree, G>>,
+}
+
+/// A mutator created by [`grammar_based_ast_mutator`]
 ///
 /// It only generates syntax trees whose [`to_string()`](crate::mutators::grammar::AST::to_string)
 /// value matches the given grammar.
-pub struct ASTMutator {
-    inner: Box<InnerASTMutator>,
+pub type ASTMutator = GrammarTreeMutator<AST, Grammar>;
+
+#[no_coverage]
+fn with_string<M: Mutator<AST>>(mutator: M) -> impl Mutator<(String, AST)> {
+    AndMapMutator::new(
+        mutator,
+        |x, s| {
+            s.clear();
+            x.generate_string_in(s);
+        },
+        String::with_capacity(16),
+    )
+}
+
+#[no_coverage]
+fn with_bytes<M: Mutator<AST>>(mutator: M) -> impl Mutator<(Vec<u8>, AST)> {
+    AndMapMutator::new(
+        mutator,
+        |x, bytes| {
+            bytes.clear();
+            x.generate_bytes_in(bytes);
+        },
+        Vec::with_capacity(16),
+    )
 }
 
-impl ASTMutator {
+impl GrammarTreeMutator<AST, Grammar> {
     #[no_coverage]
     pub fn with_string(self) -> impl Mutator<(String, AST)> {
-        AndMapMutator::new(
-            self,
-            |x, s| {
-                s.clear();
-                x.generate_string_in(s);
-            },
-            String::with_capacity(16),
-        )
+        with_string(self)
+    }
+
+    /// Transforms the mutator into a `Mutator<(Vec<u8>, AST)>`, where the first element of the tuple
+    /// is the [byte representation](AST::to_bytes) of the syntax tree.
+    #[no_coverage]
+    pub fn with_bytes(self) -> impl Mutator<(Vec<u8>, AST)> {
+        with_bytes(self)
+    }
+
+    /// Transforms the mutator such that the generated syntax trees satisfy the given
+    /// [context-sensitive constraints](GrammarConstraints).
+    #[no_coverage]
+    pub fn with_constraints(self, constraints: GrammarConstraints) -> ConstrainedASTMutator {
+        ConstrainedASTMutator {
+            mutator: self.fixup(Box::new(
+                #[no_coverage]
+                move |ast: &mut AST| constraints.apply(ast),
+            )),
+        }
     }
 }
 
-#[derive(Clone)]
-pub struct ASTMutatorCache {
-    inner: Box<<InnerASTMutator as Mutator<AST>>::Cache>,
+/// A mutator created by [`.with_constraints(..)`](ASTMutator::with_constraints)
+///
+/// It repairs the syntax trees generated by an [`ASTMutator`] such that they satisfy
+/// the [constraints](GrammarConstraints).
+pub struct ConstrainedASTMutator {
+    mutator: FixupMutator<ASTMutator, Box<dyn Fn(&mut AST)>>,
 }
-impl ASTMutatorCache {
+
+impl MutatorWrapper for ConstrainedASTMutator {
+    type Wrapped = FixupMutator<ASTMutator, Box<dyn Fn(&mut AST)>>;
     #[no_coverage]
-    fn new(inner: <InnerASTMutator as Mutator<AST>>::Cache) -> Self {
-        Self { inner: Box::new(inner) }
+    fn wrapped_mutator(&self) -> &Self::Wrapped {
+        &self.mutator
+    }
+}
+
+impl ConstrainedASTMutator {
+    /// Like [`ASTMutator::with_string`]
+    #[no_coverage]
+    pub fn with_string(self) -> impl Mutator<(String, AST)> {
+        with_string(self)
+    }
+
+    /// Like [`ASTMutator::with_bytes`]
+    #[no_coverage]
+    pub fn with_bytes(self) -> impl Mutator<(Vec<u8>, AST)> {
+        with_bytes(self)
     }
 }
-#[derive(Clone)]
-pub struct ASTMutatorMutationStep {
-    inner: Box<<InnerASTMutator as Mutator<AST>>::MutationStep>,
+
+pub struct GrammarTreeMutatorCache<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
+    inner: Box<<InnerGrammarTreeMutator<Tree, G> as Mutator<Tree>>::Cache>,
 }
-impl ASTMutatorMutationStep {
+impl<Tree, G> Clone for GrammarTreeMutatorCache<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
     #[no_coverage]
-    fn new(inner: <InnerASTMutator as Mutator<AST>>::MutationStep) -> Self {
-        Self { inner: Box::new(inner) }
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
     }
 }
-#[derive(Clone)]
-pub struct ASTMutatorArbitraryStep {
-    inner: Box<<InnerASTMutator as Mutator<AST>>::ArbitraryStep>,
+pub struct GrammarTreeMutatorMutationStep<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
+    inner: Box<<InnerGrammarTreeMutator<Tree, G> as Mutator<Tree>>::MutationStep>,
 }
-
-pub struct ASTMutatorUnmutateToken {
-    pub(crate) inner: Box<<InnerASTMutator as Mutator<AST>>::UnmutateToken>,
+impl<Tree, G> Clone for GrammarTreeMutatorMutationStep<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
+    #[no_coverage]
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
 }
-impl ASTMutatorUnmutateToken {
+pub struct GrammarTreeMutatorArbitraryStep<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
+    inner: Box<<InnerGrammarTreeMutator<Tree, G> as Mutator<Tree>>::ArbitraryStep>,
+}
+impl<Tree, G> Clone for GrammarTreeMutatorArbitraryStep<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
     #[no_coverage]
-    fn new(inner: <InnerASTMutator as Mutator<AST>>::UnmutateToken) -> Self {
-        Self { inner: Box::new(inner) }
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
     }
 }
+pub struct GrammarTreeMutatorUnmutateToken<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
+    pub(crate) inner: Box<<InnerGrammarTreeMutator<Tree, G> as Mutator<Tree>>::UnmutateToken>,
+}
 
-impl Mutator<AST> for ASTMutator {
+impl<Tree, G> Mutator<Tree> for GrammarTreeMutator<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
     #[doc(hidden)]
-    type Cache = ASTMutatorCache;
+    type Cache = GrammarTreeMutatorCache<Tree, G>;
     #[doc(hidden)]
-    type MutationStep = ASTMutatorMutationStep;
+    type MutationStep = GrammarTreeMutatorMutationStep<Tree, G>;
     #[doc(hidden)]
-    type ArbitraryStep = ASTMutatorArbitraryStep;
+    type ArbitraryStep = GrammarTreeMutatorArbitraryStep<Tree, G>;
     #[doc(hidden)]
-    type UnmutateToken = ASTMutatorUnmutateToken;
+    type UnmutateToken = GrammarTreeMutatorUnmutateToken<Tree, G>;
 
     #[doc(hidden)]
     #[no_coverage]
@@ -137,27 +306,29 @@ impl Mutator<AST> for ASTMutator {
     #[doc(hidden)]
     #[no_coverage]
     fn default_arbitrary_step(&self) -> Self::ArbitraryStep {
-        Self::ArbitraryStep {
+        GrammarTreeMutatorArbitraryStep {
             inner: Box::new(self.inner.default_arbitrary_step()),
         }
     }
     #[doc(hidden)]
     #[no_coverage]
-    fn is_valid(&self, value: &AST) -> bool {
+    fn is_valid(&self, value: &Tree) -> bool {
         self.inner.is_valid(value)
     }
 
     #[doc(hidden)]
     #[no_coverage]
-    fn validate_value(&self, value: &AST) -> Option<Self::Cache> {
+    fn validate_value(&self, value: &Tree) -> Option<Self::Cache> {
         let cache = self.inner.validate_value(value)?;
-        Some(Self::Cache::new(cache))
+        Some(GrammarTreeMutatorCache { inner: Box::new(cache) })
     }
 
     #[doc(hidden)]
     #[no_coverage]
-    fn default_mutation_step(&self, value: &AST, cache: &Self::Cache) -> Self::MutationStep {
-        Self::MutationStep::new(self.inner.default_mutation_step(value, &cache.inner))
+    fn default_mutation_step(&self, value: &Tree, cache: &Self::Cache) -> Self::MutationStep {
+        GrammarTreeMutatorMutationStep {
+            inner: Box::new(self.inner.default_mutation_step(value, &cache.inner)),
+        }
     }
 
     #[doc(hidden)]
@@ -180,19 +351,19 @@ impl Mutator<AST> for ASTMutator {
 
     #[doc(hidden)]
     #[no_coverage]
-    fn complexity(&self, value: &AST, cache: &Self::Cache) -> f64 {
+    fn complexity(&self, value: &Tree, cache: &Self::Cache) -> f64 {
         self.inner.complexity(value, &cache.inner)
     }
 
     #[doc(hidden)]
     #[no_coverage]
-    fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(AST, f64)> {
+    fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(Tree, f64)> {
         self.inner.ordered_arbitrary(&mut step.inner, max_cplx)
     }
 
     #[doc(hidden)]
     #[no_coverage]
-    fn random_arbitrary(&self, max_cplx: f64) -> (AST, f64) {
+    fn random_arbitrary(&self, max_cplx: f64) -> (Tree, f64) {
         self.inner.random_arbitrary(max_cplx)
     }
 
@@ -200,7 +371,7 @@ impl Mutator<AST> for ASTMutator {
     #[no_coverage]
     fn ordered_mutate(
         &self,
-        value: &mut AST,
+        value: &mut Tree,
         cache: &mut Self::Cache,
         step: &mut Self::MutationStep,
         subvalue_provider: &dyn crate::SubValueProvider,
@@ -209,127 +380,628 @@ impl Mutator<AST> for ASTMutator {
         let (token, cplx) =
             self.inner
                 .ordered_mutate(value, &mut cache.inner, &mut step.inner, subvalue_provider, max_cplx)?;
-        Some((Self::UnmutateToken::new(token), cplx))
+        Some((GrammarTreeMutatorUnmutateToken { inner: Box::new(token) }, cplx))
     }
 
     #[doc(hidden)]
     #[no_coverage]
-    fn random_mutate(&self, value: &mut AST, cache: &mut Self::Cache, max_cplx: f64) -> (Self::UnmutateToken, f64) {
+    fn random_mutate(&self, value: &mut Tree, cache: &mut Self::Cache, max_cplx: f64) -> (Self::UnmutateToken, f64) {
         let (token, cplx) = self.inner.random_mutate(value, &mut cache.inner, max_cplx);
-        (Self::UnmutateToken::new(token), cplx)
+        (GrammarTreeMutatorUnmutateToken { inner: Box::new(token) }, cplx)
     }
 
     #[doc(hidden)]
     #[no_coverage]
-    fn unmutate(&self, value: &mut AST, cache: &mut Self::Cache, t: Self::UnmutateToken) {
+    fn unmutate(&self, value: &mut Tree, cache: &mut Self::Cache, t: Self::UnmutateToken) {
         self.inner.unmutate(value, &mut cache.inner, *t.inner)
     }
 
     #[doc(hidden)]
     #[no_coverage]
-    fn visit_subvalues<'a>(&self, value: &'a AST, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
+    fn visit_subvalues<'a>(&self, value: &'a Tree, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
         self.inner.visit_subvalues(value, &cache.inner, visit);
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        self.inner.mutation_kind(&t.inner)
+    }
 }
 
 #[no_coverage]
 pub fn grammar_based_ast_mutator(grammar: Rc<Grammar>) -> ASTMutator {
-    ASTMutator::from_grammar(grammar)
+    ASTMutator::from_grammar(&grammar, SizeControl::default())
 }
 
-impl ASTMutator {
+/// Like [`grammar_based_ast_mutator`], but the size of the syntax trees generated by the
+/// [recursive](crate::mutators::grammar::recursive) rules of the grammar is controlled by
+/// the given [`SizeControl`].
+///
+/// ```
+/// use fuzzcheck::mutators::grammar::{
+///     alternation, concatenation, grammar_based_ast_mutator_with_size_control, literal, recurse, recursive,
+/// };
+/// use fuzzcheck::mutators::recursive::SizeControl;
+///
+/// let tree = recursive(|tree| alternation([literal('.'), concatenation([literal('('), recurse(tree), recurse(tree), literal(')')])]));
+/// let mutator = grammar_based_ast_mutator_with_size_control(
+///     tree,
+///     SizeControl::default().target_complexity(100.).max_depth(8),
+/// );
+/// ```
+#[no_coverage]
+pub fn grammar_based_ast_mutator_with_size_control(grammar: Rc<Grammar>, size_control: SizeControl) -> ASTMutator {
+    ASTMutator::from_grammar(&grammar, size_control)
+}
+
+/// A mutator for strings matching a grammar, created by [`string_from_grammar`]
+pub type StringFromGrammarMutator = impl Mutator<String>;
+
+/// Creates a mutator that only generates strings matching the given grammar.
+///
+/// Unlike [`.with_string()`](ASTMutator::with_string), the syntax tree is not part of the
+/// generated value. Instead, existing strings are parsed with a [`GrammarParser`], so that a
+/// corpus of plain text files, saved with a [`StringSerializer`](crate::StringSerializer),
+/// can be read back.
+///
+/// # Panics
+/// Panics if the grammar can match [bytes](crate::mutators::grammar::byte) that are not ASCII,
+/// since they cannot be part of a string. Use [`bytes_from_grammar`] for these grammars instead.
+#[no_coverage]
+pub fn string_from_grammar(grammar: Rc<Grammar>) -> StringFromGrammarMutator {
+    assert!(
+        !grammar.matches_non_ascii_bytes(),
+        "string_from_grammar cannot be used with a grammar matching non-ASCII bytes, use bytes_from_grammar instead"
+    );
+    let parser = GrammarParser::new(&grammar);
+    MapMutator::new(
+        grammar_based_ast_mutator(grammar).with_string(),
+        #[no_coverage]
+        move |string: &String| {
+            parser.parse(string).map(
+                #[no_coverage]
+                |ast| (string.clone(), ast),
+            )
+        },
+        #[no_coverage]
+        |(string, _): &(String, AST)| string.clone(),
+        #[no_coverage]
+        |_, cplx| cplx,
+    )
+}
+
+/// A mutator for byte vectors matching a grammar, created by [`bytes_from_grammar`]
+pub type BytesFromGrammarMutator = impl Mutator<Vec<u8>>;
+
+/// Creates a mutator that only generates byte vectors matching the given grammar.
+///
+/// The grammar can contain both [bytes](crate::mutators::grammar::byte) and characters, which are
+/// encoded in UTF-8. Existing byte vectors are parsed with [`GrammarParser::parse_bytes`].
+///
+/// ```
+/// use fuzzcheck::mutators::grammar::{byte, byte_range, bytes_from_grammar, concatenation, literal, repetition};
+/// use fuzzcheck::Mutator;
+///
+/// let grammar = concatenation([literal('é'), byte(0xff), repetition(byte_range(0x00..=0x7f), 1..10)]);
+/// let mutator = bytes_from_grammar(grammar);
+/// assert!(mutator.validate_value(&vec![0xc3, 0xa9, 0xff, 0x00, 0x7f]).is_some());
+/// assert!(mutator.validate_value(&vec![0xc3, 0xa9, 0xff, 0x80]).is_none());
+/// ```
+#[no_coverage]
+pub fn bytes_from_grammar(grammar: Rc<Grammar>) -> BytesFromGrammarMutator {
+    let parser = GrammarParser::new(&grammar);
+    MapMutator::new(
+        grammar_based_ast_mutator(grammar).with_bytes(),
+        #[no_coverage]
+        move |bytes: &Vec<u8>| {
+            parser.parse_bytes(bytes).map(
+                #[no_coverage]
+                |ast| (bytes.clone(), ast),
+            )
+        },
+        #[no_coverage]
+        |(bytes, _): &(Vec<u8>, AST)| bytes.clone(),
+        #[no_coverage]
+        |_, cplx| cplx,
+    )
+}
+
+impl<Tree, G> GrammarTreeMutator<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
     #[no_coverage]
-    fn token(m: CharacterMutator) -> Self {
-        Self {
-            inner: Box::new(Either3::B(ASTSingleVariant::Token(Tuple1Mutator::new(m)))),
+    fn new(inner: InnerGrammarTreeMutator<Tree, G>) -> Self {
+        Self { inner: Box::new(inner) }
+    }
+    #[no_coverage]
+    fn sequence(m: SequenceMutator<Tree, G>) -> Self {
+        Self::new(Either3::B(G::sequence_mutator(m)))
+    }
+    #[no_coverage]
+    fn rule(name: &str, alternative: usize, m: Self) -> Self {
+        Self::new(Either3::C(Either::Right(RuleMutator::new(name, alternative, m))))
+    }
+
+    #[no_coverage]
+    pub(crate) fn from_grammar(grammar: &Rc<G>, size_control: SizeControl) -> Self {
+        Self::from_grammar_rec(grammar, &mut HashMap::new(), size_control)
+    }
+
+    #[no_coverage]
+    fn from_grammar_rec(
+        grammar: &Rc<G>,
+        others: &mut HashMap<*const G, RecurToMutator<Self>>,
+        size_control: SizeControl,
+    ) -> Self {
+        match grammar.node() {
+            GrammarNode::Terminal => Self::new(Either3::B(grammar.terminal_mutator())),
+            GrammarNode::Alternation(gs) => Self::new(Either3::A(AlternationMutator::new(
+                gs.iter()
+                    .map(
+                        #[no_coverage]
+                        |g| Self::from_grammar_rec(g, others, size_control),
+                    )
+                    .collect(),
+                0.0,
+            ))),
+            GrammarNode::Concatenation([]) => Self::sequence(Either3::B(Either::Right(FilterMutator::new(
+                UnitMutator::new(vec![], 0.0),
+                Vec::is_empty,
+            )))),
+            GrammarNode::Concatenation(gs) => {
+                let mut ms = Vec::with_capacity(gs.len());
+                for g in gs {
+                    ms.push(Self::from_grammar_rec(g, others, size_control));
+                }
+                Self::sequence(Either3::B(Either::Left(
+                    FixedLenVecMutator::new_without_inherent_complexity(ms),
+                )))
+            }
+            GrammarNode::Repetition(g, range) => {
+                let max_len = range
+                    .end
+                    .checked_sub(1)
+                    .filter(
+                        #[no_coverage]
+                        |max_len| *max_len >= range.start,
+                    )
+                    .expect("the range of a repetition must not be empty");
+                Self::sequence(Either3::C(VecMutator::new_without_inherent_complexity(
+                    Self::from_grammar_rec(g, others, size_control),
+                    range.start..=max_len,
+                )))
+            }
+            GrammarNode::Recurse(g) => {
+                let m = others
+                    .get(&g.as_ptr())
+                    .expect("a point of recursion must be used within or after its recursive grammar");
+                Self::sequence(Either3::A(FixedLenVecMutator::new_without_inherent_complexity(vec![
+                    RecurToMutator::from(m),
+                ])))
+            }
+            GrammarNode::Recursive(g) => Self::new(Either3::C(Either::Left(
+                RecursiveMutator::new(
+                    #[no_coverage]
+                    |m| {
+                        others.insert(Rc::as_ptr(g), m.clone());
+                        Self::from_grammar_rec(g, others, size_control)
+                    },
+                )
+                .with_size_control(size_control),
+            ))),
+            GrammarNode::Rule(name, g) => {
+                if let GrammarNode::Alternation(gs) = g.node() {
+                    // each alternative is recorded in the syntax tree
+                    Self::new(Either3::A(AlternationMutator::new(
+                        gs.iter()
+                            .enumerate()
+                            .map(
+                                #[no_coverage]
+                                |(i, g)| Self::rule(name, i, Self::from_grammar_rec(g, others, size_control)),
+                            )
+                            .collect(),
+                        0.0,
+                    )))
+                } else {
+                    Self::rule(name, 0, Self::from_grammar_rec(g, others, size_control))
+                }
+            }
         }
     }
+}
+
+impl SyntaxTree for AST {
     #[no_coverage]
-    fn concatenation(m: FixedLenVecMutator<AST, ASTMutator>) -> Self {
-        Self {
-            inner: Box::new(Either3::B(ASTSingleVariant::Sequence(Tuple1Mutator::new(Either3::B(
-                m,
-            ))))),
+    fn wrap_in_rule(name: &str, alternative: usize, ast: Self) -> Self {
+        AST::Rule {
+            name: name.to_string(),
+            alternative,
+            ast: Box::new(ast),
         }
     }
     #[no_coverage]
-    fn repetition(m: VecMutator<AST, ASTMutator>) -> Self {
-        Self {
-            inner: Box::new(Either3::B(ASTSingleVariant::Sequence(Tuple1Mutator::new(Either3::C(
-                m,
-            ))))),
+    fn rule_subtree(&self, rule_name: &str, rule_alternative: usize) -> Option<&Self> {
+        match self {
+            AST::Rule { name, alternative, ast } if name == rule_name && *alternative == rule_alternative => Some(ast),
+            _ => None,
         }
     }
     #[no_coverage]
-    fn alternation(m: AlternationMutator<AST, ASTMutator>) -> Self {
-        Self {
-            inner: Box::new(Either3::A(m)),
+    fn rule_subtree_mut(&mut self) -> Option<&mut Self> {
+        match self {
+            AST::Rule { ast, .. } => Some(ast),
+            _ => None,
+        }
+    }
+}
+
+impl TreeGrammar<AST> for Grammar {
+    type NodeMutator<S> = ASTSingleVariant<
+        Tuple1Mutator<CharacterMutator>,
+        Tuple1Mutator<AlternationMutator<u8, U8WithinRangeMutator>>,
+        Tuple1Mutator<S>,
+    >
+    where
+        S: Mutator<Vec<AST>>;
+
+    #[no_coverage]
+    fn node(&self) -> GrammarNode<Self> {
+        match self {
+            Grammar::Literal(_) | Grammar::ByteLiteral(_) => GrammarNode::Terminal,
+            Grammar::Alternation(gs) => GrammarNode::Alternation(gs),
+            Grammar::Concatenation(gs) => GrammarNode::Concatenation(gs),
+            Grammar::Repetition(g, range) => GrammarNode::Repetition(g, range),
+            Grammar::Recurse(g) => GrammarNode::Recurse(g),
+            Grammar::Recursive(g) => GrammarNode::Recursive(g),
+            Grammar::Rule(name, g) => GrammarNode::Rule(name, g),
+        }
+    }
+
+    #[no_coverage]
+    fn terminal_mutator<S>(&self) -> Self::NodeMutator<S>
+    where
+        S: Mutator<Vec<AST>>,
+    {
+        match self {
+            Grammar::Literal(l) => ASTSingleVariant::Token(Tuple1Mutator::new(CharacterMutator::new(l.clone()))),
+            Grammar::ByteLiteral(l) => {
+                assert!(
+                    l.iter().any(
+                        #[no_coverage]
+                        |r| !r.is_empty()
+                    ),
+                    "a byte literal must match at least one byte"
+                );
+                ASTSingleVariant::Byte(Tuple1Mutator::new(AlternationMutator::new(
+                    l.iter()
+                        .filter(
+                            #[no_coverage]
+                            |r| !r.is_empty(),
+                        )
+                        .map(
+                            #[no_coverage]
+                            |r| U8WithinRangeMutator::new(r.clone()),
+                        )
+                        .collect(),
+                    0.0,
+                )))
+            }
+            _ => unreachable!(),
         }
     }
+
     #[no_coverage]
-    fn recur(m: RecurToMutator<ASTMutator>) -> Self {
+    fn sequence_mutator<S>(sequence: S) -> Self::NodeMutator<S>
+    where
+        S: Mutator<Vec<AST>>,
+    {
+        ASTSingleVariant::Sequence(Tuple1Mutator::new(sequence))
+    }
+}
+
+/// The mutator of the syntax trees generated by one alternative of a named grammar rule.
+///
+/// In addition to mutating the subtree of the rule, it can replace it by a subtree generated
+/// by the same rule in another test case.
+pub struct RuleMutator<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
+    name: String,
+    alternative: usize,
+    mutator: GrammarTreeMutator<Tree, G>,
+    rng: fastrand::Rng,
+}
+pub struct RuleMutatorMutationStep<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
+    crossover_step: CrossoverStep<Tree>,
+    inner: GrammarTreeMutatorMutationStep<Tree, G>,
+}
+impl<Tree, G> Clone for RuleMutatorMutationStep<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
+    #[no_coverage]
+    fn clone(&self) -> Self {
         Self {
-            inner: Box::new(Either3::B(ASTSingleVariant::Sequence(Tuple1Mutator::new(Either3::A(
-                FixedLenVecMutator::new_without_inherent_complexity(vec![m]),
-            ))))),
+            crossover_step: self.crossover_step.clone(),
+            inner: self.inner.clone(),
         }
     }
+}
+pub enum RuleMutatorUnmutateToken<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
+    Replace(Tree, GrammarTreeMutatorCache<Tree, G>),
+    Inner(GrammarTreeMutatorUnmutateToken<Tree, G>),
+}
+
+impl<Tree, G> RuleMutator<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
     #[no_coverage]
-    fn recursive(m: impl FnMut(&Weak<Self>) -> Self) -> Self {
+    fn new(name: &str, alternative: usize, mutator: GrammarTreeMutator<Tree, G>) -> Self {
         Self {
-            inner: Box::new(Either3::C(RecursiveMutator::new(m))),
+            name: name.to_string(),
+            alternative,
+            mutator,
+            rng: fastrand::Rng::new(),
         }
     }
+    /// Returns the subtree of the rule, if the value was generated by it
+    #[no_coverage]
+    fn subtree<'a>(&self, value: &'a Tree) -> Option<&'a Tree> {
+        value.rule_subtree(&self.name, self.alternative)
+    }
+    #[no_coverage]
+    fn subtree_mut<'a>(&self, value: &'a mut Tree) -> &'a mut Tree {
+        value.rule_subtree_mut().unwrap()
+    }
+    #[no_coverage]
+    fn wrap(&self, tree: Tree) -> Tree {
+        Tree::wrap_in_rule(&self.name, self.alternative, tree)
+    }
+}
+
+impl<Tree, G> Mutator<Tree> for RuleMutator<Tree, G>
+where
+    Tree: SyntaxTree,
+    G: TreeGrammar<Tree>,
+{
+    #[doc(hidden)]
+    type Cache = GrammarTreeMutatorCache<Tree, G>;
+    #[doc(hidden)]
+    type MutationStep = RuleMutatorMutationStep<Tree, G>;
+    #[doc(hidden)]
+    type ArbitraryStep = GrammarTreeMutatorArbitraryStep<Tree, G>;
+    #[doc(hidden)]
+    type UnmutateToken = RuleMutatorUnmutateToken<Tree, G>;
 
+    #[doc(hidden)]
     #[no_coverage]
-    pub(crate) fn from_grammar(grammar: Rc<Grammar>) -> Self {
-        let mut others = HashMap::new();
-        Self::from_grammar_rec(grammar, &mut others)
+    fn initialize(&self) {
+        self.mutator.initialize();
     }
 
+    #[doc(hidden)]
     #[no_coverage]
-    pub(crate) fn from_grammar_rec(
-        grammar: Rc<Grammar>,
-        others: &mut HashMap<*const Grammar, Weak<ASTMutator>>,
-    ) -> Self {
-        match grammar.as_ref() {
-            Grammar::Literal(l) => Self::token(CharacterMutator::new(l.clone())),
-            Grammar::Alternation(gs) => Self::alternation(AlternationMutator::new(
-                gs.iter()
-                    .map(
-                        #[no_coverage]
-                        |g| Self::from_grammar_rec(g.clone(), others),
-                    )
-                    .collect(),
-                0.0,
-            )),
-            Grammar::Concatenation(gs) => {
-                let mut ms = Vec::<ASTMutator>::new();
-                for g in gs {
-                    let m = Self::from_grammar_rec(g.clone(), others);
-                    ms.push(m);
-                }
-                Self::concatenation(FixedLenVecMutator::new_without_inherent_complexity(ms))
-            }
-            Grammar::Repetition(g, range) => Self::repetition(VecMutator::new_without_inherent_complexity(
-                Self::from_grammar_rec(g.clone(), others),
-                range.start..=range.end - 1,
-            )),
-            Grammar::Recurse(g) => {
-                if let Some(m) = others.get(&g.as_ptr()) {
-                    Self::recur(RecurToMutator::from(m))
-                } else {
-                    panic!()
-                }
+    fn default_arbitrary_step(&self) -> Self::ArbitraryStep {
+        self.mutator.default_arbitrary_step()
+    }
+    #[doc(hidden)]
+    #[no_coverage]
+    fn is_valid(&self, value: &Tree) -> bool {
+        self.subtree(value).map_or(
+            false,
+            #[no_coverage]
+            |tree| self.mutator.is_valid(tree),
+        )
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn validate_value(&self, value: &Tree) -> Option<Self::Cache> {
+        self.mutator.validate_value(self.subtree(value)?)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_mutation_step(&self, value: &Tree, cache: &Self::Cache) -> Self::MutationStep {
+        RuleMutatorMutationStep {
+            crossover_step: CrossoverStep::default(),
+            inner: self.mutator.default_mutation_step(self.subtree(value).unwrap(), cache),
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn global_search_space_complexity(&self) -> f64 {
+        self.mutator.global_search_space_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn max_complexity(&self) -> f64 {
+        self.mutator.max_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn min_complexity(&self) -> f64 {
+        self.mutator.min_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn complexity(&self, value: &Tree, cache: &Self::Cache) -> f64 {
+        self.mutator.complexity(self.subtree(value).unwrap(), cache)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(Tree, f64)> {
+        let (tree, cplx) = self.mutator.ordered_arbitrary(step, max_cplx)?;
+        Some((self.wrap(tree), cplx))
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_arbitrary(&self, max_cplx: f64) -> (Tree, f64) {
+        let (tree, cplx) = self.mutator.random_arbitrary(max_cplx);
+        (self.wrap(tree), cplx)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_mutate(
+        &self,
+        value: &mut Tree,
+        cache: &mut Self::Cache,
+        step: &mut Self::MutationStep,
+        subvalue_provider: &dyn crate::SubValueProvider,
+        max_cplx: f64,
+    ) -> Option<(Self::UnmutateToken, f64)> {
+        if self.rng.u8(..CROSSOVER_RATE) == 0
+            && let Some((subvalue, subcplx)) = step.crossover_step.get_next_subvalue(subvalue_provider, max_cplx)
+            && let Some(mut replacer_cache) = self.validate_value(subvalue)
+        {
+            let mut replacer = subvalue.clone();
+            std::mem::swap(value, &mut replacer);
+            std::mem::swap(cache, &mut replacer_cache);
+            return Some((RuleMutatorUnmutateToken::Replace(replacer, replacer_cache), subcplx));
+        }
+        let (token, cplx) = self.mutator.ordered_mutate(
+            self.subtree_mut(value),
+            cache,
+            &mut step.inner,
+            subvalue_provider,
+            max_cplx,
+        )?;
+        Some((RuleMutatorUnmutateToken::Inner(token), cplx))
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_mutate(&self, value: &mut Tree, cache: &mut Self::Cache, max_cplx: f64) -> (Self::UnmutateToken, f64) {
+        let (token, cplx) = self.mutator.random_mutate(self.subtree_mut(value), cache, max_cplx);
+        (RuleMutatorUnmutateToken::Inner(token), cplx)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn unmutate(&self, value: &mut Tree, cache: &mut Self::Cache, t: Self::UnmutateToken) {
+        match t {
+            RuleMutatorUnmutateToken::Replace(tree, tree_cache) => {
+                *value = tree;
+                *cache = tree_cache;
             }
-            Grammar::Recursive(g) => Self::recursive(
+            RuleMutatorUnmutateToken::Inner(t) => self.mutator.unmutate(self.subtree_mut(value), cache, t),
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn visit_subvalues<'a>(&self, value: &'a Tree, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
+        self.mutator.visit_subvalues(self.subtree(value).unwrap(), cache, visit);
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        match t {
+            RuleMutatorUnmutateToken::Replace(..) => "crossover_replace_rule",
+            RuleMutatorUnmutateToken::Inner(t) => self.mutator.mutation_kind(t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mutators::grammar::{
+        byte_range, concatenation, grammar_based_ast_mutator, literal, literal_range, repetition, rule,
+        string_from_grammar, Grammar, GrammarParser, AST,
+    };
+    use crate::subvalue_provider::{CrossoverSubValueProvider, Generation, SubValueProviderId};
+    use crate::Mutator;
+
+    #[test]
+    #[no_coverage]
+    fn test_crossover_between_same_rules() {
+        let word = rule("word", repetition(literal_range('a'..='z'), 1..5));
+        let grammar = concatenation([word.clone(), literal('='), word, literal(';')]);
+        let grammar = rule("assignment", concatenation([grammar.clone(), grammar]));
+        let parser = GrammarParser::new(&grammar);
+        let mutator = grammar_based_ast_mutator(grammar);
+        mutator.initialize();
+
+        let other = parser.parse("xyz=xyz;xyz=xyz;").unwrap();
+        let other_cache = mutator.validate_value(&other).unwrap();
+        let id = SubValueProviderId {
+            idx: 0,
+            generation: Generation(0),
+        };
+        let provider = CrossoverSubValueProvider::new(id, &other, &other_cache, &mutator);
+
+        let mut value = parser.parse("ab=cd;ef=gh;").unwrap();
+        let mut cache = mutator.validate_value(&value).unwrap();
+        let mut step = mutator.default_mutation_step(&value, &cache);
+        let mut found_crossover = false;
+        let mut nbr_mutations = 0;
+        while let Some((token, _)) = mutator.ordered_mutate(&mut value, &mut cache, &mut step, &provider, 100.) {
+            // the words can only be replaced by words, since they are generated by the same rule
+            assert!(mutator.validate_value(&value).is_some());
+            found_crossover |= value.find_rules("word").into_iter().any(
                 #[no_coverage]
-                |m| {
-                    let weak_g = Rc::downgrade(g);
-                    others.insert(weak_g.as_ptr(), m.clone());
-                    Self::from_grammar_rec(g.clone(), others)
-                },
-            ),
+                |w| w.to_string() == "xyz",
+            );
+            mutator.unmutate(&mut value, &mut cache, token);
+            assert_eq!(value.to_string(), "ab=cd;ef=gh;");
+            nbr_mutations += 1;
+            if nbr_mutations == 1000 {
+                break;
+            }
         }
+        assert!(found_crossover);
+        assert!(matches!(value, AST::Rule { .. }));
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_byte_ranges() {
+        assert!(matches!(byte_range(..0).as_ref(), Grammar::ByteLiteral(ranges) if ranges.is_empty()));
+        assert!(
+            matches!(byte_range((std::ops::Bound::Excluded(255), std::ops::Bound::Unbounded)).as_ref(), Grammar::ByteLiteral(ranges) if ranges.is_empty())
+        );
+        assert!(matches!(byte_range(..1).as_ref(), Grammar::ByteLiteral(ranges) if ranges == &[0..=0]));
+
+        let ascii = concatenation([literal('é'), byte_range(0x00..0x80)]);
+        let mutator = string_from_grammar(ascii);
+        assert!(mutator.validate_value(&"é\x7f".to_string()).is_some());
+    }
+
+    #[test]
+    #[no_coverage]
+    #[should_panic(expected = "string_from_grammar cannot be used with a grammar matching non-ASCII bytes")]
+    fn test_string_from_grammar_with_bytes() {
+        let _ = string_from_grammar(concatenation([literal('a'), byte_range(0x00..=0xff)]));
+    }
+
+    #[test]
+    #[no_coverage]
+    #[should_panic(expected = "the range of a repetition must not be empty")]
+    fn test_empty_repetition() {
+        let _ = grammar_based_ast_mutator(repetition(literal('a'), 0..0));
     }
 }
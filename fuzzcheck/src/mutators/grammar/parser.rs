@@ -0,0 +1,928 @@
+//! An Earley parser turning strings into syntax trees matching a [`Grammar`].
+//!
+//! The grammar is first translated into a context-free grammar whose nonterminals correspond
+//! to the nodes of the [`Grammar`]. The string is then recognised by an Earley parser, which
+//! supports any grammar, including ambiguous, left-recursive, and right-recursive ones.
+//! Finally, an [`AST`] is reconstructed from the Earley chart. Its shape is the one expected by
+//! the [`ASTMutator`](crate::mutators::grammar::ASTMutator) created from the same grammar.
+//!
+//! The parser works on bytes, so that grammars containing both characters and bytes can be parsed.
+//! Character literals match the UTF-8 encoding of a character, and byte literals match a single byte.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::{Range, RangeInclusive};
+use std::rc::Rc;
+
+use super::ast::AST;
+use super::grammar::Grammar;
+
+/// Repetitions allowing more optional elements than this are parsed as unbounded repetitions,
+/// and their number of elements is checked after the fact.
+const MAX_UNROLLED_REPETITION: usize = 64;
+
+#[derive(Clone, Debug)]
+enum Terminal {
+    Chars(Vec<RangeInclusive<char>>),
+    Bytes(Vec<RangeInclusive<u8>>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Symbol {
+    Terminal(usize),
+    NonTerminal(usize),
+}
+
+/// Describes how the syntax tree of a nonterminal is built from the syntax trees of its symbols
+#[derive(Clone, Debug)]
+enum NonTerminalKind {
+    Start,
+    Alternation,
+    Concatenation,
+    Repetition(Range<usize>),
+    /// The optional elements of a repetition, which are flattened into the repetition's sequence
+    RepetitionTail,
+    Recurse,
+    Recursive,
+    /// A named rule, which has one production per alternative if its grammar is an alternation
+    Rule(String),
+}
+
+#[derive(Clone, Debug)]
+struct Rule {
+    lhs: usize,
+    rhs: Vec<Symbol>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Item {
+    rule: usize,
+    dot: usize,
+    origin: usize,
+}
+impl Item {
+    #[no_coverage]
+    fn advance(self) -> Self {
+        Self {
+            dot: self.dot + 1,
+            ..self
+        }
+    }
+}
+
+#[derive(Default)]
+struct EarleySet {
+    items: Vec<Item>,
+    contains: HashSet<Item>,
+    /// The items whose next symbol is the given nonterminal
+    waiting: HashMap<usize, Vec<Item>>,
+    /// The nonterminals that were completed in this set, along with their origin
+    completed: HashSet<(usize, usize)>,
+}
+
+/**
+Parses strings into syntax trees matching a grammar.
+
+The returned [`AST`] can be given to the [`ASTMutator`](crate::mutators::grammar::ASTMutator)
+created from the same grammar, which makes it possible to seed grammar-based fuzz tests with
+existing test cases. It is also used by [`string_from_grammar`](crate::mutators::grammar::string_from_grammar)
+to read back a corpus of plain text files.
+
+```
+use fuzzcheck::mutators::grammar::{alternation, concatenation, grammar_based_ast_mutator, literal, literal_range, recurse, recursive, repetition, GrammarParser};
+use fuzzcheck::Mutator;
+
+let grammar = recursive(|expr| {
+    alternation([
+        repetition(literal_range('0'..='9'), 1..),
+        concatenation([literal('('), recurse(expr), literal('+'), recurse(expr), literal(')')]),
+    ])
+});
+let parser = GrammarParser::new(&grammar);
+
+let ast = parser.parse("(12+(3+45))").unwrap();
+assert_eq!(ast.to_string(), "(12+(3+45))");
+assert!(parser.parse("(12+)").is_none());
+
+// the syntax tree can be used by the grammar-based mutator
+let mutator = grammar_based_ast_mutator(grammar);
+assert!(mutator.validate_value(&ast).is_some());
+```
+*/
+pub struct GrammarParser {
+    terminals: Vec<Terminal>,
+    kinds: Vec<NonTerminalKind>,
+    rules: Vec<Rule>,
+    rules_of: Vec<Vec<usize>>,
+    nullable: Vec<bool>,
+    start: usize,
+}
+
+impl GrammarParser {
+    /// Creates a parser for the given grammar
+    #[no_coverage]
+    pub fn new(grammar: &Rc<Grammar>) -> Self {
+        let mut parser = Self {
+            terminals: vec![],
+            kinds: vec![],
+            rules: vec![],
+            rules_of: vec![],
+            nullable: vec![],
+            start: 0,
+        };
+        let start = parser.new_nonterminal(NonTerminalKind::Start);
+        let mut symbols = HashMap::new();
+        let root = parser.symbol(grammar, &mut symbols);
+        parser.add_rule(start, vec![root]);
+        parser.start = start;
+        parser.compute_nullable();
+        parser
+    }
+
+    #[no_coverage]
+    fn new_nonterminal(&mut self, kind: NonTerminalKind) -> usize {
+        self.kinds.push(kind);
+        self.rules_of.push(vec![]);
+        self.kinds.len() - 1
+    }
+
+    #[no_coverage]
+    fn add_rule(&mut self, lhs: usize, rhs: Vec<Symbol>) {
+        self.rules_of[lhs].push(self.rules.len());
+        self.rules.push(Rule { lhs, rhs });
+    }
+
+    /// Returns the symbol corresponding to the given grammar, creating it and its rules if needed
+    #[no_coverage]
+    fn symbol(&mut self, grammar: &Rc<Grammar>, symbols: &mut HashMap<*const Grammar, Symbol>) -> Symbol {
+        let ptr = Rc::as_ptr(grammar);
+        if let Some(symbol) = symbols.get(&ptr) {
+            return *symbol;
+        }
+        let kind = match grammar.as_ref() {
+            Grammar::Literal(ranges) => {
+                self.terminals.push(Terminal::Chars(ranges.clone()));
+                let symbol = Symbol::Terminal(self.terminals.len() - 1);
+                symbols.insert(ptr, symbol);
+                return symbol;
+            }
+            Grammar::ByteLiteral(ranges) => {
+                self.terminals.push(Terminal::Bytes(ranges.clone()));
+                let symbol = Symbol::Terminal(self.terminals.len() - 1);
+                symbols.insert(ptr, symbol);
+                return symbol;
+            }
+            Grammar::Alternation(_) => NonTerminalKind::Alternation,
+            Grammar::Concatenation(_) => NonTerminalKind::Concatenation,
+            Grammar::Repetition(_, range) => NonTerminalKind::Repetition(range.clone()),
+            Grammar::Recurse(_) => NonTerminalKind::Recurse,
+            Grammar::Recursive(_) => NonTerminalKind::Recursive,
+            Grammar::Rule(name, _) => NonTerminalKind::Rule(name.clone()),
+        };
+        // the symbol is registered before its rules are created, so that recursive grammars can refer to it
+        let nt = self.new_nonterminal(kind);
+        symbols.insert(ptr, Symbol::NonTerminal(nt));
+        match grammar.as_ref() {
+            Grammar::Literal(_) | Grammar::ByteLiteral(_) => unreachable!(),
+            Grammar::Alternation(gs) => {
+                for g in gs {
+                    let symbol = self.symbol(g, symbols);
+                    self.add_rule(nt, vec![symbol]);
+                }
+            }
+            Grammar::Concatenation(gs) => {
+                let rhs = gs
+                    .iter()
+                    .map(
+                        #[no_coverage]
+                        |g| self.symbol(g, symbols),
+                    )
+                    .collect();
+                self.add_rule(nt, rhs);
+            }
+            Grammar::Repetition(g, range) => {
+                if range.start < range.end {
+                    let element = self.symbol(g, symbols);
+                    let nbr_optional = range.end - 1 - range.start;
+                    let tail = if range.end == usize::MAX || nbr_optional > MAX_UNROLLED_REPETITION {
+                        // tail -> ε | tail element
+                        let tail = self.new_nonterminal(NonTerminalKind::RepetitionTail);
+                        self.add_rule(tail, vec![]);
+                        self.add_rule(tail, vec![Symbol::NonTerminal(tail), element]);
+                        tail
+                    } else {
+                        // tail_0 -> ε
+                        // tail_i -> ε | element tail_(i-1)
+                        let mut tail = self.new_nonterminal(NonTerminalKind::RepetitionTail);
+                        self.add_rule(tail, vec![]);
+                        for _ in 0..nbr_optional {
+                            let next_tail = self.new_nonterminal(NonTerminalKind::RepetitionTail);
+                            self.add_rule(next_tail, vec![]);
+                            self.add_rule(next_tail, vec![element, Symbol::NonTerminal(tail)]);
+                            tail = next_tail;
+                        }
+                        tail
+                    };
+                    let mut rhs = vec![element; range.start];
+                    rhs.push(Symbol::NonTerminal(tail));
+                    self.add_rule(nt, rhs);
+                }
+            }
+            Grammar::Recurse(g) => {
+                let g = g
+                    .upgrade()
+                    .expect("a recursive grammar was dropped while still referenced");
+                let symbol = self.symbol(&g, symbols);
+                self.add_rule(nt, vec![symbol]);
+            }
+            Grammar::Recursive(g) => {
+                let symbol = self.symbol(g, symbols);
+                self.add_rule(nt, vec![symbol]);
+            }
+            Grammar::Rule(_, g) => {
+                if let Grammar::Alternation(gs) = g.as_ref() {
+                    for g in gs {
+                        let symbol = self.symbol(g, symbols);
+                        self.add_rule(nt, vec![symbol]);
+                    }
+                } else {
+                    let symbol = self.symbol(g, symbols);
+                    self.add_rule(nt, vec![symbol]);
+                }
+            }
+        }
+        Symbol::NonTerminal(nt)
+    }
+
+    #[no_coverage]
+    fn compute_nullable(&mut self) {
+        self.nullable = vec![false; self.kinds.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for rule in &self.rules {
+                if self.nullable[rule.lhs] {
+                    continue;
+                }
+                let nullable = rule.rhs.iter().all(
+                    #[no_coverage]
+                    |symbol| matches!(symbol, Symbol::NonTerminal(nt) if self.nullable[*nt]),
+                );
+                if nullable {
+                    self.nullable[rule.lhs] = true;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    /// Returns the syntax tree of the terminal if it matches the input at the given position,
+    /// along with the number of bytes it spans
+    #[no_coverage]
+    fn scan(&self, terminal: usize, bytes: &[u8], idx: usize) -> Option<(AST, usize)> {
+        match &self.terminals[terminal] {
+            Terminal::Chars(ranges) => {
+                let (c, len) = char_at(bytes, idx)?;
+                ranges
+                    .iter()
+                    .any(
+                        #[no_coverage]
+                        |range| range.contains(&c),
+                    )
+                    .then_some((AST::Token(c), len))
+            }
+            Terminal::Bytes(ranges) => {
+                let b = *bytes.get(idx)?;
+                ranges
+                    .iter()
+                    .any(
+                        #[no_coverage]
+                        |range| range.contains(&b),
+                    )
+                    .then_some((AST::Byte(b), 1))
+            }
+        }
+    }
+
+    #[no_coverage]
+    fn add_item(&self, set: &mut EarleySet, item: Item) {
+        if set.contains.insert(item) {
+            set.items.push(item);
+            if let Some(Symbol::NonTerminal(nt)) = self.rules[item.rule].rhs.get(item.dot) {
+                set.waiting.entry(*nt).or_default().push(item);
+            }
+        }
+    }
+
+    /// Returns the syntax tree of the given string, or `None` if it does not match the grammar.
+    ///
+    /// If the grammar is ambiguous, one of the possible syntax trees is chosen arbitrarily.
+    #[no_coverage]
+    pub fn parse(&self, string: &str) -> Option<AST> {
+        self.parse_bytes(string.as_bytes())
+    }
+
+    /// Returns the syntax tree of the given bytes, or `None` if they do not match the grammar.
+    ///
+    /// The character literals of the grammar match the UTF-8 encoding of the characters.
+    #[no_coverage]
+    pub fn parse_bytes(&self, bytes: &[u8]) -> Option<AST> {
+        let chart = self.recognise(bytes);
+        let derivations = self.derive(&chart, bytes, self.start, 0, bytes.len());
+        let mut asts = self.build(&derivations, bytes, self.start, 0, bytes.len())?;
+        assert_eq!(asts.len(), 1);
+        asts.pop()
+    }
+
+    #[no_coverage]
+    fn recognise(&self, bytes: &[u8]) -> Vec<EarleySet> {
+        let n = bytes.len();
+        let mut chart = (0..=n)
+            .map(
+                #[no_coverage]
+                |_| EarleySet::default(),
+            )
+            .collect::<Vec<_>>();
+        for &rule in &self.rules_of[self.start] {
+            self.add_item(
+                &mut chart[0],
+                Item {
+                    rule,
+                    dot: 0,
+                    origin: 0,
+                },
+            );
+        }
+        for i in 0..=n {
+            let mut idx = 0;
+            while idx < chart[i].items.len() {
+                let item = chart[i].items[idx];
+                idx += 1;
+                let rule = &self.rules[item.rule];
+                if let Some(symbol) = rule.rhs.get(item.dot) {
+                    match *symbol {
+                        Symbol::Terminal(terminal) => {
+                            if let Some((_, len)) = self.scan(terminal, bytes, i) {
+                                self.add_item(&mut chart[i + len], item.advance());
+                            }
+                        }
+                        Symbol::NonTerminal(nt) => {
+                            for &rule in &self.rules_of[nt] {
+                                self.add_item(
+                                    &mut chart[i],
+                                    Item {
+                                        rule,
+                                        dot: 0,
+                                        origin: i,
+                                    },
+                                );
+                            }
+                            if self.nullable[nt] {
+                                self.add_item(&mut chart[i], item.advance());
+                            }
+                        }
+                    }
+                } else {
+                    chart[i].completed.insert((rule.lhs, item.origin));
+                    let waiting = chart[item.origin].waiting.get(&rule.lhs).cloned().unwrap_or_default();
+                    for waiting_item in waiting {
+                        self.add_item(&mut chart[i], waiting_item.advance());
+                    }
+                }
+            }
+        }
+        chart
+    }
+
+    /// Finds a derivation of the nonterminal `nt` spanning the bytes `start..end`.
+    ///
+    /// The search uses an explicit stack, so that long inputs cannot overflow the call stack, and
+    /// the result of each nonterminal and rule prefix is memoized for each span.
+    #[no_coverage]
+    fn derive(&self, chart: &[EarleySet], bytes: &[u8], nt: usize, start: usize, end: usize) -> Derivations {
+        let mut derivations = Derivations::default();
+        let mut stack = vec![];
+        let mut returned = derivations.enter(chart, Frame::Build(BuildFrame::new(nt, start, end)), &mut stack);
+        while let Some(frame) = stack.last_mut() {
+            let step = match frame {
+                Frame::Build(frame) => frame.resume(self, chart, returned.take()),
+                Frame::Split(frame) => frame.resume(self, chart, bytes, returned.take()),
+            };
+            match step {
+                Step::Call(callee) => {
+                    returned = derivations.enter(chart, callee, &mut stack);
+                }
+                Step::Return(result) => {
+                    let frame = stack.pop().unwrap();
+                    derivations.exit(frame, result);
+                    returned = Some(result.map(
+                        #[no_coverage]
+                        |(_, count)| count,
+                    ));
+                }
+            }
+        }
+        derivations
+    }
+
+    /// Builds the syntax trees of the nonterminal `nt` spanning the bytes `start..end`, following
+    /// the derivations found by [`derive`](GrammarParser::derive).
+    ///
+    /// It returns a single tree, except for the tails of repetitions, which return the
+    /// list of their elements.
+    #[no_coverage]
+    fn build(&self, derivations: &Derivations, bytes: &[u8], nt: usize, start: usize, end: usize) -> Option<Vec<AST>> {
+        let mut stack = vec![TreeFrame::new(self, derivations, nt, start, end)?];
+        loop {
+            let frame = stack.last_mut().unwrap();
+            if let Some((symbol, start, end)) = frame.children_spans.pop() {
+                match symbol {
+                    Symbol::Terminal(terminal) => {
+                        let (ast, _) = self.scan(terminal, bytes, start)?;
+                        frame.children.push(ast);
+                    }
+                    Symbol::NonTerminal(nt) => {
+                        stack.push(TreeFrame::new(self, derivations, nt, start, end)?);
+                    }
+                }
+            } else {
+                let frame = stack.pop().unwrap();
+                let asts = self.finish(frame.nt, frame.rule, frame.children)?;
+                if let Some(parent) = stack.last_mut() {
+                    if parent.children.is_empty() {
+                        // avoids copying the elements of left-recursive repetition tails
+                        parent.children = asts;
+                    } else {
+                        parent.children.extend(asts);
+                    }
+                } else {
+                    return Some(asts);
+                }
+            }
+        }
+    }
+
+    /// Returns the number of syntax trees returned by the nonterminal `nt` when its rule produces
+    /// the given number of syntax trees, or `None` if the derivation is not valid
+    #[no_coverage]
+    fn finished_count(&self, nt: usize, count: usize) -> Option<usize> {
+        match &self.kinds[nt] {
+            NonTerminalKind::Start | NonTerminalKind::Alternation | NonTerminalKind::Recursive => Some(count),
+            NonTerminalKind::RepetitionTail => Some(count),
+            NonTerminalKind::Concatenation | NonTerminalKind::Recurse => Some(1),
+            NonTerminalKind::Repetition(range) => range.contains(&count).then_some(1),
+            NonTerminalKind::Rule(_) => (count == 1).then_some(1),
+        }
+    }
+
+    #[no_coverage]
+    fn finish(&self, nt: usize, rule: usize, mut children: Vec<AST>) -> Option<Vec<AST>> {
+        match &self.kinds[nt] {
+            NonTerminalKind::Start | NonTerminalKind::Alternation | NonTerminalKind::Recursive => Some(children),
+            NonTerminalKind::RepetitionTail => Some(children),
+            NonTerminalKind::Concatenation | NonTerminalKind::Recurse => Some(vec![AST::Sequence(children)]),
+            NonTerminalKind::Repetition(range) => {
+                if range.contains(&children.len()) {
+                    Some(vec![AST::Sequence(children)])
+                } else {
+                    None
+                }
+            }
+            NonTerminalKind::Rule(name) => {
+                let alternative = self.rules_of[nt].iter().position(
+                    #[no_coverage]
+                    |r| *r == rule,
+                )?;
+                Some(vec![AST::Rule {
+                    name: name.clone(),
+                    alternative,
+                    ast: Box::new(children.pop()?),
+                }])
+            }
+        }
+    }
+}
+
+/// The derivations found for the nonterminals and rule prefixes spanning parts of the input
+#[derive(Default)]
+struct Derivations {
+    /// The rule deriving a nonterminal over a span, along with the number of syntax trees it returns,
+    /// indexed by `(nonterminal, start, end)`
+    builds: HashMap<(usize, usize, usize), Option<(usize, usize)>>,
+    /// The start of the last symbol of a rule prefix, along with the number of syntax trees of the prefix,
+    /// indexed by `(rule, dot, start, end)`
+    splits: HashMap<(usize, usize, usize, usize), Option<(usize, usize)>>,
+    /// The nonterminals being derived, which prevents infinite loops on cyclic derivations,
+    /// such as those of nullable nonterminals
+    in_progress: HashSet<(usize, usize, usize)>,
+    /// The number of times that a cyclic derivation was cut short
+    nbr_cuts: usize,
+}
+
+impl Derivations {
+    /// Returns the result of the frame if it is already known, or pushes it on the stack otherwise
+    #[no_coverage]
+    fn enter(&mut self, chart: &[EarleySet], mut frame: Frame, stack: &mut Vec<Frame>) -> Option<Option<usize>> {
+        let result = match &mut frame {
+            Frame::Build(build) => {
+                let key = (build.nt, build.start, build.end);
+                if let Some(result) = self.builds.get(&key) {
+                    Some(*result)
+                } else if !chart[build.end].completed.contains(&(build.nt, build.start)) {
+                    Some(None)
+                } else if !self.in_progress.insert(key) {
+                    self.nbr_cuts += 1;
+                    Some(None)
+                } else {
+                    build.nbr_cuts = self.nbr_cuts;
+                    None
+                }
+            }
+            Frame::Split(split) => {
+                if split.dot == 0 {
+                    Some((split.start == split.end).then_some((split.start, 0)))
+                } else if let Some(result) = self.splits.get(&(split.rule, split.dot, split.start, split.end)) {
+                    Some(*result)
+                } else {
+                    split.nbr_cuts = self.nbr_cuts;
+                    None
+                }
+            }
+        };
+        if result.is_none() {
+            stack.push(frame);
+        }
+        result.map(
+            #[no_coverage]
+            |result| {
+                result.map(
+                    #[no_coverage]
+                    |(_, count)| count,
+                )
+            },
+        )
+    }
+
+    /// Records the result of the frame.
+    ///
+    /// A failure is not recorded if a cyclic derivation was cut short while looking for it, since
+    /// it may then succeed in another context.
+    #[no_coverage]
+    fn exit(&mut self, frame: Frame, result: Option<(usize, usize)>) {
+        match frame {
+            Frame::Build(build) => {
+                let key = (build.nt, build.start, build.end);
+                self.in_progress.remove(&key);
+                if result.is_some() || self.nbr_cuts == build.nbr_cuts {
+                    self.builds.insert(key, result);
+                }
+            }
+            Frame::Split(split) => {
+                if result.is_some() || self.nbr_cuts == split.nbr_cuts {
+                    self.splits
+                        .insert((split.rule, split.dot, split.start, split.end), result);
+                }
+            }
+        }
+    }
+}
+
+/// A step of the search for a derivation
+enum Step {
+    /// Find the derivation of another frame, whose result is then given back to the current frame
+    Call(Frame),
+    /// The choice made by the current frame and the number of syntax trees it produces, or `None`
+    /// if there is no derivation
+    Return(Option<(usize, usize)>),
+}
+
+/// An element of the explicit stack used to search for a derivation
+enum Frame {
+    Build(BuildFrame),
+    Split(SplitFrame),
+}
+
+/// Looks for a rule deriving the nonterminal `nt` over `start..end`
+struct BuildFrame {
+    nt: usize,
+    start: usize,
+    end: usize,
+    /// The position in `rules_of[nt]` of the next rule to try
+    next_rule: usize,
+    /// The rule whose derivation is being looked for
+    current_rule: Option<usize>,
+    nbr_cuts: usize,
+}
+
+impl BuildFrame {
+    #[no_coverage]
+    fn new(nt: usize, start: usize, end: usize) -> Self {
+        Self {
+            nt,
+            start,
+            end,
+            next_rule: 0,
+            current_rule: None,
+            nbr_cuts: 0,
+        }
+    }
+
+    #[no_coverage]
+    fn resume(&mut self, parser: &GrammarParser, chart: &[EarleySet], returned: Option<Option<usize>>) -> Step {
+        if let Some(rule) = self.current_rule.take()
+            && let Some(Some(count)) = returned
+            && let Some(count) = parser.finished_count(self.nt, count)
+        {
+            return Step::Return(Some((rule, count)));
+        }
+        let rules = &parser.rules_of[self.nt];
+        while self.next_rule < rules.len() {
+            let rule = rules[self.next_rule];
+            self.next_rule += 1;
+            let len = parser.rules[rule].rhs.len();
+            if chart[self.end].contains.contains(&Item {
+                rule,
+                dot: len,
+                origin: self.start,
+            }) {
+                self.current_rule = Some(rule);
+                return Step::Call(Frame::Split(SplitFrame::new(parser, rule, len, self.start, self.end)));
+            }
+        }
+        Step::Return(None)
+    }
+}
+
+/// Looks for the start of the last symbol of the first `dot` symbols of the rule, such that
+/// these symbols span `start..end`
+struct SplitFrame {
+    rule: usize,
+    dot: usize,
+    start: usize,
+    end: usize,
+    /// The possible starts of the last symbol that remain to be tried, in decreasing order
+    mids: std::iter::Rev<RangeInclusive<usize>>,
+    awaiting: Awaiting,
+    nbr_cuts: usize,
+}
+
+/// The derivation that a [`SplitFrame`] is waiting for
+enum Awaiting {
+    Nothing,
+    /// The derivation of the symbols before the last one, which start at the given position
+    Prefix(usize),
+    /// The derivation of the last symbol, which starts at the given position, given the number of
+    /// syntax trees of the previous symbols
+    Last(usize, usize),
+}
+
+impl SplitFrame {
+    #[no_coverage]
+    fn new(parser: &GrammarParser, rule: usize, dot: usize, start: usize, end: usize) -> Self {
+        let (lowest, highest) = if dot <= 1 {
+            // the symbols before the last one are empty
+            (start, start)
+        } else {
+            match parser.rules[rule].rhs[dot - 1] {
+                // a terminal spans a single byte or a single UTF-8 encoded character
+                Symbol::Terminal(_) => (end.saturating_sub(4).max(start), end),
+                Symbol::NonTerminal(_) => (start, end),
+            }
+        };
+        Self {
+            rule,
+            dot,
+            start,
+            end,
+            mids: (lowest..=highest).rev(),
+            awaiting: Awaiting::Nothing,
+            nbr_cuts: 0,
+        }
+    }
+
+    #[no_coverage]
+    fn resume(
+        &mut self,
+        parser: &GrammarParser,
+        chart: &[EarleySet],
+        bytes: &[u8],
+        returned: Option<Option<usize>>,
+    ) -> Step {
+        let symbol = parser.rules[self.rule].rhs[self.dot - 1];
+        match (std::mem::replace(&mut self.awaiting, Awaiting::Nothing), returned) {
+            (Awaiting::Prefix(mid), Some(Some(count))) => match symbol {
+                Symbol::Terminal(_) => return Step::Return(Some((mid, count + 1))),
+                Symbol::NonTerminal(nt) => {
+                    self.awaiting = Awaiting::Last(mid, count);
+                    return Step::Call(Frame::Build(BuildFrame::new(nt, mid, self.end)));
+                }
+            },
+            (Awaiting::Last(mid, prefix_count), Some(Some(count))) => {
+                return Step::Return(Some((mid, prefix_count + count)));
+            }
+            _ => {}
+        }
+        let prefix = Item {
+            rule: self.rule,
+            dot: self.dot - 1,
+            origin: self.start,
+        };
+        for mid in self.mids.by_ref() {
+            if !chart[mid].contains.contains(&prefix) {
+                continue;
+            }
+            let matches = match symbol {
+                Symbol::Terminal(terminal) => parser.scan(terminal, bytes, mid).map_or(
+                    false,
+                    #[no_coverage]
+                    |(_, len)| mid + len == self.end,
+                ),
+                Symbol::NonTerminal(nt) => chart[self.end].completed.contains(&(nt, mid)),
+            };
+            if matches {
+                self.awaiting = Awaiting::Prefix(mid);
+                return Step::Call(Frame::Split(SplitFrame::new(
+                    parser,
+                    self.rule,
+                    self.dot - 1,
+                    self.start,
+                    mid,
+                )));
+            }
+        }
+        Step::Return(None)
+    }
+}
+
+/// A syntax tree being built from the derivation of a nonterminal
+struct TreeFrame {
+    nt: usize,
+    rule: usize,
+    /// The symbols of the rule that remain to be built, along with their spans, in reverse order
+    children_spans: Vec<(Symbol, usize, usize)>,
+    children: Vec<AST>,
+}
+
+impl TreeFrame {
+    #[no_coverage]
+    fn new(parser: &GrammarParser, derivations: &Derivations, nt: usize, start: usize, end: usize) -> Option<Self> {
+        let (rule, _) = (*derivations.builds.get(&(nt, start, end))?)?;
+        let rhs = &parser.rules[rule].rhs;
+        let mut children_spans = Vec::with_capacity(rhs.len());
+        let mut end = end;
+        for dot in (1..=rhs.len()).rev() {
+            let mid = if dot == 1 {
+                start
+            } else {
+                let (mid, _) = (*derivations.splits.get(&(rule, dot, start, end))?)?;
+                mid
+            };
+            children_spans.push((rhs[dot - 1], mid, end));
+            end = mid;
+        }
+        Some(Self {
+            nt,
+            rule,
+            children_spans,
+            children: vec![],
+        })
+    }
+}
+
+/// Decodes the UTF-8 character starting at the given index, and returns it along with its length
+#[no_coverage]
+fn char_at(bytes: &[u8], idx: usize) -> Option<(char, usize)> {
+    let len = match *bytes.get(idx)? {
+        0x00..=0x7F => 1,
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return None,
+    };
+    let c = std::str::from_utf8(bytes.get(idx..idx + len)?).ok()?.chars().next()?;
+    Some((c, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GrammarParser;
+    use crate::mutators::grammar::{
+        alternation, byte, byte_range, concatenation, grammar_based_ast_mutator, literal, literal_range, recurse,
+        recursive, repetition,
+    };
+    use crate::Mutator;
+
+    #[test]
+    #[no_coverage]
+    fn test_parse_recursive_grammar() {
+        // left-recursive, ambiguous grammar of arithmetic expressions
+        let grammar = recursive(
+            #[no_coverage]
+            |expr| {
+                alternation([
+                    repetition(literal_range('0'..='9'), 1..4),
+                    concatenation([recurse(expr), literal('+'), recurse(expr)]),
+                    concatenation([literal('('), recurse(expr), literal(')')]),
+                ])
+            },
+        );
+        let parser = GrammarParser::new(&grammar);
+        let mutator = grammar_based_ast_mutator(grammar);
+        for string in ["1", "123", "1+2+3", "(1+22)+333", "((1))", "1+(2+(3+(4)))"] {
+            let ast = parser.parse(string).unwrap();
+            assert_eq!(ast.to_string(), string);
+            assert!(mutator.validate_value(&ast).is_some(), "{}", string);
+        }
+        for string in ["", "1234", "1+", "(1", "+", "a"] {
+            assert!(parser.parse(string).is_none(), "{}", string);
+        }
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_parse_repetitions() {
+        let grammar = concatenation([
+            repetition(alternation([literal('a'), literal('b')]), 2..=3),
+            repetition(repetition(literal('c'), 0..2), 0..),
+            repetition(literal('d'), 1..500),
+        ]);
+        let parser = GrammarParser::new(&grammar);
+        let mutator = grammar_based_ast_mutator(grammar);
+        let long = format!("ab{}", "d".repeat(499));
+        for string in ["abd", "abbcccd", "bbbdd", long.as_str()] {
+            let ast = parser.parse(string).unwrap();
+            assert_eq!(ast.to_string(), string);
+            assert!(mutator.validate_value(&ast).is_some(), "{}", string);
+        }
+        let too_long = format!("ab{}", "d".repeat(500));
+        for string in ["ad", "ababd", "ab", too_long.as_str()] {
+            assert!(parser.parse(string).is_none(), "{}", string);
+        }
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_parse_long_input() {
+        // each element used to add a frame to the call stack, which overflowed on long inputs
+        let grammar = repetition(literal_range('a'..='z'), 0..);
+        let parser = GrammarParser::new(&grammar);
+        let string = "a".repeat(10_000);
+        let ast = parser.parse(&string).unwrap();
+        assert_eq!(ast.to_string(), string);
+
+        let grammar = concatenation([repetition(literal('a'), 0..), literal('b')]);
+        let parser = GrammarParser::new(&grammar);
+        let string = format!("{}b", "a".repeat(10_000));
+        assert_eq!(parser.parse(&string).unwrap().to_string(), string);
+        assert!(parser.parse(&string[..10_000]).is_none());
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_parse_generated_values() {
+        let grammar = recursive(
+            #[no_coverage]
+            |tree| {
+                concatenation([
+                    literal_range('a'..='z'),
+                    repetition(concatenation([literal('['), recurse(tree), literal(']')]), 0..3),
+                ])
+            },
+        );
+        let parser = GrammarParser::new(&grammar);
+        let mutator = grammar_based_ast_mutator(grammar);
+        mutator.initialize();
+        for _ in 0..100 {
+            let (ast, _) = mutator.random_arbitrary(200.0);
+            let string = ast.to_string();
+            let parsed = parser.parse(&string).unwrap();
+            // this grammar is unambiguous, so the syntax tree is the same
+            assert_eq!(parsed, ast);
+        }
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_parse_bytes() {
+        let grammar = concatenation([
+            repetition(
+                alternation([literal_range('a'..='z'), literal('é'), literal('😀')]),
+                0..,
+            ),
+            byte(0xff),
+            repetition(byte_range(0x80..), 1..3),
+        ]);
+        let parser = GrammarParser::new(&grammar);
+        let mutator = grammar_based_ast_mutator(grammar);
+        let mut valid = "aé😀z".as_bytes().to_vec();
+        valid.extend([0xff, 0xc3, 0xa9]);
+        for bytes in [vec![0xff, 0x80], valid] {
+            let ast = parser.parse_bytes(&bytes).unwrap();
+            assert_eq!(ast.to_bytes(), bytes);
+            assert!(mutator.validate_value(&ast).is_some(), "{:?}", bytes);
+        }
+        // truncated UTF-8 character, missing bytes, and byte outside of the range
+        for bytes in [vec![0xc3, 0xff, 0x80], vec![0xff], vec![0x61, 0xff, 0x7f]] {
+            assert!(parser.parse_bytes(&bytes).is_none(), "{:?}", bytes);
+        }
+    }
+}
@@ -1,22 +1,148 @@
+use std::fmt::{self, Display};
 use std::rc::Rc;
 
-use regex_syntax::hir::{Class, HirKind, Literal, RepetitionKind, RepetitionRange};
+use regex_syntax::hir::{Anchor, Class, HirKind, Literal, RepetitionKind, RepetitionRange};
 
-use crate::mutators::grammar::{alternation, concatenation, literal, literal_ranges, repetition, Grammar};
+use crate::mutators::grammar::{
+    alternation, byte, byte_ranges, concatenation, literal, literal_ranges, repetition, Grammar,
+};
+
+/// The reason why a regular expression could not be converted to a grammar, returned by [`try_regex`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[doc(cfg(feature = "regex_grammar"))]
+pub enum RegexError {
+    /// The regular expression is malformed
+    Syntax(String),
+    /// The regular expression uses a construct that cannot be converted to a grammar
+    Unsupported(String),
+}
+
+impl Display for RegexError {
+    #[no_coverage]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegexError::Syntax(e) => write!(f, "invalid regular expression: {}", e),
+            RegexError::Unsupported(e) => write!(f, "unsupported regular expression: {}", e),
+        }
+    }
+}
+impl std::error::Error for RegexError {}
+
+/// Options for the conversion of regular expressions into grammars, used by [`try_regex_with_options`]
+#[derive(Debug, Clone, Copy, Default)]
+#[doc(cfg(feature = "regex_grammar"))]
+pub struct RegexOptions {
+    max_repetitions: Option<usize>,
+}
+
+impl RegexOptions {
+    /// The maximum number of times that an unbounded repetition, such as `a*`, `a+`, or `a{2,}`,
+    /// can repeat its expression. By default, they are not bounded.
+    #[no_coverage]
+    pub fn max_repetitions(self, max_repetitions: usize) -> Self {
+        Self {
+            max_repetitions: Some(max_repetitions),
+        }
+    }
+}
+
+/**
+Creates a grammar from a regular expression, or returns an error describing why it is not possible.
+
+Unlike [`regex`](crate::mutators::grammar::regex), it does not panic on malformed regular expressions.
+
+The grammar generates strings that match the whole regular expression. Therefore, the anchors
+`^`, `$`, `\A`, and `\z` are ignored, but an error is returned if `\A` or `^` can be preceded by a
+character, or `\z` or `$` followed by one. This is also the case with the multi-line flag, since the
+line anchors are only supported at the start and end of the regular expression.
+Word boundaries (`\b` and `\B`) are approximated by ignoring them, such that the grammar may
+generate strings that do not respect them.
+
+```
+use fuzzcheck::mutators::grammar::{try_regex, RegexError};
+
+assert!(try_regex(r"^(GET|POST) /[a-z]*\b$").is_ok());
+assert!(matches!(try_regex("(a|b"), Err(RegexError::Syntax(_))));
+assert!(matches!(try_regex(r"a^b"), Err(RegexError::Unsupported(_))));
+```
+*/
+#[no_coverage]
+pub fn try_regex(regex: &str) -> Result<Rc<Grammar>, RegexError> {
+    try_regex_with_options(regex, RegexOptions::default())
+}
+
+/**
+Creates a grammar from a regular expression using the given options, or returns an error describing
+why it is not possible.
+
+```
+use fuzzcheck::mutators::grammar::{try_regex_with_options, GrammarParser, RegexOptions};
+
+let grammar = try_regex_with_options("a+b*", RegexOptions::default().max_repetitions(3)).unwrap();
+let parser = GrammarParser::new(&grammar);
+assert!(parser.parse("aaabbb").is_some());
+assert!(parser.parse("aaaab").is_none());
+```
+*/
+#[no_coverage]
+pub fn try_regex_with_options(regex: &str, options: RegexOptions) -> Result<Rc<Grammar>, RegexError> {
+    // non-UTF-8 regexes, such as `(?-u)\xFF`, produce grammars containing bytes
+    let mut parser = regex_syntax::ParserBuilder::new().allow_invalid_utf8(true).build();
+    let hir = parser.parse(regex).map_err(
+        #[no_coverage]
+        |e| RegexError::Syntax(e.to_string()),
+    )?;
+    let position = Position {
+        at_start: true,
+        at_end: true,
+    };
+    grammar_from_regex_hir_kind(hir.kind(), &options, position)
+}
 
 #[no_coverage]
 pub(crate) fn grammar_from_regex(regex: &str) -> Rc<Grammar> {
-    let mut parser = regex_syntax::Parser::new();
-    let hir = parser.parse(regex).unwrap();
-    grammar_from_regex_hir_kind(hir.kind())
+    try_regex(regex).unwrap_or_else(
+        #[no_coverage]
+        |e| panic!("{}", e),
+    )
 }
+
+/// Whether a part of the regex can only be preceded, or followed, by the empty string
+#[derive(Clone, Copy)]
+struct Position {
+    at_start: bool,
+    at_end: bool,
+}
+
+/// Whether the regex can only match the empty string
 #[no_coverage]
-pub fn grammar_from_regex_hir_kind(hir: &HirKind) -> Rc<Grammar> {
+fn matches_only_empty(hir: &HirKind) -> bool {
     match hir {
-        HirKind::Empty => panic!("empty regexes are not supported"),
+        HirKind::Empty | HirKind::Anchor(_) | HirKind::WordBoundary(_) => true,
+        HirKind::Literal(_) | HirKind::Class(_) => false,
+        HirKind::Repetition(rep) => {
+            matches!(rep.kind, RepetitionKind::Range(RepetitionRange::Exactly(0))) || matches_only_empty(rep.hir.kind())
+        }
+        HirKind::Group(group) => matches_only_empty(group.hir.kind()),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => hirs.iter().all(
+            #[no_coverage]
+            |hir| matches_only_empty(hir.kind()),
+        ),
+    }
+}
+
+#[no_coverage]
+fn grammar_from_regex_hir_kind(
+    hir: &HirKind,
+    options: &RegexOptions,
+    position: Position,
+) -> Result<Rc<Grammar>, RegexError> {
+    let grammar = match hir {
+        // the empty concatenation matches the empty string
+        HirKind::Empty => concatenation([]),
         HirKind::Literal(l) => match l {
             Literal::Unicode(l) => literal(*l),
-            Literal::Byte(_) => panic!("non-unicode regexes are not supported"),
+            Literal::Byte(b) => byte(*b),
         },
         HirKind::Class(class) => match class {
             Class::Unicode(class) => {
@@ -28,35 +154,228 @@ pub fn grammar_from_regex_hir_kind(hir: &HirKind) -> Rc<Grammar> {
                         |r| r.start()..=r.end(),
                     )
                     .collect::<Vec<_>>();
+                if ranges.is_empty() {
+                    return Err(RegexError::Unsupported(
+                        "a character class does not match any character".to_string(),
+                    ));
+                }
                 literal_ranges(ranges)
             }
-            Class::Bytes(_) => panic!("non-unicode regexes are not supported"),
+            Class::Bytes(class) => {
+                let ranges = class
+                    .ranges()
+                    .iter()
+                    .map(
+                        #[no_coverage]
+                        |r| r.start()..=r.end(),
+                    )
+                    .collect::<Vec<_>>();
+                if ranges.is_empty() {
+                    return Err(RegexError::Unsupported(
+                        "a byte class does not match any byte".to_string(),
+                    ));
+                }
+                byte_ranges(ranges)
+            }
         },
-        HirKind::Anchor(_) => panic!("anchors are not supported"),
-        HirKind::WordBoundary(_) => panic!("word boundaries are not supported"),
+        HirKind::Anchor(Anchor::StartText) if !position.at_start => {
+            return Err(RegexError::Unsupported(
+                "the start of text anchor can be preceded by a character".to_string(),
+            ));
+        }
+        HirKind::Anchor(Anchor::EndText) if !position.at_end => {
+            return Err(RegexError::Unsupported(
+                "the end of text anchor can be followed by a character".to_string(),
+            ));
+        }
+        HirKind::Anchor(Anchor::StartLine) if !position.at_start => {
+            return Err(RegexError::Unsupported(
+                "the start of line anchor can be preceded by a character".to_string(),
+            ));
+        }
+        HirKind::Anchor(Anchor::EndLine) if !position.at_end => {
+            return Err(RegexError::Unsupported(
+                "the end of line anchor can be followed by a character".to_string(),
+            ));
+        }
+        // the generated strings always match the whole regex, so the remaining anchors are satisfied
+        HirKind::Anchor(_) => concatenation([]),
+        // word boundaries are approximated by ignoring them
+        HirKind::WordBoundary(_) => concatenation([]),
         HirKind::Repetition(rep) => {
-            let range = match rep.kind.clone() {
-                RepetitionKind::ZeroOrOne => 0..=1u32,
-                RepetitionKind::ZeroOrMore => 0..=u32::MAX,
-                RepetitionKind::OneOrMore => 1..=u32::MAX,
+            let (min, max) = match rep.kind.clone() {
+                RepetitionKind::ZeroOrOne => (0, Some(1)),
+                RepetitionKind::ZeroOrMore => (0, None),
+                RepetitionKind::OneOrMore => (1, None),
                 RepetitionKind::Range(range) => match range {
-                    RepetitionRange::Exactly(n) => n..=n,
-                    RepetitionRange::AtLeast(n) => n..=u32::MAX,
-                    RepetitionRange::Bounded(n, m) => n..=m,
+                    RepetitionRange::Exactly(n) => (n as usize, Some(n as usize)),
+                    RepetitionRange::AtLeast(n) => (n as usize, None),
+                    RepetitionRange::Bounded(n, m) => (n as usize, Some(m as usize)),
                 },
             };
-            let range = (*range.start() as usize)..=(*range.end() as usize);
-            let grammar = grammar_from_regex_hir_kind(rep.hir.kind());
-            repetition(grammar, range)
+            // the repeated expression can be preceded or followed by other repetitions of itself
+            let position = if max.map_or(
+                true,
+                #[no_coverage]
+                |max| max > 1,
+            ) && !matches_only_empty(rep.hir.kind())
+            {
+                Position {
+                    at_start: false,
+                    at_end: false,
+                }
+            } else {
+                position
+            };
+            let grammar = grammar_from_regex_hir_kind(rep.hir.kind(), options, position)?;
+            match max.or_else(
+                #[no_coverage]
+                || {
+                    options.max_repetitions.map(
+                        #[no_coverage]
+                        |max| max.max(min),
+                    )
+                },
+            ) {
+                Some(max) => repetition(grammar, min..=max),
+                None => repetition(grammar, min..),
+            }
+        }
+        HirKind::Group(group) => grammar_from_regex_hir_kind(group.hir.kind(), options, position)?,
+        HirKind::Concat(concat) => {
+            let mut grammars = vec![];
+            for (i, hir) in concat.iter().enumerate() {
+                let position = Position {
+                    at_start: position.at_start
+                        && concat[..i].iter().all(
+                            #[no_coverage]
+                            |hir| matches_only_empty(hir.kind()),
+                        ),
+                    at_end: position.at_end
+                        && concat[i + 1..].iter().all(
+                            #[no_coverage]
+                            |hir| matches_only_empty(hir.kind()),
+                        ),
+                };
+                let grammar = grammar_from_regex_hir_kind(hir.kind(), options, position)?;
+                // the anchors and word boundaries do not need to be part of the syntax tree
+                if !matches_only_empty(hir.kind()) {
+                    grammars.push(grammar);
+                }
+            }
+            concatenation(grammars)
+        }
+        HirKind::Alternation(alt) => alternation(
+            alt.iter()
+                .map(
+                    #[no_coverage]
+                    |hir| grammar_from_regex_hir_kind(hir.kind(), options, position),
+                )
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+    };
+    Ok(grammar)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{try_regex, try_regex_with_options, RegexError, RegexOptions};
+    use crate::mutators::grammar::{grammar_based_ast_mutator, GrammarParser};
+    use crate::mutators::testing_utilities::test_mutator;
+    use crate::Mutator;
+
+    #[test]
+    #[no_coverage]
+    fn test_anchors_and_word_boundaries() {
+        for (regex, matching, not_matching) in [
+            ("", vec![""], vec!["a"]),
+            ("^$", vec![""], vec!["a"]),
+            (r"\Aab?\z", vec!["a", "ab"], vec!["", "b"]),
+            (r"(?m)^a\nb$", vec!["a\nb"], vec!["ab"]),
+            (r"\bfoo\b|\Bbar", vec!["foo", "bar"], vec!["foobar"]),
+        ] {
+            let grammar = try_regex(regex).unwrap();
+            let parser = GrammarParser::new(&grammar);
+            for string in matching {
+                assert!(parser.parse(string).is_some(), "{} {}", regex, string);
+            }
+            for string in not_matching {
+                assert!(parser.parse(string).is_none(), "{} {}", regex, string);
+            }
+        }
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_mutators_of_zero_width_regexes() {
+        for regex in ["", "^$", "^abc$", r"\bfoo\b", r"\Aab?\z", r"(?m)^a\nb$", "(a|)b{0}"] {
+            let grammar = try_regex(regex).unwrap();
+            let parser = GrammarParser::new(&grammar);
+            let mutator = grammar_based_ast_mutator(grammar);
+            mutator.initialize();
+            for _ in 0..10 {
+                let (mut ast, _) = mutator.random_arbitrary(100.);
+                assert!(
+                    parser.parse(&ast.to_string()).is_some(),
+                    "{} {}",
+                    regex,
+                    ast.to_string()
+                );
+                let mut cache = mutator.validate_value(&ast).unwrap();
+                let (token, _) = mutator.random_mutate(&mut ast, &mut cache, 100.);
+                assert!(
+                    parser.parse(&ast.to_string()).is_some(),
+                    "{} {}",
+                    regex,
+                    ast.to_string()
+                );
+                mutator.unmutate(&mut ast, &mut cache, token);
+            }
+            test_mutator(mutator, 100., 100., false, true, 10, 10);
+        }
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_max_repetitions() {
+        let options = RegexOptions::default().max_repetitions(2);
+        let grammar = try_regex_with_options("a*b+c{3,}d{1,5}", options).unwrap();
+        let parser = GrammarParser::new(&grammar);
+        assert!(parser.parse("aabbcccddddd").is_some());
+        for string in ["aaabcccd", "bbbcccd", "bccccd", "bcccdddddd"] {
+            assert!(parser.parse(string).is_none(), "{}", string);
+        }
+        let mutator = grammar_based_ast_mutator(grammar);
+        test_mutator(mutator, 1000., 1000., false, true, 50, 50);
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_errors() {
+        let error = try_regex("a{2").unwrap_err();
+        assert!(matches!(error, RegexError::Syntax(_)));
+        assert!(error.to_string().starts_with("invalid regular expression: "));
+        for regex in [
+            r"a\Ab",
+            r"(^a)+",
+            r"(a$|b)c",
+            r"^a|b\zc",
+            r"(?m)a^b",
+            r"(?m)^a$\n^b$",
+            r"(?m)(a^)?b",
+        ] {
+            assert!(matches!(try_regex(regex), Err(RegexError::Unsupported(_))), "{}", regex);
+        }
+        assert_eq!(
+            try_regex(r"a\Ab").unwrap_err().to_string(),
+            "unsupported regular expression: the start of text anchor can be preceded by a character"
+        );
+        assert_eq!(
+            try_regex(r"(?m)a^b").unwrap_err().to_string(),
+            "unsupported regular expression: the start of line anchor can be preceded by a character"
+        );
+        for regex in [r"(^a|\Ab)c", r"(\Aa)?b", r"a(b$)?", r"(?m)^a$", r"(?m)(^a|b)c$"] {
+            assert!(try_regex(regex).is_ok(), "{}", regex);
         }
-        HirKind::Group(group) => grammar_from_regex_hir_kind(group.hir.kind()),
-        HirKind::Concat(concat) => concatenation(concat.iter().map(
-            #[no_coverage]
-            |hir| grammar_from_regex_hir_kind(hir.kind()),
-        )),
-        HirKind::Alternation(alt) => alternation(alt.iter().map(
-            #[no_coverage]
-            |hir| grammar_from_regex_hir_kind(hir.kind()),
-        )),
     }
 }
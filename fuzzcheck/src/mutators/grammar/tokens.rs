@@ -0,0 +1,434 @@
+//! Grammar-based mutators whose terminals are values of a custom token type.
+//!
+//! The grammars of the [parent module](crate::mutators::grammar) generate strings, one character at a time.
+//! For languages with complex lexical rules, it is often more effective to fuzz the parser of the language
+//! above the character level, with a grammar whose terminals are the tokens produced by its lexer.
+//!
+//! A [`TokenGrammar`] is built with the functions of this module, which mirror those of the parent module,
+//! except that each [`terminal`] is backed by a `Mutator<T>`, where `T` is the type of the tokens. The
+//! mutator created by [`token_tree_mutator`] then generates [token trees](TokenTree), which can be
+//! flattened into a `Vec<T>` with [`.with_tokens()`](TokenTreeMutator::with_tokens).
+//!
+//! The simplest way to obtain a mutator for each kind of token is to derive the
+//! single-variant mutators of the token type with
+//! [`make_single_variant_mutator!`](crate::make_single_variant_mutator):
+//!
+//! ```
+//! # #![feature(no_coverage)]
+//! use fuzzcheck::make_single_variant_mutator;
+//! use fuzzcheck::mutators::grammar::tokens::{alternation, concatenation, recurse, recursive, terminal, token_tree_mutator};
+//! use fuzzcheck::mutators::tuples::{Tuple0Mutator, Tuple1Mutator};
+//! use fuzzcheck::DefaultMutator;
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! pub enum Token {
+//!     Number(u8),
+//!     Plus,
+//!     Times,
+//!     OpenParen,
+//!     CloseParen,
+//! }
+//! make_single_variant_mutator! {
+//!     pub enum Token {
+//!         Number(u8),
+//!         Plus,
+//!         Times,
+//!         OpenParen,
+//!         CloseParen,
+//!     }
+//! }
+//!
+//! let number = terminal(TokenSingleVariant::Number(Tuple1Mutator::new(u8::default_mutator())));
+//! let plus = terminal(TokenSingleVariant::Plus(Tuple0Mutator));
+//! let times = terminal(TokenSingleVariant::Times(Tuple0Mutator));
+//! let open = terminal(TokenSingleVariant::OpenParen(Tuple0Mutator));
+//! let close = terminal(TokenSingleVariant::CloseParen(Tuple0Mutator));
+//!
+//! let expr = recursive(|expr| {
+//!     alternation([
+//!         number.clone(),
+//!         concatenation([recurse(expr), alternation([plus.clone(), times.clone()]), recurse(expr)]),
+//!         concatenation([open.clone(), recurse(expr), close.clone()]),
+//!     ])
+//! });
+//! // generates values such as: (Vec<Token>, TokenTree<Token>)
+//! //    [Number(3), Plus, OpenParen, Number(1), Times, Number(7), CloseParen]
+//! let mutator = token_tree_mutator(expr).with_tokens();
+//! ```
+#![allow(clippy::type_complexity)]
+
+extern crate self as fuzzcheck;
+
+use std::ops::{Range, RangeBounds};
+use std::rc::{Rc, Weak};
+
+use fuzzcheck_mutators_derive::make_single_variant_mutator;
+#[cfg(feature = "serde_json_serializer")]
+use serde::{Deserialize, Serialize};
+
+use super::grammar::repetition_range;
+use super::mutators::{GrammarNode, GrammarTreeMutator, SyntaxTree, TreeGrammar};
+use crate::mutators::map::AndMapMutator;
+use crate::mutators::recursive::SizeControl;
+use crate::mutators::tuples::Tuple1Mutator;
+use crate::mutators::MutatorWrapper;
+use crate::Mutator;
+
+/// A grammar whose terminals are generated by mutators of type `M`.
+///
+/// See [the module documentation](crate::mutators::grammar::tokens) for advice on how to create a grammar.
+pub enum TokenGrammar<M> {
+    Terminal(Rc<M>),
+    Alternation(Vec<Rc<TokenGrammar<M>>>),
+    Concatenation(Vec<Rc<TokenGrammar<M>>>),
+    Repetition(Rc<TokenGrammar<M>>, Range<usize>),
+    Recurse(Weak<TokenGrammar<M>>),
+    Recursive(Rc<TokenGrammar<M>>),
+    Rule(String, Rc<TokenGrammar<M>>),
+}
+
+/// Creates a grammar which matches a single token generated by the given mutator.
+///
+/// The same mutator is shared by all the places where the grammar is used.
+#[no_coverage]
+pub fn terminal<M>(mutator: M) -> Rc<TokenGrammar<M>> {
+    Rc::new(TokenGrammar::Terminal(Rc::new(mutator)))
+}
+
+/// Produces a grammar which will choose between the provided grammars.
+#[no_coverage]
+pub fn alternation<M>(gs: impl IntoIterator<Item = Rc<TokenGrammar<M>>>) -> Rc<TokenGrammar<M>> {
+    Rc::new(TokenGrammar::Alternation(gs.into_iter().collect()))
+}
+
+/// Produces a grammar which will concatenate the tokens of all the provided grammars, in order.
+#[no_coverage]
+pub fn concatenation<M>(gs: impl IntoIterator<Item = Rc<TokenGrammar<M>>>) -> Rc<TokenGrammar<M>> {
+    Rc::new(TokenGrammar::Concatenation(gs.into_iter().collect()))
+}
+
+/// Repeats the provided grammar some number of times in the given range.
+#[no_coverage]
+pub fn repetition<M>(g: Rc<TokenGrammar<M>>, range: impl RangeBounds<usize>) -> Rc<TokenGrammar<M>> {
+    Rc::new(TokenGrammar::Repetition(g, repetition_range(range)))
+}
+
+/// Used to indicate a point of recursion to Fuzzcheck. Should be combined with [`recursive`].
+#[no_coverage]
+pub fn recurse<M>(g: &Weak<TokenGrammar<M>>) -> Rc<TokenGrammar<M>> {
+    Rc::new(TokenGrammar::Recurse(g.clone()))
+}
+
+/// Creates a recursive grammar. This function should be combined with [`recurse`] to make recursive calls.
+#[no_coverage]
+pub fn recursive<M>(data_fn: impl Fn(&Weak<TokenGrammar<M>>) -> Rc<TokenGrammar<M>>) -> Rc<TokenGrammar<M>> {
+    Rc::new(TokenGrammar::Recursive(Rc::new_cyclic(
+        #[no_coverage]
+        |g| Rc::try_unwrap(data_fn(g)).ok().unwrap(),
+    )))
+}
+
+/// Gives a name to a grammar rule, which is recorded in a [`TokenTree::Rule`] node.
+#[no_coverage]
+pub fn rule<M>(name: impl Into<String>, grammar: Rc<TokenGrammar<M>>) -> Rc<TokenGrammar<M>> {
+    Rc::new(TokenGrammar::Rule(name.into(), grammar))
+}
+
+/// A syntax tree whose leaves are tokens, generated by a [`TokenTreeMutator`]
+#[cfg_attr(feature = "serde_json_serializer", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TokenTree<T> {
+    /// A single token
+    Token(T),
+    /// The syntax trees of a concatenation, a repetition, or a recursion
+    Sequence(Vec<TokenTree<T>>),
+    /// The syntax tree generated by a named grammar rule
+    Rule {
+        /// The name of the rule
+        name: String,
+        /// The index of the chosen alternative if the rule is an alternation, and 0 otherwise
+        alternative: usize,
+        /// The syntax tree of the chosen alternative
+        tree: Box<TokenTree<T>>,
+    },
+}
+
+impl<T: Clone> TokenTree<T> {
+    /// Appends the tokens of the tree to the given vector, in order
+    #[no_coverage]
+    pub fn generate_tokens_in(&self, tokens: &mut Vec<T>) {
+        match self {
+            TokenTree::Token(t) => tokens.push(t.clone()),
+            TokenTree::Sequence(trees) => {
+                for tree in trees {
+                    tree.generate_tokens_in(tokens);
+                }
+            }
+            TokenTree::Rule { tree, .. } => tree.generate_tokens_in(tokens),
+        }
+    }
+
+    /// Converts the tree to the sequence of its tokens
+    #[no_coverage]
+    pub fn to_tokens(&self) -> Vec<T> {
+        let mut tokens = Vec::new();
+        self.generate_tokens_in(&mut tokens);
+        tokens
+    }
+
+    /// The name of the rule that generated this node and the index of its chosen alternative,
+    /// or `None` if the node was not generated by a named rule
+    #[no_coverage]
+    pub fn rule(&self) -> Option<(&str, usize)> {
+        match self {
+            TokenTree::Rule { name, alternative, .. } => Some((name, *alternative)),
+            _ => None,
+        }
+    }
+
+    /// The direct children of the node
+    #[no_coverage]
+    pub fn children(&self) -> &[TokenTree<T>] {
+        match self {
+            TokenTree::Token(_) => &[],
+            TokenTree::Sequence(trees) => trees,
+            TokenTree::Rule { tree, .. } => std::slice::from_ref(tree),
+        }
+    }
+
+    /// Returns all the nodes generated by the rule with the given name, in depth-first order
+    #[no_coverage]
+    pub fn find_rules<'a>(&'a self, name: &str) -> Vec<&'a TokenTree<T>> {
+        let mut nodes = vec![];
+        self.find_rules_in(name, &mut nodes);
+        nodes
+    }
+
+    #[no_coverage]
+    fn find_rules_in<'a>(&'a self, name: &str, nodes: &mut Vec<&'a TokenTree<T>>) {
+        if matches!(self, TokenTree::Rule { name: n, .. } if n == name) {
+            nodes.push(self);
+        }
+        for child in self.children() {
+            child.find_rules_in(name, nodes);
+        }
+    }
+}
+
+// As for the AST, the sequences have no inherent complexity, such that the
+// complexity of a token tree is the sum of the complexities of its tokens.
+make_single_variant_mutator! {
+    pub enum TokenTree<T> {
+        Token(T),
+        Sequence(Vec<TokenTree<T>>),
+    }
+}
+
+/// The mutator of a terminal, which is shared by all the places where the terminal is used
+pub struct TerminalMutator<M> {
+    mutator: Rc<M>,
+}
+impl<M> MutatorWrapper for TerminalMutator<M> {
+    type Wrapped = M;
+    #[no_coverage]
+    fn wrapped_mutator(&self) -> &Self::Wrapped {
+        &self.mutator
+    }
+}
+
+/// A mutator created by [`token_tree_mutator`]
+///
+/// It only generates token trees matching the given [`TokenGrammar`].
+pub type TokenTreeMutator<T, M> = GrammarTreeMutator<TokenTree<T>, TokenGrammar<M>>;
+
+/// Creates a mutator that only generates token trees matching the given grammar.
+#[no_coverage]
+pub fn token_tree_mutator<T, M>(grammar: Rc<TokenGrammar<M>>) -> TokenTreeMutator<T, M>
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    TokenTreeMutator::from_grammar(&grammar, SizeControl::default())
+}
+
+/// Like [`token_tree_mutator`], but the size of the token trees generated by the
+/// [recursive] rules of the grammar is controlled by the given [`SizeControl`].
+#[no_coverage]
+pub fn token_tree_mutator_with_size_control<T, M>(
+    grammar: Rc<TokenGrammar<M>>,
+    size_control: SizeControl,
+) -> TokenTreeMutator<T, M>
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    TokenTreeMutator::from_grammar(&grammar, size_control)
+}
+
+impl<T, M> GrammarTreeMutator<TokenTree<T>, TokenGrammar<M>>
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    /// Transforms the mutator into a `Mutator<(Vec<T>, TokenTree<T>)>`, where the first element of the
+    /// tuple is the [sequence of tokens](TokenTree::to_tokens) of the tree.
+    #[no_coverage]
+    pub fn with_tokens(self) -> impl Mutator<(Vec<T>, TokenTree<T>)> {
+        AndMapMutator::new(
+            self,
+            #[no_coverage]
+            |tree: &TokenTree<T>, tokens: &mut Vec<T>| {
+                tokens.clear();
+                tree.generate_tokens_in(tokens);
+            },
+            Vec::new(),
+        )
+    }
+}
+
+impl<T: Clone + 'static> SyntaxTree for TokenTree<T> {
+    #[no_coverage]
+    fn wrap_in_rule(name: &str, alternative: usize, tree: Self) -> Self {
+        TokenTree::Rule {
+            name: name.to_string(),
+            alternative,
+            tree: Box::new(tree),
+        }
+    }
+    #[no_coverage]
+    fn rule_subtree(&self, rule_name: &str, rule_alternative: usize) -> Option<&Self> {
+        match self {
+            TokenTree::Rule {
+                name,
+                alternative,
+                tree,
+            } if name == rule_name && *alternative == rule_alternative => Some(tree),
+            _ => None,
+        }
+    }
+    #[no_coverage]
+    fn rule_subtree_mut(&mut self) -> Option<&mut Self> {
+        match self {
+            TokenTree::Rule { tree, .. } => Some(tree),
+            _ => None,
+        }
+    }
+}
+
+impl<T, M> TreeGrammar<TokenTree<T>> for TokenGrammar<M>
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    type NodeMutator<S> = TokenTreeSingleVariant<Tuple1Mutator<TerminalMutator<M>>, Tuple1Mutator<S>>
+    where
+        S: Mutator<Vec<TokenTree<T>>>;
+
+    #[no_coverage]
+    fn node(&self) -> GrammarNode<Self> {
+        match self {
+            TokenGrammar::Terminal(_) => GrammarNode::Terminal,
+            TokenGrammar::Alternation(gs) => GrammarNode::Alternation(gs),
+            TokenGrammar::Concatenation(gs) => GrammarNode::Concatenation(gs),
+            TokenGrammar::Repetition(g, range) => GrammarNode::Repetition(g, range),
+            TokenGrammar::Recurse(g) => GrammarNode::Recurse(g),
+            TokenGrammar::Recursive(g) => GrammarNode::Recursive(g),
+            TokenGrammar::Rule(name, g) => GrammarNode::Rule(name, g),
+        }
+    }
+
+    #[no_coverage]
+    fn terminal_mutator<S>(&self) -> Self::NodeMutator<S>
+    where
+        S: Mutator<Vec<TokenTree<T>>>,
+    {
+        match self {
+            TokenGrammar::Terminal(m) => {
+                TokenTreeSingleVariant::Token(Tuple1Mutator::new(TerminalMutator { mutator: m.clone() }))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[no_coverage]
+    fn sequence_mutator<S>(sequence: S) -> Self::NodeMutator<S>
+    where
+        S: Mutator<Vec<TokenTree<T>>>,
+    {
+        TokenTreeSingleVariant::Sequence(Tuple1Mutator::new(sequence))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate self as fuzzcheck;
+
+    use super::{alternation, concatenation, recurse, recursive, repetition, rule, terminal, token_tree_mutator};
+    use crate::mutators::testing_utilities::test_mutator;
+    use crate::mutators::tuples::{Tuple0Mutator, Tuple1Mutator};
+    use crate::{make_single_variant_mutator, DefaultMutator, Mutator};
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    enum Token {
+        Number(u8),
+        Comma,
+        Open,
+        Close,
+    }
+    make_single_variant_mutator! {
+        enum Token {
+            Number(u8),
+            Comma,
+            Open,
+            Close,
+        }
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_token_tree_mutator() {
+        let number = rule(
+            "number",
+            terminal(TokenSingleVariant::Number(Tuple1Mutator::new(u8::default_mutator()))),
+        );
+        let comma = terminal(TokenSingleVariant::Comma(Tuple0Mutator));
+        let list = recursive(
+            #[no_coverage]
+            |list| {
+                rule(
+                    "list",
+                    concatenation([
+                        terminal(TokenSingleVariant::Open(Tuple0Mutator)),
+                        repetition(
+                            concatenation([alternation([number.clone(), recurse(list)]), comma.clone()]),
+                            0..4,
+                        ),
+                        terminal(TokenSingleVariant::Close(Tuple0Mutator)),
+                    ]),
+                )
+            },
+        );
+        let mutator = token_tree_mutator(list);
+        mutator.initialize();
+        for _ in 0..100 {
+            let (tree, _) = mutator.random_arbitrary(100.);
+            let tokens = tree.to_tokens();
+            let mut depth = 0;
+            for (i, token) in tokens.iter().enumerate() {
+                match token {
+                    Token::Open => depth += 1,
+                    Token::Close => depth -= 1,
+                    Token::Number(_) => assert_eq!(tokens[i + 1], Token::Comma),
+                    Token::Comma => assert!(matches!(tokens[i - 1], Token::Number(_) | Token::Close)),
+                }
+                assert_eq!(depth == 0, i == tokens.len() - 1, "{:?}", tokens);
+            }
+            let numbers = tokens.iter().filter(
+                #[no_coverage]
+                |t| matches!(t, Token::Number(_)),
+            );
+            assert_eq!(numbers.count(), tree.find_rules("number").len());
+        }
+        test_mutator(mutator, 200., 200., false, true, 50, 50);
+    }
+}
@@ -1,5 +1,9 @@
 use std::any::Any;
+use std::sync::OnceLock;
 
+use ahash::AHashSet;
+
+use crate::mutators::dictionary::{self, InterleavedStep};
 use crate::{DefaultMutator, Mutator};
 /*
     These mutators try to achieve multiple things:
@@ -41,6 +45,13 @@ use crate::{DefaultMutator, Mutator};
     9. etc.
 
     You can find more details on how it is done in `uniform_permutation`
+
+    Before any of that, both ordered_arbitrary and ordered_mutate go through a table of “interesting”
+    values, which often sit at the edge of what a program handles correctly: 0, 1, -1, MIN, MAX,
+    MIN+1, MAX-1, small length-like numbers, and powers of two ±1. Then they go through the integers
+    of the global dictionary (see `crate::mutators::dictionary`), alternating with the steps of the
+    uniform permutation so that a large dictionary does not delay it. The uniform permutation skips
+    the values of the table and of the dictionary so that they are not produced twice.
 */
 
 macro_rules! binary_search_arbitrary {
@@ -70,13 +81,80 @@ binary_search_arbitrary!(binary_search_arbitrary_u16, u16);
 binary_search_arbitrary!(binary_search_arbitrary_u32, u32);
 binary_search_arbitrary!(binary_search_arbitrary_u64, u64);
 
+/// The interesting values of an integer type
+pub(crate) struct InterestingValues<T> {
+    /// The interesting values, without duplicates, from most to least interesting
+    pub ordered: Vec<T>,
+    /// The same values, sorted in increasing order
+    pub sorted: Vec<T>,
+}
+
+macro_rules! interesting_values {
+    ($name_function: ident, $name:ty) => {
+        /// Returns the interesting values of the integer type, which are computed the first time
+        /// that this function is called.
+        #[no_coverage]
+        pub(crate) fn $name_function() -> &'static InterestingValues<$name> {
+            static INTERESTING_VALUES: OnceLock<InterestingValues<$name>> = OnceLock::new();
+            INTERESTING_VALUES.get_or_init(
+                #[no_coverage]
+                || {
+                    let mut candidates: Vec<$name> = vec![
+                        0,
+                        1,
+                        (0 as $name).wrapping_sub(1),
+                        <$name>::MAX,
+                        <$name>::MIN,
+                        <$name>::MAX - 1,
+                        <$name>::MIN.wrapping_add(1),
+                    ];
+                    // small numbers, such as lengths, counts, or indices
+                    for x in 2..=16 {
+                        candidates.push(x);
+                    }
+                    for bit in 1..<$name>::BITS {
+                        let power = (1 as $name) << bit;
+                        candidates.extend([power.wrapping_sub(1), power, power.wrapping_add(1)]);
+                        if <$name>::MIN != 0 {
+                            let power = power.wrapping_neg();
+                            candidates.extend([power.wrapping_sub(1), power, power.wrapping_add(1)]);
+                        }
+                    }
+                    let mut seen = AHashSet::with_capacity(candidates.len());
+                    candidates.retain(
+                        #[no_coverage]
+                        |x| seen.insert(*x),
+                    );
+                    let mut sorted = candidates.clone();
+                    sorted.sort_unstable();
+                    InterestingValues {
+                        ordered: candidates,
+                        sorted,
+                    }
+                },
+            )
+        }
+    };
+}
+interesting_values!(interesting_values_u8, u8);
+interesting_values!(interesting_values_u16, u16);
+interesting_values!(interesting_values_u32, u32);
+interesting_values!(interesting_values_u64, u64);
+interesting_values!(interesting_values_usize, usize);
+interesting_values!(interesting_values_i8, i8);
+interesting_values!(interesting_values_i16, i16);
+interesting_values!(interesting_values_i32, i32);
+interesting_values!(interesting_values_i64, i64);
+interesting_values!(interesting_values_isize, isize);
+
 const INITIAL_MUTATION_STEP: u64 = 0;
 
 macro_rules! impl_int_mutator {
-    ($name:ident, $name_unsigned: ident, $name_mutator:ident) => {
+    ($name:ident, $name_unsigned: ident, $name_mutator:ident, $name_interesting_values: ident) => {
         #[derive(Clone)]
         pub struct $name_mutator {
             shuffled_integers: [u8; 256],
+            interesting_values: &'static InterestingValues<$name>,
             rng: fastrand::Rng,
         }
         impl Default for $name_mutator {
@@ -90,12 +168,42 @@ macro_rules! impl_int_mutator {
                 rng.shuffle(&mut shuffled_integers);
                 $name_mutator {
                     shuffled_integers,
+                    interesting_values: $name_interesting_values(),
                     rng,
                 }
             }
         }
 
         impl $name_mutator {
+            #[no_coverage]
+            fn is_interesting(&self, value: $name) -> bool {
+                self.interesting_values.sorted.binary_search(&value).is_ok()
+            }
+            /// Returns true if the value is produced before the uniform permutation
+            #[no_coverage]
+            fn is_interesting_or_in_dictionary(&self, value: $name, dictionary: &[i128]) -> bool {
+                self.is_interesting(value) || dictionary.binary_search(&(value as i128)).is_ok()
+            }
+            /// Returns the `idx`-th value of the dictionary, unless it is not a valid value or it is
+            /// already one of the interesting values
+            #[no_coverage]
+            fn dictionary_value(&self, dictionary: &[i128], idx: usize) -> Option<$name> {
+                <$name>::try_from(dictionary[idx]).ok().filter(
+                    #[no_coverage]
+                    |x| !self.is_interesting(*x),
+                )
+            }
+            /// A random value, taken from the dictionary once in a while
+            #[no_coverage]
+            fn random_value(&self) -> $name {
+                let dictionary = dictionary::integers();
+                if !dictionary.is_empty() && self.rng.usize(..dictionary::DICTIONARY_RATE) == 0 {
+                    if let Ok(value) = <$name>::try_from(dictionary[self.rng.usize(..dictionary.len())]) {
+                        return value;
+                    }
+                }
+                self.rng.$name(..)
+            }
             #[no_coverage]
             fn uniform_permutation(&self, step: u64) -> $name_unsigned {
                 let size = <$name>::BITS as u64;
@@ -213,19 +321,40 @@ macro_rules! impl_int_mutator {
                 if max_cplx < self.min_complexity() {
                     return None;
                 }
-                if *step > <$name_unsigned>::MAX as u64 {
-                    None
-                } else {
-                    let value = self.uniform_permutation(*step) as $name;
-                    *step += 1;
-                    Some((value, <$name>::BITS as f64))
+                let dictionary = dictionary::integers();
+                let nbr_interesting = self.interesting_values.ordered.len() as u64;
+                let nbr_permutation_steps = 1u128 << <$name>::BITS;
+                loop {
+                    let current_step = *step;
+                    *step = step.wrapping_add(1);
+                    let value = if current_step < nbr_interesting {
+                        self.interesting_values.ordered[current_step as usize]
+                    } else {
+                        let dictionary_step = current_step - nbr_interesting;
+                        match dictionary::interleaved_step(dictionary_step, dictionary.len(), nbr_permutation_steps)? {
+                            InterleavedStep::Entry(idx) => {
+                                if let Some(value) = self.dictionary_value(dictionary, idx) {
+                                    return Some((value, <$name>::BITS as f64));
+                                }
+                                continue;
+                            }
+                            InterleavedStep::Other(permutation_step) => {
+                                let value = self.uniform_permutation(permutation_step) as $name;
+                                // the interesting values and the dictionary are produced separately
+                                if self.is_interesting_or_in_dictionary(value, dictionary) {
+                                    continue;
+                                }
+                                value
+                            }
+                        }
+                    };
+                    return Some((value, <$name>::BITS as f64));
                 }
             }
             #[doc(hidden)]
             #[no_coverage]
             fn random_arbitrary(&self, _max_cplx: f64) -> ($name, f64) {
-                let value = self.rng.$name(..);
-                (value, <$name>::BITS as f64)
+                (self.random_value(), <$name>::BITS as f64)
             }
             #[doc(hidden)]
             #[no_coverage]
@@ -240,27 +369,50 @@ macro_rules! impl_int_mutator {
                 if max_cplx < self.min_complexity() {
                     return None;
                 }
-                if *step > 10u64.saturating_add(<$name>::MAX as u64) {
-                    return None;
-                }
-                let token = *value;
-                *value = {
-                    let mut tmp_step = *step;
-                    if tmp_step < 8 {
-                        let nudge = (tmp_step + 2) as $name;
-                        if nudge % 2 == 0 {
-                            value.wrapping_add(nudge / 2)
-                        } else {
-                            value.wrapping_sub(nudge / 2)
-                        }
+                let dictionary = dictionary::integers();
+                let nbr_interesting = self.interesting_values.ordered.len() as u64;
+                // 8 nudges around the current value, followed by the uniform permutation
+                let nbr_other_steps = 8 + (1u128 << <$name>::BITS);
+                loop {
+                    let current_step = *step;
+                    *step = step.wrapping_add(1);
+                    let new_value = if current_step < nbr_interesting {
+                        self.interesting_values.ordered[current_step as usize]
                     } else {
-                        tmp_step -= 7;
-                        self.uniform_permutation(tmp_step) as $name
+                        let dictionary_step = current_step - nbr_interesting;
+                        match dictionary::interleaved_step(dictionary_step, dictionary.len(), nbr_other_steps)? {
+                            InterleavedStep::Entry(idx) => {
+                                if let Some(new_value) = self.dictionary_value(dictionary, idx) {
+                                    new_value
+                                } else {
+                                    continue;
+                                }
+                            }
+                            InterleavedStep::Other(other_step) => {
+                                let new_value = if other_step < 8 {
+                                    let nudge = (other_step + 2) as $name;
+                                    if nudge % 2 == 0 {
+                                        value.wrapping_add(nudge / 2)
+                                    } else {
+                                        value.wrapping_sub(nudge / 2)
+                                    }
+                                } else {
+                                    self.uniform_permutation(other_step - 8) as $name
+                                };
+                                // the interesting values and the dictionary are tried separately
+                                if self.is_interesting_or_in_dictionary(new_value, dictionary) {
+                                    continue;
+                                }
+                                new_value
+                            }
+                        }
+                    };
+                    if new_value == *value {
+                        continue;
                     }
-                };
-                *step = step.wrapping_add(1);
-
-                Some((token, <$name>::BITS as f64))
+                    let token = std::mem::replace(value, new_value);
+                    return Some((token, <$name>::BITS as f64));
+                }
             }
             #[doc(hidden)]
             #[no_coverage]
@@ -270,7 +422,10 @@ macro_rules! impl_int_mutator {
                 _cache: &mut Self::Cache,
                 _max_cplx: f64,
             ) -> (Self::UnmutateToken, f64) {
-                (std::mem::replace(value, self.rng.$name(..)), <$name>::BITS as f64)
+                (
+                    std::mem::replace(value, self.random_value()),
+                    <$name>::BITS as f64,
+                )
             }
             #[doc(hidden)]
             #[no_coverage]
@@ -287,6 +442,12 @@ macro_rules! impl_int_mutator {
                 _visit: &mut dyn FnMut(&'a dyn Any, f64),
             ) {
             }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn mutation_kind(&self, _t: &Self::UnmutateToken) -> &'static str {
+                "mutate_integer"
+            }
         }
 
         impl DefaultMutator for $name {
@@ -299,13 +460,70 @@ macro_rules! impl_int_mutator {
     };
 }
 
-impl_int_mutator!(u8, u8, U8Mutator);
-impl_int_mutator!(u16, u16, U16Mutator);
-impl_int_mutator!(u32, u32, U32Mutator);
-impl_int_mutator!(u64, u64, U64Mutator);
-impl_int_mutator!(usize, usize, USizeMutator);
-impl_int_mutator!(i8, u8, I8Mutator);
-impl_int_mutator!(i16, u16, I16Mutator);
-impl_int_mutator!(i32, u32, I32Mutator);
-impl_int_mutator!(i64, u64, I64Mutator);
-impl_int_mutator!(isize, isize, ISizeMutator);
+impl_int_mutator!(u8, u8, U8Mutator, interesting_values_u8);
+impl_int_mutator!(u16, u16, U16Mutator, interesting_values_u16);
+impl_int_mutator!(u32, u32, U32Mutator, interesting_values_u32);
+impl_int_mutator!(u64, u64, U64Mutator, interesting_values_u64);
+impl_int_mutator!(usize, usize, USizeMutator, interesting_values_usize);
+impl_int_mutator!(i8, u8, I8Mutator, interesting_values_i8);
+impl_int_mutator!(i16, u16, I16Mutator, interesting_values_i16);
+impl_int_mutator!(i32, u32, I32Mutator, interesting_values_i32);
+impl_int_mutator!(i64, u64, I64Mutator, interesting_values_i64);
+impl_int_mutator!(isize, isize, ISizeMutator, interesting_values_isize);
+
+#[cfg(test)]
+mod tests {
+    use super::{interesting_values_i16, I16Mutator, I8Mutator, U8Mutator};
+    use crate::subvalue_provider::EmptySubValueProvider;
+    use crate::Mutator;
+
+    #[test]
+    #[no_coverage]
+    fn test_ordered_arbitrary_starts_with_interesting_values() {
+        let m = U8Mutator::default();
+        let mut step = m.default_arbitrary_step();
+        let mut generated = vec![];
+        while let Some((x, _)) = m.ordered_arbitrary(&mut step, 8.0) {
+            generated.push(x);
+        }
+        assert_eq!(&generated[..4], &[0, 1, 255, 254]);
+        generated.sort_unstable();
+        assert_eq!(generated, (0..=255).collect::<Vec<_>>());
+    }
+
+    #[allow(clippy::let_unit_value)]
+    #[test]
+    #[no_coverage]
+    fn test_ordered_mutate_tries_interesting_values() {
+        let m = I8Mutator::default();
+        let mut x = 42;
+        let mut cache = m.validate_value(&x).unwrap();
+        let mut step = m.default_mutation_step(&x, &cache);
+        let mut generated = vec![];
+        while let Some((t, _)) = m.ordered_mutate(&mut x, &mut cache, &mut step, &EmptySubValueProvider, 8.0) {
+            assert_ne!(x, 42);
+            generated.push(x);
+            m.unmutate(&mut x, &mut cache, t);
+        }
+        for interesting in [0, 1, -1, i8::MIN, i8::MAX, 16, 63, 64, 65, -64] {
+            assert!(generated.contains(&interesting), "{}", interesting);
+        }
+        assert!(generated.contains(&43) && generated.contains(&41));
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_interesting_values_are_computed_once() {
+        let values = interesting_values_i16();
+        // every mutator shares the same table
+        assert!(std::ptr::eq(values, interesting_values_i16()));
+        assert!(std::ptr::eq(values, I16Mutator::default().interesting_values));
+
+        let mut deduplicated = values.ordered.clone();
+        deduplicated.sort_unstable();
+        deduplicated.dedup();
+        assert_eq!(deduplicated, values.sorted);
+        assert_eq!(values.sorted.len(), values.ordered.len());
+        assert_eq!(&values.ordered[..4], &[0, 1, -1, i16::MAX]);
+    }
+}
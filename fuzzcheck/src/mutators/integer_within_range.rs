@@ -1,17 +1,24 @@
 use std::any::Any;
 use std::ops::{Bound, RangeBounds};
 
+use ahash::AHashSet;
+
 use crate::mutators::integer::{
     binary_search_arbitrary_u16, binary_search_arbitrary_u32, binary_search_arbitrary_u64, binary_search_arbitrary_u8,
+    interesting_values_i16, interesting_values_i32, interesting_values_i64, interesting_values_i8,
+    interesting_values_u16, interesting_values_u32, interesting_values_u64, interesting_values_u8,
 };
 use crate::Mutator;
 const INITIAL_MUTATION_STEP: u64 = 0;
 
 macro_rules! impl_int_mutator_constrained {
-    ($name:ident,$name_unsigned:ident, $name_mutator:ident, $name_binary_arbitrary_function: ident) => {
+    ($name:ident,$name_unsigned:ident, $name_mutator:ident, $name_binary_arbitrary_function: ident, $name_interesting_values: ident) => {
         pub struct $name_mutator {
             start_range: $name,
             len_range: $name_unsigned,
+            /// offsets from `start_range` of the interesting values that are within the range
+            interesting_offsets: Vec<$name_unsigned>,
+            sorted_interesting_offsets: Vec<$name_unsigned>,
             search_space_complexity: f64,
             rng: fastrand::Rng,
         }
@@ -43,13 +50,57 @@ macro_rules! impl_int_mutator_constrained {
                     )
                 }
                 let length = end.wrapping_sub(start);
+                // the bounds of the range come first, then the interesting values of the whole type
+                let mut candidates = vec![start, end, start.wrapping_add(1), end.wrapping_sub(1)];
+                candidates.extend($name_interesting_values().ordered.iter().copied());
+                let mut interesting_offsets = vec![];
+                let mut seen = AHashSet::with_capacity(candidates.len());
+                for x in candidates {
+                    if !(start..=end).contains(&x) {
+                        continue;
+                    }
+                    let offset = x.wrapping_sub(start) as $name_unsigned;
+                    if seen.insert(offset) {
+                        interesting_offsets.push(offset);
+                    }
+                }
+                let mut sorted_interesting_offsets = interesting_offsets.clone();
+                sorted_interesting_offsets.sort_unstable();
                 Self {
                     start_range: start,
                     len_range: end.wrapping_sub(start) as $name_unsigned,
+                    interesting_offsets,
+                    sorted_interesting_offsets,
                     search_space_complexity: super::size_to_cplxity(length as usize),
                     rng: fastrand::Rng::default(),
                 }
             }
+            /// Returns the offset from `start_range` corresponding to the given step, or `None` if
+            /// all values within the range were already produced.
+            ///
+            /// The interesting values come first, followed by the rest of the range in binary-search
+            /// order.
+            #[no_coverage]
+            fn offset_for_step(&self, step: &mut u64) -> Option<$name_unsigned> {
+                let nbr_interesting = self.interesting_offsets.len() as u64;
+                if *step < nbr_interesting {
+                    let offset = self.interesting_offsets[*step as usize];
+                    *step += 1;
+                    return Some(offset);
+                }
+                loop {
+                    let binary_search_step = *step - nbr_interesting;
+                    if binary_search_step > self.len_range as u64 {
+                        return None;
+                    }
+                    let offset = $name_binary_arbitrary_function(0, self.len_range, binary_search_step);
+                    *step += 1;
+                    // the interesting values were already produced
+                    if self.sorted_interesting_offsets.binary_search(&offset).is_err() {
+                        return Some(offset);
+                    }
+                }
+            }
         }
 
         impl Mutator<$name> for $name_mutator {
@@ -122,16 +173,11 @@ macro_rules! impl_int_mutator_constrained {
                 if max_cplx < self.min_complexity() {
                     return None;
                 }
-                if *step > self.len_range as u64 {
-                    None
-                } else {
-                    let result = $name_binary_arbitrary_function(0, self.len_range, *step);
-                    *step = step.wrapping_add(1);
-                    Some((
-                        self.start_range.wrapping_add(result as $name),
-                        <$name>::BITS as f64,
-                    ))
-                }
+                let offset = self.offset_for_step(step)?;
+                Some((
+                    self.start_range.wrapping_add(offset as $name),
+                    <$name>::BITS as f64,
+                ))
             }
 
             #[doc(hidden)]
@@ -156,14 +202,8 @@ macro_rules! impl_int_mutator_constrained {
                 if max_cplx < self.min_complexity() {
                     return None;
                 }
-                if *step > self.len_range as u64 {
-                    return None;
-                }
-                let token = *value;
-
-                let result = $name_binary_arbitrary_function(0, self.len_range, *step);
-                *value = self.start_range.wrapping_add(result as $name);
-                *step = step.wrapping_add(1);
+                let offset = self.offset_for_step(step)?;
+                let token = std::mem::replace(value, self.start_range.wrapping_add(offset as $name));
 
                 Some((token, <$name>::BITS as f64))
             }
@@ -201,24 +241,93 @@ macro_rules! impl_int_mutator_constrained {
                 _visit: &mut dyn FnMut(&'a dyn Any, f64),
             ) {
             }
+
+            #[doc(hidden)]
+            #[no_coverage]
+            fn mutation_kind(&self, _t: &Self::UnmutateToken) -> &'static str {
+                "mutate_integer"
+            }
         }
     };
 }
 
-impl_int_mutator_constrained!(u8, u8, U8WithinRangeMutator, binary_search_arbitrary_u8);
-impl_int_mutator_constrained!(u16, u16, U16WithinRangeMutator, binary_search_arbitrary_u16);
-impl_int_mutator_constrained!(u32, u32, U32WithinRangeMutator, binary_search_arbitrary_u32);
-impl_int_mutator_constrained!(u64, u64, U64WithinRangeMutator, binary_search_arbitrary_u64);
-impl_int_mutator_constrained!(i8, u8, I8WithinRangeMutator, binary_search_arbitrary_u8);
-impl_int_mutator_constrained!(i16, u16, I16WithinRangeMutator, binary_search_arbitrary_u16);
-impl_int_mutator_constrained!(i32, u32, I32WithinRangeMutator, binary_search_arbitrary_u32);
-impl_int_mutator_constrained!(i64, u64, I64WithinRangeMutator, binary_search_arbitrary_u64);
+impl_int_mutator_constrained!(
+    u8,
+    u8,
+    U8WithinRangeMutator,
+    binary_search_arbitrary_u8,
+    interesting_values_u8
+);
+impl_int_mutator_constrained!(
+    u16,
+    u16,
+    U16WithinRangeMutator,
+    binary_search_arbitrary_u16,
+    interesting_values_u16
+);
+impl_int_mutator_constrained!(
+    u32,
+    u32,
+    U32WithinRangeMutator,
+    binary_search_arbitrary_u32,
+    interesting_values_u32
+);
+impl_int_mutator_constrained!(
+    u64,
+    u64,
+    U64WithinRangeMutator,
+    binary_search_arbitrary_u64,
+    interesting_values_u64
+);
+impl_int_mutator_constrained!(
+    i8,
+    u8,
+    I8WithinRangeMutator,
+    binary_search_arbitrary_u8,
+    interesting_values_i8
+);
+impl_int_mutator_constrained!(
+    i16,
+    u16,
+    I16WithinRangeMutator,
+    binary_search_arbitrary_u16,
+    interesting_values_i16
+);
+impl_int_mutator_constrained!(
+    i32,
+    u32,
+    I32WithinRangeMutator,
+    binary_search_arbitrary_u32,
+    interesting_values_i32
+);
+impl_int_mutator_constrained!(
+    i64,
+    u64,
+    I64WithinRangeMutator,
+    binary_search_arbitrary_u64,
+    interesting_values_i64
+);
 
 #[cfg(test)]
 mod tests {
     use super::U8WithinRangeMutator;
     use crate::Mutator;
 
+    #[test]
+    #[no_coverage]
+    fn test_interesting_values_come_first() {
+        let m = U8WithinRangeMutator::new(10..=200);
+        let mut step = 0;
+        let mut generated = vec![];
+        while let Some((x, _)) = m.ordered_arbitrary(&mut step, 100.0) {
+            generated.push(x);
+        }
+        assert_eq!(&generated[..4], &[10, 200, 11, 199]);
+        assert!(generated[..m.interesting_offsets.len()].contains(&128));
+        generated.sort_unstable();
+        assert_eq!(generated, (10..=200).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_int_constrained() {
         let m = U8WithinRangeMutator::new(1..2);
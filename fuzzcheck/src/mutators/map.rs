@@ -47,6 +47,26 @@ where
     }
 }
 
+/// The output of a fallible constructor, which is either an `Option` or a `Result`
+///
+/// It is used by the mutators derived for structs with a `#[fuzzcheck(try_constructor = ..)]` attribute.
+#[doc(hidden)]
+pub trait FallibleConstructorOutput<T> {
+    fn into_option(self) -> Option<T>;
+}
+impl<T> FallibleConstructorOutput<T> for Option<T> {
+    #[no_coverage]
+    fn into_option(self) -> Option<T> {
+        self
+    }
+}
+impl<T, E> FallibleConstructorOutput<T> for Result<T, E> {
+    #[no_coverage]
+    fn into_option(self) -> Option<T> {
+        self.ok()
+    }
+}
+
 pub struct Cache<From, M>
 where
     From: Clone + 'static,
@@ -211,6 +231,234 @@ where
         self.mutator
             .visit_subvalues(&cache.from_value, &cache.from_cache, visit)
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        self.mutator.mutation_kind(t)
+    }
+}
+
+/// [`FilterMapMutator`] is a [`MapMutator`] whose `map` function may fail.
+///
+/// The values of type `From` for which `map` returns `None` are skipped, as with a
+/// [`FilterMutator`](crate::mutators::filter::FilterMutator). The `map` function is called
+/// only once for each value generated or mutated by the underlying mutator.
+pub struct FilterMapMutator<From, To, M, Parse, Map, Cplx>
+where
+    From: Clone + 'static,
+    To: Clone + 'static,
+    M: Mutator<From>,
+    Parse: Fn(&To) -> Option<From>,
+    Map: Fn(&From) -> Option<To>,
+    Cplx: Fn(&To, f64) -> f64,
+{
+    pub mutator: M,
+    pub parse: Parse,
+    pub map: Map,
+    pub cplx: Cplx,
+    _phantom: PhantomData<(To, From)>,
+}
+impl<From, To, M, Parse, Map, Cplx> FilterMapMutator<From, To, M, Parse, Map, Cplx>
+where
+    From: Clone + 'static,
+    To: Clone + 'static,
+    M: Mutator<From>,
+    Parse: Fn(&To) -> Option<From>,
+    Map: Fn(&From) -> Option<To>,
+    Cplx: Fn(&To, f64) -> f64,
+{
+    #[no_coverage]
+    pub fn new(mutator: M, parse: Parse, map: Map, cplx: Cplx) -> Self {
+        Self {
+            mutator,
+            parse,
+            map,
+            cplx,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+pub struct FilterMapUnmutateToken<To, U> {
+    inner: U,
+    old_value: To,
+}
+
+impl<From, To, M, Parse, Map, Cplx> Mutator<To> for FilterMapMutator<From, To, M, Parse, Map, Cplx>
+where
+    From: Clone + 'static,
+    To: Clone + 'static,
+    M: Mutator<From>,
+    Parse: Fn(&To) -> Option<From>,
+    Map: Fn(&From) -> Option<To>,
+    Cplx: Fn(&To, f64) -> f64,
+    Self: 'static,
+{
+    #[doc(hidden)]
+    type Cache = Cache<From, M>;
+    #[doc(hidden)]
+    type MutationStep = M::MutationStep;
+    #[doc(hidden)]
+    type ArbitraryStep = M::ArbitraryStep;
+    #[doc(hidden)]
+    type UnmutateToken = FilterMapUnmutateToken<To, M::UnmutateToken>;
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn initialize(&self) {
+        self.mutator.initialize();
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_arbitrary_step(&self) -> Self::ArbitraryStep {
+        self.mutator.default_arbitrary_step()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn is_valid(&self, value: &To) -> bool {
+        if let Some(from_value) = (self.parse)(value) {
+            self.mutator.is_valid(&from_value) && (self.map)(&from_value).is_some()
+        } else {
+            false
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn validate_value(&self, to_value: &To) -> Option<Self::Cache> {
+        let from_value = (self.parse)(to_value)?;
+        let from_cache = self.mutator.validate_value(&from_value)?;
+        (self.map)(&from_value)?;
+        Some(Cache { from_value, from_cache })
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn default_mutation_step(&self, _value: &To, cache: &Self::Cache) -> Self::MutationStep {
+        self.mutator.default_mutation_step(&cache.from_value, &cache.from_cache)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn global_search_space_complexity(&self) -> f64 {
+        self.mutator.global_search_space_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn max_complexity(&self) -> f64 {
+        self.mutator.max_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn min_complexity(&self) -> f64 {
+        self.mutator.min_complexity()
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn complexity(&self, value: &To, cache: &Self::Cache) -> f64 {
+        let orig_cplx = self.mutator.complexity(&cache.from_value, &cache.from_cache);
+        (self.cplx)(value, orig_cplx)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(To, f64)> {
+        loop {
+            let (from_value, orig_cplx) = self.mutator.ordered_arbitrary(step, max_cplx)?;
+            if let Some(to_value) = (self.map)(&from_value) {
+                let cplx = (self.cplx)(&to_value, orig_cplx);
+                return Some((to_value, cplx));
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_arbitrary(&self, max_cplx: f64) -> (To, f64) {
+        loop {
+            let (from_value, orig_cplx) = self.mutator.random_arbitrary(max_cplx);
+            if let Some(to_value) = (self.map)(&from_value) {
+                let cplx = (self.cplx)(&to_value, orig_cplx);
+                return (to_value, cplx);
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn ordered_mutate(
+        &self,
+        value: &mut To,
+        cache: &mut Self::Cache,
+        step: &mut Self::MutationStep,
+        subvalue_provider: &dyn crate::SubValueProvider,
+        max_cplx: f64,
+    ) -> Option<(Self::UnmutateToken, f64)> {
+        loop {
+            let (inner, orig_cplx) = self.mutator.ordered_mutate(
+                &mut cache.from_value,
+                &mut cache.from_cache,
+                step,
+                subvalue_provider,
+                max_cplx,
+            )?;
+            if let Some(new_value) = (self.map)(&cache.from_value) {
+                let old_value = std::mem::replace(value, new_value);
+                return Some((
+                    FilterMapUnmutateToken { inner, old_value },
+                    (self.cplx)(value, orig_cplx),
+                ));
+            }
+            self.mutator
+                .unmutate(&mut cache.from_value, &mut cache.from_cache, inner);
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn random_mutate(&self, value: &mut To, cache: &mut Self::Cache, max_cplx: f64) -> (Self::UnmutateToken, f64) {
+        loop {
+            let (inner, orig_cplx) = self
+                .mutator
+                .random_mutate(&mut cache.from_value, &mut cache.from_cache, max_cplx);
+            if let Some(new_value) = (self.map)(&cache.from_value) {
+                let old_value = std::mem::replace(value, new_value);
+                return (
+                    FilterMapUnmutateToken { inner, old_value },
+                    (self.cplx)(value, orig_cplx),
+                );
+            }
+            self.mutator
+                .unmutate(&mut cache.from_value, &mut cache.from_cache, inner);
+        }
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn unmutate(&self, value: &mut To, cache: &mut Self::Cache, t: Self::UnmutateToken) {
+        self.mutator
+            .unmutate(&mut cache.from_value, &mut cache.from_cache, t.inner);
+        *value = t.old_value;
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn visit_subvalues<'a>(&self, _value: &'a To, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
+        self.mutator
+            .visit_subvalues(&cache.from_value, &cache.from_cache, visit)
+    }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        self.mutator.mutation_kind(&t.inner)
+    }
 }
 
 pub struct AndMapMutator<From, To, M, Map>
@@ -387,4 +635,10 @@ where
         let (_, from_value) = value;
         self.mutator.visit_subvalues(from_value, cache, visit)
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        self.mutator.mutation_kind(t)
+    }
 }
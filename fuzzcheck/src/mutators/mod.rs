@@ -8,11 +8,20 @@ This module provides the following mutators:
     * `char` ([here](crate::mutators::char::CharWithinRangeMutator) and [here](crate::mutators::character_classes::CharacterMutator))
     * integers ([here](crate::mutators::integer) and [here](crate::mutators::integer_within_range))
     * `Vec` ([here](crate::mutators::vector::VecMutator) and [here](crate::mutators::fixed_len_vector::FixedLenVecMutator))
+    * `Vec<u8>` as unstructured binary data, with AFL-style bit-level mutations ([here](crate::mutators::bytes::BytesMutator))
     * `Option` ([here](crate::mutators::option::OptionMutator))
     * `Result` ([here](crate::mutators::result::ResultMutator))
     * `Box` ([here](crate::mutators::boxed))
     * tuples of up to 10 elements ([here](crate::mutators::tuples))
 
+* a [micro-benchmark harness](crate::mutators::benchmark) measuring the throughput and allocations of a mutator
+
+* a [conformance checker](crate::mutators::conformance) verifying that a mutator respects the requirements
+  of the [Mutator] trait
+
+* a [dictionary](crate::mutators::dictionary) of constants harvested from the tested binary, which is used by
+  the integer, `char`, string and `Vec<u8>` mutators
+
 * procedural macros to generate mutators for custom types:
     * [`#[derive(DefaultMutator)]`](fuzzcheck_mutators_derive::DefaultMutator) which works on most structs and enums
     * [`make_mutator! { .. }`](fuzzcheck_mutators_derive::make_mutator) which works like `#[derive(DefaultMutator)]` but is customisable
@@ -31,6 +40,8 @@ This module provides the following mutators:
     * [`Either<M1, M2>`](crate::mutators::either::Either) is the regular `Either` type, which also implements `Mutator<T>` if both `M1` and `M2` implement it too
     * [`RecursiveMutator` and `RecurToMutator`](crate::mutators::recursive) are wrappers allowing mutators to call themselves recursively, which is necessary to mutate recursive types.
     * [`MapMutator<..>`](crate::mutators::map::MapMutator) wraps a mutator and transforms the generated value using a user-provided function.
+    * [`FilterMapMutator<..>`](crate::mutators::map::FilterMapMutator) is a `MapMutator` whose transformation may fail, in which case the generated value is skipped.
+    * [`FixupMutator<M, F>`](crate::mutators::fixup::FixupMutator) wraps a mutator and repairs the generated value using a user-provided function, for example to recompute a checksum.
 */
 
 pub const CROSSOVER_RATE: u8 = 10;
@@ -42,6 +53,7 @@ use std::ops::Range;
 use ahash::AHashMap;
 
 use self::filter::FilterMutator;
+use self::fixup::FixupMutator;
 use self::map::MapMutator;
 use crate::subvalue_provider::Generation;
 use crate::{Mutator, SubValueProvider};
@@ -49,15 +61,20 @@ use crate::{Mutator, SubValueProvider};
 pub mod alternation;
 pub mod arc;
 pub mod array;
+pub mod benchmark;
 pub mod bool;
 pub mod boxed;
+pub mod bytes;
 pub mod char;
 pub mod character_classes;
+pub mod conformance;
 pub mod cow;
+pub mod dictionary;
 pub mod either;
 pub mod enums;
 pub mod filter;
 pub mod fixed_len_vector;
+pub mod fixup;
 #[cfg(feature = "grammar_mutator")]
 #[doc(cfg(feature = "grammar_mutator"))]
 pub mod grammar;
@@ -144,6 +161,15 @@ where
     {
         FilterMutator::new(self, filter)
     }
+    /// Create a mutator which wraps `self` and repairs the values generated by `self`
+    /// using the `fixup` closure, for example to recompute a checksum
+    #[no_coverage]
+    fn fixup<F>(self, fixup: F) -> FixupMutator<Self, F>
+    where
+        F: Fn(&mut T) + 'static,
+    {
+        FixupMutator::new(self, fixup)
+    }
     /// Create a mutator which wraps `self` and transforms the values generated by `self`
     /// using the `map` closure. The second closure, `parse`, should apply the opposite
     /// transformation.
@@ -287,6 +313,12 @@ where
     fn visit_subvalues<'a>(&self, value: &'a T, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
         self.wrapped_mutator().visit_subvalues(value, cache, visit)
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        self.wrapped_mutator().mutation_kind(t)
+    }
 }
 
 impl<M> MutatorWrapper for Box<M> {
@@ -457,45 +489,4 @@ pub mod testing_utilities {
         //     }
         // }
     }
-    // #[no_coverage]
-    // pub fn bench_mutator<T, M>(
-    //     m: M,
-    //     maximum_complexity_arbitrary: f64,
-    //     maximum_complexity_mutate: f64,
-    //     nbr_arbitraries: usize,
-    //     nbr_mutations: usize,
-    // ) where
-    //     M: Mutator<T>,
-    //     T: Clone + Debug + PartialEq + Eq + Hash + 'static,
-    //     M::Cache: Clone,
-    // {
-    //     let mut arbitrary_step = m.default_arbitrary_step();
-    //     for _i in 0..nbr_arbitraries {
-    //         if let Some((mut x, cplx)) = m.ordered_arbitrary(&mut arbitrary_step, maximum_complexity_arbitrary) {
-    //             let mut cache = m.validate_value(&x).unwrap();
-    //             let mut mutation_step = m.default_mutation_step(&x, &cache);
-    //             let other_cplx = m.complexity(&x, &cache);
-    //             for _j in 0..nbr_mutations {
-    //                 if let Some((token, _cplx)) =
-    //                     m.ordered_mutate(&mut x, &mut cache, &mut mutation_step, maximum_complexity_mutate)
-    //                 {
-    //                     m.unmutate(&mut x, &mut cache, token);
-    //                 } else {
-    //                     break;
-    //                 }
-    //             }
-    //         } else {
-    //             break;
-    //         }
-    //     }
-    //     for _i in 0..nbr_arbitraries {
-    //         let (mut x, cplx) = m.random_arbitrary(maximum_complexity_arbitrary);
-    //         let mut cache = m.validate_value(&x).unwrap();
-    //         let other_cplx = m.complexity(&x, &cache);
-    //         for _j in 0..nbr_mutations {
-    //             let (token, _cplx) = m.random_mutate(&mut x, &mut cache, maximum_complexity_mutate);
-    //             m.unmutate(&mut x, &mut cache, token);
-    //         }
-    //     }
-    // }
 }
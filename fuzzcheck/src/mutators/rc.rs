@@ -174,6 +174,15 @@ impl<T: Clone + 'static, M: Mutator<T>> Mutator<Rc<T>> for RcMutator<M> {
     fn visit_subvalues<'a>(&self, value: &'a Rc<T>, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
         self.mutator.visit_subvalues(value, cache, visit)
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        match t {
+            UnmutateToken::Replace(_) => "replace",
+            UnmutateToken::Inner(t) => self.mutator.mutation_kind(t),
+        }
+    }
 }
 
 impl<T> DefaultMutator for Rc<T>
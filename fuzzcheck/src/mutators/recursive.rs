@@ -2,7 +2,8 @@
 //!
 //! There are two main mutators:
 //! 1. [`RecursiveMutator`] is the top-level mutator for the recursive type
-//! 2. [`RecurToMutator`] is the mutator used at points of recursion. It is essentially a weak reference to [`RecursiveMutator`]
+//! 2. [`RecurToMutator`] is the mutator used at points of recursion. It is essentially a weak reference to [`RecursiveMutator`],
+//!    which is handed to the closure building the inner mutator
 //!
 //! In practice, you will want to use the [`make_mutator!`](crate::make_mutator) procedural macro to create recursive mutators.
 //! For example:
@@ -45,8 +46,15 @@
 //! // s_mutator impl Mutator<S>
 //! # }
 //! ```
+//!
+//! By default, the size of the generated values is only limited by the maximum complexity given
+//! to the mutator. Since each point of recursion receives its own share of that complexity, deeply recursive
+//! types tend to produce values that are either very large or trivially small. The
+//! [`.with_size_control(..)`](RecursiveMutator::with_size_control) method can be used to pick a target
+//! complexity and a maximum depth for the generated values instead.
 
 use std::any::Any;
+use std::cell::Cell;
 use std::fmt::Debug;
 use std::rc::{Rc, Weak};
 
@@ -65,6 +73,75 @@ impl<AS> Default for RecursingArbitraryStep<AS> {
     }
 }
 
+/// Options controlling the size of the values generated by a [`RecursiveMutator`]
+///
+/// ```
+/// use fuzzcheck::mutators::recursive::SizeControl;
+///
+/// // the complexity of the generated values will be 50 on average, and
+/// // the points of recursion will not be nested more than 10 times
+/// let size_control = SizeControl::default().target_complexity(50.).max_depth(10);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SizeControl {
+    target_complexity: Option<f64>,
+    max_depth: Option<usize>,
+}
+
+impl SizeControl {
+    /// The average complexity of the values generated by [`random_arbitrary`](crate::Mutator::random_arbitrary).
+    ///
+    /// The complexity of each generated value is drawn from an exponential distribution whose mean is the
+    /// target complexity, and which is truncated to the maximum complexity given to `random_arbitrary`.
+    /// Small values are therefore generated more often than large ones, but large values remain possible.
+    #[must_use]
+    #[no_coverage]
+    pub fn target_complexity(self, target_complexity: f64) -> Self {
+        Self {
+            target_complexity: Some(target_complexity),
+            ..self
+        }
+    }
+
+    /// The maximum number of nested points of recursion in the generated values.
+    ///
+    /// At that depth, the points of recursion generate values of minimal complexity, which
+    /// should not contain any point of recursion themselves.
+    ///
+    /// The depth is counted from the root of the value, including when only a part of it is
+    /// mutated. But mutations are only limited on a best-effort basis: the points of recursion at the
+    /// maximum depth are mutated with a maximum complexity equal to their minimal complexity, which
+    /// the inner mutators may exceed. The subvalues inserted by crossover mutations are not limited.
+    #[must_use]
+    #[no_coverage]
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        Self {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+}
+
+/// The state shared by a [`RecursiveMutator`] and its [`RecurToMutator`]s
+#[derive(Default)]
+struct RecursionState {
+    size_control: Cell<SizeControl>,
+    /// The number of nested points of recursion above the part of the value that is being
+    /// generated or mutated
+    depth: Cell<usize>,
+}
+impl RecursionState {
+    /// The maximum complexity of the values generated or mutated at the given depth
+    #[no_coverage]
+    fn max_cplx_at_depth(&self, depth: usize, max_cplx: f64, min_cplx: f64) -> f64 {
+        match self.size_control.get().max_depth {
+            // the values at the maximum depth must not contain any point of recursion
+            Some(max_depth) if depth >= max_depth => min_cplx,
+            _ => max_cplx,
+        }
+    }
+}
+
 /**
 A wrapper that allows a mutator to call itself recursively.
 
@@ -111,32 +188,108 @@ let s_mutator = RecursiveMutator::new(|mutator| {
 */
 pub struct RecursiveMutator<M> {
     pub mutator: Rc<M>,
+    state: Rc<RecursionState>,
     rng: fastrand::Rng,
 }
 impl<M> RecursiveMutator<M> {
     /// Create a new `RecursiveMutator` using a weak reference to itself.
-    #[no_coverage]
-    pub fn new(data_fn: impl FnOnce(&Weak<M>) -> M) -> Self {
+    ///
+    /// The points of recursion of the inner mutator are created from that reference
+    /// with [`RecurToMutator::from`].
+    #[no_coverage]
+    pub fn new(data_fn: impl FnOnce(&RecurToMutator<M>) -> M) -> Self {
+        let state = Rc::new(RecursionState::default());
+        let mutator = Rc::new_cyclic(
+            #[no_coverage]
+            |reference| {
+                data_fn(&RecurToMutator {
+                    reference: reference.clone(),
+                    state: state.clone(),
+                })
+            },
+        );
         Self {
-            mutator: Rc::new_cyclic(data_fn),
+            mutator,
+            state,
             rng: fastrand::Rng::new(),
         }
     }
+
+    /// Controls the size of the generated values, see [`SizeControl`].
+    #[must_use]
+    #[no_coverage]
+    pub fn with_size_control(self, size_control: SizeControl) -> Self {
+        self.state.size_control.set(size_control);
+        self
+    }
+
+    /// The maximum complexity of the values generated or mutated at the root of the value
+    #[no_coverage]
+    fn root_max_cplx<T>(&self, max_cplx: f64) -> f64
+    where
+        M: Mutator<T>,
+        T: Clone + 'static,
+    {
+        self.state
+            .max_cplx_at_depth(self.state.depth.get(), max_cplx, self.mutator.min_complexity())
+    }
 }
 
 /// A mutator that defers to a weak reference of a
 /// [`RecursiveMutator`](crate::mutators::recursive::RecursiveMutator)
+///
+/// It is created from the reference given to the closure of [`RecursiveMutator::new`],
+/// and shares the [size control](SizeControl) of that mutator.
 pub struct RecurToMutator<M> {
     reference: Weak<M>,
+    state: Rc<RecursionState>,
+}
+impl<M> Clone for RecurToMutator<M> {
+    #[no_coverage]
+    fn clone(&self) -> Self {
+        Self {
+            reference: self.reference.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+impl<M> From<&RecurToMutator<M>> for RecurToMutator<M> {
+    #[no_coverage]
+    fn from(reference: &RecurToMutator<M>) -> Self {
+        reference.clone()
+    }
 }
+/// Creates a point of recursion from a weak reference to the inner mutator of a [`RecursiveMutator`].
+///
+/// It is kept for compatibility with the closures of [`RecursiveMutator::new`] written for a
+/// `&Weak<M>` argument. Unlike the ones created from a `&RecurToMutator<M>`, the points of recursion
+/// created this way do not follow the [size control](SizeControl) of the recursive mutator.
 impl<M> From<&Weak<M>> for RecurToMutator<M> {
     #[no_coverage]
     fn from(reference: &Weak<M>) -> Self {
         Self {
             reference: reference.clone(),
+            state: Rc::new(RecursionState::default()),
         }
     }
 }
+impl<M> RecurToMutator<M> {
+    /// Calls `f` with the maximum complexity of the values generated or mutated at this point of recursion
+    #[no_coverage]
+    fn recurse<T, R>(&self, max_cplx: f64, f: impl FnOnce(&M, f64) -> R) -> R
+    where
+        M: Mutator<T>,
+        T: Clone + 'static,
+    {
+        let mutator = self.reference.upgrade().unwrap();
+        let depth = self.state.depth.get() + 1;
+        let max_cplx = self.state.max_cplx_at_depth(depth, max_cplx, mutator.min_complexity());
+        self.state.depth.set(depth);
+        let result = f(&mutator, max_cplx);
+        self.state.depth.set(depth - 1);
+        result
+    }
+}
 
 impl<T, M> Mutator<T> for RecurToMutator<M>
 where
@@ -216,18 +369,22 @@ where
                 *step = RecursingArbitraryStep::Initialized(inner_step);
                 self.ordered_arbitrary(step, max_cplx)
             }
-            RecursingArbitraryStep::Initialized(inner_step) => self
-                .reference
-                .upgrade()
-                .unwrap()
-                .ordered_arbitrary(inner_step, max_cplx),
+            RecursingArbitraryStep::Initialized(inner_step) => self.recurse(
+                max_cplx,
+                #[no_coverage]
+                |mutator, max_cplx| mutator.ordered_arbitrary(inner_step, max_cplx),
+            ),
         }
     }
 
     #[doc(hidden)]
     #[no_coverage]
     fn random_arbitrary(&self, max_cplx: f64) -> (T, f64) {
-        self.reference.upgrade().unwrap().random_arbitrary(max_cplx)
+        self.recurse(
+            max_cplx,
+            #[no_coverage]
+            |mutator, max_cplx| mutator.random_arbitrary(max_cplx),
+        )
     }
 
     #[doc(hidden)]
@@ -240,16 +397,21 @@ where
         subvalue_provider: &dyn crate::SubValueProvider,
         max_cplx: f64,
     ) -> Option<(Self::UnmutateToken, f64)> {
-        self.reference
-            .upgrade()
-            .unwrap()
-            .ordered_mutate(value, cache, step, subvalue_provider, max_cplx)
+        self.recurse(
+            max_cplx,
+            #[no_coverage]
+            |mutator, max_cplx| mutator.ordered_mutate(value, cache, step, subvalue_provider, max_cplx),
+        )
     }
 
     #[doc(hidden)]
     #[no_coverage]
     fn random_mutate(&self, value: &mut T, cache: &mut Self::Cache, max_cplx: f64) -> (Self::UnmutateToken, f64) {
-        self.reference.upgrade().unwrap().random_mutate(value, cache, max_cplx)
+        self.recurse(
+            max_cplx,
+            #[no_coverage]
+            |mutator, max_cplx| mutator.random_mutate(value, cache, max_cplx),
+        )
     }
 
     #[doc(hidden)]
@@ -263,6 +425,12 @@ where
     fn visit_subvalues<'a>(&self, value: &'a T, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
         self.reference.upgrade().unwrap().visit_subvalues(value, cache, visit)
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        self.reference.upgrade().unwrap().mutation_kind(t)
+    }
 }
 
 #[derive(Clone)]
@@ -381,12 +549,21 @@ where
     #[doc(hidden)]
     #[no_coverage]
     fn ordered_arbitrary(&self, step: &mut Self::ArbitraryStep, max_cplx: f64) -> Option<(T, f64)> {
+        let max_cplx = self.root_max_cplx(max_cplx);
         self.mutator.ordered_arbitrary(step, max_cplx)
     }
 
     #[doc(hidden)]
     #[no_coverage]
     fn random_arbitrary(&self, max_cplx: f64) -> (T, f64) {
+        let max_cplx = self.root_max_cplx(max_cplx);
+        let max_cplx = if let Some(target_cplx) = self.state.size_control.get().target_complexity {
+            // sample from an exponential distribution whose mean is the target complexity
+            let cplx = -target_cplx * (1.0 - self.rng.f64()).ln();
+            cplx.max(self.mutator.min_complexity()).min(max_cplx)
+        } else {
+            max_cplx
+        };
         self.mutator.random_arbitrary(max_cplx)
     }
 
@@ -400,6 +577,7 @@ where
         subvalue_provider: &dyn crate::SubValueProvider,
         max_cplx: f64,
     ) -> Option<(Self::UnmutateToken, f64)> {
+        let max_cplx = self.root_max_cplx(max_cplx);
         if step.idx_sub_self_values < cache.sub_self_values.len() {
             let (subself, cplx) = cache.sub_self_values[step.idx_sub_self_values];
             let subself = unsafe { subself.as_ref() }.unwrap();
@@ -425,6 +603,7 @@ where
     #[doc(hidden)]
     #[no_coverage]
     fn random_mutate(&self, value: &mut T, cache: &mut Self::Cache, max_cplx: f64) -> (Self::UnmutateToken, f64) {
+        let max_cplx = self.root_max_cplx(max_cplx);
         if !cache.sub_self_values.is_empty() && self.rng.usize(..100) == 0 {
             let idx = self.rng.usize(..cache.sub_self_values.len());
             let (subself, cplx) = cache.sub_self_values[idx];
@@ -455,4 +634,13 @@ where
     fn visit_subvalues<'a>(&self, value: &'a T, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
         self.mutator.visit_subvalues(value, &cache.inner, visit)
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        match t {
+            RecursiveMutatorUnmutateToken::Replace(_) => "replace",
+            RecursiveMutatorUnmutateToken::Token(t) => self.mutator.mutation_kind(t),
+        }
+    }
 }
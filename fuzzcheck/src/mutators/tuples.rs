@@ -178,6 +178,11 @@ where
         cache: &'a Self::Cache,
         visit: &mut dyn FnMut(&'a dyn Any, f64),
     );
+
+    #[no_coverage]
+    fn mutation_kind(&self, _token: &Self::UnmutateToken) -> &'static str {
+        "mutate"
+    }
 }
 
 /// A wrapper that transforms a [`TupleMutator`] into a [`Mutator`] of values [with a tuple structure](TupleStructure).
@@ -326,6 +331,12 @@ where
     fn visit_subvalues<'a>(&self, value: &'a T, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64)) {
         self.mutator.visit_subvalues(value.get_ref(), cache, visit)
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        self.mutator.mutation_kind(t)
+    }
 }
 
 pub use tuple0::{Tuple0, Tuple0Mutator};
@@ -681,6 +692,15 @@ mod tuple1 {
             visit(value.0, cplx);
             self.mutator_0.visit_subvalues(value.0, cache, visit);
         }
+
+        #[doc(hidden)]
+        #[no_coverage]
+        fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+            match t {
+                UnmutateTuple1Token::Replace(_) => "crossover_replace_field",
+                UnmutateTuple1Token::Inner(t) => self.mutator_0.mutation_kind(t),
+            }
+        }
     }
     impl<T0> crate::mutators::DefaultMutator for (T0,)
     where
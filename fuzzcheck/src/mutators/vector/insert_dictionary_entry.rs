@@ -0,0 +1,189 @@
+use std::ops::Range;
+
+use super::insert_many_elements::insert_many;
+use super::VecMutator;
+use crate::mutators::dictionary;
+use crate::mutators::mutations::{Mutation, RevertMutation};
+use crate::{Mutator, SubValueProvider};
+
+/// Inserts a sequence of the [global dictionary](crate::mutators::dictionary) into the vector.
+///
+/// It is only available for vectors of `u8` or `char`, and only if the dictionary is not empty.
+/// The ordered mutations try at most [`MAX_NBR_ORDERED_ENTRIES`] entries for each value, starting
+/// from a random one, so that a large dictionary does not crowd out the other mutations.
+pub struct InsertDictionaryEntry;
+
+/// The maximum number of dictionary entries tried by the ordered mutations of a value
+const MAX_NBR_ORDERED_ENTRIES: usize = 256;
+
+#[derive(Clone)]
+pub struct InsertDictionaryEntryRandomStep;
+
+#[derive(Clone)]
+pub struct InsertDictionaryEntryStep {
+    first_entry_idx: usize,
+    nbr_entries_tried: usize,
+}
+
+pub struct ConcreteInsertDictionaryEntry<T> {
+    idx: usize,
+    entry: Vec<T>,
+    added_cplx: f64,
+}
+pub struct RevertInsertDictionaryEntry {
+    idxs: Range<usize>,
+}
+
+impl<T, M> RevertMutation<Vec<T>, VecMutator<T, M>> for RevertInsertDictionaryEntry
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    #[no_coverage]
+    fn revert(
+        self,
+        _mutator: &VecMutator<T, M>,
+        value: &mut Vec<T>,
+        _cache: &mut <VecMutator<T, M> as Mutator<Vec<T>>>::Cache,
+    ) {
+        let _ = value.drain(self.idxs);
+    }
+}
+
+/// Returns the complexity added by the elements of the entry, or `None` if the entry
+/// cannot be inserted in the vector.
+#[no_coverage]
+fn added_complexity<T, M>(
+    mutator: &VecMutator<T, M>,
+    value: &[T],
+    cache: &<VecMutator<T, M> as Mutator<Vec<T>>>::Cache,
+    entry: &[T],
+    max_cplx: f64,
+) -> Option<f64>
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    let new_len = value.len() + entry.len();
+    if entry.is_empty() || new_len > *mutator.len_range.end() {
+        return None;
+    }
+    let mut added_cplx = 0.0;
+    for el in entry {
+        let el_cache = mutator.m.validate_value(el)?;
+        added_cplx += mutator.m.complexity(el, &el_cache);
+    }
+    if mutator.complexity_from_inner(cache.sum_cplx + added_cplx, new_len) > max_cplx {
+        None
+    } else {
+        Some(added_cplx)
+    }
+}
+
+impl<T, M> Mutation<Vec<T>, VecMutator<T, M>> for InsertDictionaryEntry
+where
+    T: Clone + 'static,
+    M: Mutator<T>,
+{
+    type RandomStep = InsertDictionaryEntryRandomStep;
+    type Step = InsertDictionaryEntryStep;
+    type Concrete<'a> = ConcreteInsertDictionaryEntry<T>;
+    type Revert = RevertInsertDictionaryEntry;
+
+    #[no_coverage]
+    fn default_random_step(&self, mutator: &VecMutator<T, M>, value: &Vec<T>) -> Option<Self::RandomStep> {
+        if dictionary::sequences::<T>().is_empty() || value.len() >= *mutator.len_range.end() {
+            None
+        } else {
+            Some(InsertDictionaryEntryRandomStep)
+        }
+    }
+
+    #[no_coverage]
+    fn random<'a>(
+        mutator: &VecMutator<T, M>,
+        value: &Vec<T>,
+        cache: &<VecMutator<T, M> as Mutator<Vec<T>>>::Cache,
+        _random_step: &Self::RandomStep,
+        max_cplx: f64,
+    ) -> Self::Concrete<'a> {
+        let entries = dictionary::sequences::<T>();
+        let entry = &entries[mutator.rng.usize(..entries.len())];
+        let idx = mutator.rng.usize(..=value.len());
+        if let Some(added_cplx) = added_complexity(mutator, value, cache, entry, max_cplx) {
+            ConcreteInsertDictionaryEntry {
+                idx,
+                entry: entry.clone(),
+                added_cplx,
+            }
+        } else {
+            // the entry does not fit, so nothing is inserted
+            ConcreteInsertDictionaryEntry {
+                idx,
+                entry: vec![],
+                added_cplx: 0.0,
+            }
+        }
+    }
+
+    #[no_coverage]
+    fn default_step(
+        &self,
+        mutator: &VecMutator<T, M>,
+        value: &Vec<T>,
+        _cache: &<VecMutator<T, M> as Mutator<Vec<T>>>::Cache,
+    ) -> Option<Self::Step> {
+        if dictionary::sequences::<T>().is_empty() || value.len() >= *mutator.len_range.end() {
+            None
+        } else {
+            Some(InsertDictionaryEntryStep {
+                first_entry_idx: mutator.rng.usize(..dictionary::sequences::<T>().len()),
+                nbr_entries_tried: 0,
+            })
+        }
+    }
+
+    #[no_coverage]
+    fn from_step<'a>(
+        mutator: &VecMutator<T, M>,
+        value: &Vec<T>,
+        cache: &<VecMutator<T, M> as Mutator<Vec<T>>>::Cache,
+        step: &'a mut Self::Step,
+        _subvalue_provider: &dyn SubValueProvider,
+        max_cplx: f64,
+    ) -> Option<Self::Concrete<'a>> {
+        let entries = dictionary::sequences::<T>();
+        while step.nbr_entries_tried < entries.len().min(MAX_NBR_ORDERED_ENTRIES) {
+            let entry = &entries[(step.first_entry_idx + step.nbr_entries_tried) % entries.len()];
+            step.nbr_entries_tried += 1;
+            if let Some(added_cplx) = added_complexity(mutator, value, cache, entry, max_cplx) {
+                return Some(ConcreteInsertDictionaryEntry {
+                    idx: mutator.rng.usize(..=value.len()),
+                    entry: entry.clone(),
+                    added_cplx,
+                });
+            }
+        }
+        None
+    }
+
+    #[no_coverage]
+    fn apply<'a>(
+        mutation: Self::Concrete<'a>,
+        mutator: &VecMutator<T, M>,
+        value: &mut Vec<T>,
+        cache: &mut <VecMutator<T, M> as Mutator<Vec<T>>>::Cache,
+        _subvalue_provider: &dyn SubValueProvider,
+        _max_cplx: f64,
+    ) -> (Self::Revert, f64) {
+        let ConcreteInsertDictionaryEntry { idx, entry, added_cplx } = mutation;
+        let revert = RevertInsertDictionaryEntry {
+            idxs: idx..idx + entry.len(),
+        };
+        insert_many(value, idx, entry.into_iter());
+        (
+            revert,
+            mutator.complexity_from_inner(cache.sum_cplx + added_cplx, value.len()),
+        )
+    }
+}
@@ -12,6 +12,7 @@ pub mod arbitrary;
 pub mod copy_element;
 pub mod crossover_insert_slice;
 pub mod crossover_replace_element;
+pub mod insert_dictionary_entry;
 pub mod insert_element;
 pub mod insert_many_elements;
 pub mod mutate_element;
@@ -351,6 +352,12 @@ where
             }
         }
     }
+
+    #[doc(hidden)]
+    #[no_coverage]
+    fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+        t.kind()
+    }
 }
 
 impl<T, M> VecMutator<T, M>
@@ -1,8 +1,9 @@
 use super::crossover_insert_slice::CrossoverInsertSlice;
 use super::crossover_replace_element::CrossoverReplaceElement;
 use super::{
-    arbitrary, copy_element, crossover_insert_slice, crossover_replace_element, insert_element, insert_many_elements,
-    mutate_element, only_choose_length, remove, remove_and_insert_element, swap_elements, VecMutator,
+    arbitrary, copy_element, crossover_insert_slice, crossover_replace_element, insert_dictionary_entry,
+    insert_element, insert_many_elements, mutate_element, only_choose_length, remove, remove_and_insert_element,
+    swap_elements, VecMutator,
 };
 use crate::mutators::mutations::{Mutation, NoMutation, RevertMutation};
 use crate::mutators::vose_alias::VoseAlias;
@@ -14,7 +15,7 @@ pub struct WeightedMutation<M> {
     ordered_weight: f64,
 }
 macro_rules! impl_vec_mutation {
-    ($(($i:ident,$t:ty)),*) => {
+    ($(($i:ident,$t:ty,$kind:literal)),*) => {
         pub enum InnerVectorMutation {
             $($i($t),)*
         }
@@ -128,6 +129,22 @@ macro_rules! impl_vec_mutation {
             }
         }
 
+        impl<T, M> RevertVectorMutation<T, M>
+        where
+            T: Clone + 'static,
+            M: Mutator<T>,
+        {
+            /// A short description of the mutation
+            #[no_coverage]
+            pub fn kind(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$i(_) => $kind
+                    ),*
+                }
+            }
+        }
+
         impl<T, M> RevertMutation<Vec<T>, VecMutator<T, M>> for RevertVectorMutation<T, M>
         where
             T: Clone + 'static,
@@ -306,18 +323,19 @@ macro_rules! impl_vec_mutation {
 }
 
 impl_vec_mutation! {
-    (NoMutation, NoMutation),
-    (CopyElement, copy_element::CopyElement),
-    (Remove, remove::Remove),
-    (MutateElement, mutate_element::MutateElement),
-    (InsertElement, insert_element::InsertElement),
-    (SwapElements, swap_elements::SwapElements),
-    (InsertManyElements, insert_many_elements::InsertManyElements),
-    (RemoveAndInsertElement, remove_and_insert_element::RemoveAndInsertElement),
-    (OnlyChooseLength, only_choose_length::OnlyChooseLength),
-    (Arbitrary, arbitrary::Arbitrary),
-    (CrossoverReplaceElement, crossover_replace_element::CrossoverReplaceElement),
-    (CrossoverInsertSlice, crossover_insert_slice::CrossoverInsertSlice)
+    (NoMutation, NoMutation, "no_mutation"),
+    (CopyElement, copy_element::CopyElement, "copy_element"),
+    (Remove, remove::Remove, "remove"),
+    (MutateElement, mutate_element::MutateElement, "mutate_element"),
+    (InsertElement, insert_element::InsertElement, "insert_element"),
+    (SwapElements, swap_elements::SwapElements, "swap_elements"),
+    (InsertManyElements, insert_many_elements::InsertManyElements, "insert_many_elements"),
+    (RemoveAndInsertElement, remove_and_insert_element::RemoveAndInsertElement, "remove_and_insert_element"),
+    (OnlyChooseLength, only_choose_length::OnlyChooseLength, "only_choose_length"),
+    (Arbitrary, arbitrary::Arbitrary, "arbitrary"),
+    (CrossoverReplaceElement, crossover_replace_element::CrossoverReplaceElement, "crossover_replace_element"),
+    (CrossoverInsertSlice, crossover_insert_slice::CrossoverInsertSlice, "crossover_insert_slice"),
+    (InsertDictionaryEntry, insert_dictionary_entry::InsertDictionaryEntry, "insert_dictionary_entry")
 }
 
 impl<'a, T, M> std::fmt::Debug for ConcreteVectorMutation<'a, T, M>
@@ -363,6 +381,9 @@ where
             ConcreteVectorMutation::CrossoverInsertSlice(_) => {
                 write!(f, "CrossoverInsertSlice")
             }
+            ConcreteVectorMutation::InsertDictionaryEntry(_) => {
+                write!(f, "InsertDictionaryEntry")
+            }
         }
     }
 }
@@ -475,6 +496,13 @@ impl Default for VectorMutation {
                     random_weight: 0.,
                     ordered_weight: 50.,
                 },
+                WeightedMutation {
+                    mutation: InnerVectorMutation::InsertDictionaryEntry(
+                        insert_dictionary_entry::InsertDictionaryEntry,
+                    ),
+                    random_weight: 20.,
+                    ordered_weight: 50.,
+                },
                 // WeightedMutation {
                 //     mutation: InnerVectorMutation::InsertManyElements(insert_many_elements::InsertManyElements {
                 //         nbr_added_elements: 4,
@@ -223,11 +223,11 @@ where
 }
 
 #[derive(Default)]
-struct AllocationsStats {
+pub(crate) struct AllocationsStats {
     /// Total number of allocated blocks. Does not decrease after a deallocation.
-    total_blocks: u64,
+    pub total_blocks: u64,
     /// Total number of allocated bytes. Does not decrease after a deallocation.
-    total_bytes: u64,
+    pub total_bytes: u64,
     // /// Number of currently allocated blocks. Decreases after a deallocation.
     // curr_blocks: usize,
     // /// Number of currently allocated bytes. Decreases after a deallocation.
@@ -235,7 +235,7 @@ struct AllocationsStats {
 }
 
 #[no_coverage]
-fn get_allocation_stats() -> AllocationsStats {
+pub(crate) fn get_allocation_stats() -> AllocationsStats {
     unsafe {
         AllocationsStats {
             total_blocks: ALLOC_STATS.total_blocks.load(Ordering::SeqCst),
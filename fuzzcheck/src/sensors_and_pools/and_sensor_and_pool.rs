@@ -147,6 +147,12 @@ where
         }
     }
 
+    #[no_coverage]
+    fn set_parent(&mut self, parent: Option<PoolStorageIndex>) {
+        self.p1.set_parent(parent);
+        self.p2.set_parent(parent);
+    }
+
     fn weight(&self) -> f64 {
         self.p1_weight() + self.p2_weight()
     }
@@ -392,7 +398,7 @@ impl SensorAndPool for AndSensorAndPool {
     #[no_coverage]
     fn get_random_index(&mut self) -> Option<PoolStorageIndex> {
         let sum_weight = self.sap1_weight + self.sap2_weight;
-        if self.rng.f64() <= sum_weight {
+        if self.rng.f64() * sum_weight <= self.sap1_weight {
             if let Some(idx) = self.sap1.get_random_index() {
                 self.sap1_number_times_chosen_since_last_progress += 1;
                 Some(idx)
@@ -408,4 +414,66 @@ impl SensorAndPool for AndSensorAndPool {
             self.sap1.get_random_index()
         }
     }
+
+    #[no_coverage]
+    fn set_parent(&mut self, parent: Option<PoolStorageIndex>) {
+        self.sap1.set_parent(parent);
+        self.sap2.set_parent(parent);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::AndSensorAndPool;
+    use crate::sensors_and_pools::stats::EmptyStats;
+    use crate::traits::{CorpusDelta, SaveToStatsFolder, SensorAndPool, Stats};
+    use crate::PoolStorageIndex;
+
+    /// A sensor and pool whose random index is always the same
+    struct ConstantIndex(PoolStorageIndex);
+    impl SaveToStatsFolder for ConstantIndex {
+        #[no_coverage]
+        fn save_to_stats_folder(&self) -> Vec<(PathBuf, Vec<u8>)> {
+            vec![]
+        }
+    }
+    impl SensorAndPool for ConstantIndex {
+        #[no_coverage]
+        fn stats(&self) -> Box<dyn Stats> {
+            Box::new(EmptyStats)
+        }
+        #[no_coverage]
+        fn start_recording(&mut self) {}
+        #[no_coverage]
+        fn stop_recording(&mut self) {}
+        #[no_coverage]
+        fn process(&mut self, _input_id: PoolStorageIndex, _cplx: f64) -> Vec<CorpusDelta> {
+            vec![]
+        }
+        #[no_coverage]
+        fn get_random_index(&mut self) -> Option<PoolStorageIndex> {
+            Some(self.0)
+        }
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_and_sensor_and_pool_weights() {
+        let mut sap = AndSensorAndPool::new(
+            Box::new(ConstantIndex(PoolStorageIndex::mock(1))),
+            Box::new(ConstantIndex(PoolStorageIndex::mock(2))),
+            1.0,
+            3.0,
+        );
+        let nbr_first = (0..4000)
+            .filter(
+                #[no_coverage]
+                |_| sap.get_random_index() == Some(PoolStorageIndex::mock(1)),
+            )
+            .count();
+        // the first sensor and pool is chosen a quarter of the time
+        assert!((800..1200).contains(&nbr_first), "{nbr_first}");
+    }
 }
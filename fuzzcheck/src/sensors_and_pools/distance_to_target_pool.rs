@@ -0,0 +1,277 @@
+use std::fmt::{Debug, Display};
+use std::path::Path;
+
+use ahash::{AHashMap, AHashSet};
+use nu_ansi_term::Color;
+
+use super::ranked_inputs::{RankedInput, RankedInputs};
+use crate::data_structures::SlabKey;
+use crate::traits::{CorpusDelta, Pool, SaveToStatsFolder, Stats};
+use crate::{CSVField, CompatibleWithObservations, PoolStorageIndex, ToCSV};
+
+/// The statistics of a [DistanceToTargetPool]
+#[derive(Clone)]
+pub struct DistanceToTargetPoolStats {
+    name: String,
+    size: usize,
+    closest_distance: Option<usize>,
+}
+
+impl Display for DistanceToTargetPoolStats {
+    #[no_coverage]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.closest_distance {
+            Some(0) => write!(
+                f,
+                "{}",
+                Color::LightGreen.paint(format!("{}({} reached)", self.name, self.size))
+            ),
+            Some(distance) => write!(
+                f,
+                "{}",
+                Color::LightCyan.paint(format!("{}({} dist: {})", self.name, self.size, distance))
+            ),
+            None => write!(f, "{}", Color::LightCyan.paint(format!("{}({})", self.name, self.size))),
+        }
+    }
+}
+
+impl ToCSV for DistanceToTargetPoolStats {
+    #[no_coverage]
+    fn csv_headers(&self) -> Vec<CSVField> {
+        vec![
+            CSVField::String(format!("{}-count", self.name)),
+            CSVField::String(format!("{}-distance", self.name)),
+            CSVField::String(format!("{}-reached", self.name)),
+        ]
+    }
+    #[no_coverage]
+    fn to_csv_record(&self) -> Vec<CSVField> {
+        vec![
+            CSVField::Integer(self.size as isize),
+            CSVField::Integer(self.closest_distance.map_or(
+                -1,
+                #[no_coverage]
+                |d| d as isize,
+            )),
+            CSVField::Integer((self.closest_distance == Some(0)) as isize),
+        ]
+    }
+}
+impl Stats for DistanceToTargetPoolStats {}
+
+#[derive(Debug)]
+struct Input {
+    best_for_counters: AHashSet<usize>,
+    cplx: f64,
+}
+
+/// A pool that directs the fuzzer toward a target region of code.
+///
+/// Each counter of the sensor is given a distance to the target, where a distance of 0 means that the
+/// counter covers the target. The pool keeps the simplest test case activating each counter that has a
+/// distance, and prioritises the test cases that come closest to the target. Its statistics show the
+/// closest distance reached so far, which is 0 once the target is reached.
+///
+/// The distances of the counters of a [`CodeCoverageSensor`](crate::sensors_and_pools::CodeCoverageSensor)
+/// to a [`CoverageTarget`](crate::CoverageTarget) are given by
+/// [`sensor.distances_to_target(..)`](crate::sensors_and_pools::CodeCoverageSensor::distances_to_target).
+/// Only the counters located in the same files as the target have a distance, since the distances are
+/// measured in lines of source code rather than along the call graph. So the pool only guides the
+/// fuzzer once it reaches a file containing the target.
+///
+/// It is [compatible with](crate::CompatibleWithObservations) the following sensors:
+/// * [`CodeCoverageSensor`](crate::sensors_and_pools::CodeCoverageSensor)
+/// * any other sensor whose [observations](crate::Sensor::Observations) are given by an iterator of `(usize, u64)`
+pub struct DistanceToTargetPool {
+    name: String,
+    distances: AHashMap<usize, usize>,
+    inputs: RankedInputs<Input>,
+    best_input_for_counter: AHashMap<usize, SlabKey<RankedInput<Input>>>,
+    stats: DistanceToTargetPoolStats,
+}
+impl Debug for DistanceToTargetPool {
+    #[no_coverage]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DistanceToTargetPool")
+            .field("inputs", &self.inputs)
+            .field("best_input_for_counter", &self.best_input_for_counter)
+            .finish()
+    }
+}
+
+impl DistanceToTargetPool {
+    /// Create a new pool from the distance to the target of each counter.
+    ///
+    /// The counters that are not in `distances` are ignored by the pool.
+    #[no_coverage]
+    pub fn new(name: &str, distances: AHashMap<usize, usize>) -> Self {
+        Self {
+            name: name.to_string(),
+            distances,
+            inputs: RankedInputs::new(),
+            best_input_for_counter: AHashMap::new(),
+            stats: DistanceToTargetPoolStats {
+                name: name.to_string(),
+                size: 0,
+                closest_distance: None,
+            },
+        }
+    }
+
+    /// The score of a test case that is the simplest one to activate the given counters
+    #[no_coverage]
+    fn score(&self, best_for_counters: &AHashSet<usize>) -> f64 {
+        best_for_counters
+            .iter()
+            .map(
+                #[no_coverage]
+                |counter| 1.0 / (1.0 + self.distances[counter] as f64),
+            )
+            .fold(0.0, f64::max)
+    }
+}
+
+impl Pool for DistanceToTargetPool {
+    type Stats = DistanceToTargetPoolStats;
+
+    #[no_coverage]
+    fn stats(&self) -> Self::Stats {
+        self.stats.clone()
+    }
+
+    #[no_coverage]
+    fn get_random_index(&mut self) -> Option<PoolStorageIndex> {
+        self.inputs.get_random_index()
+    }
+}
+
+impl SaveToStatsFolder for DistanceToTargetPool {
+    #[no_coverage]
+    fn save_to_stats_folder(&self) -> Vec<(std::path::PathBuf, Vec<u8>)> {
+        vec![]
+    }
+}
+
+impl DistanceToTargetPool {
+    #[no_coverage]
+    fn update_stats(&mut self) {
+        self.inputs.update_ranks();
+
+        self.stats.size = self.inputs.len();
+        self.stats.closest_distance = self
+            .best_input_for_counter
+            .keys()
+            .map(
+                #[no_coverage]
+                |counter| self.distances[counter],
+            )
+            .min();
+    }
+}
+
+impl<O> CompatibleWithObservations<O> for DistanceToTargetPool
+where
+    for<'a> &'a O: IntoIterator<Item = &'a (usize, u64)>,
+{
+    #[no_coverage]
+    fn process(&mut self, input_id: PoolStorageIndex, observations: &O, complexity: f64) -> Vec<CorpusDelta> {
+        let mut best_for_counters = AHashSet::new();
+        for &(index, count) in observations.into_iter() {
+            if count == 0 || !self.distances.contains_key(&index) {
+                continue;
+            }
+            match self.best_input_for_counter.get(&index) {
+                Some(&key) if self.inputs[key].data.cplx <= complexity => {}
+                _ => {
+                    best_for_counters.insert(index);
+                }
+            }
+        }
+        if best_for_counters.is_empty() {
+            return vec![];
+        }
+
+        let input = Input {
+            best_for_counters: best_for_counters.clone(),
+            cplx: complexity,
+        };
+        let input_key = self.inputs.insert(input_id, self.score(&best_for_counters), input);
+
+        let mut removed_keys = vec![];
+        for counter in best_for_counters {
+            if let Some(previous_best_key) = self.best_input_for_counter.insert(counter, input_key) {
+                let previous_best = &mut self.inputs[previous_best_key];
+                let was_present_in_set = previous_best.data.best_for_counters.remove(&counter);
+                assert!(was_present_in_set);
+                if previous_best.data.best_for_counters.is_empty() {
+                    removed_keys.push(previous_best_key);
+                } else {
+                    let score = self.score(&self.inputs[previous_best_key].data.best_for_counters);
+                    self.inputs[previous_best_key].score = score;
+                }
+            }
+        }
+        let removed_idxs = removed_keys
+            .into_iter()
+            .map(
+                #[no_coverage]
+                |key| self.inputs.remove(key),
+            )
+            .collect();
+
+        self.update_stats();
+
+        vec![CorpusDelta {
+            path: Path::new(&self.name).to_path_buf(),
+            add: true,
+            remove: removed_idxs,
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ahash::AHashMap;
+
+    use super::DistanceToTargetPool;
+    use crate::traits::{CompatibleWithObservations, Pool};
+    use crate::{CSVField, PoolStorageIndex, ToCSV};
+
+    #[test]
+    #[no_coverage]
+    fn test_distance_to_target_pool() {
+        let distances = AHashMap::from_iter([(0, 0), (1, 2), (2, 9)]);
+        let mut pool = DistanceToTargetPool::new("target", distances);
+
+        // counter 3 is too far from the target to be considered
+        assert!(pool.process(PoolStorageIndex::mock(0), &[(3, 1)], 1.0).is_empty());
+        assert_eq!(pool.stats().closest_distance, None);
+
+        let deltas = pool.process(PoolStorageIndex::mock(1), &[(2, 1), (3, 1)], 2.0);
+        assert!(deltas[0].add && deltas[0].remove.is_empty());
+        assert_eq!(pool.stats().closest_distance, Some(9));
+
+        let deltas = pool.process(PoolStorageIndex::mock(2), &[(1, 1), (2, 1)], 3.0);
+        assert!(deltas[0].add && deltas[0].remove.is_empty());
+        assert_eq!(pool.stats().closest_distance, Some(2));
+        assert_eq!(pool.stats().size, 2);
+
+        // the closer test case is chosen more often
+        let mut nbr_closest_chosen = 0;
+        for _ in 0..1000 {
+            if pool.get_random_index().unwrap() == PoolStorageIndex::mock(2) {
+                nbr_closest_chosen += 1;
+            }
+        }
+        assert!(nbr_closest_chosen > 500);
+
+        // the target is reached by a simpler test case, which replaces the other ones
+        let deltas = pool.process(PoolStorageIndex::mock(3), &[(0, 1), (1, 1), (2, 1)], 1.0);
+        assert_eq!(deltas[0].remove.len(), 2);
+        assert_eq!(pool.stats().closest_distance, Some(0));
+        assert_eq!(pool.stats().size, 1);
+        assert!(matches!(pool.stats().to_csv_record()[2], CSVField::Integer(1)));
+        assert_eq!(pool.get_random_index(), Some(PoolStorageIndex::mock(3)));
+    }
+}
@@ -0,0 +1,529 @@
+//! A sensor and pool measuring which parts of a grammar are exercised by the syntax trees of the test cases.
+//!
+//! The [`GrammarCoverageSensor`] assigns a counter to each feature of a [`Grammar`]:
+//!
+//! * each named [rule](crate::mutators::grammar::rule)
+//! * each branch of an [alternation](crate::mutators::grammar::alternation)
+//! * the number of times a [repetition](crate::mutators::grammar::repetition) repeats its rule,
+//! grouped in buckets: 0, 1, 2-3, 4-7, …, 128 or more
+//! * the depth of a [recursion](crate::mutators::grammar::recursive), grouped in the same buckets
+//!
+//! The sensor cannot see the test cases by itself. Instead, the syntax tree must be given to a
+//! [`GrammarCoverageRecorder`] inside the test function. The [`GrammarCoveragePool`] then keeps the
+//! simplest test case activating each counter, independently of the code coverage.
+//!
+//! ```no_run
+//! use fuzzcheck::builder::basic_sensor_and_pool;
+//! use fuzzcheck::mutators::grammar::{alternation, grammar_based_ast_mutator, literal, repetition, rule, AST};
+//! use fuzzcheck::sensors_and_pools::{AndSensor, DifferentObservations, GrammarCoveragePool, GrammarCoverageSensor};
+//! use fuzzcheck::{Arguments, GrammarSerializer, PoolExt};
+//!
+//! let grammar = repetition(rule("bit", alternation([literal('0'), literal('1')])), 1..10);
+//!
+//! let grammar_sensor = GrammarCoverageSensor::new(&grammar);
+//! let recorder = grammar_sensor.recorder();
+//! let grammar_pool = GrammarCoveragePool::new("grammar_cov", &grammar_sensor);
+//!
+//! // combine the grammar coverage with the code coverage
+//! let (code_sensor, code_pool) = basic_sensor_and_pool().finish();
+//! let sensor = AndSensor(code_sensor, grammar_sensor);
+//! let pool = code_pool.and(grammar_pool, None, DifferentObservations);
+//!
+//! let result = fuzzcheck::fuzz_test(move |ast: &AST| {
+//!         recorder.record(ast);
+//!         // the actual test goes here
+//!         ast.to_string().len() < 100
+//!     })
+//!     .mutator(grammar_based_ast_mutator(grammar.clone()))
+//!     .serializer(GrammarSerializer::<AST>::new(&grammar, "txt"))
+//!     .sensor_and_pool(sensor, pool)
+//!     .arguments(Arguments::for_internal_documentation_test())
+//!     .launch();
+//! ```
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use ahash::AHashMap;
+
+use super::stats::UniqueCoveragePoolStats;
+use super::SimplestToActivateCounterPool;
+use crate::mutators::grammar::{Grammar, AST};
+use crate::traits::{CorpusDelta, Pool, SaveToStatsFolder, Sensor};
+use crate::{CompatibleWithObservations, PoolStorageIndex};
+
+/// The bucket of repetition counts and recursion depths that are at least `2^(MAX_BUCKET - 1)`
+const MAX_BUCKET: usize = 8;
+
+#[no_coverage]
+fn bucket(n: usize) -> usize {
+    // 0 => 0, 1 => 1, 2..=3 => 2, 4..=7 => 3, etc.
+    std::cmp::min((usize::BITS - n.leading_zeros()) as usize, MAX_BUCKET)
+}
+
+#[no_coverage]
+fn describe_bucket(bucket: usize) -> String {
+    match bucket {
+        0 => "0".to_string(),
+        1 => "1".to_string(),
+        MAX_BUCKET => format!("{} or more", 1 << (MAX_BUCKET - 1)),
+        _ => format!("{} to {}", 1 << (bucket - 1), (1 << bucket) - 1),
+    }
+}
+
+/// The counters of a grammar, and their human-readable descriptions
+struct GrammarCoverageMap {
+    grammar: Rc<Grammar>,
+    /// The first counter of each rule, alternation, and repetition, along with the bucket that it represents
+    counters: AHashMap<*const Grammar, (usize, usize)>,
+    /// The first counter of each recursion, keyed by the inner grammar of [`Grammar::Recursive`]
+    recursions: AHashMap<*const Grammar, usize>,
+    descriptions: Vec<String>,
+    nbr_alternations: usize,
+    nbr_repetitions: usize,
+    nbr_recursions: usize,
+}
+
+impl GrammarCoverageMap {
+    #[no_coverage]
+    fn new(grammar: &Rc<Grammar>) -> Self {
+        let mut map = Self {
+            grammar: grammar.clone(),
+            counters: AHashMap::new(),
+            recursions: AHashMap::new(),
+            descriptions: vec![],
+            nbr_alternations: 0,
+            nbr_repetitions: 0,
+            nbr_recursions: 0,
+        };
+        map.add_counters(grammar, None, None);
+        map
+    }
+
+    /// Assigns counters to the grammar and its children.
+    ///
+    /// `rule` is the name of the enclosing rule, and `body_of_rule` whether the grammar is
+    /// directly the body of that rule.
+    #[no_coverage]
+    fn add_counters(&mut self, grammar: &Rc<Grammar>, rule: Option<&str>, body_of_rule: Option<&str>) {
+        let key = Rc::as_ptr(grammar);
+        // grammars can be shared by multiple rules, but are only given counters once
+        if self.counters.contains_key(&key) {
+            return;
+        }
+        let in_rule = rule.map_or(
+            String::new(),
+            #[no_coverage]
+            |rule| format!(" in rule \"{}\"", rule),
+        );
+        match grammar.as_ref() {
+            Grammar::Literal(_) | Grammar::ByteLiteral(_) | Grammar::Recurse(_) => {}
+            Grammar::Alternation(gs) => {
+                let name = if let Some(rule) = body_of_rule {
+                    format!("rule \"{}\"", rule)
+                } else {
+                    self.nbr_alternations += 1;
+                    format!("alternation #{}{}", self.nbr_alternations - 1, in_rule)
+                };
+                self.counters.insert(key, (self.descriptions.len(), 0));
+                for i in 0..gs.len() {
+                    self.descriptions.push(format!("{}, alternative {}", name, i));
+                }
+                for g in gs {
+                    self.add_counters(g, rule, None);
+                }
+            }
+            Grammar::Concatenation(gs) => {
+                for g in gs {
+                    self.add_counters(g, rule, None);
+                }
+            }
+            Grammar::Repetition(g, range) => {
+                let name = format!("repetition #{}{}", self.nbr_repetitions, in_rule);
+                self.nbr_repetitions += 1;
+                let (min_bucket, max_bucket) = (bucket(range.start), bucket(range.end.saturating_sub(1)));
+                self.counters.insert(key, (self.descriptions.len(), min_bucket));
+                for b in min_bucket..=max_bucket {
+                    self.descriptions
+                        .push(format!("{}, repeated {} times", name, describe_bucket(b)));
+                }
+                self.add_counters(g, rule, None);
+            }
+            Grammar::Recursive(g) => {
+                if self.recursions.contains_key(&Rc::as_ptr(g)) {
+                    return;
+                }
+                let name = if let Grammar::Rule(name, _) = g.as_ref() {
+                    format!("recursive rule \"{}\"", name)
+                } else {
+                    self.nbr_recursions += 1;
+                    format!("recursion #{}{}", self.nbr_recursions - 1, in_rule)
+                };
+                self.recursions.insert(Rc::as_ptr(g), self.descriptions.len());
+                for b in 0..=MAX_BUCKET {
+                    self.descriptions
+                        .push(format!("{}, depth {}", name, describe_bucket(b)));
+                }
+                self.add_counters(g, rule, None);
+            }
+            Grammar::Rule(name, g) => {
+                self.counters.insert(key, (self.descriptions.len(), 0));
+                self.descriptions.push(format!("rule \"{}\"", name));
+                self.add_counters(g, Some(name), Some(name));
+            }
+        }
+    }
+
+    #[no_coverage]
+    fn counter(&self, grammar: &Grammar, bucket: usize) -> usize {
+        let (first_counter, first_bucket) = self.counters[&(grammar as *const Grammar)];
+        first_counter + bucket - first_bucket
+    }
+
+    /// Pushes the counters activated by the syntax tree to `counters`, and returns whether the
+    /// syntax tree matches the grammar.
+    #[no_coverage]
+    fn visit(
+        &self,
+        grammar: &Grammar,
+        ast: &AST,
+        depths: &mut AHashMap<*const Grammar, usize>,
+        counters: &mut Vec<usize>,
+    ) -> bool {
+        match (grammar, ast) {
+            (Grammar::Literal(ranges), AST::Token(c)) => ranges.iter().any(
+                #[no_coverage]
+                |r| r.contains(c),
+            ),
+            (Grammar::ByteLiteral(ranges), AST::Byte(b)) => ranges.iter().any(
+                #[no_coverage]
+                |r| r.contains(b),
+            ),
+            (Grammar::Alternation(gs), _) => {
+                // the syntax tree does not record the chosen alternative, so we use the first one that matches
+                let len = counters.len();
+                for (i, g) in gs.iter().enumerate() {
+                    if self.visit(g, ast, depths, counters) {
+                        counters.push(self.counter(grammar, 0) + i);
+                        return true;
+                    }
+                    counters.truncate(len);
+                }
+                false
+            }
+            (Grammar::Concatenation(gs), AST::Sequence(asts)) => {
+                gs.len() == asts.len()
+                    && gs.iter().zip(asts.iter()).all(
+                        #[no_coverage]
+                        |(g, ast)| self.visit(g, ast, depths, counters),
+                    )
+            }
+            (Grammar::Repetition(g, range), AST::Sequence(asts)) => {
+                if !range.contains(&asts.len()) {
+                    return false;
+                }
+                counters.push(self.counter(grammar, bucket(asts.len())));
+                asts.iter().all(
+                    #[no_coverage]
+                    |ast| self.visit(g, ast, depths, counters),
+                )
+            }
+            (Grammar::Recurse(g), AST::Sequence(asts)) if asts.len() == 1 => {
+                if let Some(g) = g.upgrade() {
+                    let key = Rc::as_ptr(&g);
+                    let depth = depths.get(&key).copied().unwrap_or(0) + 1;
+                    depths.insert(key, depth);
+                    counters.push(self.recursions[&key] + bucket(depth));
+                    let matches = self.visit(&g, &asts[0], depths, counters);
+                    depths.insert(key, depth - 1);
+                    matches
+                } else {
+                    false
+                }
+            }
+            (Grammar::Recursive(g), _) => {
+                let key = Rc::as_ptr(g);
+                let previous_depth = depths.insert(key, 0);
+                counters.push(self.recursions[&key]);
+                let matches = self.visit(g, ast, depths, counters);
+                if let Some(previous_depth) = previous_depth {
+                    depths.insert(key, previous_depth);
+                } else {
+                    depths.remove(&key);
+                }
+                matches
+            }
+            (
+                Grammar::Rule(name, g),
+                AST::Rule {
+                    name: ast_name,
+                    alternative,
+                    ast,
+                },
+            ) if name == ast_name => {
+                counters.push(self.counter(grammar, 0));
+                if let Grammar::Alternation(gs) = g.as_ref() {
+                    // the alternative is recorded in the syntax tree
+                    if let Some(alternative_grammar) = gs.get(*alternative) {
+                        counters.push(self.counter(g, 0) + alternative);
+                        self.visit(alternative_grammar, ast, depths, counters)
+                    } else {
+                        false
+                    }
+                } else {
+                    *alternative == 0 && self.visit(g, ast, depths, counters)
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A sensor recording which rules, alternatives, repetition counts, and recursion depths of
+/// a grammar are exercised by the syntax tree of a test case.
+///
+/// The syntax trees must be given to the sensor through a [`GrammarCoverageRecorder`] from within
+/// the test function. See the [module documentation](self) for an example.
+///
+/// Its observations are a list of `(counter_index, number_of_hits)`, like those of the
+/// [`CodeCoverageSensor`](crate::sensors_and_pools::CodeCoverageSensor). It is meant to be
+/// used with a [`GrammarCoveragePool`], but any pool compatible with these observations, such
+/// as the [`SimplestToActivateCounterPool`], works as well.
+pub struct GrammarCoverageSensor {
+    map: Rc<GrammarCoverageMap>,
+    recorded: Rc<RefCell<Vec<usize>>>,
+    observations: Vec<(usize, u64)>,
+}
+
+impl GrammarCoverageSensor {
+    /// Create a sensor for the syntax trees of the given grammar.
+    #[no_coverage]
+    pub fn new(grammar: &Rc<Grammar>) -> Self {
+        Self {
+            map: Rc::new(GrammarCoverageMap::new(grammar)),
+            recorded: Rc::new(RefCell::new(vec![])),
+            observations: vec![],
+        }
+    }
+    /// The number of counters of the sensor, i.e. the number of grammar features that can be covered.
+    #[no_coverage]
+    pub fn count_counters(&self) -> usize {
+        self.map.descriptions.len()
+    }
+    /// A human-readable description of each counter of the sensor, such as `rule "expr", alternative 1`.
+    #[no_coverage]
+    pub fn counter_descriptions(&self) -> &[String] {
+        &self.map.descriptions
+    }
+    /// Create a recorder that passes the syntax trees of the test cases to this sensor.
+    ///
+    /// It should be moved into the test function.
+    #[no_coverage]
+    pub fn recorder(&self) -> GrammarCoverageRecorder {
+        GrammarCoverageRecorder {
+            map: self.map.clone(),
+            recorded: self.recorded.clone(),
+        }
+    }
+}
+
+impl Sensor for GrammarCoverageSensor {
+    type Observations = Vec<(usize, u64)>;
+
+    #[no_coverage]
+    fn start_recording(&mut self) {
+        self.recorded.borrow_mut().clear();
+    }
+
+    #[no_coverage]
+    fn stop_recording(&mut self) {
+        let mut recorded = self.recorded.borrow_mut();
+        recorded.sort_unstable();
+        self.observations.clear();
+        for &counter in recorded.iter() {
+            match self.observations.last_mut() {
+                Some((last, hits)) if *last == counter => *hits += 1,
+                _ => self.observations.push((counter, 1)),
+            }
+        }
+    }
+
+    #[no_coverage]
+    fn get_observations(&mut self) -> Self::Observations {
+        std::mem::take(&mut self.observations)
+    }
+}
+impl SaveToStatsFolder for GrammarCoverageSensor {
+    #[no_coverage]
+    fn save_to_stats_folder(&self) -> Vec<(PathBuf, Vec<u8>)> {
+        vec![]
+    }
+}
+
+/// Passes the syntax trees of the test cases to a [`GrammarCoverageSensor`].
+///
+/// It is created by [`GrammarCoverageSensor::recorder`].
+#[derive(Clone)]
+pub struct GrammarCoverageRecorder {
+    map: Rc<GrammarCoverageMap>,
+    recorded: Rc<RefCell<Vec<usize>>>,
+}
+
+impl GrammarCoverageRecorder {
+    /// Record the grammar features exercised by the syntax tree.
+    ///
+    /// It should be called from within the test function. Syntax trees that do not match the
+    /// grammar of the sensor are ignored.
+    #[no_coverage]
+    pub fn record(&self, ast: &AST) {
+        let mut counters = vec![];
+        if self
+            .map
+            .visit(&self.map.grammar, ast, &mut AHashMap::new(), &mut counters)
+        {
+            self.recorded.borrow_mut().extend(counters);
+        }
+    }
+}
+
+/// A pool that keeps the simplest test case exercising each grammar feature observed by a
+/// [`GrammarCoverageSensor`].
+///
+/// It can be combined with the code coverage pool using [`AndPool`](crate::sensors_and_pools::AndPool),
+/// as shown in the [module documentation](self). At the end of the fuzz test, the list of
+/// covered and uncovered grammar features is saved to the `stats` folder.
+pub struct GrammarCoveragePool {
+    pool: SimplestToActivateCounterPool,
+    map: Rc<GrammarCoverageMap>,
+    least_complexity_for_counter: Vec<f64>,
+}
+
+impl GrammarCoveragePool {
+    #[no_coverage]
+    pub fn new(name: &str, sensor: &GrammarCoverageSensor) -> Self {
+        let nbr_counters = sensor.count_counters();
+        Self {
+            pool: SimplestToActivateCounterPool::new(name, nbr_counters),
+            map: sensor.map.clone(),
+            least_complexity_for_counter: vec![f64::INFINITY; nbr_counters],
+        }
+    }
+}
+
+impl Pool for GrammarCoveragePool {
+    type Stats = UniqueCoveragePoolStats;
+
+    #[no_coverage]
+    fn stats(&self) -> Self::Stats {
+        self.pool.stats()
+    }
+    #[no_coverage]
+    fn get_random_index(&mut self) -> Option<PoolStorageIndex> {
+        self.pool.get_random_index()
+    }
+}
+
+impl SaveToStatsFolder for GrammarCoveragePool {
+    #[no_coverage]
+    fn save_to_stats_folder(&self) -> Vec<(PathBuf, Vec<u8>)> {
+        let mut content = String::new();
+        for (description, complexity) in self.map.descriptions.iter().zip(&self.least_complexity_for_counter) {
+            if *complexity == f64::INFINITY {
+                content.push_str(&format!("uncovered: {}\n", description));
+            } else {
+                content.push_str(&format!("covered (cplx: {:.2}): {}\n", complexity, description));
+            }
+        }
+        let mut files = self.pool.save_to_stats_folder();
+        files.push((
+            PathBuf::new().join(format!("{}.txt", self.pool.name)),
+            content.into_bytes(),
+        ));
+        files
+    }
+}
+
+impl CompatibleWithObservations<Vec<(usize, u64)>> for GrammarCoveragePool {
+    #[no_coverage]
+    fn process(
+        &mut self,
+        input_id: PoolStorageIndex,
+        observations: &Vec<(usize, u64)>,
+        complexity: f64,
+    ) -> Vec<CorpusDelta> {
+        for &(counter, _) in observations {
+            let least_complexity = &mut self.least_complexity_for_counter[counter];
+            if complexity < *least_complexity {
+                *least_complexity = complexity;
+            }
+        }
+        self.pool.process(input_id, observations, complexity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GrammarCoverageSensor;
+    use crate::mutators::grammar::{
+        alternation, concatenation, literal, recurse, recursive, repetition, rule, GrammarParser,
+    };
+    use crate::Sensor;
+
+    #[test]
+    #[no_coverage]
+    fn test_grammar_coverage() {
+        let grammar = recursive(
+            #[no_coverage]
+            |expr| {
+                rule(
+                    "expr",
+                    alternation([
+                        repetition(literal('1'), 1..10),
+                        concatenation([literal('('), recurse(expr), literal(')')]),
+                    ]),
+                )
+            },
+        );
+        let mut sensor = GrammarCoverageSensor::new(&grammar);
+        let descriptions = sensor.counter_descriptions().to_vec();
+        assert_eq!(sensor.count_counters(), 9 + 1 + 2 + 4);
+
+        let recorder = sensor.recorder();
+        let parser = GrammarParser::new(&grammar);
+        let mut observe = #[no_coverage]
+        |string: &str| {
+            sensor.start_recording();
+            recorder.record(&parser.parse(string).unwrap());
+            sensor.stop_recording();
+            sensor
+                .get_observations()
+                .into_iter()
+                .map(
+                    #[no_coverage]
+                    |(counter, hits)| (descriptions[counter].as_str(), hits),
+                )
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            observe("((111))"),
+            [
+                ("recursive rule \"expr\", depth 0", 1),
+                ("recursive rule \"expr\", depth 1", 1),
+                ("recursive rule \"expr\", depth 2 to 3", 1),
+                ("rule \"expr\"", 3),
+                ("rule \"expr\", alternative 0", 1),
+                ("rule \"expr\", alternative 1", 2),
+                ("repetition #0 in rule \"expr\", repeated 2 to 3 times", 1),
+            ]
+        );
+        assert_eq!(
+            observe("11111"),
+            [
+                ("recursive rule \"expr\", depth 0", 1),
+                ("rule \"expr\"", 1),
+                ("rule \"expr\", alternative 0", 1),
+                ("repetition #0 in rule \"expr\", repeated 4 to 7 times", 1),
+            ]
+        );
+    }
+}
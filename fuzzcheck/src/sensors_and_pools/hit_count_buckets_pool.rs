@@ -0,0 +1,244 @@
+use std::fmt::{Debug, Display};
+use std::path::Path;
+
+use ahash::{AHashMap, AHashSet};
+use nu_ansi_term::Color;
+
+use super::ranked_inputs::{RankedInput, RankedInputs};
+use crate::data_structures::SlabKey;
+use crate::traits::{CorpusDelta, Pool, SaveToStatsFolder, Stats};
+use crate::{CSVField, CompatibleWithObservations, PoolStorageIndex, ToCSV};
+
+/// The statistics of a [HitCountBucketsPool]
+#[derive(Clone)]
+pub struct HitCountBucketsPoolStats {
+    name: String,
+    size: usize,
+    nbr_buckets: usize,
+}
+
+impl Display for HitCountBucketsPoolStats {
+    #[no_coverage]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            Color::LightCyan.paint(format!("{}({} buckets: {})", self.name, self.size, self.nbr_buckets))
+        )
+    }
+}
+
+impl ToCSV for HitCountBucketsPoolStats {
+    #[no_coverage]
+    fn csv_headers(&self) -> Vec<CSVField> {
+        vec![
+            CSVField::String(format!("{}-count", self.name)),
+            CSVField::String(format!("{}-buckets", self.name)),
+        ]
+    }
+    #[no_coverage]
+    fn to_csv_record(&self) -> Vec<CSVField> {
+        vec![
+            CSVField::Integer(self.size as isize),
+            CSVField::Integer(self.nbr_buckets as isize),
+        ]
+    }
+}
+impl Stats for HitCountBucketsPoolStats {}
+
+/// A counter and one of the buckets of its hit count
+type Bucket = (usize, u8);
+
+#[derive(Debug)]
+struct Input {
+    best_for_buckets: AHashSet<Bucket>,
+    cplx: f64,
+}
+
+/// Returns the bucket of a non-zero hit count.
+///
+/// The buckets are: 1, 2, 3, 4-7, 8-15, 16-31, etc.
+#[no_coverage]
+fn bucket(count: u64) -> u8 {
+    if count < 4 {
+        count as u8 - 1
+    } else {
+        (u64::BITS - count.leading_zeros()) as u8
+    }
+}
+
+/// A pool that keeps the simplest test case reaching each bucket of hit counts of each counter of a sensor.
+///
+/// Like the pools of AFL, it rewards test cases executing a region of code a different number of times,
+/// such as a loop running for 4 iterations when the other test cases only ran it once or twice. The
+/// hit counts are grouped in logarithmic buckets: 1, 2, 3, 4-7, 8-15, 16-31, etc.
+///
+/// It is [compatible with](crate::CompatibleWithObservations) the following sensors:
+/// * [`CodeCoverageSensor`](crate::sensors_and_pools::CodeCoverageSensor)
+/// * any other sensor whose [observations](crate::Sensor::Observations) are given by an iterator of `(usize, u64)`
+pub struct HitCountBucketsPool {
+    name: String,
+    inputs: RankedInputs<Input>,
+    best_input_for_bucket: AHashMap<Bucket, SlabKey<RankedInput<Input>>>,
+    stats: HitCountBucketsPoolStats,
+}
+impl Debug for HitCountBucketsPool {
+    #[no_coverage]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HitCountBucketsPool")
+            .field("inputs", &self.inputs)
+            .field("best_input_for_bucket", &self.best_input_for_bucket)
+            .finish()
+    }
+}
+
+impl HitCountBucketsPool {
+    #[no_coverage]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            inputs: RankedInputs::new(),
+            best_input_for_bucket: AHashMap::new(),
+            stats: HitCountBucketsPoolStats {
+                name: name.to_string(),
+                size: 0,
+                nbr_buckets: 0,
+            },
+        }
+    }
+}
+
+impl Pool for HitCountBucketsPool {
+    type Stats = HitCountBucketsPoolStats;
+
+    #[no_coverage]
+    fn stats(&self) -> Self::Stats {
+        self.stats.clone()
+    }
+
+    #[no_coverage]
+    fn get_random_index(&mut self) -> Option<PoolStorageIndex> {
+        self.inputs.get_random_index()
+    }
+}
+
+impl SaveToStatsFolder for HitCountBucketsPool {
+    #[no_coverage]
+    fn save_to_stats_folder(&self) -> Vec<(std::path::PathBuf, Vec<u8>)> {
+        vec![]
+    }
+}
+
+impl HitCountBucketsPool {
+    #[no_coverage]
+    fn update_stats(&mut self) {
+        self.inputs.update_ranks();
+
+        self.stats.size = self.inputs.len();
+        self.stats.nbr_buckets = self.best_input_for_bucket.len();
+    }
+}
+
+impl<O> CompatibleWithObservations<O> for HitCountBucketsPool
+where
+    for<'a> &'a O: IntoIterator<Item = &'a (usize, u64)>,
+{
+    #[no_coverage]
+    fn process(&mut self, input_id: PoolStorageIndex, observations: &O, complexity: f64) -> Vec<CorpusDelta> {
+        let mut best_for_buckets = AHashSet::new();
+        for &(index, count) in observations.into_iter() {
+            if count == 0 {
+                continue;
+            }
+            let bucket = (index, bucket(count));
+            match self.best_input_for_bucket.get(&bucket) {
+                Some(&key) if self.inputs[key].data.cplx <= complexity => {}
+                _ => {
+                    best_for_buckets.insert(bucket);
+                }
+            }
+        }
+        if best_for_buckets.is_empty() {
+            return vec![];
+        }
+        let input = Input {
+            best_for_buckets: best_for_buckets.clone(),
+            cplx: complexity,
+        };
+        let input_key = self.inputs.insert(input_id, best_for_buckets.len() as f64, input);
+
+        let mut removed_keys = vec![];
+        for bucket in best_for_buckets {
+            if let Some(previous_best_key) = self.best_input_for_bucket.insert(bucket, input_key) {
+                let previous_best = &mut self.inputs[previous_best_key];
+                let was_present_in_set = previous_best.data.best_for_buckets.remove(&bucket);
+                assert!(was_present_in_set);
+                previous_best.score = previous_best.data.best_for_buckets.len() as f64;
+                if previous_best.data.best_for_buckets.is_empty() {
+                    removed_keys.push(previous_best_key);
+                }
+            }
+        }
+        let removed_idxs = removed_keys
+            .into_iter()
+            .map(
+                #[no_coverage]
+                |key| self.inputs.remove(key),
+            )
+            .collect();
+
+        self.update_stats();
+
+        vec![CorpusDelta {
+            path: Path::new(&self.name).to_path_buf(),
+            add: true,
+            remove: removed_idxs,
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bucket, HitCountBucketsPool};
+    use crate::traits::{CompatibleWithObservations, Pool};
+    use crate::PoolStorageIndex;
+
+    #[test]
+    #[no_coverage]
+    fn test_buckets() {
+        let buckets = [1, 2, 3, 4, 7, 8, 15, 16, 1000, u64::MAX].map(bucket);
+        assert_eq!(buckets, [0, 1, 2, 3, 3, 4, 4, 5, 10, 64]);
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_hit_count_buckets_pool() {
+        let mut pool = HitCountBucketsPool::new("hit_counts");
+
+        // a new bucket for counter 0
+        let deltas = pool.process(PoolStorageIndex::mock(0), &[(0, 1)], 2.0);
+        assert!(deltas[0].add && deltas[0].remove.is_empty());
+        // same bucket, but not simpler
+        assert!(pool.process(PoolStorageIndex::mock(1), &[(0, 1)], 3.0).is_empty());
+        // the loop is executed more times, which reaches a new bucket
+        let deltas = pool.process(PoolStorageIndex::mock(2), &[(0, 5)], 3.0);
+        assert!(deltas[0].add && deltas[0].remove.is_empty());
+        // same bucket as 5, but not simpler
+        assert!(pool.process(PoolStorageIndex::mock(3), &[(0, 6)], 4.0).is_empty());
+        assert_eq!(pool.stats().size, 2);
+        assert_eq!(pool.stats().nbr_buckets, 2);
+
+        // simpler for both buckets, which replaces both previous inputs
+        let deltas = pool.process(PoolStorageIndex::mock(4), &[(0, 1), (1, 7)], 1.0);
+        assert_eq!(deltas[0].remove, vec![PoolStorageIndex::mock(0)]);
+        let deltas = pool.process(PoolStorageIndex::mock(5), &[(0, 4)], 1.0);
+        assert_eq!(deltas[0].remove, vec![PoolStorageIndex::mock(2)]);
+        assert_eq!(pool.stats().size, 2);
+        assert_eq!(pool.stats().nbr_buckets, 3);
+
+        for _ in 0..100 {
+            let index = pool.get_random_index().unwrap();
+            assert!(index == PoolStorageIndex::mock(4) || index == PoolStorageIndex::mock(5));
+        }
+    }
+}
@@ -4,8 +4,8 @@ use std::path::Path;
 use ahash::AHashSet;
 use nu_ansi_term::Color;
 
-use crate::data_structures::{Slab, SlabKey};
-use crate::fenwick_tree::FenwickTree;
+use super::ranked_inputs::{RankedInput, RankedInputs};
+use crate::data_structures::SlabKey;
 use crate::traits::{CorpusDelta, Pool, SaveToStatsFolder, Stats};
 use crate::{CSVField, CompatibleWithObservations, PoolStorageIndex, ToCSV};
 
@@ -50,9 +50,6 @@ impl Stats for MaximiseEachCounterPoolStats {}
 struct Input {
     best_for_counters: AHashSet<usize>,
     cplx: f64,
-    idx: PoolStorageIndex,
-    score: f64,
-    number_times_chosen: usize,
 }
 
 /// A pool that tries to find test cases maximizing the value of each counter of a sensor.
@@ -64,11 +61,9 @@ pub struct MaximiseEachCounterPool {
     name: String,
     complexities: Vec<f64>,
     highest_counts: Vec<u64>,
-    inputs: Slab<Input>,
-    best_input_for_counter: Vec<Option<SlabKey<Input>>>,
-    ranked_inputs: FenwickTree,
+    inputs: RankedInputs<Input>,
+    best_input_for_counter: Vec<Option<SlabKey<RankedInput<Input>>>>,
     stats: MaximiseEachCounterPoolStats,
-    rng: fastrand::Rng,
 }
 impl Debug for MaximiseEachCounterPool {
     #[no_coverage]
@@ -90,15 +85,13 @@ impl MaximiseEachCounterPool {
             name: name.to_string(),
             complexities: vec![0.0; size],
             highest_counts: vec![0; size],
-            inputs: Slab::new(),
+            inputs: RankedInputs::new(),
             best_input_for_counter: vec![None; size],
-            ranked_inputs: FenwickTree::new(vec![]),
             stats: MaximiseEachCounterPoolStats {
                 name: name.to_string(),
                 size: 0,
                 total_counts: 0,
             },
-            rng: fastrand::Rng::new(),
         }
     }
 }
@@ -113,18 +106,7 @@ impl Pool for MaximiseEachCounterPool {
 
     #[no_coverage]
     fn get_random_index(&mut self) -> Option<PoolStorageIndex> {
-        let choice = self.ranked_inputs.sample(&self.rng)?;
-
-        let key = self.inputs.get_nth_key(choice);
-
-        let input = &mut self.inputs[key];
-        let old_rank = input.score / (input.number_times_chosen as f64);
-        input.number_times_chosen += 1;
-        let new_rank = input.score / (input.number_times_chosen as f64);
-
-        let delta = new_rank - old_rank;
-        self.ranked_inputs.update(choice, delta);
-        Some(input.idx)
+        self.inputs.get_random_index()
     }
 }
 
@@ -138,19 +120,7 @@ impl SaveToStatsFolder for MaximiseEachCounterPool {
 impl MaximiseEachCounterPool {
     #[no_coverage]
     fn update_stats(&mut self) {
-        let inputs = &self.inputs;
-        let ranked_inputs = self
-            .inputs
-            .keys()
-            .map(
-                #[no_coverage]
-                |key| {
-                    let input = &inputs[key];
-                    input.score / (input.number_times_chosen as f64)
-                },
-            )
-            .collect();
-        self.ranked_inputs = FenwickTree::new(ranked_inputs);
+        self.inputs.update_ranks();
 
         self.stats.size = self.inputs.len();
         self.stats.total_counts = self.highest_counts.iter().sum();
@@ -169,7 +139,7 @@ where
                 state.push((index, counter));
             } else if pool_counter == counter
                 && let Some(candidate_key) = self.best_input_for_counter[index]
-                && self.inputs[candidate_key].cplx > complexity {
+                && self.inputs[candidate_key].data.cplx > complexity {
                 state.push((index, counter));
             }
         }
@@ -187,11 +157,8 @@ where
                 )
                 .collect(),
             cplx,
-            idx: input_id,
-            score: highest_for_counters.len() as f64,
-            number_times_chosen: 1,
         };
-        let input_key = self.inputs.insert(input);
+        let input_key = self.inputs.insert(input_id, highest_for_counters.len() as f64, input);
 
         let mut removed_keys = vec![];
 
@@ -206,10 +173,10 @@ where
             let previous_best_key = &mut self.best_input_for_counter[counter];
             if let Some(previous_best_key) = previous_best_key {
                 let previous_best = &mut self.inputs[*previous_best_key];
-                let was_present_in_set = previous_best.best_for_counters.remove(&counter);
+                let was_present_in_set = previous_best.data.best_for_counters.remove(&counter);
                 assert!(was_present_in_set);
-                previous_best.score = previous_best.best_for_counters.len() as f64;
-                if previous_best.best_for_counters.is_empty() {
+                previous_best.score = previous_best.data.best_for_counters.len() as f64;
+                if previous_best.data.best_for_counters.is_empty() {
                     removed_keys.push(*previous_best_key);
                 }
                 *previous_best_key = input_key;
@@ -217,11 +184,13 @@ where
                 *previous_best_key = Some(input_key);
             }
         }
-        let mut removed_idxs = vec![];
-        for &removed_key in &removed_keys {
-            removed_idxs.push(self.inputs[removed_key].idx);
-            self.inputs.remove(removed_key);
-        }
+        let removed_idxs = removed_keys
+            .into_iter()
+            .map(
+                #[no_coverage]
+                |key| self.inputs.remove(key),
+            )
+            .collect();
 
         self.update_stats();
 
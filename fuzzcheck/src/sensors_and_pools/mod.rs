@@ -2,13 +2,18 @@
 Types implementing the [Sensor](crate::Sensor) and [Pool](crate::Pool) traits.
 */
 
-mod allocations_sensor;
+pub(crate) mod allocations_sensor;
 mod and_sensor_and_pool;
+mod distance_to_target_pool;
+#[cfg(feature = "grammar_mutator")]
+mod grammar_coverage;
+mod hit_count_buckets_pool;
 mod map_sensor;
 mod maximise_each_counter_pool;
 mod maximise_observation_pool;
 mod most_n_diverse_pool;
 mod noop_sensor;
+mod ranked_inputs;
 mod simplest_to_activate_counter_pool;
 mod static_value_sensor;
 mod test_failure_pool;
@@ -20,6 +25,14 @@ pub use allocations_sensor::{AllocationSensor, CountingAllocator};
 #[doc(inline)]
 pub use and_sensor_and_pool::{AndPool, AndSensor, AndSensorAndPool, DifferentObservations, SameObservations};
 #[doc(inline)]
+pub use distance_to_target_pool::DistanceToTargetPool;
+#[cfg(feature = "grammar_mutator")]
+#[doc(inline)]
+#[doc(cfg(feature = "grammar_mutator"))]
+pub use grammar_coverage::{GrammarCoveragePool, GrammarCoverageRecorder, GrammarCoverageSensor};
+#[doc(inline)]
+pub use hit_count_buckets_pool::HitCountBucketsPool;
+#[doc(inline)]
 pub use map_sensor::MapSensor;
 #[doc(inline)]
 pub use map_sensor::WrapperSensor;
@@ -32,7 +45,7 @@ pub use most_n_diverse_pool::MostNDiversePool;
 #[doc(inline)]
 pub use noop_sensor::NoopSensor;
 #[doc(inline)]
-pub use simplest_to_activate_counter_pool::SimplestToActivateCounterPool;
+pub use simplest_to_activate_counter_pool::{PowerSchedule, SimplestToActivateCounterPool};
 #[doc(inline)]
 pub use static_value_sensor::StaticValueSensor;
 #[doc(inline)]
@@ -110,6 +123,10 @@ pub mod stats {
     #[doc(inline)]
     pub use super::and_sensor_and_pool::AndPoolStats;
     #[doc(inline)]
+    pub use super::distance_to_target_pool::DistanceToTargetPoolStats;
+    #[doc(inline)]
+    pub use super::hit_count_buckets_pool::HitCountBucketsPoolStats;
+    #[doc(inline)]
     pub use super::maximise_each_counter_pool::MaximiseEachCounterPoolStats;
     #[doc(inline)]
     pub use super::most_n_diverse_pool::MostNDiversePoolStats;
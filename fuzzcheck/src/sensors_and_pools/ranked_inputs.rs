@@ -0,0 +1,114 @@
+use std::fmt::Debug;
+use std::ops::{Index, IndexMut};
+
+use crate::data_structures::{Slab, SlabKey};
+use crate::fenwick_tree::FenwickTree;
+use crate::PoolStorageIndex;
+
+/// A test case of a pool, along with the data that the pool keeps about it
+#[derive(Debug)]
+pub(crate) struct RankedInput<T> {
+    pub idx: PoolStorageIndex,
+    pub score: f64,
+    number_times_chosen: usize,
+    pub data: T,
+}
+impl<T> RankedInput<T> {
+    #[no_coverage]
+    fn rank(&self) -> f64 {
+        self.score / (self.number_times_chosen as f64)
+    }
+}
+
+/// The test cases of a pool, each chosen with a probability proportional to its score divided by
+/// the number of times it was already chosen.
+///
+/// The ranks of the test cases must be recomputed with [`update_ranks`](RankedInputs::update_ranks)
+/// after test cases are inserted or removed, or after their score changes.
+pub(crate) struct RankedInputs<T> {
+    inputs: Slab<RankedInput<T>>,
+    ranks: FenwickTree,
+    rng: fastrand::Rng,
+}
+impl<T: Debug> Debug for RankedInputs<T> {
+    #[no_coverage]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inputs.fmt(f)
+    }
+}
+
+impl<T> RankedInputs<T> {
+    #[no_coverage]
+    pub fn new() -> Self {
+        Self {
+            inputs: Slab::new(),
+            ranks: FenwickTree::new(vec![]),
+            rng: fastrand::Rng::new(),
+        }
+    }
+
+    #[no_coverage]
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    #[no_coverage]
+    pub fn insert(&mut self, idx: PoolStorageIndex, score: f64, data: T) -> SlabKey<RankedInput<T>> {
+        self.inputs.insert(RankedInput {
+            idx,
+            score,
+            number_times_chosen: 1,
+            data,
+        })
+    }
+
+    /// Removes the test case and returns its index in the fuzzer’s storage
+    #[no_coverage]
+    pub fn remove(&mut self, key: SlabKey<RankedInput<T>>) -> PoolStorageIndex {
+        let idx = self.inputs[key].idx;
+        self.inputs.remove(key);
+        idx
+    }
+
+    #[no_coverage]
+    pub fn update_ranks(&mut self) {
+        let inputs = &self.inputs;
+        let ranks = inputs
+            .keys()
+            .map(
+                #[no_coverage]
+                |key| inputs[key].rank(),
+            )
+            .collect();
+        self.ranks = FenwickTree::new(ranks);
+    }
+
+    /// Chooses a random test case, whose rank then decreases
+    #[no_coverage]
+    pub fn get_random_index(&mut self) -> Option<PoolStorageIndex> {
+        let choice = self.ranks.sample(&self.rng)?;
+        let key = self.inputs.get_nth_key(choice);
+
+        let input = &mut self.inputs[key];
+        let old_rank = input.rank();
+        input.number_times_chosen += 1;
+        let new_rank = input.rank();
+
+        self.ranks.update(choice, new_rank - old_rank);
+        Some(input.idx)
+    }
+}
+
+impl<T> Index<SlabKey<RankedInput<T>>> for RankedInputs<T> {
+    type Output = RankedInput<T>;
+    #[no_coverage]
+    fn index(&self, key: SlabKey<RankedInput<T>>) -> &Self::Output {
+        &self.inputs[key]
+    }
+}
+impl<T> IndexMut<SlabKey<RankedInput<T>>> for RankedInputs<T> {
+    #[no_coverage]
+    fn index_mut(&mut self, key: SlabKey<RankedInput<T>>) -> &mut Self::Output {
+        &mut self.inputs[key]
+    }
+}
@@ -25,6 +25,13 @@
 //! In short, an input’s final score is the sum of the score of each of its
 //! activated counters divided by their frequencies.
 //!
+//! # Choosing the next input to mutate
+//!
+//! By default, an input is chosen with a probability proportional to its score
+//! divided by the number of times it was chosen. Alternatively, the
+//! [entropic](PowerSchedule::Entropic) power schedule favours the inputs whose
+//! mutations are expected to reveal the most information about the program.
+//!
 
 use std::fmt::Display;
 use std::hash::Hash;
@@ -80,6 +87,33 @@ impl CounterIdx {
     }
 }
 
+/// Counters activated by fewer test cases than this threshold are considered rare
+/// by the entropic power schedule.
+const RARE_COUNTER_THRESHOLD: u16 = 0xFF;
+/// The energy added to every input under the entropic power schedule, such that
+/// inputs whose mutations are not expected to reveal anything can still be chosen.
+const MIN_ENERGY: f64 = 0.01;
+
+/// The strategy used by a [`SimplestToActivateCounterPool`] to choose the next test case to mutate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PowerSchedule {
+    /// Choose a test case with a probability proportional to its score, which is shared with
+    /// the other test cases activating the same counters, divided by the number of times it
+    /// was chosen.
+    #[default]
+    Shared,
+    /// Choose a test case with a probability proportional to the information expected to be
+    /// gained by mutating it, inspired by the entropic schedule of libFuzzer.
+    ///
+    /// The information gain of a test case is estimated by the entropy of the rare counters
+    /// activated by its mutants. It decreases as its mutations keep activating the same counters,
+    /// and it is multiplied by the fraction of times that choosing the test case led to a new
+    /// test case being added to the pool.
+    ///
+    /// The mutated test case is the one given by the fuzzer to [`set_parent`](Pool::set_parent).
+    Entropic,
+}
+
 /**
  * An element stored in the pool, containing its value, cache, mutation step,
  * as well as analysed code coverage and computed score.
@@ -100,6 +134,54 @@ struct Input {
     ///
     /// This is used to prioritise new inputs over old ones.
     number_times_chosen: usize,
+    /// The number of times that a mutation of this input was tested, used by the entropic schedule
+    number_mutations_tested: usize,
+    /// The number of mutations of this input that were added to the pool, used by the entropic schedule
+    number_successes: usize,
+    /// The number of mutations of this input activating each rare counter, used by the entropic schedule
+    rare_counter_frequencies: AHashMap<CounterIdx, u16>,
+    /// The information expected to be gained by mutating this input, used by the entropic schedule
+    energy: f64,
+}
+
+impl Input {
+    /// The weight of the input when choosing the next input to mutate
+    #[no_coverage]
+    fn rank(&self, schedule: PowerSchedule) -> f64 {
+        match schedule {
+            PowerSchedule::Shared => self.score / (self.number_times_chosen as f64),
+            PowerSchedule::Entropic => {
+                let success_rate = (1 + self.number_successes) as f64 / (self.number_times_chosen as f64);
+                (self.energy + MIN_ENERGY) * success_rate
+            }
+        }
+    }
+
+    /// Estimate the entropy of the rare counters activated by the mutations of this input.
+    ///
+    /// This is the estimator used by libFuzzer, with add-one smoothing for each rare counter
+    /// and a single abundant counter activated by every mutation.
+    #[no_coverage]
+    fn update_energy(&mut self, counter_frequencies: &[u16], nbr_rare_counters: usize) {
+        self.rare_counter_frequencies.retain(
+            #[no_coverage]
+            |counter, _| counter_frequencies[counter.0] < RARE_COUNTER_THRESHOLD,
+        );
+        let mut energy = 0.0;
+        let mut sum_incidence = 0.0;
+        for &frequency in self.rare_counter_frequencies.values() {
+            let local_incidence = frequency as f64 + 1.0;
+            energy -= local_incidence * local_incidence.ln();
+            sum_incidence += local_incidence;
+        }
+        sum_incidence += nbr_rare_counters.saturating_sub(self.rare_counter_frequencies.len()) as f64;
+
+        let abundant_incidence = self.number_mutations_tested as f64 + 1.0;
+        energy -= abundant_incidence * abundant_incidence.ln();
+        sum_incidence += abundant_incidence;
+
+        self.energy = energy / sum_incidence + sum_incidence.ln();
+    }
 }
 
 /**
@@ -146,11 +228,20 @@ pub struct SimplestToActivateCounterPool {
     least_complexity_for_counter: Vec<f64>,
     analysed_counters: AHashMap<CounterIdx, AnalysedCounter>,
     slab_inputs: Slab<Input>,
+    /// The key of the input storing each test case of the pool
+    input_keys: AHashMap<PoolStorageIndex, SlabKey<Input>>,
 
     pub average_complexity: f64,
     pub total_score: f64,
     pub ranked_inputs: FenwickTree,
 
+    schedule: PowerSchedule,
+    /// The number of tested inputs activating each counter, used by the entropic schedule
+    counter_frequencies: Vec<u16>,
+    nbr_rare_counters: usize,
+    /// The test case from which the next processed test case was mutated, used by the entropic schedule
+    parent: Option<PoolStorageIndex>,
+
     rng: Rng,
 }
 
@@ -163,15 +254,28 @@ impl SimplestToActivateCounterPool {
             analysed_counters: AHashMap::with_hasher(ahash::RandomState::with_seeds(0, 0, 0, 0)),
 
             slab_inputs: Slab::new(),
+            input_keys: AHashMap::new(),
 
             average_complexity: 0.0,
             total_score: 0.0,
             ranked_inputs: FenwickTree::new(vec![]),
 
+            schedule: PowerSchedule::default(),
+            counter_frequencies: vec![0; nbr_counters],
+            nbr_rare_counters: 0,
+            parent: None,
+
             rng: fastrand::Rng::new(),
         }
     }
 
+    /// Use the given strategy to choose the next test case to mutate
+    #[must_use]
+    #[no_coverage]
+    pub fn with_power_schedule(self, schedule: PowerSchedule) -> Self {
+        Self { schedule, ..self }
+    }
+
     #[no_coverage]
     pub fn score(&self) -> f64 {
         self.total_score
@@ -201,8 +305,13 @@ impl SimplestToActivateCounterPool {
             data,
             complexity,
             number_times_chosen: 1,
+            number_mutations_tested: 0,
+            number_successes: 0,
+            rare_counter_frequencies: AHashMap::new(),
+            energy: 0.0,
         };
         let element_key = self.slab_inputs.insert(element);
+        self.input_keys.insert(data, element_key);
 
         let mut to_delete: AHashSet<SlabKey<Input>> = AHashSet::with_hasher(ahash::RandomState::with_seeds(0, 0, 0, 0));
 
@@ -291,6 +400,7 @@ impl SimplestToActivateCounterPool {
             let counter_score = Self::score_of_counter(analyzed_counter.inputs.len());
             element.score += counter_score;
         }
+        element.update_energy(&self.counter_frequencies, self.nbr_rare_counters);
 
         self.update_self_stats();
 
@@ -329,6 +439,7 @@ impl SimplestToActivateCounterPool {
                 analyzed_f.inputs.swap_remove(idx_to_delete_key);
             }
 
+            self.input_keys.remove(&to_delete_el.data);
             self.slab_inputs.remove(to_delete_key);
         }
     }
@@ -338,20 +449,62 @@ impl SimplestToActivateCounterPool {
         1.0 / (exact_counter_multiplicity as f64)
     }
 
+    /// Returns the position in `ranked_inputs` and the key of the input storing the given test case,
+    /// if it is part of the pool
+    #[no_coverage]
+    fn find_input(&self, data: PoolStorageIndex) -> Option<(usize, SlabKey<Input>)> {
+        let key = *self.input_keys.get(&data)?;
+        Some((self.slab_inputs.position_of_key(key), key))
+    }
+
+    /// Record that a mutation of the given input activated the given counters, and update
+    /// the energy of that input. This is used by the entropic schedule.
+    #[no_coverage]
+    fn record_tested_mutation(
+        &mut self,
+        parent: Option<(usize, SlabKey<Input>)>,
+        observed_counters: impl Iterator<Item = usize>,
+    ) {
+        let mut rare_counters = vec![];
+        for index in observed_counters {
+            let frequency = &mut self.counter_frequencies[index];
+            if *frequency == 0 {
+                self.nbr_rare_counters += 1;
+            }
+            if *frequency < RARE_COUNTER_THRESHOLD {
+                rare_counters.push(CounterIdx::new(index));
+            }
+            *frequency = frequency.saturating_add(1);
+            if *frequency == RARE_COUNTER_THRESHOLD {
+                self.nbr_rare_counters -= 1;
+            }
+        }
+        if let Some((position, key)) = parent {
+            let input = &mut self.slab_inputs[key];
+            let old_rank = input.rank(self.schedule);
+            input.number_mutations_tested += 1;
+            for counter in rare_counters {
+                let frequency = input.rare_counter_frequencies.entry(counter).or_insert(0);
+                *frequency = frequency.saturating_add(1);
+            }
+            input.update_energy(&self.counter_frequencies, self.nbr_rare_counters);
+            let new_rank = input.rank(self.schedule);
+            self.ranked_inputs.update(position, new_rank - old_rank);
+        }
+    }
+
     /// Update global statistics of the pool following a change in its content
     #[no_coverage]
     fn update_self_stats(&mut self) {
         let slab = &self.slab_inputs;
+        let schedule = self.schedule;
 
         let ranked_inputs = self
             .slab_inputs
             .keys()
             .map(
                 #[no_coverage]
-                |key| {
-                    let input = &slab[key];
-                    input.score / (input.number_times_chosen as f64)
-                },
+                |key| slab[key].rank(schedule),
             )
             .collect();
         self.ranked_inputs = FenwickTree::new(ranked_inputs);
@@ -450,6 +603,12 @@ impl SimplestToActivateCounterPool {
         dedupped_inputs.dedup();
         assert_eq!(dedupped_inputs.len(), self.slab_inputs.len());
 
+        assert_eq!(self.input_keys.len(), self.slab_inputs.len());
+        for (position, input_key) in self.slab_inputs.keys().enumerate() {
+            let input = &self.slab_inputs[input_key];
+            assert_eq!(self.find_input(input.data), Some((position, input_key)));
+        }
+
         // let mut dedupped_counters = self.counters.clone();
         // dedupped_counters.sort();
         // dedupped_counters.dedup();
@@ -477,14 +636,19 @@ impl Pool for SimplestToActivateCounterPool {
         let key = self.slab_inputs.get_nth_key(choice);
 
         let input = &mut self.slab_inputs[key];
-        let old_rank = input.score / (input.number_times_chosen as f64);
+        let old_rank = input.rank(self.schedule);
         input.number_times_chosen += 1;
-        let new_rank = input.score / (input.number_times_chosen as f64);
+        let new_rank = input.rank(self.schedule);
 
         let delta = new_rank - old_rank;
         self.ranked_inputs.update(choice, delta);
         Some(input.data)
     }
+
+    #[no_coverage]
+    fn set_parent(&mut self, parent: Option<PoolStorageIndex>) {
+        self.parent = parent;
+    }
 }
 
 impl SaveToStatsFolder for SimplestToActivateCounterPool {
@@ -636,6 +800,20 @@ where
 {
     #[no_coverage]
     fn process(&mut self, input_id: PoolStorageIndex, observations: &O, complexity: f64) -> Vec<CorpusDelta> {
+        let parent = match self.parent.take() {
+            Some(parent) if self.schedule == PowerSchedule::Entropic => self.find_input(parent),
+            _ => None,
+        };
+        if self.schedule == PowerSchedule::Entropic {
+            self.record_tested_mutation(
+                parent,
+                observations.into_iter().map(
+                    #[no_coverage]
+                    |&(index, _)| index,
+                ),
+            );
+        }
+
         let mut state = UniqueCoveragePoolObservationState::default();
 
         for &(index, _) in observations.into_iter() {
@@ -645,6 +823,9 @@ where
         if !state.is_interesting {
             return vec![];
         }
+        if let Some((_, parent_key)) = parent {
+            self.slab_inputs[parent_key].number_successes += 1;
+        }
         let mut result = AnalysisResult::default();
         for &(index, _counter) in observations.into_iter() {
             let counter_idx = CounterIdx::new(index);
@@ -677,6 +858,61 @@ mod tests {
         CounterIdx(index * 64 + intensity as usize)
     }
 
+    #[test]
+    #[no_coverage]
+    fn test_entropic_schedule() {
+        let mut pool = SimplestToActivateCounterPool::new("cov", 8).with_power_schedule(PowerSchedule::Entropic);
+        pool.process(PoolStorageIndex::mock(0), &[(0, 1), (1, 1)], 1.0);
+        let parent_key = pool.slab_inputs.get_nth_key(0);
+        let initial_energy = pool.slab_inputs[parent_key].energy;
+        assert!((initial_energy - 3.0f64.ln()).abs() < 0.001);
+
+        // the mutations of the input keep activating the same counters
+        for i in 1..20 {
+            assert_eq!(pool.get_random_index(), Some(PoolStorageIndex::mock(0)));
+            pool.set_parent(Some(PoolStorageIndex::mock(0)));
+            assert!(pool.process(PoolStorageIndex::mock(i), &[(0, 1)], 2.0).is_empty());
+        }
+        let parent = &pool.slab_inputs[parent_key];
+        assert_eq!(parent.number_mutations_tested, 19);
+        assert_eq!(parent.number_successes, 0);
+        assert!(parent.energy < initial_energy);
+
+        // a mutation activates a new counter and is added to the pool
+        assert_eq!(pool.get_random_index(), Some(PoolStorageIndex::mock(0)));
+        pool.set_parent(Some(PoolStorageIndex::mock(0)));
+        assert!(!pool
+            .process(PoolStorageIndex::mock(20), &[(0, 1), (2, 1)], 2.0)
+            .is_empty());
+        assert_eq!(pool.slab_inputs[parent_key].number_successes, 1);
+
+        // the new input is expected to reveal more information than its parent
+        let child_key = pool
+            .slab_inputs
+            .keys()
+            .find(
+                #[no_coverage]
+                |&key| key != parent_key,
+            )
+            .unwrap();
+        let schedule = pool.schedule;
+        assert!(pool.slab_inputs[child_key].rank(schedule) > pool.slab_inputs[parent_key].rank(schedule));
+
+        // the observations are attributed to the mutated input, not to the last input that was chosen
+        pool.get_random_index();
+        pool.set_parent(Some(PoolStorageIndex::mock(20)));
+        pool.process(PoolStorageIndex::mock(21), &[(0, 1)], 3.0);
+        assert_eq!(pool.slab_inputs[parent_key].number_mutations_tested, 20);
+        assert_eq!(pool.slab_inputs[child_key].number_mutations_tested, 1);
+
+        // observations of test cases that are not mutations are not attributed to any input
+        pool.get_random_index();
+        pool.set_parent(None);
+        pool.process(PoolStorageIndex::mock(22), &[(0, 1)], 3.0);
+        assert_eq!(pool.slab_inputs[parent_key].number_mutations_tested, 20);
+        assert_eq!(pool.slab_inputs[child_key].number_mutations_tested, 1);
+    }
+
     #[test]
     #[no_coverage]
     fn property_test() {
@@ -767,7 +1003,7 @@ mod tests {
                     new_counters: new_counters_1,
                 };
                 // println!("adding input of cplx {:.2} with new counters {:?} and existing counters {:?}", cplx1, new_counters_1, existing_counters_1);
-                let _ = pool.add(PoolStorageIndex::mock(0), cplx1, analysis_result);
+                let _ = pool.add(PoolStorageIndex::mock(i), cplx1, analysis_result);
                 // pool.print_recap();
                 pool.sanity_check();
                 assert!(
@@ -0,0 +1,104 @@
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use crate::mutators::grammar::{Grammar, GrammarParser, AST};
+
+/**
+A serializer for the syntax trees generated by a [grammar-based mutator](crate::mutators::grammar).
+
+Only the string represented by the syntax tree is written to the file, such that the corpus can be
+read and edited like any other text file. When a file is read, the syntax tree is reconstructed by
+parsing its content with a [`GrammarParser`]. Files whose content does not match the grammar are
+rejected.
+
+It can serialize values of type [`AST`], as generated by [`grammar_based_ast_mutator`](crate::mutators::grammar::grammar_based_ast_mutator),
+values of type `(String, AST)`, as generated by [`.with_string()`](crate::mutators::grammar::ASTMutator::with_string),
+and values of type `(Vec<u8>, AST)`, as generated by [`.with_bytes()`](crate::mutators::grammar::ASTMutator::with_bytes).
+The syntax trees of grammars containing [bytes](crate::mutators::grammar::byte) are written as raw bytes.
+
+```
+use fuzzcheck::mutators::grammar::{literal_range, repetition, AST};
+use fuzzcheck::{GrammarSerializer, Serializer};
+
+let grammar = repetition(literal_range('a'..='z'), 1..);
+let serializer = GrammarSerializer::<(String, AST)>::new(&grammar, "txt");
+
+let (string, ast) = serializer.from_data(b"hello").unwrap();
+assert_eq!(string, "hello");
+assert_eq!(serializer.to_data(&(string, ast)), b"hello");
+assert!(serializer.from_data(b"Hello").is_none());
+```
+*/
+#[doc(cfg(feature = "grammar_mutator"))]
+pub struct GrammarSerializer<V> {
+    parser: GrammarParser,
+    extension: &'static str,
+    _phantom: PhantomData<V>,
+}
+
+impl<V> GrammarSerializer<V> {
+    /// Create a serializer for the syntax trees of the given grammar. The second argument is the
+    /// extension that the created files should have, such as `"sql"` or `"json"`.
+    #[no_coverage]
+    pub fn new(grammar: &Rc<Grammar>, extension: &'static str) -> Self {
+        Self {
+            parser: GrammarParser::new(grammar),
+            extension,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl crate::traits::Serializer for GrammarSerializer<AST> {
+    type Value = AST;
+
+    #[no_coverage]
+    fn extension(&self) -> &str {
+        self.extension
+    }
+    #[no_coverage]
+    fn from_data(&self, data: &[u8]) -> Option<Self::Value> {
+        self.parser.parse_bytes(data)
+    }
+    #[no_coverage]
+    fn to_data(&self, value: &Self::Value) -> Vec<u8> {
+        value.to_bytes()
+    }
+}
+
+impl crate::traits::Serializer for GrammarSerializer<(String, AST)> {
+    type Value = (String, AST);
+
+    #[no_coverage]
+    fn extension(&self) -> &str {
+        self.extension
+    }
+    #[no_coverage]
+    fn from_data(&self, data: &[u8]) -> Option<Self::Value> {
+        let string = std::str::from_utf8(data).ok()?;
+        let ast = self.parser.parse(string)?;
+        Some((string.to_string(), ast))
+    }
+    #[no_coverage]
+    fn to_data(&self, value: &Self::Value) -> Vec<u8> {
+        value.0.clone().into_bytes()
+    }
+}
+
+impl crate::traits::Serializer for GrammarSerializer<(Vec<u8>, AST)> {
+    type Value = (Vec<u8>, AST);
+
+    #[no_coverage]
+    fn extension(&self) -> &str {
+        self.extension
+    }
+    #[no_coverage]
+    fn from_data(&self, data: &[u8]) -> Option<Self::Value> {
+        let ast = self.parser.parse_bytes(data)?;
+        Some((data.to_vec(), ast))
+    }
+    #[no_coverage]
+    fn to_data(&self, value: &Self::Value) -> Vec<u8> {
+        value.0.clone()
+    }
+}
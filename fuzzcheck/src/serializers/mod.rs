@@ -1,6 +1,6 @@
 //! Types implementing the [Serializer] trait.
 //!
-//! There are currently three implementations:
+//! There are currently four implementations:
 //!
 //! * SerdeSerializer uses the `serde` and `serde_json` crate to serialize
 //! the test inputs (of arbitrary Serializable type) to a `.json` file.
@@ -10,7 +10,13 @@
 //!
 //! * [StringSerializer] encodes and decodes values of any type implementing
 //! `FromStr` and `ToString` into utf-8 encoded text files.
+//!
+//! * GrammarSerializer encodes the syntax trees generated by a grammar-based
+//! mutator as the plain text they represent, and parses the text files back
+//! into syntax trees.
 
+#[cfg(feature = "grammar_mutator")]
+mod grammar_serializer;
 #[cfg(feature = "serde_ron_serializer")]
 mod serde_ron_serializer;
 #[cfg(feature = "serde_json_serializer")]
@@ -19,6 +25,8 @@ mod serde_serializer;
 use std::marker::PhantomData;
 use std::str::FromStr;
 
+#[cfg(feature = "grammar_mutator")]
+pub use grammar_serializer::GrammarSerializer;
 #[cfg(feature = "serde_ron_serializer")]
 pub use serde_ron_serializer::SerdeRonSerializer;
 #[cfg(feature = "serde_json_serializer")]
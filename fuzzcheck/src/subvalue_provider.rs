@@ -189,3 +189,284 @@ where
         }
     }
 }
+
+/// A [`SubValueProvider`](crate::SubValueProvider) giving the subvalues of every
+/// test case in the fuzzer's pool.
+///
+/// Unlike the [`CrossoverSubValueProvider`], which only knows about a single
+/// test case, it indexes the subvalues of the whole corpus by type and
+/// complexity. It is updated incrementally whenever a test case is added to or
+/// removed from the pool. The subvalues themselves are owned by the
+/// `CrossoverSubValueProvider` of each test case, which must outlive its entries
+/// in the index.
+pub(crate) struct CorpusSubValueProvider {
+    identifier: SubValueProviderId,
+    /// For each type, the subvalues sorted by complexity, along with the key of the test case that owns them
+    subvalues: HashMap<TypeId, Vec<(*const dyn Any, f64, usize)>>,
+    /// For each test case, the types of its subvalues
+    types_of_key: HashMap<usize, Vec<TypeId>>,
+    rng: fastrand::Rng,
+}
+impl CorpusSubValueProvider {
+    #[no_coverage]
+    pub(crate) fn new(idx: usize) -> Self {
+        Self {
+            identifier: SubValueProviderId {
+                idx,
+                generation: Generation(0),
+            },
+            subvalues: HashMap::new(),
+            types_of_key: HashMap::new(),
+            rng: fastrand::Rng::new(),
+        }
+    }
+
+    /// Adds the test case and the subvalues of the given provider to the index.
+    ///
+    /// `key` identifies the test case so that its subvalues can be removed later with
+    /// [`self.remove(key)`](CorpusSubValueProvider::remove). The provider must outlive
+    /// its entries in the index.
+    ///
+    /// The subvalues of the provider are already sorted by complexity, so they are merged
+    /// into the index in a single pass for each type.
+    #[no_coverage]
+    pub(crate) fn add<T, M>(&mut self, key: usize, provider: &CrossoverSubValueProvider<T, M>)
+    where
+        T: Clone + 'static,
+        M: Mutator<T>,
+    {
+        let mut new_subvalues = provider
+            .subvalues
+            .iter()
+            .map(
+                #[no_coverage]
+                |(typeid, subvalues)| {
+                    let subvalues = subvalues
+                        .iter()
+                        .map(
+                            #[no_coverage]
+                            |&(subvalue, cplx)| (subvalue, cplx, key),
+                        )
+                        .collect::<Vec<_>>();
+                    (*typeid, subvalues)
+                },
+            )
+            .collect::<HashMap<_, _>>();
+        let whole_value = &provider.immutable_data.0 as &dyn Any as *const dyn Any;
+        let whole_complexity = provider.whole_complexity;
+        let subvalues = new_subvalues.entry(TypeId::of::<T>()).or_default();
+        let idx = subvalues.partition_point(
+            #[no_coverage]
+            |x| x.1 <= whole_complexity,
+        );
+        subvalues.insert(idx, (whole_value, whole_complexity, key));
+
+        let types_of_key = self.types_of_key.entry(key).or_default();
+        for (typeid, new_subvalues) in new_subvalues {
+            let subvalues = self.subvalues.entry(typeid).or_default();
+            *subvalues = Self::merge_by_complexity(std::mem::take(subvalues), new_subvalues);
+            types_of_key.push(typeid);
+        }
+        self.identifier.generation.0 += 1;
+    }
+
+    /// Merges two lists of subvalues sorted by complexity. The subvalues of `a` come before
+    /// those of `b` that have the same complexity.
+    #[no_coverage]
+    fn merge_by_complexity(
+        a: Vec<(*const dyn Any, f64, usize)>,
+        b: Vec<(*const dyn Any, f64, usize)>,
+    ) -> Vec<(*const dyn Any, f64, usize)> {
+        let mut merged = Vec::with_capacity(a.len() + b.len());
+        let mut a = a.into_iter().peekable();
+        let mut b = b.into_iter().peekable();
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            if x.1 <= y.1 {
+                merged.extend(a.next());
+            } else {
+                merged.extend(b.next());
+            }
+        }
+        merged.extend(a);
+        merged.extend(b);
+        merged
+    }
+
+    /// Removes all the subvalues of the test case identified by `key`.
+    ///
+    /// Like [`add`](CorpusSubValueProvider::add), it increments the generation of the
+    /// provider, so that the mutators know that the indices of its subvalues have changed.
+    #[no_coverage]
+    pub(crate) fn remove(&mut self, key: usize) {
+        for typeid in self.types_of_key.remove(&key).unwrap_or_default() {
+            let subvalues = self.subvalues.get_mut(&typeid).unwrap();
+            subvalues.retain(
+                #[no_coverage]
+                |x| x.2 != key,
+            );
+            if subvalues.is_empty() {
+                self.subvalues.remove(&typeid);
+            }
+        }
+        self.identifier.generation.0 += 1;
+    }
+
+    /// The number of subvalues of the given type in the index
+    #[cfg(test)]
+    #[no_coverage]
+    pub(crate) fn len(&self, typeid: TypeId) -> usize {
+        self.subvalues.get(&typeid).map_or(0, Vec::len)
+    }
+}
+impl SubValueProvider for CorpusSubValueProvider {
+    #[no_coverage]
+    fn identifier(&self) -> SubValueProviderId {
+        self.identifier
+    }
+
+    #[no_coverage]
+    fn get_random_subvalue(&self, typeid: TypeId, max_cplx: f64) -> Option<(&dyn Any, f64)> {
+        let subvalues = self.subvalues.get(&typeid)?;
+        let end_index_for_complexity = subvalues.partition_point(
+            #[no_coverage]
+            |x| x.1 < max_cplx,
+        );
+        if end_index_for_complexity == 0 {
+            return None;
+        }
+        let (subvalue, complexity, _) = &subvalues[self.rng.usize(..end_index_for_complexity)];
+        let subvalue = unsafe { subvalue.as_ref() }.unwrap();
+        Some((subvalue, *complexity))
+    }
+
+    #[no_coverage]
+    fn get_subvalue(&self, typeid: TypeId, max_cplx: f64, index: &mut usize) -> Option<(&dyn Any, f64)> {
+        let subvalues = self.subvalues.get(&typeid)?;
+        let (subvalue, complexity, _) = subvalues.get(*index)?;
+        if *complexity < max_cplx {
+            let subvalue = unsafe { subvalue.as_ref() }.unwrap();
+            *index += 1;
+            Some((subvalue, *complexity))
+        } else {
+            // the values are sorted by complexity
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use super::{CorpusSubValueProvider, CrossoverSubValueProvider, Generation, SubValueProvider, SubValueProviderId};
+    use crate::DefaultMutator;
+
+    #[test]
+    #[no_coverage]
+    fn test_corpus_subvalue_provider() {
+        let mutator = <Vec<Vec<u8>>>::default_mutator();
+        let mut corpus = CorpusSubValueProvider::new(usize::MAX);
+        let providers = [vec![vec![1, 2], vec![3]], vec![vec![4, 5, 6]]]
+            .into_iter()
+            .enumerate()
+            .map(|(idx, value)| {
+                let cache = crate::Mutator::validate_value(&mutator, &value).unwrap();
+                let id = SubValueProviderId {
+                    idx,
+                    generation: Generation(idx),
+                };
+                CrossoverSubValueProvider::new(id, &value, &cache, &mutator)
+            })
+            .collect::<Vec<_>>();
+        for (key, provider) in providers.iter().enumerate() {
+            corpus.add(key, provider);
+        }
+        assert_eq!(corpus.len(TypeId::of::<Vec<u8>>()), 3);
+        assert_eq!(corpus.len(TypeId::of::<Vec<Vec<u8>>>()), 2);
+
+        let mut index = 0;
+        let mut complexities = vec![];
+        while let Some((subvalue, cplx)) = corpus.get_subvalue(TypeId::of::<Vec<u8>>(), 1000., &mut index) {
+            assert!(subvalue.downcast_ref::<Vec<u8>>().is_some());
+            complexities.push(cplx);
+        }
+        assert_eq!(complexities.len(), 3);
+        assert!(complexities.windows(2).all(|w| w[0] <= w[1]));
+
+        corpus.remove(0);
+        assert_eq!(corpus.len(TypeId::of::<Vec<u8>>()), 1);
+        let (subvalue, _) = corpus.get_random_subvalue(TypeId::of::<Vec<u8>>(), 1000.).unwrap();
+        assert_eq!(subvalue.downcast_ref::<Vec<u8>>(), Some(&vec![4, 5, 6]));
+        assert!(corpus.get_random_subvalue(TypeId::of::<Vec<u8>>(), 1.).is_none());
+        assert!(corpus.identifier().generation == Generation(3));
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_corpus_subvalue_provider_remove_equal_complexities() {
+        let mutator = <Vec<Vec<u8>>>::default_mutator();
+        let mut corpus = CorpusSubValueProvider::new(usize::MAX);
+        let value = vec![vec![1, 2], vec![3, 4]];
+        let cache = crate::Mutator::validate_value(&mutator, &value).unwrap();
+        let providers = (0..3)
+            .map(|idx| {
+                let id = SubValueProviderId {
+                    idx,
+                    generation: Generation(idx),
+                };
+                CrossoverSubValueProvider::new(id, &value, &cache, &mutator)
+            })
+            .collect::<Vec<_>>();
+        for (key, provider) in providers.iter().enumerate() {
+            corpus.add(key, provider);
+        }
+        assert_eq!(corpus.len(TypeId::of::<Vec<u8>>()), 6);
+
+        corpus.remove(1);
+        corpus.remove(1);
+        assert_eq!(corpus.len(TypeId::of::<Vec<u8>>()), 4);
+        assert_eq!(corpus.len(TypeId::of::<Vec<Vec<u8>>>()), 2);
+        let removed = &providers[1].immutable_data.0 as *const Vec<Vec<u8>> as usize;
+        let removed = removed..removed + std::mem::size_of::<Vec<Vec<u8>>>();
+        let mut index = 0;
+        while let Some((subvalue, _)) = corpus.get_subvalue(TypeId::of::<Vec<Vec<u8>>>(), 1000., &mut index) {
+            assert!(!removed.contains(&(subvalue as *const dyn std::any::Any as *const u8 as usize)));
+        }
+    }
+
+    #[test]
+    #[no_coverage]
+    fn test_corpus_subvalue_provider_stays_sorted() {
+        let mutator = <Vec<Vec<u8>>>::default_mutator();
+        let mut corpus = CorpusSubValueProvider::new(usize::MAX);
+        let providers = (0..20)
+            .map(|idx| {
+                let value = (0..idx % 7).map(|len| vec![idx as u8; len]).collect::<Vec<_>>();
+                let cache = crate::Mutator::validate_value(&mutator, &value).unwrap();
+                let id = SubValueProviderId {
+                    idx,
+                    generation: Generation(idx),
+                };
+                CrossoverSubValueProvider::new(id, &value, &cache, &mutator)
+            })
+            .collect::<Vec<_>>();
+        for (key, provider) in providers.iter().enumerate() {
+            corpus.add(key, provider);
+        }
+        for key in (0..20).step_by(3) {
+            corpus.remove(key);
+        }
+        let expected_len = (0..20).filter(|idx| idx % 3 != 0).map(|idx| idx % 7).sum::<usize>();
+        assert_eq!(corpus.len(TypeId::of::<Vec<u8>>()), expected_len);
+        assert_eq!(corpus.len(TypeId::of::<Vec<Vec<u8>>>()), 13);
+        for typeid in [TypeId::of::<Vec<u8>>(), TypeId::of::<Vec<Vec<u8>>>()] {
+            let mut index = 0;
+            let mut complexities = vec![];
+            while let Some((_, cplx)) = corpus.get_subvalue(typeid, 1000., &mut index) {
+                complexities.push(cplx);
+            }
+            assert_eq!(complexities.len(), corpus.len(typeid));
+            assert!(complexities.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+}
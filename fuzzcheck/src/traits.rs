@@ -313,6 +313,20 @@ pub trait Mutator<Value: Clone + 'static>: 'static {
 
     /// Call the given closure on all subvalues and their complexities.
     fn visit_subvalues<'a>(&self, value: &'a Value, cache: &'a Self::Cache, visit: &mut dyn FnMut(&'a dyn Any, f64));
+
+    /// A short description of the mutation that produced the given [`UnmutateToken`](Mutator::UnmutateToken),
+    /// such as `"insert_element"` or `"crossover_insert_slice"`.
+    ///
+    /// It is used by the fuzzer to record how each test case of the corpus was obtained,
+    /// and to count how many test cases produced by each kind of mutation were added to
+    /// the pool (see the `mutations.csv` file of the stats folder).
+    ///
+    /// By default, it returns `"mutate"`. Mutators that wrap another one should forward
+    /// the call to the inner mutator whenever the mutation was performed by it.
+    #[no_coverage]
+    fn mutation_kind(&self, _token: &Self::UnmutateToken) -> &'static str {
+        "mutate"
+    }
 }
 
 /// A [Serializer] is used to encode and decode test cases into bytes.
@@ -424,6 +438,11 @@ pub trait SensorAndPool: SaveToStatsFolder {
     fn stop_recording(&mut self);
     fn process(&mut self, input_id: PoolStorageIndex, cplx: f64) -> Vec<CorpusDelta>;
     fn get_random_index(&mut self) -> Option<PoolStorageIndex>;
+    /// Notify the pool of the test case from which the next processed test case was mutated, if any.
+    ///
+    /// See [`Pool::set_parent`].
+    #[no_coverage]
+    fn set_parent(&mut self, _parent: Option<PoolStorageIndex>) {}
 }
 impl<A, B> SaveToStatsFolder for (A, B)
 where
@@ -464,6 +483,10 @@ where
     fn get_random_index(&mut self) -> Option<PoolStorageIndex> {
         self.1.get_random_index()
     }
+    #[no_coverage]
+    fn set_parent(&mut self, parent: Option<PoolStorageIndex>) {
+        self.1.set_parent(parent);
+    }
 }
 
 pub enum CSVField {
@@ -548,6 +571,14 @@ pub trait Pool: SaveToStatsFolder {
     /// over others based on their associated observations.
     fn get_random_index(&mut self) -> Option<PoolStorageIndex>;
 
+    /// Notifies the pool that the next test case to be [processed](CompatibleWithObservations::process)
+    /// is a mutation of the given test case, or that it was not obtained by mutating a test case
+    /// of the fuzzer’s storage if `parent` is `None`.
+    ///
+    /// The fuzzer calls this method before every call to `process`. It does nothing by default.
+    #[no_coverage]
+    fn set_parent(&mut self, _parent: Option<PoolStorageIndex>) {}
+
     /// Gives the relative importance of the pool. It must be a positive number.
     ///
     /// The weight of the pool is not used by the fuzzer directly, but can be used
@@ -13,6 +13,7 @@ use fuzzcheck_common::{FuzzerEvent, FuzzerStats};
 use nu_ansi_term::Color;
 
 use crate::fuzzer::{PoolStorageIndex, TerminationStatus};
+use crate::lineage::{InputOrigin, Lineage, LINEAGE_EXTENSION};
 use crate::traits::{CorpusDelta, SaveToStatsFolder, Stats};
 use crate::{CSVField, ToCSV};
 
@@ -22,13 +23,26 @@ impl ToCSV for FuzzerStats {
         vec![
             CSVField::String("nbr_iter".to_string()),
             CSVField::String("iter/s".to_string()),
+            CSVField::String("best_mutation".to_string()),
+            CSVField::String("best_mutation_additions".to_string()),
         ]
     }
     #[no_coverage]
     fn to_csv_record(&self) -> Vec<CSVField> {
+        let best_mutation = self.mutations.most_productive_kind();
         vec![
             CSVField::Integer(self.total_number_of_runs as isize),
             CSVField::Integer(self.exec_per_s as isize),
+            CSVField::String(best_mutation.map_or(
+                String::new(),
+                #[no_coverage]
+                |best| best.kind.to_string(),
+            )),
+            CSVField::Integer(best_mutation.map_or(
+                0,
+                #[no_coverage]
+                |best| best.corpus_additions as isize,
+            )),
         ]
     }
 }
@@ -41,6 +55,8 @@ pub struct World {
     pub corpus: HashMap<(PathBuf, PoolStorageIndex), String>,
     pub stats: Option<RefCell<File>>,
     pub stats_folder: Option<PathBuf>,
+    /// keeps track of how each input in the corpus was obtained
+    lineage: Lineage,
 }
 
 impl World {
@@ -65,6 +81,7 @@ impl World {
             corpus: HashMap::new(),
             stats,
             stats_folder,
+            lineage: Lineage::default(),
         })
     }
 
@@ -93,24 +110,45 @@ impl World {
         content: Vec<u8>,
         deltas: &[CorpusDelta],
         extension: &str,
+        origin: InputOrigin,
     ) -> Result<()> {
+        let lineage = if deltas.iter().any(
+            #[no_coverage]
+            |delta| delta.add,
+        ) {
+            let hash = self.hash(&content);
+            Some(self.lineage.record(hash, Some(idx.0), origin))
+        } else {
+            None
+        };
         for delta in deltas {
             let CorpusDelta { path, add, remove } = delta;
             for to_remove_key in remove {
                 let hash = self.corpus.remove(&(path.to_path_buf(), *to_remove_key)).unwrap();
                 self.remove_from_output_corpus(path, hash.clone(), extension)?;
+                self.remove_from_output_corpus(path, hash, LINEAGE_EXTENSION)?;
             }
 
             if *add {
                 let hash = self.hash(&content);
                 let _old = self.corpus.insert((path.to_path_buf(), idx), hash.clone());
                 self.add_to_output_corpus(path, hash.clone(), content.clone(), extension)?;
+                if let Some(lineage) = &lineage {
+                    self.add_to_output_corpus(path, hash, lineage.clone().into_bytes(), LINEAGE_EXTENSION)?;
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Removes the test case stored at the given key from the provenance tree, once the pool no longer
+    /// refers to it
+    #[no_coverage]
+    pub(crate) fn evict_from_lineage(&mut self, idx: PoolStorageIndex) {
+        self.lineage.remove(idx.0);
+    }
+
     #[no_coverage]
     pub fn add_to_output_corpus(&self, path: &Path, name: String, content: Vec<u8>, extension: &str) -> Result<()> {
         if self.settings.corpus_out.is_none() {
@@ -211,6 +249,12 @@ This should never happen, and is probably a bug in fuzzcheck. Sorry :("#
                 "{} ",
                 Color::Yellow.paint(format!("iter/s {}", fuzzer_stats.exec_per_s))
             );
+            if let Some(best) = fuzzer_stats.mutations.most_productive_kind() && best.corpus_additions > 0 {
+                print!(
+                    "{} ",
+                    Color::Yellow.paint(format!("best {} {}", best.kind, best.corpus_additions))
+                );
+            }
 
             println!();
             let mut stats_fields = vec![CSVField::Integer(time_since_start.as_millis() as isize)];
@@ -264,6 +308,13 @@ This should never happen, and is probably a bug in fuzzcheck. Sorry :("#
             let path = entry.path();
             if path.is_dir() {
                 self.read_input_corpus_rec(&path, values)?;
+            } else if path.extension().map_or(
+                false,
+                #[no_coverage]
+                |extension| extension == LINEAGE_EXTENSION,
+            ) {
+                // sidecar files do not contain inputs
+                continue;
             } else {
                 let data = fs::read(path)?;
                 values.push(data);
@@ -279,7 +330,13 @@ This should never happen, and is probably a bug in fuzzcheck. Sorry :("#
     }
 
     #[no_coverage]
-    pub fn save_artifact(&mut self, content: Vec<u8>, cplx: f64, extension: &str) -> Result<()> {
+    pub(crate) fn save_artifact(
+        &mut self,
+        content: Vec<u8>,
+        cplx: f64,
+        extension: &str,
+        origin: Option<InputOrigin>,
+    ) -> Result<()> {
         let artifacts_folder = self.settings.artifacts_folder.as_ref();
         if artifacts_folder.is_none() {
             return Ok(());
@@ -302,6 +359,10 @@ This should never happen, and is probably a bug in fuzzcheck. Sorry :("#
 
         let path = artifacts_folder.join(&name).with_extension(extension);
         fs::write(&path, &content)?;
+        if let Some(origin) = origin {
+            let lineage = self.lineage.record(format!("{:x}", hash), None, origin);
+            fs::write(artifacts_folder.join(&name).with_extension(LINEAGE_EXTENSION), lineage)?;
+        }
         println!("Failing test case found. Saving at {:?}", path);
 
         Result::Ok(())
@@ -327,13 +388,16 @@ This should never happen, and is probably a bug in fuzzcheck. Sorry :("#
 impl SaveToStatsFolder for World {
     #[no_coverage]
     fn save_to_stats_folder(&self) -> Vec<(PathBuf, Vec<u8>)> {
+        let mut contents = vec![
+            (PathBuf::new().join("lineage.json"), self.lineage.to_json().into_bytes()),
+            (PathBuf::new().join("lineage.dot"), self.lineage.to_dot().into_bytes()),
+        ];
         cfg_if::cfg_if! {
             if #[cfg(feature = "serde_json_serializer")] {
                 let content = serde_json::to_vec(&self.corpus.iter().collect::<Vec<_>>()).unwrap();
-                vec![(PathBuf::new().join("world.json"), content)]
-            } else {
-                vec![]
+                contents.push((PathBuf::new().join("world.json"), content));
             }
         }
+        contents
     }
 }
@@ -2,7 +2,10 @@ use std::ops::RangeInclusive;
 
 use fuzzcheck::mutators::alternation::AlternationMutator;
 use fuzzcheck::mutators::char::CharWithinRangeMutator;
+use fuzzcheck::mutators::integer::U8Mutator;
 use fuzzcheck::mutators::testing_utilities::test_mutator;
+use fuzzcheck::mutators::vector::VecMutator;
+use fuzzcheck::Mutator;
 
 fn test_alternation_char_helper(ranges: impl IntoIterator<Item = RangeInclusive<char>> + Clone) {
     let m = AlternationMutator::new(
@@ -28,3 +31,20 @@ fn test_alternation_char() {
     // a possibility for all three first choices.
     // test_alternation_char_helper(['a'..='z', 'a'..='b', 'a'..='c', '0'..='9', '0'..='5']);
 }
+
+#[test]
+fn test_alternation_random_arbitrary_within_max_cplx() {
+    let m = AlternationMutator::new(
+        vec![
+            VecMutator::new(U8Mutator::default(), 5..=10),
+            VecMutator::new(U8Mutator::default(), 0..=2),
+        ],
+        0.0,
+    );
+    m.initialize();
+    // the first mutator cannot generate values within the maximum complexity
+    for _ in 0..100 {
+        let (x, _) = m.random_arbitrary(30.0);
+        assert!(x.len() <= 2, "{:?}", x);
+    }
+}
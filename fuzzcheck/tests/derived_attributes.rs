@@ -0,0 +1,144 @@
+#![allow(unused_attributes)]
+#![feature(no_coverage)]
+use fuzzcheck::mutators::testing_utilities::test_mutator;
+use fuzzcheck::{DefaultMutator, Mutator};
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Version {
+    V1,
+    V2,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+struct Packet {
+    #[fuzzcheck(range = 0..=100)]
+    priority: u8,
+    #[fuzzcheck(range = -10..10)]
+    offset: i32,
+    #[fuzzcheck(len = 1..=8)]
+    payload: Vec<bool>,
+    #[fuzzcheck(len = 2..=4, range = 10..=20)]
+    ports: Vec<u16>,
+    #[fuzzcheck(fixed = Version::V2)]
+    version: Version,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+enum Message {
+    #[fuzzcheck(weight = 0.01)]
+    Ping,
+    Data(#[fuzzcheck(range = 1..=3)] u8),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+enum Level {
+    Low,
+    #[fuzzcheck(weight = 10)]
+    High,
+}
+
+#[test]
+fn test_derived_attributes_struct() {
+    let mutator = Packet::default_mutator();
+    for _ in 0..1000 {
+        let (value, _) = mutator.random_arbitrary(1000.);
+        assert!(value.priority <= 100);
+        assert!((-10..10).contains(&value.offset));
+        assert!((1..=8).contains(&value.payload.len()));
+        assert!((2..=4).contains(&value.ports.len()));
+        assert!(value.ports.iter().all(|port| (10..=20).contains(port)));
+        assert_eq!(value.version, Version::V2);
+    }
+    test_mutator(mutator, 1000., 1000., false, true, 100, 100);
+}
+
+#[test]
+fn test_derived_attributes_weights() {
+    let mutator = Message::default_mutator();
+    let nbr_pings = (0..1000)
+        .filter(|_| mutator.random_arbitrary(1000.).0 == Message::Ping)
+        .count();
+    assert!(nbr_pings < 100, "{nbr_pings}");
+    // the ordered values also favour the heavier variant, until it runs out of values
+    mutator.initialize();
+    let mut step = mutator.default_arbitrary_step();
+    let ordered = (0..4)
+        .map(|_| mutator.ordered_arbitrary(&mut step, 1000.).unwrap().0)
+        .collect::<Vec<_>>();
+    assert!(ordered[..3].iter().all(|value| matches!(value, Message::Data(_))));
+    assert_eq!(ordered[3], Message::Ping);
+    test_mutator(mutator, 1000., 1000., false, true, 100, 100);
+
+    let mutator = Level::default_mutator();
+    let nbr_high = (0..1000)
+        .filter(|_| {
+            let (value, _): (Level, _) = mutator.random_arbitrary(1000.);
+            value == Level::High
+        })
+        .count();
+    assert!(nbr_high > 800, "{nbr_high}");
+    let mut step = Mutator::<Level>::default_arbitrary_step(&mutator);
+    let (first, _): (Level, _) = mutator.ordered_arbitrary(&mut step, 1000.).unwrap();
+    assert_eq!(first, Level::High);
+    let mut value = Level::High;
+    let mut step = Mutator::<Level>::default_mutation_step(&mutator, &value, &());
+    mutator
+        .ordered_mutate(
+            &mut value,
+            &mut (),
+            &mut step,
+            &fuzzcheck::subvalue_provider::EmptySubValueProvider,
+            1000.,
+        )
+        .unwrap();
+    assert_eq!(value, Level::Low);
+    test_mutator::<Level, _>(mutator, 1000., 1000., false, true, 100, 100);
+}
+
+#[cfg(feature = "regex_grammar")]
+#[test]
+fn test_derived_attributes_regex() {
+    #[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+    struct Email {
+        #[fuzzcheck(regex = "[a-z]{1,8}@[a-z]{1,8}\\.(com|org)")]
+        address: String,
+    }
+    let is_valid = |email: &Email| {
+        let (user, domain) = email.address.split_once('@').unwrap();
+        !user.is_empty()
+            && user.chars().all(|c| c.is_ascii_lowercase())
+            && (domain.ends_with(".com") || domain.ends_with(".org"))
+    };
+    let mutator = Email::default_mutator();
+    mutator.initialize();
+    for _ in 0..100 {
+        let (value, _) = mutator.random_arbitrary(1000.);
+        assert!(is_valid(&value));
+    }
+
+    // existing values, such as those of a corpus, are parsed back
+    let invalid = Email {
+        address: "Jane@example.net".to_string(),
+    };
+    assert!(mutator.validate_value(&invalid).is_none());
+    let mut value = Email {
+        address: "jane@example.org".to_string(),
+    };
+    let mut cache = mutator.validate_value(&value).unwrap();
+    let mut step = mutator.default_mutation_step(&value, &cache);
+    for _ in 0..100 {
+        let (token, _) = mutator
+            .ordered_mutate(
+                &mut value,
+                &mut cache,
+                &mut step,
+                &fuzzcheck::subvalue_provider::EmptySubValueProvider,
+                1000.,
+            )
+            .unwrap();
+        assert!(is_valid(&value), "{value:?}");
+        mutator.unmutate(&mut value, &mut cache, token);
+        assert_eq!(value.address, "jane@example.org");
+    }
+}
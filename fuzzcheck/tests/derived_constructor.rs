@@ -0,0 +1,191 @@
+#![allow(unused_attributes)]
+#![feature(no_coverage)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use fuzzcheck::mutators::testing_utilities::test_mutator;
+use fuzzcheck::{DefaultMutator, Mutator};
+
+/// A range whose start is never greater than its end
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+#[fuzzcheck(constructor = SortedRange::new)]
+struct SortedRange {
+    start: u8,
+    end: u8,
+}
+impl SortedRange {
+    fn new(a: u8, b: u8) -> Self {
+        Self {
+            start: a.min(b),
+            end: a.max(b),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+#[fuzzcheck(constructor = NonEmpty::<T>::new)]
+struct NonEmpty<T> {
+    first: T,
+    rest: Vec<T>,
+}
+impl<T> NonEmpty<T> {
+    fn new(first: T, rest: Vec<T>) -> Self {
+        Self { first, rest }
+    }
+}
+
+/// A square whose area is always the square of its side
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+#[fuzzcheck(constructor = Square::from_side, args = (u8,), parse = Square::side)]
+struct Square {
+    side: u16,
+    area: u32,
+}
+impl Square {
+    fn from_side(side: u8) -> Self {
+        Self {
+            side: side as u16,
+            area: (side as u32).pow(2),
+        }
+    }
+    fn side(&self) -> Option<(u8,)> {
+        u8::try_from(self.side).ok().map(|side| (side,))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+#[fuzzcheck(try_constructor = Even::new)]
+struct Even {
+    value: u8,
+}
+impl Even {
+    fn new(value: u8) -> Option<Self> {
+        (value % 2 == 0).then_some(Self { value })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+#[fuzzcheck(try_constructor = Ratio::new, args = (u8, u8), parse = Ratio::parts)]
+struct Ratio {
+    numerator: u8,
+    denominator: u8,
+}
+impl Ratio {
+    fn new(numerator: u8, denominator: u8) -> Result<Self, &'static str> {
+        if denominator == 0 {
+            Err("the denominator is zero")
+        } else {
+            Ok(Self { numerator, denominator })
+        }
+    }
+    fn parts(&self) -> Option<(u8, u8)> {
+        Some((self.numerator, self.denominator))
+    }
+}
+
+static NBR_CALLS_TO_COUNTED_NEW: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
+#[fuzzcheck(try_constructor = Counted::new)]
+struct Counted {
+    value: u8,
+}
+impl Counted {
+    fn new(value: u8) -> Option<Self> {
+        NBR_CALLS_TO_COUNTED_NEW.fetch_add(1, Ordering::SeqCst);
+        Some(Self { value })
+    }
+}
+
+#[test]
+fn test_derived_constructor() {
+    let mutator = SortedRange::default_mutator();
+    for _ in 0..1000 {
+        let (value, _) = mutator.random_arbitrary(1000.);
+        assert!(value.start <= value.end);
+    }
+    test_mutator(mutator, 1000., 1000., false, true, 100, 100);
+
+    let mutator = NonEmpty::<bool>::default_mutator();
+    test_mutator(mutator, 1000., 1000., false, true, 100, 100);
+}
+
+#[test]
+fn test_derived_constructor_mutations_keep_invariants() {
+    let mutator = SortedRange::default_mutator();
+    let mut value = SortedRange::new(200, 10);
+    let mut cache = mutator.validate_value(&value).unwrap();
+    for _ in 0..1000 {
+        let original = value.clone();
+        let (token, _) = mutator.random_mutate(&mut value, &mut cache, 1000.);
+        assert!(value.start <= value.end);
+        mutator.unmutate(&mut value, &mut cache, token);
+        assert_eq!(value, original);
+        let _ = mutator.random_mutate(&mut value, &mut cache, 1000.);
+    }
+}
+
+#[test]
+fn test_derived_constructor_with_args() {
+    let mutator = Square::default_mutator();
+    for _ in 0..1000 {
+        let (value, _) = mutator.random_arbitrary(1000.);
+        assert_eq!(value.area, (value.side as u32).pow(2));
+    }
+    assert!(mutator
+        .validate_value(&Square {
+            side: 300,
+            area: 90_000
+        })
+        .is_none());
+    test_mutator(mutator, 1000., 1000., false, true, 100, 100);
+}
+
+#[test]
+fn test_derived_fallible_constructor() {
+    let mutator = Even::default_mutator();
+    let mut value = Even::new(4).unwrap();
+    let mut cache = mutator.validate_value(&value).unwrap();
+    for _ in 0..1000 {
+        let (arbitrary, _) = mutator.random_arbitrary(1000.);
+        assert_eq!(arbitrary.value % 2, 0);
+        let (token, _) = mutator.random_mutate(&mut value, &mut cache, 1000.);
+        assert_eq!(value.value % 2, 0);
+        mutator.unmutate(&mut value, &mut cache, token);
+    }
+    assert!(mutator.validate_value(&Even { value: 3 }).is_none());
+    test_mutator(mutator, 1000., 1000., false, true, 100, 100);
+
+    let mutator = Ratio::default_mutator();
+    for _ in 0..1000 {
+        let (value, _) = mutator.random_arbitrary(1000.);
+        assert_ne!(value.denominator, 0);
+    }
+    assert!(mutator
+        .validate_value(&Ratio {
+            numerator: 1,
+            denominator: 0
+        })
+        .is_none());
+    test_mutator(mutator, 1000., 1000., false, true, 100, 100);
+}
+
+#[test]
+fn test_derived_fallible_constructor_is_called_once_per_value() {
+    let mutator = Counted::default_mutator();
+    let mut value = Counted::new(0).unwrap();
+    let mut cache = mutator.validate_value(&value).unwrap();
+
+    NBR_CALLS_TO_COUNTED_NEW.store(0, Ordering::SeqCst);
+    for _ in 0..100 {
+        let _ = mutator.random_arbitrary(1000.);
+    }
+    assert_eq!(NBR_CALLS_TO_COUNTED_NEW.load(Ordering::SeqCst), 100);
+
+    NBR_CALLS_TO_COUNTED_NEW.store(0, Ordering::SeqCst);
+    for _ in 0..100 {
+        let (token, _) = mutator.random_mutate(&mut value, &mut cache, 1000.);
+        mutator.unmutate(&mut value, &mut cache, token);
+    }
+    // unmutating restores the previous value without calling the constructor again
+    assert_eq!(NBR_CALLS_TO_COUNTED_NEW.load(Ordering::SeqCst), 100);
+}
@@ -6,12 +6,15 @@
 use std::fmt::Debug;
 
 use fuzzcheck::mutators::boxed::BoxMutator;
+use fuzzcheck::mutators::conformance::{ConformanceChecker, Requirement};
 use fuzzcheck::mutators::integer::U8Mutator;
+use fuzzcheck::mutators::map::MapMutator;
 use fuzzcheck::mutators::option::OptionMutator;
-use fuzzcheck::mutators::recursive::RecurToMutator;
+use fuzzcheck::mutators::recursive::{RecurToMutator, SizeControl};
 use fuzzcheck::mutators::testing_utilities::test_mutator;
 use fuzzcheck::mutators::tuples::{Tuple2, Tuple2Mutator, TupleMutatorWrapper};
 use fuzzcheck::mutators::vector::VecMutator;
+use fuzzcheck::subvalue_provider::EmptySubValueProvider;
 use fuzzcheck::{make_mutator, DefaultMutator, Mutator};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -97,3 +100,60 @@ fn test_derived_struct() {
     let mutator = <Vec<SampleStruct<u8, u8>>>::default_mutator();
     test_mutator(mutator, 500., 500., false, true, 50, 100);
 }
+
+fn depth<T, U>(x: &SampleStruct<T, U>) -> usize {
+    let w = x.w.as_ref().map_or(0, |w| 1 + depth(w));
+    let z = x.z.iter().map(|(_, x)| 1 + depth(x)).max().unwrap_or(0);
+    std::cmp::max(w, z)
+}
+
+#[test]
+fn test_size_control() {
+    let size_control = SizeControl::default().target_complexity(100.).max_depth(3);
+    let mutator = <SampleStruct<u8, u8>>::default_mutator().with_size_control(size_control);
+    mutator.initialize();
+    for _ in 0..1000 {
+        let (x, _) = mutator.random_arbitrary(10_000.);
+        assert!(depth(&x) <= 3, "{:?}", x);
+    }
+    // the depth is counted from the root of the value when mutating its deepest parts, but the
+    // mutators only respect the maximum complexity on a best-effort basis, so that a mutation at
+    // the maximum depth may still add a point of recursion of minimal complexity
+    for _ in 0..100 {
+        let (mut x, _) = mutator.random_arbitrary(10_000.);
+        let mut cache = mutator.validate_value(&x).unwrap();
+        let mut step = mutator.default_mutation_step(&x, &cache);
+        for _ in 0..100 {
+            let (token, _) = mutator.random_mutate(&mut x, &mut cache, 10_000.);
+            assert!(depth(&x) <= 4, "{:?}", x);
+            mutator.unmutate(&mut x, &mut cache, token);
+            if let Some((token, _)) =
+                mutator.ordered_mutate(&mut x, &mut cache, &mut step, &EmptySubValueProvider, 10_000.)
+            {
+                assert!(depth(&x) <= 4, "{:?}", x);
+                mutator.unmutate(&mut x, &mut cache, token);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_conformance_of_recursive_struct() {
+    let checker = ConformanceChecker::default()
+        .max_complexity(200.)
+        .arbitraries(30)
+        .mutations(30)
+        .check_repetitions(false)
+        .check_max_complexity(false);
+    checker.check(&<SampleStruct<u8, u8>>::default_mutator()).unwrap();
+
+    // the nested values are given the complexity of the inner mutator instead of the outer one
+    let mutator = MapMutator::new(
+        <SampleStruct<u8, u8>>::default_mutator(),
+        |x: &SampleStruct<u8, u8>| Some(x.clone()),
+        |x: &SampleStruct<u8, u8>| x.clone(),
+        |_x: &SampleStruct<u8, u8>, cplx: f64| cplx + 1.0,
+    );
+    let violation = checker.check(&mutator).unwrap_err();
+    assert_eq!(violation.requirement, Requirement::Subvalues);
+}
@@ -1,7 +1,7 @@
 #![allow(unused_attributes)]
 #![feature(no_coverage)]
 use fuzzcheck::mutators::testing_utilities::test_mutator;
-use fuzzcheck::DefaultMutator;
+use fuzzcheck::{DefaultMutator, Mutator};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, DefaultMutator)]
 struct SampleStruct<T, U> {
@@ -16,3 +16,15 @@ fn test_derived_struct() {
     let mutator = <Vec<SampleStruct<u8, u8>>>::default_mutator();
     test_mutator(mutator, 1000., 1000., false, true, 100, 100);
 }
+
+#[test]
+fn test_derived_struct_mutation_kind() {
+    let mutator = SampleStruct::<u8, char>::default_mutator();
+    let mut value = SampleStruct { x: 1, y: 'a' };
+    let mut cache = mutator.validate_value(&value).unwrap();
+    for _ in 0..100 {
+        let (token, _) = mutator.random_mutate(&mut value, &mut cache, 1000.);
+        assert!(["mutate_integer", "mutate_char"].contains(&mutator.mutation_kind(&token)));
+        mutator.unmutate(&mut value, &mut cache, token);
+    }
+}
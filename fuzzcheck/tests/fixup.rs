@@ -0,0 +1,124 @@
+use fuzzcheck::mutators::integer_within_range::U8WithinRangeMutator;
+use fuzzcheck::mutators::testing_utilities::test_mutator;
+use fuzzcheck::{DefaultMutator, Mutator, MutatorExt};
+
+fn checksum(payload: &[u8]) -> u8 {
+    payload.iter().fold(0u8, |sum, x| sum.wrapping_add(*x))
+}
+
+/// A message made of a length byte, a payload, and a checksum byte
+fn fix_message(msg: &mut Vec<u8>) {
+    msg.truncate(255);
+    if msg.len() < 2 {
+        msg.resize(2, 0);
+    }
+    let len = msg.len();
+    msg[0] = len as u8;
+    msg[len - 1] = checksum(&msg[1..len - 1]);
+}
+
+fn is_valid_message(msg: &[u8]) -> bool {
+    msg.len() >= 2 && msg[0] as usize == msg.len() && msg[msg.len() - 1] == checksum(&msg[1..msg.len() - 1])
+}
+
+#[test]
+fn test_fixup_mutator() {
+    let m = <Vec<u8>>::default_mutator().fixup(fix_message);
+    test_mutator(m, 1000., 1000., false, true, 100, 100);
+
+    let m = <Vec<u8>>::default_mutator().fixup(fix_message);
+    for _ in 0..100 {
+        let (mut x, cplx) = m.random_arbitrary(1000.);
+        assert!(is_valid_message(&x));
+        let mut cache = m.validate_value(&x).unwrap();
+        assert_eq!(cplx, m.complexity(&x, &cache));
+        let mut step = m.default_mutation_step(&x, &cache);
+        for _ in 0..100 {
+            let original = x.clone();
+            let (token, _) = m.random_mutate(&mut x, &mut cache, 1000.);
+            assert!(is_valid_message(&x));
+            m.unmutate(&mut x, &mut cache, token);
+            assert_eq!(x, original);
+            if let Some((token, _)) = m.ordered_mutate(
+                &mut x,
+                &mut cache,
+                &mut step,
+                &fuzzcheck::subvalue_provider::EmptySubValueProvider,
+                1000.,
+            ) {
+                assert!(is_valid_message(&x));
+                m.unmutate(&mut x, &mut cache, token);
+                assert_eq!(x, original);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_fixup_mutator_subvalues() {
+    let m = <Vec<u8>>::default_mutator().fixup(fix_message);
+    for _ in 0..100 {
+        let (mut x, _) = m.random_arbitrary(1000.);
+        let mut cache = m.validate_value(&x).unwrap();
+        let (_, _) = m.random_mutate(&mut x, &mut cache, 1000.);
+        // crossover mutations only see the elements of the repaired message
+        let mut subvalues = vec![];
+        m.visit_subvalues(&x, &cache, &mut |subvalue, _| {
+            subvalues.push(*subvalue.downcast_ref::<u8>().unwrap());
+        });
+        subvalues.sort_unstable();
+        let mut elements = x.clone();
+        elements.sort_unstable();
+        assert_eq!(subvalues, elements);
+    }
+}
+
+#[test]
+fn test_fixup_mutator_skips_invalid_repairs() {
+    // the doubled values above 10 are not valid values of the underlying mutator
+    let m = U8WithinRangeMutator::new(0..=10).fixup(|x: &mut u8| *x *= 2);
+    for _ in 0..100 {
+        let (mut x, _) = m.random_arbitrary(10.);
+        assert!(x <= 10 && x % 2 == 0);
+        let mut cache = m.validate_value(&x).unwrap();
+        let (token, _) = m.random_mutate(&mut x, &mut cache, 10.);
+        assert!(x <= 10 && x % 2 == 0);
+        m.unmutate(&mut x, &mut cache, token);
+    }
+}
+
+#[test]
+fn test_fixup_mutator_respects_max_complexity_after_repair() {
+    // the repair doubles the length of the vector, and thus roughly its complexity
+    let m = <Vec<u8>>::default_mutator().fixup(|x: &mut Vec<u8>| x.extend(x.clone()));
+    let max_cplx = 33.;
+    let mut step = m.default_arbitrary_step();
+    let mut nbr_arbitraries = 0;
+    while let Some((mut x, cplx)) = m.ordered_arbitrary(&mut step, max_cplx) {
+        nbr_arbitraries += 1;
+        if nbr_arbitraries > 100 {
+            break;
+        }
+        assert!(cplx <= max_cplx);
+        let mut cache = m.validate_value(&x).unwrap();
+        let mut mutation_step = m.default_mutation_step(&x, &cache);
+        for _ in 0..100 {
+            let original = x.clone();
+            if let Some((token, cplx)) = m.ordered_mutate(
+                &mut x,
+                &mut cache,
+                &mut mutation_step,
+                &fuzzcheck::subvalue_provider::EmptySubValueProvider,
+                max_cplx,
+            ) {
+                assert!(cplx <= max_cplx);
+                m.unmutate(&mut x, &mut cache, token);
+                assert_eq!(x, original);
+            }
+        }
+    }
+
+    // the random methods can still exceed the maximum complexity, but rarely do
+    let nbr_too_complex = (0..100).filter(|_| m.random_arbitrary(max_cplx).1 > max_cplx).count();
+    assert!(nbr_too_complex < 10);
+}
@@ -5,8 +5,10 @@
 use std::rc::{Rc, Weak};
 
 use fuzzcheck::mutators::grammar::*;
+use fuzzcheck::mutators::recursive::SizeControl;
 use fuzzcheck::mutators::testing_utilities::test_mutator;
 // use fuzzcheck::{DefaultMutator, Mutator};
+use fuzzcheck::Mutator;
 
 #[no_coverage]
 fn text() -> Rc<Grammar> {
@@ -182,3 +184,178 @@ fn test_grammar_based_ast_mutator() {
     let mutator = grammar_based_ast_mutator(markdown());
     test_mutator(mutator, 500., 500., false, true, 60, 100);
 }
+
+#[test]
+fn test_parse_markdown() {
+    let grammar = markdown();
+    let parser = GrammarParser::new(&grammar);
+    let document = "# Fuzzcheck\n\nA *structure-aware* fuzzer, see [the guide](https://fuzzcheck.neocities.org).\n\n- one\n- two\n";
+    let ast = parser.parse(document).unwrap();
+    assert_eq!(ast.to_string(), document);
+    assert!(grammar_based_ast_mutator(grammar.clone())
+        .validate_value(&ast)
+        .is_some());
+
+    let mutator = string_from_grammar(grammar);
+    assert!(mutator.validate_value(&document.to_string()).is_some());
+}
+
+#[no_coverage]
+fn arithmetic() -> Rc<Grammar> {
+    let number = rule("number", repetition(literal_range('0'..='9'), 1..4));
+    recursive(|expr| {
+        rule(
+            "expr",
+            alternation([
+                number.clone(),
+                concatenation([
+                    literal('('),
+                    recurse(expr),
+                    rule("op", regex("[+*]")),
+                    number.clone(),
+                    literal(')'),
+                ]),
+            ]),
+        )
+    })
+}
+
+#[test]
+fn test_named_rules() {
+    let grammar = arithmetic();
+    let parser = GrammarParser::new(&grammar);
+    let ast = parser.parse("((12+3)*4)").unwrap();
+    assert_eq!(ast.rule(), Some(("expr", 1)));
+    let numbers = ast
+        .find_rules("number")
+        .into_iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(numbers, ["12", "3", "4"]);
+    let operators = ast
+        .find_rules("op")
+        .into_iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(operators, ["+", "*"]);
+    let exprs = ast.find_rules("expr");
+    assert_eq!(exprs.len(), 3);
+    assert_eq!(exprs[2].rule(), Some(("expr", 0)));
+
+    let mutator = grammar_based_ast_mutator(grammar);
+    assert!(mutator.validate_value(&ast).is_some());
+    // the rule names are part of the syntax tree
+    let renamed = AST::Rule {
+        name: "number".to_string(),
+        alternative: 1,
+        ast: Box::new(ast.children()[0].clone()),
+    };
+    assert!(mutator.validate_value(&renamed).is_none());
+
+    test_mutator(mutator, 200., 200., false, true, 50, 50);
+}
+
+#[test]
+fn test_byte_grammar() {
+    let grammar = concatenation([regex(r"(?-u)\xFF[\x00-\x7F]+"), literal('é')]);
+    let mutator = bytes_from_grammar(grammar.clone());
+    assert!(mutator.validate_value(&vec![0xff, 0x00, 0x7f, 0xc3, 0xa9]).is_some());
+    assert!(mutator.validate_value(&vec![0xff, 0x80, 0xc3, 0xa9]).is_none());
+    assert!(mutator.validate_value(&vec![0xff, 0x00, 0xe9]).is_none());
+
+    let mutator = grammar_based_ast_mutator(grammar).with_bytes();
+    mutator.initialize();
+    for _ in 0..100 {
+        let ((bytes, ast), _) = mutator.random_arbitrary(100.);
+        assert_eq!(bytes, ast.to_bytes());
+        assert_eq!(bytes[0], 0xff);
+        assert_eq!(&bytes[bytes.len() - 2..], "é".as_bytes());
+        assert!(bytes[1..bytes.len() - 2].iter().all(|b| b.is_ascii()));
+    }
+    test_mutator(mutator, 100., 100., false, true, 50, 50);
+}
+
+#[test]
+fn test_declared_before_used() {
+    let name = repetition(literal_range('a'..='c'), 1..3);
+    let statement = alternation([
+        concatenation([regex("let "), rule("declared", name.clone()), literal(';')]),
+        concatenation([regex("use "), rule("used", name), literal(';')]),
+    ]);
+    let grammar = repetition(statement, 0..10);
+    let constraints = GrammarConstraints::new(&grammar)
+        .declaration("declared", "variables")
+        .reference("used", "variables");
+    let mutator = grammar_based_ast_mutator(grammar.clone()).with_constraints(constraints);
+    mutator.initialize();
+
+    let parser = GrammarParser::new(&grammar);
+    for _ in 0..100 {
+        let (ast, _) = mutator.random_arbitrary(100.);
+        // the repaired syntax tree still matches the grammar
+        assert!(parser.parse(&ast.to_string()).is_some());
+        let mut declared = vec![];
+        for statement in ast.children() {
+            let string = statement.to_string();
+            if let Some(variable) = string.strip_prefix("let ") {
+                declared.push(variable.to_string());
+            } else if !declared.is_empty() {
+                let variable = string.strip_prefix("use ").unwrap().to_string();
+                assert!(declared.contains(&variable), "{}", ast.to_string());
+            }
+        }
+    }
+
+    // crossover mutations only see the subtrees of the repaired syntax tree
+    let (mut ast, _) = mutator.random_arbitrary(100.);
+    let mut cache = mutator.validate_value(&ast).unwrap();
+    for _ in 0..100 {
+        let (token, _) = mutator.random_mutate(&mut ast, &mut cache, 100.);
+        let used = ast
+            .find_rules("used")
+            .into_iter()
+            .map(|used| used.to_string())
+            .collect::<Vec<_>>();
+        mutator.visit_subvalues(&ast, &cache, &mut |subvalue, _| {
+            if let Some(subvalue) = subvalue.downcast_ref::<AST>() {
+                if matches!(subvalue.rule(), Some(("used", _))) {
+                    assert!(used.contains(&subvalue.to_string()));
+                }
+            }
+        });
+        mutator.unmutate(&mut ast, &mut cache, token);
+    }
+    test_mutator(mutator.with_string(), 100., 100., false, true, 50, 50);
+}
+
+#[test]
+fn test_size_control() {
+    // doubly recursive grammars explode without size control
+    let tree = recursive(|tree| {
+        alternation([
+            literal('.'),
+            concatenation([literal('('), recurse(tree), recurse(tree), literal(')')]),
+        ])
+    });
+    let size_control = SizeControl::default().target_complexity(50.).max_depth(5);
+    let mutator = grammar_based_ast_mutator_with_size_control(tree, size_control);
+    mutator.initialize();
+    let mut total_cplx = 0.;
+    for _ in 0..1000 {
+        let (ast, cplx) = mutator.random_arbitrary(1000.);
+        total_cplx += cplx;
+        let mut depth = 0;
+        let mut max_depth = 0;
+        for c in ast.to_string().chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            max_depth = std::cmp::max(depth, max_depth);
+        }
+        assert!(max_depth <= 5, "{}", ast.to_string());
+    }
+    assert!(total_cplx / 1000. < 200.);
+    test_mutator(mutator, 200., 200., false, true, 50, 50);
+}
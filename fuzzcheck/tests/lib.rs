@@ -5,12 +5,15 @@ mod alternation_char_mutators;
 mod char_mutators;
 mod const_generics;
 mod constrained_integer;
+mod derived_attributes;
+mod derived_constructor;
 mod derived_mutually_recursive_structs;
 mod derived_recursive_struct;
 mod derived_recursive_struct_fully_custom;
 mod derived_struct;
 mod enum_with_ignored_variant;
 mod expansions;
+mod fixup;
 #[cfg(feature = "regex_grammar")]
 mod grammar_based_mutators;
 mod option;
@@ -0,0 +1,32 @@
+//! The counting allocator replaces the global allocator of the whole binary, so this test has
+//! its own target without the default test harness.
+#![allow(unused_attributes)]
+#![feature(no_coverage)]
+
+use std::alloc::System;
+
+use fuzzcheck::mutators::benchmark::MutatorBenchmark;
+use fuzzcheck::sensors_and_pools::CountingAllocator;
+use fuzzcheck::DefaultMutator;
+
+#[global_allocator]
+static ALLOC: CountingAllocator<System> = CountingAllocator(System);
+
+#[no_coverage]
+fn main() {
+    test_benchmark_counts_allocations();
+}
+
+#[no_coverage]
+fn test_benchmark_counts_allocations() {
+    let mutator = <Vec<Vec<u8>>>::default_mutator();
+    let report = MutatorBenchmark::default()
+        .max_complexity(500.0)
+        .arbitraries(20)
+        .mutations(20)
+        .run(&mutator);
+    assert!(report.counts_allocations);
+    assert!(report.arbitrary.nbr_allocations > 0);
+    assert!(report.arbitrary.allocated_bytes > 0);
+    assert!(report.mutate.nbr_calls > 0);
+}
@@ -1,10 +1,46 @@
+use fuzzcheck::mutators::bytes::BytesMutator;
 use fuzzcheck::mutators::integer::U8Mutator;
 use fuzzcheck::mutators::vector::VecMutator;
+use fuzzcheck::Mutator;
+
 #[test]
 fn test_vector_mutator() {
     let m = VecMutator::new(VecMutator::new(U8Mutator::default(), 0..=usize::MAX), 0..=usize::MAX);
     fuzzcheck::mutators::testing_utilities::test_mutator(m, 500.0, 500.0, false, true, 100, 150);
 }
+#[test]
+fn test_bytes_mutator() {
+    let m = BytesMutator::new(0..=usize::MAX);
+    fuzzcheck::mutators::testing_utilities::test_mutator(m, 500.0, 500.0, false, true, 100, 1000);
+    let m = BytesMutator::new_without_deterministic_stages(2..=10);
+    fuzzcheck::mutators::testing_utilities::test_mutator(m, 500.0, 500.0, false, true, 100, 1000);
+}
+
+#[test]
+fn test_vector_mutation_kind() {
+    let m = Box::new(VecMutator::new(U8Mutator::default(), 0..=usize::MAX));
+    let mut value = vec![1, 2, 3];
+    let mut cache = m.validate_value(&value).unwrap();
+    let kinds = [
+        "copy_element",
+        "remove",
+        "mutate_element",
+        "insert_element",
+        "swap_elements",
+        "insert_many_elements",
+        "remove_and_insert_element",
+        "only_choose_length",
+        "arbitrary",
+        "crossover_replace_element",
+        "crossover_insert_slice",
+        "insert_dictionary_entry",
+    ];
+    for _ in 0..100 {
+        let (token, _) = m.random_mutate(&mut value, &mut cache, 500.0);
+        assert!(kinds.contains(&m.mutation_kind(&token)));
+        m.unmutate(&mut value, &mut cache, token);
+    }
+}
 
 // #[test]
 // fn test_vector_explore() {
@@ -23,9 +23,20 @@ pub const STOP_AFTER_FIRST_FAILURE_FLAG: &str = "stop-after-first-failure";
 
 pub const DETECT_INFINITE_LOOP_FLAG: &str = "detect-infinite-loop";
 
+pub const NO_DICTIONARY_FLAG: &str = "no-dictionary";
+
+pub const CORPUS_WIDE_CROSSOVER_FLAG: &str = "corpus-wide-crossover";
+
+pub const CHECK_REPETITIONS_FLAG: &str = "check-repetitions";
+
+pub const TARGET_FN_FLAG: &str = "target-fn";
+pub const TARGET_LINE_FLAG: &str = "target-line";
+
 pub const COMMAND_FUZZ: &str = "fuzz";
 pub const COMMAND_MINIFY_INPUT: &str = "minify";
 pub const COMMAND_READ: &str = "read";
+pub const COMMAND_ENUMERATE: &str = "enumerate";
+pub const COMMAND_CHECK_MUTATOR: &str = "check-mutator";
 
 #[derive(Clone)]
 pub struct DefaultArguments {
@@ -42,8 +53,20 @@ impl Default for DefaultArguments {
 #[derive(Debug, Clone)]
 pub enum FuzzerCommand {
     Fuzz,
-    Read { input_file: PathBuf },
-    MinifyInput { input_file: PathBuf },
+    Read {
+        input_file: PathBuf,
+    },
+    MinifyInput {
+        input_file: PathBuf,
+    },
+    /// Test the values of the input corpus, followed by the values that the mutator generates up to
+    /// the maximum complexity, in the order given by its `ordered_arbitrary` method, until they are
+    /// exhausted. Mutators that never run out of values, such as those of vectors, are only stopped
+    /// by the maximum number of iterations or the maximum duration.
+    Enumerate,
+    /// Check that the mutator respects the requirements of the `Mutator` trait, using the
+    /// values it generates up to the maximum complexity, then report the first violation.
+    CheckMutator,
 }
 impl Default for FuzzerCommand {
     fn default() -> Self {
@@ -51,6 +74,24 @@ impl Default for FuzzerCommand {
     }
 }
 
+/// A region of the fuzzed code that the fuzzer should try to reach.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoverageTarget {
+    /// Every function whose demangled name contains the given string
+    Function(String),
+    /// The code regions spanning the given line of the file whose path ends with `file`
+    Line { file: PathBuf, line: usize },
+}
+impl Display for CoverageTarget {
+    #[no_coverage]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CoverageTarget::Function(name) => write!(f, "{}", name),
+            CoverageTarget::Line { file, line } => write!(f, "{}:{}", file.display(), line),
+        }
+    }
+}
+
 /// Various arguments given to the fuzzer, typically provided by the `cargo fuzzcheck` command line tool.
 #[derive(Debug, Clone)]
 pub struct Arguments {
@@ -60,6 +101,14 @@ pub struct Arguments {
     pub maximum_duration: Duration,
     pub maximum_iterations: usize,
     pub stop_after_first_failure: bool,
+    /// Whether the constants of the fuzzed binary are added to the dictionary of the mutators
+    pub harvest_constants: bool,
+    /// Whether the mutators can use the subvalues of every test case in the pool for crossover
+    pub corpus_wide_crossover: bool,
+    /// Whether the `check-mutator` command also fails when a mutator generates the same value twice
+    pub check_repetitions: bool,
+    /// The region of code toward which the fuzzer is directed, if any
+    pub target: Option<CoverageTarget>,
     pub corpus_in: Option<PathBuf>,
     pub corpus_out: Option<PathBuf>,
     pub artifacts_folder: Option<PathBuf>,
@@ -74,6 +123,10 @@ impl Arguments {
             maximum_duration: Duration::MAX,
             maximum_iterations: usize::MAX,
             stop_after_first_failure: true,
+            harvest_constants: true,
+            corpus_wide_crossover: false,
+            check_repetitions: false,
+            target: None,
             corpus_in: None,
             corpus_out: None,
             artifacts_folder: None,
@@ -96,7 +149,10 @@ pub fn options_parser() -> Options {
             "the action to be performed (default: fuzz). --{} is required when using `{}`",
             INPUT_FILE_FLAG, COMMAND_MINIFY_INPUT
         ),
-        &format!("<{} | {}>", COMMAND_FUZZ, COMMAND_MINIFY_INPUT),
+        &format!(
+            "<{} | {} | {} | {}>",
+            COMMAND_FUZZ, COMMAND_MINIFY_INPUT, COMMAND_ENUMERATE, COMMAND_CHECK_MUTATOR
+        ),
     );
     options.optopt(
         "",
@@ -118,6 +174,41 @@ pub fn options_parser() -> Options {
         "stop the fuzzer after the first test failure is found",
     );
 
+    options.optflag(
+        "",
+        NO_DICTIONARY_FLAG,
+        "do not harvest the constants of the fuzzed binary into a dictionary used by the mutators",
+    );
+
+    options.optflag(
+        "",
+        CORPUS_WIDE_CROSSOVER_FLAG,
+        "index the subvalues of the whole corpus so that the mutators can splice them into any test case",
+    );
+
+    options.optflag(
+        "",
+        CHECK_REPETITIONS_FLAG,
+        "with the check-mutator command, also fail if the mutator generates the same value twice",
+    );
+
+    options.optopt(
+        "",
+        TARGET_FN_FLAG,
+        "direct the fuzzer toward the functions whose name contains NAME",
+        "NAME",
+    );
+    options.optopt(
+        "",
+        TARGET_LINE_FLAG,
+        format!(
+            "direct the fuzzer toward a line of a source file, overrides --{target_fn}",
+            target_fn = TARGET_FN_FLAG
+        )
+        .as_str(),
+        "FILE:LINE",
+    );
+
     options.optopt("", IN_CORPUS_FLAG, "folder for the input corpus", "PATH");
     options.optflag(
         "",
@@ -196,12 +287,17 @@ impl Arguments {
 
         let command = command.as_str();
 
-        if !matches!(command, COMMAND_FUZZ | COMMAND_READ | COMMAND_MINIFY_INPUT) {
+        if !matches!(
+            command,
+            COMMAND_FUZZ | COMMAND_READ | COMMAND_MINIFY_INPUT | COMMAND_ENUMERATE | COMMAND_CHECK_MUTATOR
+        ) {
             return Err(ArgumentsError::Validation(format!(
-                r#"The command {c} is not supported. It can either be ‘{fuzz}’ or ‘{minify}’."#,
+                r#"The command {c} is not supported. It can either be ‘{fuzz}’, ‘{minify}’, ‘{enumerate}’, or ‘{check_mutator}’."#,
                 c = &matches.free[0],
                 fuzz = COMMAND_FUZZ,
                 minify = COMMAND_MINIFY_INPUT,
+                enumerate = COMMAND_ENUMERATE,
+                check_mutator = COMMAND_CHECK_MUTATOR,
             )));
         }
 
@@ -267,6 +363,37 @@ impl Arguments {
             |x| x.parse::<PathBuf>().ok(),
         );
 
+        let target_line = matches
+            .opt_str(TARGET_LINE_FLAG)
+            .map(
+                #[no_coverage]
+                |x| {
+                    x.rsplit_once(':')
+                        .and_then(
+                            #[no_coverage]
+                            |(file, line)| Some((file.parse::<PathBuf>().ok()?, line.parse::<usize>().ok()?)),
+                        )
+                        .map(
+                            #[no_coverage]
+                            |(file, line)| CoverageTarget::Line { file, line },
+                        )
+                        .ok_or_else(
+                            #[no_coverage]
+                            || {
+                                ArgumentsError::Validation(format!(
+                                    "The target line {} is invalid. It must be given as FILE:LINE, for example src/lib.rs:42.",
+                                    x
+                                ))
+                            },
+                        )
+                },
+            )
+            .transpose()?;
+        let target = target_line.or_else(
+            #[no_coverage]
+            || matches.opt_str(TARGET_FN_FLAG).map(CoverageTarget::Function),
+        );
+
         // verify all the right options are here
 
         let command = match command {
@@ -295,6 +422,8 @@ impl Arguments {
                 );
                 FuzzerCommand::MinifyInput { input_file }
             }
+            COMMAND_ENUMERATE => FuzzerCommand::Enumerate,
+            COMMAND_CHECK_MUTATOR => FuzzerCommand::CheckMutator,
             _ => unreachable!(),
         };
 
@@ -316,6 +445,9 @@ impl Arguments {
             )
             .unwrap_or(usize::MAX);
         let stop_after_first_failure = matches.opt_present(STOP_AFTER_FIRST_FAILURE_FLAG);
+        let harvest_constants = !matches.opt_present(NO_DICTIONARY_FLAG);
+        let corpus_wide_crossover = matches.opt_present(CORPUS_WIDE_CROSSOVER_FLAG);
+        let check_repetitions = matches.opt_present(CHECK_REPETITIONS_FLAG);
 
         let defaults = DefaultArguments::default();
         let max_input_cplx: f64 = max_input_cplx.unwrap_or(defaults.max_input_cplx as f64);
@@ -331,6 +463,10 @@ impl Arguments {
             maximum_duration,
             maximum_iterations,
             stop_after_first_failure,
+            harvest_constants,
+            corpus_wide_crossover,
+            check_repetitions,
+            target,
             max_input_cplx,
             corpus_in,
             corpus_out,
@@ -383,17 +519,37 @@ cargo-fuzzcheck tests::fuzzit --{max_cplx} 4000 --{out_corpus} fuzz_results/out/
     and write the output corpus (i.e. the folder of most interesting test cases) 
     to fuzz_results/out/.
 
+cargo-fuzzcheck tests::fuzzit --{target_line} src/parser.rs:120
+    Fuzz "tests::fuzzit", prioritising the test cases that come closest to 
+    line 120 of src/parser.rs within that file. The statistics show the closest
+    distance to that line reached so far.
+
 cargo-fuzzcheck tests::fuzz --command {minify} --{input_file} "artifacts/crash.json"
     Using the fuzz test located at "tests::fuzz_test", minify the test input defined 
     in the file "artifacts/crash.json". It will put minified inputs in the folder 
     artifacts/crash.minified/ and name them {{complexity}}-{{hash}}.json. 
     For example, artifacts/crash.minified/4213--8cd7777109b57b8c.json
     is a minified input of complexity 42.13.
+
+cargo-fuzzcheck tests::fuzz --command {enumerate} --{max_cplx} 20
+    Using the fuzz test located at "tests::fuzz_test", test the values of 
+    complexity no greater than 20 that the mutator generates in order, until
+    it cannot generate more of them. Mutators that never run out of values,
+    such as those of vectors, require --{max_iter} to stop.
+
+cargo-fuzzcheck tests::fuzz --command {check_mutator}
+    Using the fuzz test located at "tests::fuzz_test", check that its mutator
+    respects the requirements of the Mutator trait and report the first 
+    violation found.
 "#,
         minify = COMMAND_MINIFY_INPUT,
+        enumerate = COMMAND_ENUMERATE,
+        check_mutator = COMMAND_CHECK_MUTATOR,
         input_file = INPUT_FILE_FLAG,
         max_cplx = MAX_INPUT_CPLX_FLAG,
+        max_iter = MAX_ITERATIONS_FLAG,
         out_corpus = OUT_CORPUS_FLAG,
+        target_line = TARGET_LINE_FLAG,
     )
     .as_str();
     help
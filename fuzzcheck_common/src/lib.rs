@@ -1,12 +1,87 @@
 #![feature(no_coverage)]
 
+use std::collections::HashMap;
+
 pub mod arg;
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Default)]
 pub struct FuzzerStats {
     pub total_number_of_runs: usize,
     pub number_of_runs_since_last_reset_time: usize,
     pub exec_per_s: usize,
+    pub mutations: MutationStats,
+}
+
+/// The number of test cases tested and added to the pool for one kind of mutation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MutationKindStats {
+    pub kind: &'static str,
+    pub attempts: usize,
+    pub corpus_additions: usize,
+}
+impl MutationKindStats {
+    #[no_coverage]
+    pub fn addition_rate(&self) -> f64 {
+        if self.attempts == 0 {
+            0.0
+        } else {
+            self.corpus_additions as f64 / self.attempts as f64
+        }
+    }
+}
+
+/// The number of test cases tested and added to the pool for each kind of mutation
+#[derive(Clone, Default)]
+pub struct MutationStats {
+    kinds: Vec<MutationKindStats>,
+    index_of_kind: HashMap<&'static str, usize>,
+}
+
+impl MutationStats {
+    /// Records that a test case obtained through the given kind of mutation was tested,
+    /// and whether it was added to the pool
+    #[no_coverage]
+    pub fn record(&mut self, kind: &'static str, added_to_corpus: bool) {
+        let kinds = &mut self.kinds;
+        let idx = *self.index_of_kind.entry(kind).or_insert_with(
+            #[no_coverage]
+            || {
+                kinds.push(MutationKindStats {
+                    kind,
+                    attempts: 0,
+                    corpus_additions: 0,
+                });
+                kinds.len() - 1
+            },
+        );
+        let stats = &mut self.kinds[idx];
+        stats.attempts += 1;
+        if added_to_corpus {
+            stats.corpus_additions += 1;
+        }
+    }
+
+    /// The counters of each kind of mutation, sorted by decreasing number of corpus additions
+    #[no_coverage]
+    pub fn sorted_kinds(&self) -> Vec<MutationKindStats> {
+        let mut kinds = self.kinds.clone();
+        kinds.sort_by(
+            #[no_coverage]
+            |a, b| {
+                b.corpus_additions
+                    .cmp(&a.corpus_additions)
+                    .then(b.attempts.cmp(&a.attempts))
+                    .then(a.kind.cmp(b.kind))
+            },
+        );
+        kinds
+    }
+
+    /// The counters of the kind of mutation that added the most test cases to the pool
+    #[no_coverage]
+    pub fn most_productive_kind(&self) -> Option<MutationKindStats> {
+        self.sorted_kinds().first().copied()
+    }
 }
 
 #[derive(Clone, Copy)]
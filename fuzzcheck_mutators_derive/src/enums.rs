@@ -1,4 +1,4 @@
-use proc_macro2::Ident;
+use proc_macro2::{Ident, TokenStream};
 use syn::{DataEnum, Generics, Visibility};
 
 use crate::structs_and_enums::{CreateWrapperMutatorParams, FieldMutator, FieldMutatorKind};
@@ -8,6 +8,15 @@ use crate::{q, Common, MakeMutatorSettings};
 fn size_to_cplxity(size: usize) -> f64 {
     (usize::BITS - (size.saturating_sub(1)).leading_zeros()) as f64
 }
+/// The weight of a variant given by its `#[fuzzcheck(weight = ..)]` attribute, or 1.0 by default
+fn weight_expr(weight: &Option<TokenStream>) -> TokenStream {
+    if let Some(weight) = weight {
+        ts!("((" weight ") as f64)")
+    } else {
+        ts!("1.0")
+    }
+}
+
 #[allow(non_snake_case)]
 pub(crate) fn impl_default_mutator_for_enum(
     tb: &mut TokenBuilder,
@@ -45,19 +54,7 @@ pub(crate) fn impl_default_mutator_for_enum(
                                 kind: FieldMutatorKind::Ignore,
                             };
                         }
-                        let mut mutator = None;
-                        for attribute in field.attrs.iter() {
-                            match super::read_field_default_mutator_attribute(attribute) {
-                                Ok(Some(field_mutator_attribute)) => {
-                                    mutator = Some((field_mutator_attribute.ty, field_mutator_attribute.equal));
-                                }
-                                Ok(None) => {}
-                                Err(e) => {
-                                    tb.stream(e.to_compile_error());
-                                }
-                            }
-                        }
-                        if let Some(m) = mutator {
+                        if let Some(m) = super::read_field_mutator_attributes(tb, field) {
                             FieldMutator {
                                 i,
                                 j: Some(j),
@@ -80,6 +77,18 @@ pub(crate) fn impl_default_mutator_for_enum(
         })
         .collect::<Vec<_>>();
 
+    let weights = enu
+        .variants
+        .iter()
+        .filter(|variant| {
+            variant
+                .attrs
+                .iter()
+                .all(|attr| !super::has_ignore_variant_attribute(attr))
+        })
+        .map(|variant| super::read_variant_weight_attribute(tb, variant))
+        .collect::<Vec<_>>();
+
     let TupleNMutator = cm.TupleNMutator.as_ref();
     let EnumSingleVariant = ident!(&enum_ident "SingleVariant");
 
@@ -129,7 +138,8 @@ pub(crate) fn impl_default_mutator_for_enum(
                 ident!("mutator_" enu.variants[field_mutator.i].ident "_" access_field(&field_mutator.field, field_mutator.j.unwrap())) ":" field_mutator.mutator_stream(&cm)
             , separator: ",") ") -> Self {
                 Self {
-                    mutator: " cm.AlternationMutator "::new(vec!["
+                    mutator: " cm.AlternationMutator
+                        if weights.iter().any(Option::is_some) { "::with_weights(vec![" } else { "::new(vec![" }
                         join_ts!(enu.variants.iter().enumerate().filter(|(_, variant)| {
                                     variant.attrs.iter().all(|attr| {
                                         !super::has_ignore_variant_attribute(attr)
@@ -149,7 +159,13 @@ pub(crate) fn impl_default_mutator_for_enum(
                         }
                         ")"
                         , separator: ",")
-                    "], " format!("{:.2}", size_to_cplxity(enu.variants.len())) ")
+                    "], "
+                    if weights.iter().any(Option::is_some) {
+                        ts!("vec![" join_ts!(weights.iter(), weight, weight_expr(weight), separator: ",") "], ")
+                    } else {
+                        ts!()
+                    }
+                    format!("{:.2}", size_to_cplxity(enu.variants.len())) ")
                 }
             }"
         ),
@@ -220,7 +236,7 @@ pub(crate) fn impl_default_mutator_for_basic_enum(tb: &mut TokenBuilder, enum_id
 
     let BasicEnumMutator = ts!(cm.mutators "::enums::BasicEnumMutator");
 
-    let count_non_ignored = enu
+    let weights = enu
         .variants
         .iter()
         .filter(|variant| {
@@ -229,15 +245,23 @@ pub(crate) fn impl_default_mutator_for_basic_enum(tb: &mut TokenBuilder, enum_id
                 .iter()
                 .all(|attr| !super::has_ignore_variant_attribute(attr))
         })
-        .count();
+        .map(|variant| super::read_variant_weight_attribute(tb, variant))
+        .collect::<Vec<_>>();
+    let count_non_ignored = weights.len();
+
+    let new_mutator = if weights.iter().any(Option::is_some) {
+        ts!("Self::Mutator::with_weights::<" enum_ident ">(vec![" join_ts!(weights.iter(), weight, weight_expr(weight), separator: ",") "])")
+    } else {
+        ts!("Self::Mutator::new::<" enum_ident ">(" q!(count_non_ignored) ")")
+    };
 
     extend_ts!(tb,
         "impl" cm.DefaultMutator "for " enum_ident " {
             type Mutator = " BasicEnumMutator ";
             #[no_coverage]
-            fn default_mutator() -> Self::Mutator {
-                Self::Mutator::new::<" enum_ident ">(" q!(count_non_ignored) ")
-            }
+            fn default_mutator() -> Self::Mutator {"
+                new_mutator
+            "}
         }"
     )
 }
@@ -6,7 +6,9 @@
 use proc_macro2::{Ident, Literal, TokenStream, TokenTree};
 use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
-use syn::{parenthesized, parse2, parse_macro_input, token, Attribute, DeriveInput, Error, LitBool, Token};
+use syn::{
+    parenthesized, parse2, parse_macro_input, token, Attribute, DeriveInput, Error, Field, LitBool, Token, Variant,
+};
 use token_builder::{extend_ts, ident, ts, TokenBuilder};
 
 mod enums;
@@ -81,7 +83,7 @@ pub fn make_mutator(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     derive_default_mutator_(settings).into()
 }
 
-#[proc_macro_derive(DefaultMutator, attributes(field_mutator, ignore_variant))]
+#[proc_macro_derive(DefaultMutator, attributes(field_mutator, ignore_variant, fuzzcheck))]
 pub fn derive_default_mutator(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let settings = MakeMutatorSettings {
         name: None,
@@ -92,7 +94,6 @@ pub fn derive_default_mutator(item: proc_macro::TokenStream) -> proc_macro::Toke
     derive_default_mutator_(settings).into()
 }
 
-#[doc(hidden)]
 #[proc_macro]
 pub fn make_single_variant_mutator(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = proc_macro2::TokenStream::from(item);
@@ -109,7 +110,25 @@ fn derive_default_mutator_(settings: MakeMutatorSettings) -> proc_macro2::TokenS
     match item.data {
         syn::Data::Struct(s) => {
             let nbr_fields = s.fields.len();
-            if nbr_fields == 0 {
+            if let Some(constructor) = read_constructor_attribute(&mut tb, &item.attrs) {
+                let nbr_args = constructor.args.as_ref().map_or(nbr_fields, |args| args.len());
+                if nbr_args == 0 {
+                    extend_ts!(
+                        &mut tb,
+                        "compile_error!(" q!("The constructor setting of the fuzzcheck attribute requires a constructor with one or more arguments.") ");"
+                    );
+                } else {
+                    tuples::impl_default_mutator_for_struct_with_constructor(
+                        &mut tb,
+                        &item.ident,
+                        &item.generics,
+                        &item.vis,
+                        &s,
+                        &constructor,
+                        &settings,
+                    );
+                }
+            } else if nbr_fields == 0 {
                 tuples::impl_default_mutator_for_struct_with_0_field(&mut tb, &item.ident, &s);
             } else {
                 tuples::impl_tuple_structure_trait(&mut tb, &item.ident, &item.generics, &s);
@@ -117,6 +136,12 @@ fn derive_default_mutator_(settings: MakeMutatorSettings) -> proc_macro2::TokenS
             }
         }
         syn::Data::Enum(e) => {
+            if read_constructor_attribute(&mut tb, &item.attrs).is_some() {
+                extend_ts!(
+                    &mut tb,
+                    "compile_error!(" q!("The constructor setting of the fuzzcheck attribute only applies to structs.") ");"
+                );
+            }
             if e.variants.iter().any(|variant| match &variant.fields {
                 syn::Fields::Named(fs) => !fs.named.is_empty(),
                 syn::Fields::Unnamed(fs) => !fs.unnamed.is_empty(),
@@ -261,6 +286,7 @@ pub(crate) struct Common {
     VoseAlias: TokenStream,
     RecursiveMutator: TokenStream,
     Box: TokenStream,
+    MapMutator: TokenStream,
     SubValueProvider: TokenStream,
     NeverMutator: TokenStream,
 }
@@ -326,6 +352,7 @@ impl Common {
             VoseAlias: ts!(mutators "::vose_alias::VoseAlias"),
             RecursiveMutator: ts!(mutators "::recursive::RecursiveMutator"),
             Box: ts!("::std::boxed::Box"),
+            MapMutator: ts!(mutators "::map::MapMutator"),
             NeverMutator: ts!("::fuzzcheck::mutators::never::NeverMutator"),
             SubValueProvider: ts!("fuzzcheck::SubValueProvider"),
         }
@@ -390,6 +417,376 @@ fn read_field_default_mutator_attribute(attribute: &Attribute) -> Result<Option<
     }
 }
 
+/// The arguments of a `#[fuzzcheck(...)]` attribute, such as:
+/// ```ignore
+/// #[fuzzcheck(range = 0 ..= 100)]
+/// #[fuzzcheck(len = 1 ..= 8, range = 10 ..= 20)]
+/// #[fuzzcheck(weight = 0.1)]
+/// #[fuzzcheck(fixed = Version::V2)]
+/// #[fuzzcheck(regex = "[a-z]+@[a-z]+")]
+/// #[fuzzcheck(constructor = Foo::new)]
+/// #[fuzzcheck(try_constructor = Foo::new, args = (u8, Vec<u8>), parse = Foo::args)]
+/// ```
+/// Each value except the regex is kept as an unparsed expression, which ends at the next comma.
+#[derive(Default)]
+struct FuzzcheckAttribute {
+    range: Option<TokenStream>,
+    len: Option<TokenStream>,
+    weight: Option<TokenStream>,
+    fixed: Option<TokenStream>,
+    regex: Option<syn::LitStr>,
+    constructor: Option<TokenStream>,
+    try_constructor: Option<TokenStream>,
+    args: Option<TokenStream>,
+    parse: Option<TokenStream>,
+}
+impl FuzzcheckAttribute {
+    /// Returns `true` if one of the settings that apply to types is set
+    fn has_type_settings(&self) -> bool {
+        self.constructor.is_some() || self.try_constructor.is_some() || self.args.is_some() || self.parse.is_some()
+    }
+}
+
+/// The constructor of a struct, given by its `#[fuzzcheck(constructor = ..)]` or
+/// `#[fuzzcheck(try_constructor = ..)]` attribute
+pub(crate) struct Constructor {
+    path: TokenStream,
+    /// Whether the constructor returns an `Option` or a `Result` instead of a value of the struct
+    fallible: bool,
+    /// The types of the arguments of the constructor, if they are not those of the fields of the struct
+    args: Option<Vec<syn::Type>>,
+    /// The function returning the arguments from which a value of the struct can be constructed
+    parse: Option<TokenStream>,
+}
+impl syn::parse::Parse for FuzzcheckAttribute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let _ = parenthesized!(content in input);
+        let input = content;
+
+        let mut attribute = FuzzcheckAttribute::default();
+        while !input.is_empty() {
+            let ident = input.call(Ident::parse_any)?;
+            let _ = input.parse::<Token![=]>()?;
+            if ident == "regex" {
+                attribute.regex = Some(input.parse::<syn::LitStr>()?);
+            } else {
+                let mut expr = TokenStream::new();
+                while !input.is_empty() && !input.peek(Token![,]) {
+                    expr.extend([input.parse::<TokenTree>()?]);
+                }
+                if expr.is_empty() {
+                    return Err(Error::new(
+                        ident.span(),
+                        format!("Expected an expression after `{ident} =`"),
+                    ));
+                }
+                let setting = match ident.to_string().as_str() {
+                    "range" => &mut attribute.range,
+                    "len" => &mut attribute.len,
+                    "weight" => &mut attribute.weight,
+                    "fixed" => &mut attribute.fixed,
+                    "constructor" => &mut attribute.constructor,
+                    "try_constructor" => &mut attribute.try_constructor,
+                    "args" => &mut attribute.args,
+                    "parse" => &mut attribute.parse,
+                    x => {
+                        return Err(Error::new(
+                            ident.span(),
+                            &format!("{x} is not a valid setting of the fuzzcheck attribute. Expected one of: range, len, weight, fixed, regex, constructor, try_constructor, args, parse"),
+                        ));
+                    }
+                };
+                *setting = Some(expr);
+            }
+            if !input.is_empty() {
+                let _ = input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(attribute)
+    }
+}
+
+fn read_fuzzcheck_attribute(attribute: &Attribute) -> Result<Option<FuzzcheckAttribute>, syn::Error> {
+    if let Some(ident) = attribute.path.get_ident() && ident == "fuzzcheck" {
+        parse2::<FuzzcheckAttribute>(attribute.tokens.clone()).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns the type and initializer of the mutator prescribed for the given field by its
+/// `#[field_mutator(..)]` or `#[fuzzcheck(..)]` attributes, if any.
+///
+/// Errors are written to the token builder.
+fn read_field_mutator_attributes(tb: &mut TokenBuilder, field: &Field) -> Option<(syn::Type, Option<TokenStream>)> {
+    let mut mutator = None;
+    for attribute in field.attrs.iter() {
+        match read_field_default_mutator_attribute(attribute) {
+            Ok(Some(field_mutator_attribute)) => {
+                mutator = Some((field_mutator_attribute.ty, field_mutator_attribute.equal));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tb.stream(e.to_compile_error());
+            }
+        }
+        match read_fuzzcheck_attribute(attribute).and_then(|a| a.map(|a| a.field_mutator(attribute, field)).transpose())
+        {
+            Ok(Some((ty, init))) => {
+                mutator = Some((ty, Some(init)));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tb.stream(e.to_compile_error());
+            }
+        }
+    }
+    mutator
+}
+
+/// Returns the constructor given by a `#[fuzzcheck(constructor = ..)]` or `#[fuzzcheck(try_constructor = ..)]`
+/// attribute of the type, if any.
+///
+/// Errors are written to the token builder.
+fn read_constructor_attribute(tb: &mut TokenBuilder, attrs: &[Attribute]) -> Option<Constructor> {
+    let mut constructor = None;
+    for attribute in attrs {
+        match read_fuzzcheck_attribute(attribute).and_then(|a| a.map(|a| a.constructor(attribute)).transpose()) {
+            Ok(Some(c)) => {
+                constructor = Some(c);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tb.stream(e.to_compile_error());
+            }
+        }
+    }
+    constructor
+}
+
+/// Returns the weight given to the variant by its `#[fuzzcheck(weight = ..)]` attribute, if any.
+///
+/// Errors are written to the token builder.
+fn read_variant_weight_attribute(tb: &mut TokenBuilder, variant: &Variant) -> Option<TokenStream> {
+    let mut weight = None;
+    for attribute in variant.attrs.iter() {
+        match read_fuzzcheck_attribute(attribute) {
+            Ok(Some(FuzzcheckAttribute {
+                range: None,
+                len: None,
+                weight: Some(w),
+                fixed: None,
+                regex: None,
+                constructor: None,
+                try_constructor: None,
+                args: None,
+                parse: None,
+            })) => {
+                weight = Some(w);
+            }
+            Ok(Some(_)) => {
+                tb.stream(
+                    Error::new_spanned(
+                        attribute,
+                        "Only the `weight` setting of the fuzzcheck attribute applies to enum variants",
+                    )
+                    .to_compile_error(),
+                );
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tb.stream(e.to_compile_error());
+            }
+        }
+    }
+    weight
+}
+
+impl FuzzcheckAttribute {
+    /// Returns the constructor described by the attribute of a type
+    fn constructor(self, attribute: &Attribute) -> syn::Result<Constructor> {
+        let error = |message: &str| Err(Error::new_spanned(attribute, message));
+        let (path, fallible) = match self {
+            Self {
+                range: None,
+                len: None,
+                weight: None,
+                fixed: None,
+                regex: None,
+                ..
+            } => match (self.constructor, self.try_constructor) {
+                (Some(path), None) => (path, false),
+                (None, Some(path)) => (path, true),
+                (Some(_), Some(_)) => {
+                    return error("The `constructor` and `try_constructor` settings of the fuzzcheck attribute cannot be combined");
+                }
+                (None, None) => {
+                    return error("The `args` and `parse` settings of the fuzzcheck attribute require a `constructor` or `try_constructor` setting");
+                }
+            },
+            _ => {
+                return error("Only the `constructor`, `try_constructor`, `args`, and `parse` settings of the fuzzcheck attribute apply to types");
+            }
+        };
+        let args = if let Some(args) = self.args {
+            if self.parse.is_none() {
+                return error("The `args` setting of the fuzzcheck attribute requires a `parse` setting, which returns the arguments from which a value can be constructed");
+            }
+            match parse2::<syn::Type>(args)? {
+                syn::Type::Tuple(tuple) => Some(tuple.elems.into_iter().collect()),
+                syn::Type::Paren(paren) => Some(vec![*paren.elem]),
+                _ => {
+                    return error(
+                        "The `args` setting of the fuzzcheck attribute expects a tuple of types, such as `(u8, bool)`",
+                    );
+                }
+            }
+        } else {
+            None
+        };
+        Ok(Constructor {
+            path,
+            fallible,
+            args,
+            parse: self.parse,
+        })
+    }
+
+    /// Returns the type of the mutator described by the attribute for the given field, along
+    /// with the expression initialising it.
+    fn field_mutator(&self, attribute: &Attribute, field: &Field) -> syn::Result<(syn::Type, TokenStream)> {
+        let cm = Common::new(0);
+        let error = |message: &str| Err(Error::new_spanned(attribute, message));
+        let (ty, init) = match self {
+            Self { weight: Some(_), .. } => {
+                return error("The `weight` setting of the fuzzcheck attribute only applies to enum variants");
+            }
+            _ if self.has_type_settings() => {
+                return error("The `constructor`, `try_constructor`, `args`, and `parse` settings of the fuzzcheck attribute only apply to structs");
+            }
+            Self {
+                fixed: Some(value),
+                range: None,
+                len: None,
+                regex: None,
+                ..
+            } => (
+                ts!(cm.UnitMutator "<" q!(field.ty) ">"),
+                ts!(cm.UnitMutator "::new(" value ", 0.0)"),
+            ),
+            Self {
+                regex: Some(regex),
+                range: None,
+                len: None,
+                fixed: None,
+                ..
+            } => {
+                let grammar = ts!(cm.mutators "::grammar");
+                (
+                    ts!(grammar "::StringFromGrammarMutator"),
+                    ts!(grammar "::string_from_grammar(" grammar "::regex(" q!(regex) "))"),
+                )
+            }
+            Self {
+                len: Some(len),
+                fixed: None,
+                regex: None,
+                range,
+                ..
+            } => {
+                let element_ty = if let Some(element_ty) = vec_element_type(&field.ty) {
+                    element_ty
+                } else {
+                    return error(
+                        "The `len` setting of the fuzzcheck attribute only applies to fields of type `Vec<T>`",
+                    );
+                };
+                let (element_mutator, element_init) = if let Some(range) = range {
+                    if let Some(IntWithinRangeMutator) = int_within_range_mutator(&cm, element_ty) {
+                        let init = ts!(IntWithinRangeMutator "::new(" range ")");
+                        (IntWithinRangeMutator, init)
+                    } else {
+                        return error("The `range` setting of the fuzzcheck attribute only applies to vectors of integers when it is combined with `len`");
+                    }
+                } else {
+                    (
+                        ts!("<" q!(element_ty) "as" cm.DefaultMutator ">::Mutator"),
+                        ts!("<" q!(element_ty) "as" cm.DefaultMutator ">::default_mutator()"),
+                    )
+                };
+                let VecMutator = ts!(cm.mutators "::vector::VecMutator");
+                (
+                    ts!(VecMutator "<" q!(element_ty) "," element_mutator ">"),
+                    ts!(VecMutator "::new(" element_init ", " len ")"),
+                )
+            }
+            Self {
+                range: Some(range),
+                len: None,
+                fixed: None,
+                regex: None,
+                ..
+            } => {
+                if let Some(IntWithinRangeMutator) = int_within_range_mutator(&cm, &field.ty) {
+                    let init = ts!(IntWithinRangeMutator "::new(" range ")");
+                    (IntWithinRangeMutator, init)
+                } else {
+                    return error("The `range` setting of the fuzzcheck attribute only applies to fields of type u8, u16, u32, u64, i8, i16, i32, i64, or `Vec` of these types when it is combined with `len`");
+                }
+            }
+            Self {
+                range: None,
+                len: None,
+                fixed: None,
+                regex: None,
+                ..
+            } => {
+                return error("The fuzzcheck attribute expects one of the settings: range, len, fixed, regex");
+            }
+            _ => {
+                return error("The `fixed` and `regex` settings of the fuzzcheck attribute cannot be combined with other settings");
+            }
+        };
+        Ok((parse2(ty)?, init))
+    }
+}
+
+/// Returns the type `T` if `ty` is written as `Vec<T>`
+fn vec_element_type(ty: &syn::Type) -> Option<&syn::Type> {
+    if let syn::Type::Path(path) = ty
+        && let Some(segment) = path.path.segments.last()
+        && segment.ident == "Vec"
+        && let syn::PathArguments::AngleBracketed(args) = &segment.arguments
+        && args.args.len() == 1
+        && let Some(syn::GenericArgument::Type(element_ty)) = args.args.first()
+    {
+        Some(element_ty)
+    } else {
+        None
+    }
+}
+
+/// Returns the path to the mutator of integers within a range for the given integer type
+fn int_within_range_mutator(cm: &Common, ty: &syn::Type) -> Option<TokenStream> {
+    if let syn::Type::Path(path) = ty && let Some(ident) = path.path.get_ident() {
+        let name = match ident.to_string().as_str() {
+            "u8" => "U8",
+            "u16" => "U16",
+            "u32" => "U32",
+            "u64" => "U64",
+            "i8" => "I8",
+            "i16" => "I16",
+            "i32" => "I32",
+            "i64" => "I64",
+            _ => return None,
+        };
+        Some(ts!(cm.mutators "::integer_within_range::" ident!(name "WithinRangeMutator")))
+    } else {
+        None
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use syn::{parse2, DeriveInput};
@@ -398,6 +398,16 @@ pub fn make_single_variant_mutator(
             )" _ => unreachable!()
             }
         }
+
+        #[doc(hidden)]
+        #[no_coverage]
+        fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+            match (self, t) {"
+            join_ts!(&enu.variants, variant,
+                "(" EnumSingleVariant "::" variant.ident "(m), " EnumSingleVariant "::" variant.ident "(t)) => m.mutation_kind(t),"
+            )" _ => unreachable!()
+            }
+        }
     }
     ");
 }
@@ -376,6 +376,15 @@ Generated by a procedural macro of [`fuzzcheck`]",
             fn visit_subvalues<'__fuzzcheck_derive_lt>(&self, value: &'__fuzzcheck_derive_lt " selfty ", cache: &'__fuzzcheck_derive_lt Self::Cache, visit: &mut dyn FnMut(&'__fuzzcheck_derive_lt dyn " cm.Any ", f64)) {
                 " InnerMutator_as_Mutator "::visit_subvalues(&self.mutator, value, &cache.inner, visit);
             }
+            #[doc(hidden)]
+            #[no_coverage]
+            fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+                " InnerMutator_as_Mutator "::mutation_kind(&self.mutator, " if settings.recursive {
+                "&*t.inner"
+                } else {
+                    "&t.inner"
+                }")
+            }
         }"
         if settings.default {
             ts!("impl" q!(type_generics_split.0) cm.DefaultMutator "for" selfty q!(DefaultMutator_Mutator_generics.where_clause) "{"
@@ -1,9 +1,9 @@
 use proc_macro2::Ident;
-use syn::{parse2, DataStruct, Generics, Visibility, WhereClause};
+use syn::{parse2, DataStruct, Field, Generics, Visibility, WhereClause};
 
 use crate::structs_and_enums::{FieldMutator, FieldMutatorKind};
 use crate::token_builder::*;
-use crate::{q, Common, MakeMutatorSettings};
+use crate::{q, Common, Constructor, MakeMutatorSettings};
 
 pub fn make_basic_tuple_mutator(tb: &mut TokenBuilder, nbr_elements: usize) {
     make_tuple_type_structure(tb, nbr_elements);
@@ -151,40 +151,14 @@ pub(crate) fn impl_default_mutator_for_struct_with_0_field(
     ");
 }
 
-#[allow(non_snake_case)]
-pub(crate) fn impl_default_mutator_for_struct(
-    tb: &mut TokenBuilder,
-    struct_ident: &Ident,
-    generics: &Generics,
-    visibility: &Visibility,
-    struc: &DataStruct,
-    settings: &MakeMutatorSettings,
-) {
-    let nbr_elements = struc.fields.len();
-
-    let cm = Common::new(nbr_elements);
-    let TupleNMutator = cm.TupleNMutator.as_ref()(nbr_elements);
-
-    let field_types = join_ts!(&struc.fields, field, field.ty, separator: ",");
-
-    let field_mutators = vec![struc
+/// Returns the mutators of the fields of the struct, as prescribed by their attributes
+fn struct_field_mutators(tb: &mut TokenBuilder, struc: &DataStruct) -> Vec<Vec<FieldMutator>> {
+    vec![struc
         .fields
         .iter()
         .enumerate()
         .map(|(i, field)| {
-            let mut mutator = None;
-            for attribute in field.attrs.iter() {
-                match super::read_field_default_mutator_attribute(attribute) {
-                    Ok(Some(field_mutator_attribute)) => {
-                        mutator = Some((field_mutator_attribute.ty, field_mutator_attribute.equal));
-                    }
-                    Ok(None) => {}
-                    Err(e) => {
-                        tb.stream(e.to_compile_error());
-                    }
-                }
-            }
-            if let Some(m) = mutator {
+            if let Some(m) = super::read_field_mutator_attributes(tb, field) {
                 FieldMutator {
                     i,
                     j: None,
@@ -200,7 +174,177 @@ pub(crate) fn impl_default_mutator_for_struct(
                 }
             }
         })
-        .collect::<Vec<_>>()];
+        .collect::<Vec<_>>()]
+}
+
+/// Implements a mutator for a struct whose values are created by a constructor function.
+///
+/// By default, the arguments of the constructor have the same types as the fields of the struct,
+/// in the same order. Otherwise, their types are given by the `args` setting, and each argument
+/// is mutated by its default mutator. The generated mutator is a `MapMutator` from the tuple of
+/// arguments to the struct, which keeps the arguments in its cache. If the constructor returns an
+/// `Option` or a `Result`, it is a `FilterMapMutator` instead, which skips the arguments for which
+/// the constructor fails and calls the constructor only once per generated value.
+#[allow(non_snake_case)]
+pub(crate) fn impl_default_mutator_for_struct_with_constructor(
+    tb: &mut TokenBuilder,
+    struct_ident: &Ident,
+    generics: &Generics,
+    visibility: &Visibility,
+    struc: &DataStruct,
+    constructor: &Constructor,
+    settings: &MakeMutatorSettings,
+) {
+    let (arg_types, field_mutators) = if let Some(args) = &constructor.args {
+        if struc
+            .fields
+            .iter()
+            .any(|field| super::read_field_mutator_attributes(tb, field).is_some())
+        {
+            extend_ts!(tb,
+                "compile_error!(" q!("The attributes of the fields of a struct do not apply to the arguments given by the `args` setting of its fuzzcheck attribute.") ");"
+            );
+            return;
+        }
+        let field_mutators = args
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| FieldMutator {
+                i,
+                j: None,
+                field: Field {
+                    attrs: vec![],
+                    vis: Visibility::Inherited,
+                    ident: None,
+                    colon_token: None,
+                    ty: ty.clone(),
+                },
+                kind: FieldMutatorKind::Generic,
+            })
+            .collect::<Vec<_>>();
+        (args.clone(), vec![field_mutators])
+    } else {
+        let arg_types = struc.fields.iter().map(|field| field.ty.clone()).collect::<Vec<_>>();
+        (arg_types, struct_field_mutators(tb, struc))
+    };
+    let nbr_elements = arg_types.len();
+
+    let cm = Common::new(nbr_elements);
+    let TupleNMutator = cm.TupleNMutator.as_ref()(nbr_elements);
+
+    let arg_types_stream = join_ts!(&arg_types, ty, q!(ty), separator: ",");
+    let Args = ts!("(" arg_types_stream ",)");
+    let selfty = ts!(struct_ident q!(generics.split_for_impl().1));
+
+    let ArgsMutator = ts!(
+        cm.TupleMutatorWrapper "<"
+            TupleNMutator "<"
+                join_ts!(field_mutators.iter().flatten(), m,
+                    m.mutator_stream(&cm)
+                , separator: ",")
+            ">,"
+            cm.TupleN_path "<" arg_types_stream ">"
+        ">"
+    );
+    let Parse = ts!("fn(&" selfty ") -> " cm.Option "<" Args ">");
+    let Map = if constructor.fallible {
+        ts!("fn(&" Args ") -> " cm.Option "<" selfty ">")
+    } else {
+        ts!("fn(&" Args ") -> " selfty)
+    };
+    let Cplx = ts!("fn(&" selfty ", f64) -> f64");
+    let MapMutatorPath = if constructor.fallible {
+        ts!(cm.mutators "::map::FilterMapMutator")
+    } else {
+        cm.MapMutator.clone()
+    };
+    let MapMutator = ts!(
+        MapMutatorPath "<" Args "," selfty "," ArgsMutator "," Parse "," Map "," Cplx ">"
+    );
+
+    let mutator_names = field_mutators
+        .iter()
+        .flatten()
+        .map(|m| ident!("mutator_" access_field(&m.field, m.i)))
+        .collect::<Vec<_>>();
+
+    let call_constructor = ts!(
+        constructor.path "("
+            join_ts!(0..nbr_elements, idx,
+                cm.Clone "::clone(&args." idx "),"
+            )
+        ")"
+    );
+    let args_mutator_init = ts!(
+        cm.TupleMutatorWrapper "::new(" TupleNMutator "::new("
+            join_ts!(&mutator_names, name, name, separator: ",")
+        "))"
+    );
+    let map = if constructor.fallible {
+        ts!(cm.mutators "::map::FallibleConstructorOutput::into_option(" call_constructor ")")
+    } else {
+        call_constructor
+    };
+    let parse = if let Some(parse) = &constructor.parse {
+        ts!(parse "(value)")
+    } else {
+        ts!(cm.Some "(("
+            join_ts!(struc.fields.iter().enumerate(), (idx, field),
+                cm.Clone "::clone(&value." access_field(field, idx) "),"
+            )
+        "))")
+    };
+
+    use crate::structs_and_enums::{make_mutator_type_and_impl, CreateWrapperMutatorParams};
+
+    let params = CreateWrapperMutatorParams {
+        cm: &cm,
+        visibility,
+        type_ident: struct_ident,
+        type_generics: generics,
+        field_mutators: &field_mutators,
+        InnerMutator: &MapMutator,
+        new_impl: &ts!(
+            "
+            #[no_coverage]
+            pub fn new("
+            join_ts!(mutator_names.iter().zip(field_mutators.iter().flatten()), (name, mutator),
+                name ":" mutator.mutator_stream(&cm)
+            , separator: ",")
+            ") -> Self {
+            Self {
+                mutator : " MapMutatorPath "::new("
+                    args_mutator_init ",
+                    (|value: &" selfty "|" parse ") as " Parse ",
+                    (|args: &" Args "|" map ") as " Map ",
+                    (|_: &" selfty ", cplx: f64| cplx) as " Cplx ",
+                )
+            }
+            }"
+        ),
+        settings,
+    };
+
+    extend_ts!(tb, make_mutator_type_and_impl(params));
+}
+
+#[allow(non_snake_case)]
+pub(crate) fn impl_default_mutator_for_struct(
+    tb: &mut TokenBuilder,
+    struct_ident: &Ident,
+    generics: &Generics,
+    visibility: &Visibility,
+    struc: &DataStruct,
+    settings: &MakeMutatorSettings,
+) {
+    let nbr_elements = struc.fields.len();
+
+    let cm = Common::new(nbr_elements);
+    let TupleNMutator = cm.TupleNMutator.as_ref()(nbr_elements);
+
+    let field_types = join_ts!(&struc.fields, field, field.ty, separator: ",");
+
+    let field_mutators = struct_field_mutators(tb, struc);
 
     let TupleKind = cm.TupleN_path.clone();
 
@@ -707,6 +851,27 @@ fn impl_mutator_trait(tb: &mut TokenBuilder, nbr_elements: usize) {
             )
             "
         }
+
+        #[doc(hidden)]
+        #[no_coverage]
+        fn mutation_kind(&self, t: &Self::UnmutateToken) -> &'static str {
+            let mut kind = \"mutate\";
+            let mut nbr_mutated_fields = 0;"
+            join_ts!(0..nbr_elements, i,
+                "if let" cm.Some "(element_token) = &t." ti(i) "{
+                    nbr_mutated_fields += 1;
+                    kind = match element_token {
+                        UnmutateElementToken::Unmutate(subtoken) => self." mutator_i(i) ".mutation_kind(subtoken),
+                        UnmutateElementToken::Replace(_) => \"crossover_replace_field\",
+                    };
+                }"
+            )
+            "if nbr_mutated_fields == 1 {
+                kind
+            } else {
+                \"mutate_several_fields\"
+            }
+        }
     }"
     )
 }